@@ -0,0 +1,176 @@
+// Copyright (c) 2015, 2018, 2020 Brandon Thomas <bt@brand.io>
+
+//! Query the dictionary from the shell.
+//!
+//! ```text
+//! arpabet-cli lookup <word>...
+//! arpabet-cli explain <word>...
+//! arpabet-cli search <query>...
+//! arpabet-cli transcribe <file>
+//! arpabet-cli convert --to <ipa|xsampa|kirshenbaum> [<pronunciation>...]
+//! ```
+//!
+//! `transcribe` reads standard input when the file is '-'. `convert` reads
+//! ARPABET pronunciations, eg. "T EH1 S T", one per line from standard input
+//! when none are given as arguments. `search` joins its arguments into one
+//! query, eg. `arpabet-cli search word:~cat syllables:1`; see the
+//! `arpabet::query` module for the syntax.
+
+extern crate arpabet;
+
+use arpabet::casing::fold_case;
+use arpabet::extensions::SentenceToken;
+use arpabet::ipa::polyphone_to_ipa;
+use arpabet::notation::{polyphone_to_notation, Notation};
+use arpabet::{load_cmudict, Polyphone, PHONEME_MAP};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+const USAGE : &str = "Usage:
+  arpabet-cli lookup <word>...
+  arpabet-cli explain <word>...
+  arpabet-cli search <query>...
+  arpabet-cli transcribe <file>
+  arpabet-cli convert --to <ipa|xsampa|kirshenbaum> [<pronunciation>...]";
+
+fn main() {
+  let args = env::args().skip(1).collect::<Vec<String>>();
+  let stdout = io::stdout();
+  let mut out = stdout.lock();
+
+  let result = match args.first().map(|command| command.as_str()) {
+    Some("lookup") if args.len() > 1 => lookup(&mut out, &args[1 ..]),
+    Some("explain") if args.len() > 1 => explain(&mut out, &args[1 ..]),
+    Some("search") if args.len() > 1 => search(&mut out, &args[1 ..].join(" ")),
+    Some("transcribe") if args.len() == 2 => transcribe(&mut out, &args[1]),
+    Some("convert") => convert(&mut out, &args[1 ..]),
+    Some("help") | Some("--help") | Some("-h") => {
+      writeln!(out, "{}", USAGE).map_err(output_error)
+    },
+    _ => Err(USAGE.to_string()),
+  };
+  let result = result.and_then(|_| out.flush().map_err(output_error));
+
+  if let Err(message) = result {
+    eprintln!("{}", message);
+    process::exit(1);
+  }
+}
+
+// Print every pronunciation of each word. Fails if any word is missing.
+fn lookup(out: &mut dyn Write, words: &[String]) -> Result<(), String> {
+  let arpabet = load_cmudict();
+  let mut missing = Vec::new();
+
+  for word in words.iter() {
+    let lowercase = fold_case(word);
+    let variants = arpabet.get_polyphone_variants(&lowercase);
+    if variants.is_empty() {
+      missing.push(word.as_str());
+      continue;
+    }
+    for polyphone in variants {
+      let symbols = polyphone.iter().map(|phoneme| phoneme.to_str()).collect::<Vec<&str>>();
+      writeln!(out, "{}\t{}", word, symbols.join(" ")).map_err(output_error)?;
+    }
+  }
+
+  if missing.is_empty() {
+    Ok(())
+  } else {
+    Err(format!("Not found: {}", missing.join(", ")))
+  }
+}
+
+// Print how each word's pronunciation is found.
+fn explain(out: &mut dyn Write, words: &[String]) -> Result<(), String> {
+  let arpabet = load_cmudict();
+  for word in words.iter() {
+    write!(out, "{}", arpabet.explain_lookup(word)).map_err(output_error)?;
+  }
+  Ok(())
+}
+
+// Print the words matching the query, one per line.
+fn search(out: &mut dyn Write, query: &str) -> Result<(), String> {
+  let arpabet = load_cmudict();
+  for word in arpabet.search_query(query).map_err(|err| err.to_string())? {
+    writeln!(out, "{}", word).map_err(output_error)?;
+  }
+  Ok(())
+}
+
+// Transcribe each line of the file into sentence tokens.
+fn transcribe(out: &mut dyn Write, filename: &str) -> Result<(), String> {
+  let mut text = String::new();
+  if filename == "-" {
+    io::stdin().read_to_string(&mut text).map_err(|err| err.to_string())?;
+  } else {
+    text = fs::read_to_string(filename).map_err(|err| format!("{}: {}", filename, err))?;
+  }
+
+  let arpabet = load_cmudict();
+
+  for (i, line) in text.lines().enumerate() {
+    if line.trim().is_empty() {
+      continue;
+    }
+    let tokens = arpabet.transcribe_sentence(line)
+      .map_err(|err| format!("Line {}: {}", i + 1, err))?;
+    let symbols = tokens.iter().map(SentenceToken::to_str).collect::<Vec<&str>>();
+    writeln!(out, "{}", symbols.join(" ")).map_err(output_error)?;
+  }
+
+  Ok(())
+}
+
+// Convert ARPABET pronunciations to another notation.
+fn convert(out: &mut dyn Write, args: &[String]) -> Result<(), String> {
+  let (target, pronunciations) = match args {
+    [flag, target, rest @ ..] if flag == "--to" => (target.as_str(), rest),
+    _ => return Err(USAGE.to_string()),
+  };
+
+  let notation = match target {
+    "ipa" => None,
+    "xsampa" => Some(Notation::XSampa),
+    "kirshenbaum" => Some(Notation::Kirshenbaum),
+    _ => return Err(format!("Unknown notation '{}'. Expected ipa, xsampa or kirshenbaum.", target)),
+  };
+
+  let mut convert_one = |pronunciation: &str| -> Result<(), String> {
+    let polyphone = pronunciation.split_whitespace()
+      .map(|symbol| PHONEME_MAP.get(symbol)
+        .copied()
+        .ok_or_else(|| format!("Unknown ARPABET symbol '{}'", symbol)))
+      .collect::<Result<Polyphone, String>>()?;
+    let converted = match notation {
+      None => polyphone_to_ipa(&polyphone),
+      Some(notation) => polyphone_to_notation(&polyphone, notation),
+    };
+    writeln!(out, "{}", converted).map_err(output_error)
+  };
+
+  if !pronunciations.is_empty() {
+    return pronunciations.iter().try_for_each(|pronunciation| convert_one(pronunciation));
+  }
+
+  for line in io::stdin().lock().lines() {
+    let line = line.map_err(|err| err.to_string())?;
+    if !line.trim().is_empty() {
+      convert_one(&line)?;
+    }
+  }
+  Ok(())
+}
+
+// Describe an error writing output. Output closed early, eg. by `| head`,
+// ends the program quietly.
+fn output_error(err: io::Error) -> String {
+  if err.kind() == io::ErrorKind::BrokenPipe {
+    process::exit(0);
+  }
+  err.to_string()
+}
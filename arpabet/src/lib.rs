@@ -23,26 +23,131 @@
 //! assert_eq!(arpabet.get_polyphone_str("test"),
 //!   Some(vec!["T".into(), "EH1".into(), "S".into(), "T".into()]));
 //! ```
+//!
+//! The full CMUdict adds several megabytes to the binary. On memory constrained
+//! targets, enable the `mini-dict` feature to embed only a basic English
+//! vocabulary of roughly 3,000 common words instead, or set the
+//! `ARPABET_CMUDICT_WORD_LIST` environment variable to a word list file at build
+//! time to embed only the words listed there. See `SUBSET_REPORT`.
 
 extern crate arpabet_cmudict;
+extern crate arpabet_macros;
 extern crate arpabet_parser;
 extern crate arpabet_types;
 
+// NB: `arpa!` expands to `::arpabet` paths, which need this to resolve in
+// the crate's own tests.
+#[cfg(test)] extern crate self as arpabet;
+
+pub mod prelude;
+mod version;
+
 // We simply re-export the symbols in the shape of the original arpabet crate
 // as it was before its decomposition into several crates.
 pub use arpabet_cmudict::load_cmudict;
+pub use arpabet_cmudict::{SubsetReport, SUBSET_REPORT};
+pub use arpabet_macros::arpa;
+pub use arpabet_parser::LoadReport;
+pub use arpabet_parser::ParseEntries;
+pub use arpabet_parser::ParseLimits;
+pub use arpabet_parser::ParseMode;
+pub use arpabet_parser::SkippedLine;
+pub use arpabet_parser::load_from_compressed_file;
+pub use arpabet_parser::load_from_compressed_file_with_mode;
 pub use arpabet_parser::load_from_file;
+pub use arpabet_parser::load_from_file_with_limits;
+pub use arpabet_parser::load_from_file_with_casing;
+pub use arpabet_parser::load_from_file_with_mode;
+pub use arpabet_parser::load_from_reader;
+pub use arpabet_parser::load_from_reader_with_limits;
+pub use arpabet_parser::load_from_reader_with_mode;
 pub use arpabet_parser::load_from_str;
+pub use arpabet_parser::load_from_str_with_casing;
+pub use arpabet_parser::load_from_str_with_limits;
+pub use arpabet_parser::load_from_str_with_mode;
+pub use arpabet_parser::parse_entries;
+pub use arpabet_parser::parse_entries_with_limits;
 pub use arpabet_types::Arpabet;
 pub use arpabet_types::Polyphone;
 pub use arpabet_types::Word;
+pub use arpabet_types::alignment;
+pub use arpabet_types::articulation;
+pub use arpabet_types::binary;
+#[cfg(feature = "cache")]
+pub use arpabet_types::cache;
+pub use arpabet_types::casing;
+pub use arpabet_types::concordance;
+pub use arpabet_types::concurrent;
+pub use arpabet_types::confusability;
+pub use arpabet_types::consistency;
+pub use arpabet_types::coverage;
 pub use arpabet_types::constants::ALL_CONSONANTS;
 pub use arpabet_types::constants::ALL_PUNCTUATION;
 pub use arpabet_types::constants::ALL_VOWELS;
 pub use arpabet_types::constants::PHONEME_MAP;
+pub use arpabet_types::decode;
+pub use arpabet_types::dialect;
+pub use arpabet_types::diff;
+pub use arpabet_types::duplicates;
 pub use arpabet_types::error::ArpabetError;
+pub use arpabet_types::error::WithContext;
+pub use arpabet_types::export;
 pub use arpabet_types::extensions;
+pub use arpabet_types::family;
+pub use arpabet_types::festival;
+pub use arpabet_types::frequency;
+pub use arpabet_types::fuzzy;
+pub use arpabet_types::heteronym;
+pub use arpabet_types::histogram;
+pub use arpabet_types::impact;
+pub use arpabet_types::index;
+pub use arpabet_types::initialism;
+pub use arpabet_types::ipa;
+pub use arpabet_types::iteration;
+#[cfg(feature = "json")]
+pub use arpabet_types::json;
+pub use arpabet_types::kaldi;
+pub use arpabet_types::lattice;
+pub use arpabet_types::lemmatize;
+pub use arpabet_types::letters;
+pub use arpabet_types::lookup;
+pub use arpabet_types::memory;
+pub use arpabet_types::metadata;
+pub use arpabet_types::meter;
+#[cfg(feature = "test-support")]
+pub use arpabet_types::mock;
+pub use arpabet_types::normalization;
+pub use arpabet_types::notation;
+pub use arpabet_types::numbers;
+pub use arpabet_types::ocr;
+pub use arpabet_types::pattern;
 pub use arpabet_types::phoneme;
+pub use arpabet_types::phrasing;
+pub use arpabet_types::pipeline;
+pub use arpabet_types::poetry;
+pub use arpabet_types::prominence;
+pub use arpabet_types::query;
+pub use arpabet_types::readability;
+#[cfg(feature = "json")]
+pub use arpabet_types::report_json;
+pub use arpabet_types::respelling;
+pub use arpabet_types::reversal;
+pub use arpabet_types::sampling;
+pub use arpabet_types::ssml;
+pub use arpabet_types::stats;
+pub use arpabet_types::stress;
+pub use arpabet_types::substitution;
+pub use arpabet_types::syllable;
+pub use arpabet_types::syllable_encoding;
+pub use arpabet_types::texting;
+pub use arpabet_types::tokenizer;
+pub use arpabet_types::trace;
+pub use arpabet_types::transcribe;
+pub use arpabet_types::validation;
+pub use arpabet_types::variant_id;
+pub use arpabet_types::watermark;
+pub use arpabet_types::weak_forms;
+pub use version::{version_info, VersionInfo};
 
 // Integration tests.
 #[cfg(test)]
@@ -89,11 +194,39 @@ mod tests {
     // Constants
     assert_eq!(arpabet::ALL_CONSONANTS.len(), 31);
     assert_eq!(arpabet::ALL_VOWELS.len(), 76);
-    assert_eq!(arpabet::ALL_PUNCTUATION.len(), 10);
+    assert_eq!(arpabet::ALL_PUNCTUATION.len(), 12);
     assert_eq!(arpabet::PHONEME_MAP.len(), 107);
 
     // Core structs + Errors
     assert_eq!(arpabet::Arpabet::new().len(), 0);
     assert_eq!(&arpabet::ArpabetError::EmptyFile.to_string(), "The file was empty.");
   }
+
+  #[test]
+  fn test_prelude() {
+    use prelude::v1::*;
+
+    let arpabet : Arpabet = load_from_str("TEST  T EH1 S T").expect("should load");
+    let polyphone : Polyphone = arpabet.get_polyphone("test").expect("should exist");
+
+    assert_eq!(polyphone[0], Phoneme::Consonant(Consonant::T));
+    assert_eq!(polyphone[1], Phoneme::Vowel(Vowel::EH(VowelStress::PrimaryStress)));
+    assert_eq!(polyphone_to_ipa(&polyphone), "ˈtɛst");
+
+    let tokens : Vec<SentenceToken> = arpabet.transcribe_sentence("test")
+      .expect("should transcribe");
+    assert_eq!(tokens[0], SentenceToken::Punctuation(Punctuation::StartToken));
+  }
+
+  #[test]
+  fn test_arpa_macro() {
+    use prelude::v1::*;
+
+    const TEST : &[Phoneme] = arpa!("T EH1 S T");
+    assert_eq!(TEST, load_cmudict().get_polyphone_ref("test").expect("should exist"));
+    assert_eq!(arpa!("AH0 ER"), &[
+      Phoneme::Vowel(Vowel::AH(VowelStress::NoStress)),
+      Phoneme::Vowel(Vowel::ER(VowelStress::UnknownStress)),
+    ]);
+  }
 }
@@ -0,0 +1,32 @@
+//! The stable API surface of the crate, versioned so that downstream code is
+//! insulated from changes to the internal crate layout.
+//!
+//! ```
+//! use arpabet::prelude::*;
+//!
+//! let arpabet : &Arpabet = load_cmudict();
+//! ```
+//!
+//! `arpabet::prelude` re-exports the latest version. Items are only ever added
+//! to a version; depend on a specific version, eg. `arpabet::prelude::v1`, to
+//! be protected from name clashes with future additions too.
+
+pub use self::v1::*;
+
+/// Version 1 of the prelude.
+pub mod v1 {
+  pub use arpabet_cmudict::load_cmudict;
+  pub use arpabet_macros::arpa;
+  pub use arpabet_parser::{load_from_file, load_from_str, ParseLimits};
+  pub use arpabet_types::{Arpabet, ArpabetError, Polyphone, Word};
+  pub use arpabet_types::export::ExportOptions;
+  pub use arpabet_types::extensions::{Punctuation, SentenceToken};
+  pub use arpabet_types::ipa::{parse_ipa, polyphone_to_ipa};
+  pub use arpabet_types::lookup::PronunciationLookup;
+  pub use arpabet_types::normalization::LookupOptions;
+  pub use arpabet_types::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+  pub use arpabet_types::stress::PolyphoneStress;
+  pub use arpabet_types::tokenizer::{OffsetUnit, Span};
+  pub use arpabet_types::transcribe::{AlphanumericPolicy, SingleLetterPolicy, TranscribeOptions,
+    TranscribedWord};
+}
@@ -0,0 +1,83 @@
+//! Reports the versions of the arpabet crates linked into the binary.
+
+use arpabet_cmudict;
+use arpabet_macros;
+use arpabet_parser;
+use arpabet_types;
+use std::fmt;
+
+/// Versions of the arpabet crates actually linked, and the revision of the
+/// embedded dictionary. See `version_info`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+  /// Version of the arpabet crate.
+  pub arpabet: &'static str,
+  /// Version of the arpabet_types crate.
+  pub types: &'static str,
+  /// Version of the arpabet_parser crate.
+  pub parser: &'static str,
+  /// Version of the arpabet_cmudict crate.
+  pub cmudict: &'static str,
+  /// Version of the arpabet_macros crate.
+  pub macros: &'static str,
+  /// The CMUdict release the embedded dictionary is generated from, eg. '0.7b'.
+  pub dictionary_revision: &'static str,
+}
+
+impl VersionInfo {
+  /// Whether every arpabet crate has the same version. Mixed versions cause
+  /// confusing type mismatches, eg. an `Arpabet` from one arpabet_types not
+  /// being accepted where another's is expected.
+  pub fn is_aligned(&self) -> bool {
+    [self.types, self.parser, self.cmudict, self.macros].iter().all(|version| *version == self.arpabet)
+  }
+}
+
+impl fmt::Display for VersionInfo {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "arpabet {} (types {}, parser {}, cmudict {}, macros {}; CMUdict {})",
+      self.arpabet, self.types, self.parser, self.cmudict, self.macros, self.dictionary_revision)
+  }
+}
+
+/// Report the versions of the arpabet crates actually linked, to diagnose
+/// mixed sub-crate versions in a lockfile.
+///
+/// ```
+/// extern crate arpabet;
+///
+/// let info = arpabet::version_info();
+/// assert!(info.is_aligned());
+/// assert_eq!(info.dictionary_revision, "0.7b");
+/// ```
+pub fn version_info() -> VersionInfo {
+  VersionInfo {
+    arpabet: env!("CARGO_PKG_VERSION"),
+    types: arpabet_types::VERSION,
+    parser: arpabet_parser::VERSION,
+    cmudict: arpabet_cmudict::VERSION,
+    macros: arpabet_macros::version!(),
+    dictionary_revision: arpabet_cmudict::CMUDICT_REVISION,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn aligned() {
+    let info = version_info();
+    assert!(info.is_aligned());
+    assert_eq!(info.arpabet, env!("CARGO_PKG_VERSION"));
+
+    let mixed = VersionInfo { parser: "1.9.0", ..info };
+    assert!(!mixed.is_aligned());
+    assert_eq!(mixed.to_string(),
+      format!("arpabet {0} (types {0}, parser 1.9.0, cmudict {0}, macros {0}; CMUdict 0.7b)",
+        info.arpabet));
+
+    let mixed = VersionInfo { macros: "2.0.0", ..info };
+    assert!(!mixed.is_aligned());
+  }
+}
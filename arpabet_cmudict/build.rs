@@ -1,21 +1,30 @@
 // Copyright (c) 2020 Brandon Thomas <bt@brand.io>, <echelon@gmail.com>
 #[macro_use] extern crate lazy_static;
 
+extern crate arpabet_codegen;
 extern crate arpabet_parser;
 extern crate arpabet_types;
-extern crate phf_codegen;
 
-use arpabet_types::{Arpabet, Phoneme, VowelStress};
-use phf_codegen::Map;
+use arpabet_codegen::CodegenOptions;
+use arpabet_types::casing::fold_case;
+use arpabet_types::index::strip_variant;
+use arpabet_types::Arpabet;
+use std::collections::HashSet;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::Path;
 
 const CMU_DICT_TEXT : &'static str = include_str!("./cmudict/cmudict-0.7b");
 
+// Vocabulary kept by the 'mini-dict' feature.
+const BASIC_ENGLISH_TEXT : &str = include_str!("./cmudict/basic-english.txt");
+
+// Names a word list file; only the listed words are embedded. Relative paths
+// are resolved against the 'arpabet_cmudict' crate directory.
+const WORD_LIST_VAR : &str = "ARPABET_CMUDICT_WORD_LIST";
+
 lazy_static! {
-  // NB: Static lifetime is required for use in phf_codegen.
   // TODO: Super inefficient to process this THREE TIMES for codegen. Sigh.
   //  Hopefully builds won't time out.
   static ref CMUDICT : Arpabet = arpabet_parser::load_from_str(CMU_DICT_TEXT)
@@ -23,50 +32,83 @@ lazy_static! {
 }
 
 fn main() {
+  println!("cargo:rerun-if-changed=build.rs");
+  println!("cargo:rerun-if-changed=cmudict/cmudict-0.7b");
+  println!("cargo:rerun-if-changed=cmudict/basic-english.txt");
+  println!("cargo:rerun-if-env-changed={}", WORD_LIST_VAR);
+
   let path = Path::new(&env::var("OUT_DIR").unwrap()).join("codegen.rs");
 
   let mut file = BufWriter::new(File::create(&path).unwrap());
 
-  write!(&mut file, "use arpabet_types::Consonant;\n").unwrap();
-  write!(&mut file, "use arpabet_types::Phoneme;\n").unwrap();
-  write!(&mut file, "use arpabet_types::Vowel;\n").unwrap();
-  write!(&mut file, "use arpabet_types::VowelStress;\n").unwrap();
-  write!(&mut file, "\n").unwrap();
-
-  write!(&mut file, "/// Compile-time CMUdict via codegen (see 'arpabet_cmudict/build.rs')\n").unwrap();
-  write!(&mut file, "pub static CMU_DICT: phf::Map<&'static str, &'static [Phoneme]> = ").unwrap();
-
-  let mut builder : Map<&'static str> = phf_codegen::Map::new();
-
-  for key in CMUDICT.keys() {
-    let polyphone = CMUDICT.get_polyphone(key).unwrap();
-
-    let mut code = String::from("&[");
-
-    for phone in polyphone.iter() {
-      match phone {
-        Phoneme::Consonant(consonant) => {
-          code.push_str(&format!("Phoneme::Consonant(Consonant::{}), ", consonant.to_str()))
-        },
-        Phoneme::Vowel(vowel) => {
-          let vowel_stress = format!("VowelStress::{}", match vowel.get_stress() {
-            VowelStress::UnknownStress => "UnknownStress",
-            VowelStress::NoStress => "NoStress",
-            VowelStress::PrimaryStress => "PrimaryStress",
-            VowelStress::SecondaryStress => "SecondaryStress",
-          });
-          code.push_str(&format!("Phoneme::Vowel(Vowel::{}({})), ", vowel.to_str_stressless(), vowel_stress))
-        },
-      }
-    }
-
-    code.push_str("]");
-
-    builder.entry(&key, &code);
+  // Word lists the dictionary is filtered by; a word must be on all of them.
+  let mut vocabularies : Vec<(String, HashSet<String>)> = Vec::new();
+
+  if env::var_os("CARGO_FEATURE_MINI_DICT").is_some() {
+    vocabularies.push(("basic-english.txt".to_string(), read_word_list(BASIC_ENGLISH_TEXT)));
   }
 
-  let codegen_map = builder.build();
+  if let Some(word_list_path) = env::var_os(WORD_LIST_VAR) {
+    let word_list_path = word_list_path.to_string_lossy().to_string();
+    println!("cargo:rerun-if-changed={}", word_list_path);
+    let text = fs::read_to_string(&word_list_path)
+        .unwrap_or_else(|err| panic!("Can't read {} '{}': {}", WORD_LIST_VAR, word_list_path, err));
+    vocabularies.push((word_list_path, read_word_list(&text)));
+  }
+
+  let options = CodegenOptions {
+    name: "CMU_DICT".to_string(),
+    doc: Some("Compile-time CMUdict via codegen (see 'arpabet_cmudict/build.rs')".to_string()),
+    phoneme_path: "::arpabet_types".to_string(),
+    ..Default::default()
+  };
+
+  // Keep every variant of a listed word, eg. 'the(1)'.
+  let entries = CMUDICT.iter().filter(|(key, _)| {
+    vocabularies.iter().all(|(_, words)| words.contains(strip_variant(key)))
+  });
+
+  let embedded_entries = arpabet_codegen::emit_phf_entries(entries, &options, &mut file)
+      .expect("Must write codegen");
+
+  // Listed words CMUdict has no pronunciation for.
+  let mut missing_words = vocabularies.iter()
+      .flat_map(|(_, words)| words.iter())
+      .filter(|word| !CMUDICT.contains_key(word))
+      .cloned()
+      .collect::<Vec<String>>();
+  missing_words.sort();
+  missing_words.dedup();
+
+  // CMUdict words the filter left out, once each however many variants they have.
+  let mut excluded_words = CMUDICT.keys()
+      .map(strip_variant)
+      .filter(|word| !vocabularies.iter().all(|(_, words)| words.contains(*word)))
+      .collect::<Vec<&str>>();
+  excluded_words.sort();
+  excluded_words.dedup();
+
+  let word_lists = vocabularies.iter()
+      .map(|(name, _)| name.clone())
+      .collect::<Vec<String>>();
+
+  writeln!(&mut file).unwrap();
+  writeln!(&mut file, "/// How the compile-time CMUdict was subset (see 'arpabet_cmudict/build.rs')").unwrap();
+  writeln!(&mut file, "pub static SUBSET_REPORT: SubsetReport = SubsetReport {{").unwrap();
+  writeln!(&mut file, "  word_lists: &{:?},", word_lists).unwrap();
+  writeln!(&mut file, "  total_entries: {},", CMUDICT.len()).unwrap();
+  writeln!(&mut file, "  embedded_entries: {},", embedded_entries).unwrap();
+  writeln!(&mut file, "  missing_words: &{:?},", missing_words).unwrap();
+  writeln!(&mut file, "  excluded_word_list: {:?},", excluded_words.join("\n")).unwrap();
+  writeln!(&mut file, "}};").unwrap();
+}
 
-  write!(&mut file, "{}", codegen_map.to_string()).unwrap();
-  write!(&mut file, ";\n").unwrap();
+/// Read a word list: one word per line, blank lines and '#' comments ignored.
+/// Words are folded to lowercase as dictionary keys are, eg. 'İ' to 'i'.
+fn read_word_list(text: &str) -> HashSet<String> {
+  text.lines()
+      .map(|line| line.trim())
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .map(fold_case)
+      .collect()
 }
@@ -14,7 +14,15 @@
 //!
 //! You shouldn't need to import this crate directly. The **arpabet** crate
 //! includes this transitively.
-//! ```
+//!
+//! With the `mini-dict` feature only a basic English vocabulary of roughly
+//! 3,000 common words is embedded (see 'cmudict/basic-english.txt').
+//!
+//! To embed only the vocabulary a product needs, point the
+//! `ARPABET_CMUDICT_WORD_LIST` environment variable at a word list file (one
+//! word per line, '#' comments allowed) when building. Relative paths are
+//! resolved against this crate's directory. `SUBSET_REPORT` describes the
+//! outcome at runtime.
 
 #[cfg(test)] #[macro_use] extern crate expectest;
 #[cfg(test)] extern crate chrono;
@@ -26,6 +34,53 @@ use arpabet_types::Arpabet;
 
 include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
 
+/// The version of this crate, eg. to check it matches the other arpabet crates.
+pub const VERSION : &str = env!("CARGO_PKG_VERSION");
+
+/// The CMUdict release the embedded dictionary is generated from.
+pub const CMUDICT_REVISION : &str = "0.7b";
+
+/// Describes how the embedded dictionary was narrowed down at compile time.
+/// See `SUBSET_REPORT`.
+#[derive(Clone, Copy, Debug)]
+pub struct SubsetReport {
+  /// Word lists the dictionary was filtered by, empty if the full CMUdict is embedded.
+  pub word_lists: &'static [&'static str],
+  /// Number of entries in the full CMUdict, counting alternate pronunciations.
+  pub total_entries: usize,
+  /// Number of entries embedded, counting alternate pronunciations.
+  pub embedded_entries: usize,
+  /// Listed words that were left out because CMUdict can't pronounce them.
+  pub missing_words: &'static [&'static str],
+  /// CMUdict words that were left out because they aren't listed, sorted and
+  /// separated by newlines. A single string keeps the report small when most
+  /// of the dictionary is left out; see `excluded_words`.
+  pub excluded_word_list: &'static str,
+}
+
+impl SubsetReport {
+  /// Whether the full CMUdict is embedded.
+  pub fn is_complete(&self) -> bool {
+    self.word_lists.is_empty()
+  }
+
+  /// Number of CMUdict entries left out of the embedded dictionary.
+  pub fn excluded_entries(&self) -> usize {
+    self.total_entries - self.embedded_entries
+  }
+
+  /// CMUdict words left out of the embedded dictionary, in sorted order. Each
+  /// word is listed once, without its alternate pronunciation markers.
+  pub fn excluded_words(&self) -> impl Iterator<Item=&'static str> {
+    self.excluded_word_list.lines()
+  }
+
+  /// Whether a CMUdict word was left out of the embedded dictionary.
+  pub fn is_excluded(&self, word: &str) -> bool {
+    self.excluded_words().any(|excluded| excluded == word)
+  }
+}
+
 // TODO: When static constexpr are added to Rust, evaluate this at compile time.
 lazy_static! {
   // Lazily cached copy of the entire CMU arpabet.
@@ -33,8 +88,7 @@ lazy_static! {
 }
 
 /// Load the in-memory CMUdict.
-/// The first call lazily converts an already in-memory phf::Map into wrapped HashMap.
-/// In the future this may be further optimized away.
+/// The compile-time phf::Map is wrapped in place, so no entries are copied.
 pub fn load_cmudict() -> &'static Arpabet {
   &ARPABET_CMU_DICT
 }
@@ -50,14 +104,56 @@ mod tests {
   use expectest::prelude::*;
 
   // NB: Codegen.
-  use super::{CMU_DICT, load_cmudict};
+  use super::{CMU_DICT, CMUDICT_REVISION, SUBSET_REPORT, load_cmudict};
+  #[cfg(not(feature = "mini-dict"))]
   use arpabet_types::{Phoneme, Consonant, Vowel, VowelStress};
 
+  #[cfg(not(feature = "mini-dict"))]
   #[test]
   fn test_cmudict_codegen_length() {
     assert_eq!(CMU_DICT.len(), 133_793);
   }
 
+  #[cfg(feature = "mini-dict")]
+  #[test]
+  fn test_mini_dict_codegen_length() {
+    // Listed words plus their alternate pronunciations.
+    assert!(CMU_DICT.len() >= 3_000);
+    assert!(CMU_DICT.len() < 4_000);
+    assert!(CMU_DICT.contains_key("the(1)"));
+    assert!(!CMU_DICT.contains_key("yoshi"));
+  }
+
+  #[test]
+  fn test_cmudict_revision() {
+    let source = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("cmudict")
+      .join(format!("cmudict-{}", CMUDICT_REVISION));
+    assert!(source.exists(), "{:?}", source);
+  }
+
+  #[test]
+  fn test_subset_report() {
+    assert_eq!(SUBSET_REPORT.total_entries, 133_793);
+    assert_eq!(SUBSET_REPORT.embedded_entries, CMU_DICT.len());
+    assert_eq!(SUBSET_REPORT.excluded_entries(),
+      SUBSET_REPORT.total_entries - CMU_DICT.len());
+
+    if cfg!(feature = "mini-dict") {
+      assert!(SUBSET_REPORT.word_lists.contains(&"basic-english.txt"));
+      assert!(!SUBSET_REPORT.is_complete());
+      assert!(SUBSET_REPORT.is_excluded("yoshi"));
+      assert!(!SUBSET_REPORT.is_excluded("the"));
+    } else {
+      assert_eq!(SUBSET_REPORT.excluded_words().count(), 0);
+    }
+
+    let excluded = SUBSET_REPORT.excluded_words().collect::<Vec<&str>>();
+    assert!(excluded.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(excluded.iter().all(|word| !word.is_empty() && !CMU_DICT.contains_key(word)));
+  }
+
+  #[cfg(not(feature = "mini-dict"))]
   #[test]
   fn test_cmudict_codegen_entries() {
     let expected = vec![
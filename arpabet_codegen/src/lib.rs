@@ -0,0 +1,169 @@
+// Copyright (c) 2015, 2018, 2020 Brandon Thomas <bt@brand.io>
+
+#![deny(dead_code)]
+#![deny(missing_docs)]
+#![deny(unreachable_patterns)]
+#![deny(unused_extern_crates)]
+#![deny(unused_imports)]
+#![deny(unused_qualifications)]
+
+//! This crate bakes dictionaries into static maps at compile time, the way
+//! the **arpabet** crate embeds CMUdict, for crates that ship their own
+//! lexicons. Use it from a build script:
+//!
+//! ```no_run
+//! // build.rs
+//! use std::fs::{self, File};
+//! use std::io::BufWriter;
+//! use std::path::Path;
+//!
+//! let text = fs::read_to_string("lexicon.dict").unwrap();
+//! let path = Path::new(&std::env::var("OUT_DIR").unwrap()).join("lexicon.rs");
+//! let mut file = BufWriter::new(File::create(path).unwrap());
+//! arpabet_codegen::emit_phf(&text, &mut file).unwrap();
+//! println!("cargo:rerun-if-changed=lexicon.dict");
+//! ```
+//!
+//! Then include the generated static and wrap it without copying:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/lexicon.rs"));
+//!
+//! let lexicon = arpabet::Arpabet::from_phf_map(&DICTIONARY);
+//! ```
+//!
+//! The generated code refers to the `arpabet` and `phf` (0.8) crates, so the
+//! crate including it depends on both. See `CodegenOptions` to change the
+//! paths, eg. for crates that depend on `arpabet_types` directly.
+
+use arpabet_types::{Arpabet, ArpabetError, Phoneme, VowelStress};
+use std::io::Write;
+
+/// Options for the generated code.
+#[derive(Clone, Debug)]
+pub struct CodegenOptions {
+  /// The name of the generated static. "DICTIONARY" by default.
+  pub name: String,
+  /// A doc comment for the static, if any.
+  pub doc: Option<String>,
+  /// The path to the phoneme types. "::arpabet::phoneme" by default.
+  pub phoneme_path: String,
+  /// The path to the phf crate. "::phf" by default.
+  pub phf_path: String,
+}
+
+impl Default for CodegenOptions {
+  fn default() -> Self {
+    Self {
+      name: "DICTIONARY".to_string(),
+      doc: None,
+      phoneme_path: "::arpabet::phoneme".to_string(),
+      phf_path: "::phf".to_string(),
+    }
+  }
+}
+
+/// Parse a dictionary in CMUdict format and write it as Rust code declaring a
+/// static `phf::Map<&'static str, &'static [Phoneme]>` named `DICTIONARY`,
+/// ready for `Arpabet::from_phf_map`. Returns the number of entries written.
+pub fn emit_phf<W: Write>(dict_text: &str, writer: &mut W) -> Result<usize, ArpabetError> {
+  emit_phf_with_options(dict_text, &CodegenOptions::default(), writer)
+}
+
+/// Like `emit_phf`, with options for the generated code.
+pub fn emit_phf_with_options<W: Write>(dict_text: &str, options: &CodegenOptions, writer: &mut W)
+  -> Result<usize, ArpabetError>
+{
+  let arpabet : Arpabet = arpabet_parser::load_from_str(dict_text)?;
+  emit_phf_entries(arpabet.iter(), options, writer)
+}
+
+/// Write the entries as Rust code declaring a static map, as `emit_phf` does,
+/// eg. after filtering a parsed dictionary. Keys are written as given,
+/// including any variant marker, eg. "read(1)". Returns the number of
+/// entries written.
+pub fn emit_phf_entries<'a, I, W>(entries: I, options: &CodegenOptions, writer: &mut W)
+  -> Result<usize, ArpabetError>
+  where I: IntoIterator<Item=(&'a str, &'a [Phoneme])>,
+        W: Write,
+{
+  let mut map = phf_codegen::Map::new();
+  map.phf_path(&options.phf_path);
+  let mut count = 0;
+  for (key, polyphone) in entries {
+    map.entry(key, &polyphone_code(polyphone, &options.phoneme_path));
+    count += 1;
+  }
+
+  if let Some(doc) = options.doc.as_ref() {
+    for line in doc.lines() {
+      writeln!(writer, "/// {}", line)?;
+    }
+  }
+  writeln!(writer, "pub static {}: {}::Map<&'static str, &'static [{}::Phoneme]> = {};",
+    options.name, options.phf_path, options.phoneme_path, map.build())?;
+  Ok(count)
+}
+
+/// The Rust expression for a pronunciation as a `&'static [Phoneme]`, with
+/// the phoneme types under the path, eg. "::arpabet::phoneme".
+pub fn polyphone_code(polyphone: &[Phoneme], phoneme_path: &str) -> String {
+  let phonemes = polyphone.iter()
+    .map(|phoneme| match phoneme {
+      Phoneme::Consonant(consonant) => format!("{0}::Phoneme::Consonant({0}::Consonant::{1})",
+        phoneme_path, consonant.to_str()),
+      Phoneme::Vowel(vowel) => {
+        let stress = match vowel.get_stress() {
+          VowelStress::UnknownStress => "UnknownStress",
+          VowelStress::NoStress => "NoStress",
+          VowelStress::PrimaryStress => "PrimaryStress",
+          VowelStress::SecondaryStress => "SecondaryStress",
+        };
+        format!("{0}::Phoneme::Vowel({0}::Vowel::{1}({0}::VowelStress::{2}))",
+          phoneme_path, vowel.to_str_stressless(), stress)
+      },
+    })
+    .collect::<Vec<String>>();
+  format!("&[{}]", phonemes.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn polyphones() {
+    let polyphone = [
+      Phoneme::Consonant(arpabet_types::Consonant::K),
+      Phoneme::Vowel(arpabet_types::Vowel::AE(VowelStress::PrimaryStress)),
+    ];
+    assert_eq!(polyphone_code(&polyphone, "p"), "&[p::Phoneme::Consonant(p::Consonant::K), \
+      p::Phoneme::Vowel(p::Vowel::AE(p::VowelStress::PrimaryStress))]");
+    assert_eq!(polyphone_code(&[], "p"), "&[]");
+  }
+
+  #[test]
+  fn maps() {
+    let mut code = Vec::new();
+    assert_eq!(emit_phf("CAT  K AE1 T\nREAD  R IY1 D\nREAD(1)  R EH1 D\n", &mut code).unwrap(), 3);
+    let code = String::from_utf8(code).unwrap();
+    assert!(code.starts_with("pub static DICTIONARY: \
+      ::phf::Map<&'static str, &'static [::arpabet::phoneme::Phoneme]> = "));
+    assert!(code.contains("(\"read(1)\", &[::arpabet::phoneme::Phoneme::Consonant("));
+    assert!(code.ends_with(";\n"));
+
+    let options = CodegenOptions {
+      name: "LEXICON".to_string(),
+      doc: Some("My lexicon.\nGenerated.".to_string()),
+      phoneme_path: "::arpabet_types".to_string(),
+      phf_path: "phf".to_string(),
+    };
+    let mut code = Vec::new();
+    emit_phf_with_options("CAT  K AE1 T\n", &options, &mut code).unwrap();
+    let code = String::from_utf8(code).unwrap();
+    assert!(code.starts_with("/// My lexicon.\n/// Generated.\npub static LEXICON: phf::Map<"));
+    assert!(code.contains("phf::Map {"));
+
+    assert!(emit_phf("CAT  K AE1 XX\n", &mut Vec::new()).is_err());
+  }
+}
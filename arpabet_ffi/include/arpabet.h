@@ -0,0 +1,77 @@
+#ifndef ARPABET_H
+#define ARPABET_H
+
+/* Generated with cbindgen from arpabet_ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An opaque pronunciation dictionary.
+typedef struct ArpabetDictionary ArpabetDictionary;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Load the embedded CMUdict. This does not copy the dictionary entries.
+// Release the dictionary with `arpabet_free`.
+ArpabetDictionary *arpabet_load_cmudict(void);
+
+// Load a dictionary from a file in the CMUdict format. Returns null if the
+// file can't be read or parsed. Release the dictionary with `arpabet_free`.
+//
+// # Safety
+//
+// `path` must be null or a valid NUL-terminated string.
+ArpabetDictionary *arpabet_load_file(const char *path);
+
+// Load a dictionary from text in the CMUdict format. Returns null if the
+// text can't be parsed. Release the dictionary with `arpabet_free`.
+//
+// # Safety
+//
+// `text` must be null or a valid NUL-terminated string.
+ArpabetDictionary *arpabet_load_str(const char *text);
+
+// Release a dictionary. Null is ignored.
+//
+// # Safety
+//
+// `dictionary` must be null or returned by one of the loading functions, and
+// must not be used afterwards.
+void arpabet_free(ArpabetDictionary *dictionary);
+
+// Number of entries in the dictionary, alternate pronunciations included.
+// Zero for null.
+//
+// # Safety
+//
+// `dictionary` must be null or a live dictionary.
+uintptr_t arpabet_len(const ArpabetDictionary *dictionary);
+
+// Look up the primary pronunciation of a word, eg. "T EH1 S T" for "test".
+// The word is folded to lowercase first, so "Test" finds the same entry.
+// Returns null if the word isn't in the dictionary. Release the result with
+// `arpabet_string_free`.
+//
+// # Safety
+//
+// `dictionary` must be null or a live dictionary, and `word` must be null or
+// a valid NUL-terminated string.
+char *arpabet_lookup(const ArpabetDictionary *dictionary, const char *word);
+
+// Release a string returned by `arpabet_lookup`. Null is ignored.
+//
+// # Safety
+//
+// `string` must be null or returned by `arpabet_lookup`, and must not be used
+// afterwards.
+void arpabet_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ARPABET_H */
@@ -0,0 +1,212 @@
+// Copyright (c) 2015, 2018, 2020 Brandon Thomas <bt@brand.io>
+
+#![deny(dead_code)]
+#![deny(missing_docs)]
+#![deny(unreachable_patterns)]
+#![deny(unused_extern_crates)]
+#![deny(unused_imports)]
+#![deny(unused_qualifications)]
+
+//! C bindings for the **arpabet** crate, for looking up pronunciations from C
+//! and C++ without linking a Rust API. The declarations are in
+//! `include/arpabet.h`, generated with cbindgen (see `cbindgen.toml`).
+//!
+//! Dictionaries are opaque pointers released with `arpabet_free`. Looked up
+//! pronunciations are strings of space-separated ARPABET symbols, eg.
+//! "T EH1 S T", owned by the caller and released with `arpabet_string_free`.
+//! Functions return null when given null pointers or invalid UTF-8, and when
+//! loading or lookup fails.
+
+use arpabet::casing::fold_case;
+use arpabet::{load_cmudict, load_from_file, load_from_str, Arpabet};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+/// An opaque pronunciation dictionary.
+pub struct ArpabetDictionary {
+  arpabet: Arpabet,
+}
+
+/// Load the embedded CMUdict. This does not copy the dictionary entries.
+/// Release the dictionary with `arpabet_free`.
+#[no_mangle]
+pub extern "C" fn arpabet_load_cmudict() -> *mut ArpabetDictionary {
+  into_raw(load_cmudict().clone())
+}
+
+/// Load a dictionary from a file in the CMUdict format. Returns null if the
+/// file can't be read or parsed. Release the dictionary with `arpabet_free`.
+///
+/// # Safety
+///
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_load_file(path: *const c_char) -> *mut ArpabetDictionary {
+  match to_str(path).map(load_from_file) {
+    Some(Ok(arpabet)) => into_raw(arpabet),
+    _ => ptr::null_mut(),
+  }
+}
+
+/// Load a dictionary from text in the CMUdict format. Returns null if the
+/// text can't be parsed. Release the dictionary with `arpabet_free`.
+///
+/// # Safety
+///
+/// `text` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_load_str(text: *const c_char) -> *mut ArpabetDictionary {
+  match to_str(text).map(load_from_str) {
+    Some(Ok(arpabet)) => into_raw(arpabet),
+    _ => ptr::null_mut(),
+  }
+}
+
+/// Release a dictionary. Null is ignored.
+///
+/// # Safety
+///
+/// `dictionary` must be null or returned by one of the loading functions, and
+/// must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_free(dictionary: *mut ArpabetDictionary) {
+  if !dictionary.is_null() {
+    drop(Box::from_raw(dictionary));
+  }
+}
+
+/// Number of entries in the dictionary, alternate pronunciations included.
+/// Zero for null.
+///
+/// # Safety
+///
+/// `dictionary` must be null or a live dictionary.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_len(dictionary: *const ArpabetDictionary) -> usize {
+  dictionary.as_ref().map_or(0, |dictionary| dictionary.arpabet.len())
+}
+
+/// Look up the primary pronunciation of a word, eg. "T EH1 S T" for "test".
+/// The word is folded to lowercase first, so "Test" finds the same entry.
+/// Returns null if the word isn't in the dictionary. Release the result with
+/// `arpabet_string_free`.
+///
+/// # Safety
+///
+/// `dictionary` must be null or a live dictionary, and `word` must be null or
+/// a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_lookup(dictionary: *const ArpabetDictionary, word: *const c_char)
+  -> *mut c_char
+{
+  let (dictionary, word) = match (dictionary.as_ref(), to_str(word)) {
+    (Some(dictionary), Some(word)) => (dictionary, word),
+    _ => return ptr::null_mut(),
+  };
+  match dictionary.arpabet.get_polyphone_str(&fold_case(word)) {
+    // NB: ARPABET symbols never contain NUL.
+    Some(symbols) => CString::new(symbols.join(" ")).map_or(ptr::null_mut(), CString::into_raw),
+    None => ptr::null_mut(),
+  }
+}
+
+/// Release a string returned by `arpabet_lookup`. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or returned by `arpabet_lookup`, and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn arpabet_string_free(string: *mut c_char) {
+  if !string.is_null() {
+    drop(CString::from_raw(string));
+  }
+}
+
+fn into_raw(arpabet: Arpabet) -> *mut ArpabetDictionary {
+  Box::into_raw(Box::new(ArpabetDictionary { arpabet }))
+}
+
+unsafe fn to_str<'a>(string: *const c_char) -> Option<&'a str> {
+  if string.is_null() {
+    return None;
+  }
+  CStr::from_ptr(string).to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lookup(dictionary: *const ArpabetDictionary, word: &str) -> Option<String> {
+    let word = CString::new(word).unwrap();
+    unsafe {
+      let result = arpabet_lookup(dictionary, word.as_ptr());
+      if result.is_null() {
+        return None;
+      }
+      let string = CStr::from_ptr(result).to_str().unwrap().to_string();
+      arpabet_string_free(result);
+      Some(string)
+    }
+  }
+
+  #[test]
+  fn cmudict() {
+    let dictionary = arpabet_load_cmudict();
+    assert!(!dictionary.is_null());
+    assert_eq!(lookup(dictionary, "test"), Some("T EH1 S T".to_string()));
+    assert_eq!(lookup(dictionary, "Test"), Some("T EH1 S T".to_string()));
+    assert_eq!(lookup(dictionary, "qwxzv"), None);
+    unsafe {
+      assert!(arpabet_len(dictionary) > 0);
+      arpabet_free(dictionary);
+    }
+  }
+
+  #[test]
+  fn load_str() {
+    let text = CString::new("HELLO  HH AH0 L OW1\nWORLD  W ER1 L D\n").unwrap();
+    unsafe {
+      let dictionary = arpabet_load_str(text.as_ptr());
+      assert_eq!(arpabet_len(dictionary), 2);
+      assert_eq!(lookup(dictionary, "world"), Some("W ER1 L D".to_string()));
+      arpabet_free(dictionary);
+
+      let invalid = CString::new("HELLO  HH XX0\n").unwrap();
+      assert!(arpabet_load_str(invalid.as_ptr()).is_null());
+
+      let missing = CString::new("/nonexistent/cmudict.dict").unwrap();
+      assert!(arpabet_load_file(missing.as_ptr()).is_null());
+    }
+  }
+
+  #[test]
+  fn null_pointers() {
+    unsafe {
+      assert!(arpabet_load_str(ptr::null()).is_null());
+      assert!(arpabet_load_file(ptr::null()).is_null());
+      assert_eq!(arpabet_len(ptr::null()), 0);
+      assert!(arpabet_lookup(ptr::null(), ptr::null()).is_null());
+      arpabet_free(ptr::null_mut());
+      arpabet_string_free(ptr::null_mut());
+    }
+  }
+
+  #[test]
+  fn header_declares_every_function() {
+    let header = include_str!("../include/arpabet.h");
+    for function in [
+      "arpabet_load_cmudict",
+      "arpabet_load_file",
+      "arpabet_load_str",
+      "arpabet_free",
+      "arpabet_len",
+      "arpabet_lookup",
+      "arpabet_string_free",
+    ].iter() {
+      assert!(header.contains(&format!("{}(", function)), "{} is not in the header", function);
+    }
+  }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2015, 2018, 2020 Brandon Thomas <bt@brand.io>
+
+#![deny(dead_code)]
+#![deny(missing_docs)]
+#![deny(unreachable_patterns)]
+#![deny(unused_extern_crates)]
+#![deny(unused_imports)]
+#![deny(unused_qualifications)]
+
+//! This crate contains the procedural macros for the **arpabet** crate.
+//!
+//! You shouldn't need to import this crate directly. The **arpabet** crate
+//! re-exports its macros.
+
+use arpabet_types::{Phoneme, PHONEME_MAP};
+use proc_macro::{Group, Literal, Span, TokenStream, TokenTree};
+
+/// A pronunciation literal, eg. `arpa!("T EH1 S T")`, checked when compiled.
+/// Expands to a `&'static [Phoneme]`, so it can initialize constants and
+/// statics. Unknown phonemes are compile errors.
+///
+/// ```ignore
+/// use arpabet::arpa;
+/// use arpabet::phoneme::{Consonant, Phoneme};
+///
+/// const TEST : &[Phoneme] = arpa!("T EH1 S T");
+/// assert_eq!(TEST[0], Phoneme::Consonant(Consonant::T));
+/// ```
+#[proc_macro]
+pub fn arpa(input: TokenStream) -> TokenStream {
+  let mut tokens = input.into_iter();
+  let (pronunciation, span) = match (tokens.next(), tokens.next()) {
+    (Some(TokenTree::Literal(literal)), None) => (literal.to_string(), literal.span()),
+    (Some(token), _) => return compile_error("expected a string literal", token.span()),
+    (None, _) => return compile_error("expected a string literal", Span::call_site()),
+  };
+  let expanded = match pronunciation.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+    Some(pronunciation) => expand(pronunciation),
+    None => Err("expected a string literal".to_string()),
+  };
+  match expanded {
+    Ok(code) => with_span(code.parse().expect("Expansion should be valid Rust."), span),
+    Err(message) => compile_error(&message, span),
+  }
+}
+
+/// The version of this crate as a string literal, eg. to check it matches the
+/// other arpabet crates. A procedural macro crate can't export a `VERSION`
+/// constant like they do. See `arpabet::version_info`.
+#[proc_macro]
+pub fn version(input: TokenStream) -> TokenStream {
+  match input.into_iter().next() {
+    None => TokenTree::Literal(Literal::string(env!("CARGO_PKG_VERSION"))).into(),
+    Some(token) => compile_error("expected no arguments", token.span()),
+  }
+}
+
+// The Rust expression for the pronunciation, or a description of why it's
+// invalid. Paths are through the arpabet crate, which re-exports the macro.
+fn expand(pronunciation: &str) -> Result<String, String> {
+  let mut phonemes = Vec::new();
+  for symbol in pronunciation.split_whitespace() {
+    let phoneme = match PHONEME_MAP.get(symbol) {
+      Some(phoneme) => phoneme,
+      None => return Err(format!("unknown phoneme '{}' in \"{}\"", symbol, pronunciation)),
+    };
+    phonemes.push(match phoneme {
+      Phoneme::Consonant(consonant) =>
+        format!("::arpabet::phoneme::Phoneme::Consonant(::arpabet::phoneme::Consonant::{})",
+          consonant.to_str()),
+      Phoneme::Vowel(vowel) =>
+        format!("::arpabet::phoneme::Phoneme::Vowel(::arpabet::phoneme::Vowel::{}(\
+            ::arpabet::phoneme::VowelStress::{:?}))",
+          vowel.to_str_stressless(), vowel.get_stress()),
+    });
+  }
+  if phonemes.is_empty() {
+    return Err("empty pronunciation".to_string());
+  }
+  Ok(format!("{{ let polyphone : &'static [::arpabet::phoneme::Phoneme] = &[{}]; polyphone }}",
+    phonemes.join(", ")))
+}
+
+fn compile_error(message: &str, span: Span) -> TokenStream {
+  let code = format!("compile_error!({:?})", message);
+  with_span(code.parse().expect("Error should be valid Rust."), span)
+}
+
+// Point the tokens at the literal, so errors in them are reported there.
+fn with_span(tokens: TokenStream, span: Span) -> TokenStream {
+  tokens.into_iter()
+    .map(|mut token| {
+      if let TokenTree::Group(group) = token {
+        let mut spanned = Group::new(group.delimiter(), with_span(group.stream(), span));
+        spanned.set_span(span);
+        token = TokenTree::Group(spanned);
+      } else {
+        token.set_span(span);
+      }
+      token
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn expansion() {
+    let code = expand(" HH  AH0 ").unwrap();
+    assert!(code.contains("Phoneme::Consonant(::arpabet::phoneme::Consonant::HH)"));
+    assert!(code.contains("Vowel::AH(::arpabet::phoneme::VowelStress::NoStress)"));
+    assert_eq!(expand("T EH1 S TT"), Err("unknown phoneme 'TT' in \"T EH1 S TT\"".to_string()));
+    assert_eq!(expand(" "), Err("empty pronunciation".to_string()));
+  }
+}
@@ -17,11 +17,18 @@
 
 #[macro_use] extern crate lazy_static;
 
-use arpabet_types::{Arpabet, ArpabetError, Word, Phoneme, PHONEME_MAP, Polyphone};
+use arpabet_types::{Arpabet, ArpabetError, Word, Phoneme, PHONEME_MAP, Polyphone, WithContext};
+use arpabet_types::casing::{CasingMap, fold_case};
+use arpabet_types::metadata::LexiconMetadata;
+use arpabet_types::tokenizer::normalize_apostrophes;
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io;
+use std::io::{BufRead, BufReader, Read};
+
+/// The version of this crate, eg. to check it matches the other arpabet crates.
+pub const VERSION : &str = env!("CARGO_PKG_VERSION");
 
 // TODO: Expose non-cmudict Arpabet-only parser.
 //  We can use this from vocodes.
@@ -32,7 +39,8 @@ lazy_static! {
   // Regex for reading CMU arpabet, or similarly formatted files.
   // Format resembles the following,
   // ABBREVIATE  AH0 B R IY1 V IY0 EY2 T
-  static ref FILE_REGEX : Regex = Regex::new(r"^([\w\-\(\)\.']+)\s+([^\s].*)\s*$")
+  // Apostrophe variants (’ ‘ `) are accepted in words and normalized to (').
+  static ref FILE_REGEX : Regex = Regex::new(r"^([\w\-\(\)\.'’‘`]+)\s+([^\s].*)\s*$")
       .expect("Regex is correct.");
 
   // Comments begin with this preamble.
@@ -40,116 +48,549 @@ lazy_static! {
       .expect("Regex is correct.");
 }
 
+/// Limits on the size of parsed input. Exceeding any of them fails the parse
+/// with `ArpabetError::LimitExceeded`. Use these when parsing untrusted input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseLimits {
+  /// Maximum length of a line in bytes, excluding the line ending.
+  pub max_line_length: usize,
+  /// Maximum number of distinct dictionary entries.
+  pub max_entries: usize,
+  /// Maximum number of phonemes in a single pronunciation.
+  pub max_phonemes_per_word: usize,
+}
+
+impl ParseLimits {
+  /// No limits. This is what the plain `load_*` functions use.
+  pub fn unlimited() -> Self {
+    Self {
+      max_line_length: usize::MAX,
+      max_entries: usize::MAX,
+      max_phonemes_per_word: usize::MAX,
+    }
+  }
+
+  /// Conservative limits for user-supplied files. These comfortably admit the
+  /// full CMUdict, whose longest line is under 100 bytes.
+  pub fn untrusted() -> Self {
+    Self {
+      max_line_length: 1024,
+      max_entries: 500_000,
+      max_phonemes_per_word: 64,
+    }
+  }
+}
+
+impl Default for ParseLimits {
+  fn default() -> Self {
+    Self::unlimited()
+  }
+}
+
+/// How malformed lines are handled while parsing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+  /// Fail on the first malformed line. This is what the plain `load_*`
+  /// functions use.
+  #[default]
+  Strict,
+  /// Skip malformed lines, listing them in the `LoadReport`. Exceeding a
+  /// `ParseLimits` limit still fails the parse.
+  Lenient,
+}
+
+/// A line skipped while parsing leniently.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedLine {
+  /// The line number, counting from 1.
+  pub line_number: usize,
+  /// Why the line was skipped.
+  pub reason: String,
+}
+
+/// What happened while loading a dictionary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+  /// The number of entries loaded. Repeated words are counted once.
+  pub entries: usize,
+  /// The malformed lines skipped, in order. Always empty in strict mode.
+  pub skipped: Vec<SkippedLine>,
+}
+
+#[cfg(feature = "json")]
+impl serde::Serialize for LoadReport {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let skipped = self.skipped.iter()
+      .map(|line| serde_json::json!({ "line_number": line.line_number, "reason": line.reason }))
+      .collect::<Vec<_>>();
+    serde_json::json!({
+      "schema_version": arpabet_types::report_json::REPORT_SCHEMA_VERSION,
+      "entries": self.entries,
+      "skipped": skipped,
+    }).serialize(serializer)
+  }
+}
+
 /// Load a dictionary from string
 /// The file format is expected to match that of
 /// [CMUdict](http://www.speech.cs.cmu.edu/cgi-bin/cmudict).
+/// Metadata comments heading the text, eg. ';;; language: en-US' as written by
+/// `write_dictionary`, are read into `Arpabet::metadata`, here and by the
+/// other `load_*` functions.
 pub fn load_from_str(text: &str) -> Result<Arpabet, ArpabetError> {
-  let mut map : HashMap<Word, Polyphone> = HashMap::new();
-  let mut reader = BufReader::new(text.as_bytes());
+  load_from_str_with_limits(text, &ParseLimits::unlimited())
+}
+
+/// Load a dictionary from string, enforcing the supplied limits.
+pub fn load_from_str_with_limits(text: &str, limits: &ParseLimits)
+  -> Result<Arpabet, ArpabetError>
+{
+  load_from_str_with_mode(text, ParseMode::Strict, limits).map(|(arpabet, _)| arpabet)
+}
 
-  let _r = read_lines(&mut reader, &mut map)?;
+/// Load a dictionary from string in the parse mode, enforcing the supplied
+/// limits. Fails with `ArpabetError::EmptyFile` if no entries are loaded.
+pub fn load_from_str_with_mode(text: &str, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  let mut reader = BufReader::new(text.as_bytes());
+  load(&mut reader, mode, limits, None)
+}
 
-  if map.is_empty() {
-    Err(ArpabetError::EmptyFile)
-  } else {
-    Ok(Arpabet::from_map(map))
-  }
+/// Load a dictionary from string, also returning the original casing of its
+/// words, eg. to restore it on export with `ExportOptions::casing`. Words are
+/// folded to lowercase in the dictionary as usual.
+pub fn load_from_str_with_casing(text: &str) -> Result<(Arpabet, CasingMap), ArpabetError> {
+  let mut reader = BufReader::new(text.as_bytes());
+  let mut casing = CasingMap::new();
+  let (arpabet, _) = load(&mut reader, ParseMode::Strict, &ParseLimits::unlimited(), Some(&mut casing))?;
+  Ok((arpabet, casing))
 }
 
 /// Load a dictionary from file
 /// The file format is expected to match that of
 /// [CMUdict](http://www.speech.cs.cmu.edu/cgi-bin/cmudict).
 pub fn load_from_file(filename: &str) -> Result<Arpabet, ArpabetError> {
-  let f = File::open(filename)?;
-  let mut reader = BufReader::new(f);
+  load_from_file_with_limits(filename, &ParseLimits::unlimited())
+}
+
+/// Load a dictionary from file, enforcing the supplied limits.
+pub fn load_from_file_with_limits(filename: &str, limits: &ParseLimits)
+  -> Result<Arpabet, ArpabetError>
+{
+  load_from_file_with_mode(filename, ParseMode::Strict, limits).map(|(arpabet, _)| arpabet)
+}
+
+/// Load a dictionary from file in the parse mode, enforcing the supplied
+/// limits. Fails with `ArpabetError::EmptyFile` if no entries are loaded.
+pub fn load_from_file_with_mode(filename: &str, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  let load_file = || -> Result<(Arpabet, LoadReport), ArpabetError> {
+    let f = File::open(filename)?;
+    let mut reader = BufReader::new(f);
+    load(&mut reader, mode, limits, None)
+  };
+  load_file().with_context(|| format!("Loading '{}'", filename))
+}
+
+/// Load a dictionary from file, also returning the original casing of its
+/// words. See `load_from_str_with_casing`.
+pub fn load_from_file_with_casing(filename: &str) -> Result<(Arpabet, CasingMap), ArpabetError> {
+  let load_file = || -> Result<(Arpabet, CasingMap), ArpabetError> {
+    let f = File::open(filename)?;
+    let mut reader = BufReader::new(f);
+    let mut casing = CasingMap::new();
+    let (arpabet, _) = load(&mut reader, ParseMode::Strict, &ParseLimits::unlimited(), Some(&mut casing))?;
+    Ok((arpabet, casing))
+  };
+  load_file().with_context(|| format!("Loading '{}'", filename))
+}
+
+/// Load a dictionary from a reader, eg. a socket or decompression stream.
+/// The format is expected to match that of
+/// [CMUdict](http://www.speech.cs.cmu.edu/cgi-bin/cmudict).
+pub fn load_from_reader<R: BufRead>(reader: R) -> Result<Arpabet, ArpabetError> {
+  load_from_reader_with_limits(reader, &ParseLimits::unlimited())
+}
+
+/// Load a dictionary from a reader, enforcing the supplied limits.
+pub fn load_from_reader_with_limits<R: BufRead>(reader: R, limits: &ParseLimits)
+  -> Result<Arpabet, ArpabetError>
+{
+  load_from_reader_with_mode(reader, ParseMode::Strict, limits).map(|(arpabet, _)| arpabet)
+}
+
+/// Load a dictionary from a reader in the parse mode, enforcing the supplied
+/// limits. Fails with `ArpabetError::EmptyFile` if no entries are loaded.
+pub fn load_from_reader_with_mode<R: BufRead>(mut reader: R, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  load(&mut reader, mode, limits, None)
+}
+
+/// Load a dictionary from a file that may be compressed. Gzip and Zstandard
+/// files are recognized by their contents and decompressed while parsing;
+/// other files are read as text. Reading gzip needs the `flate2` feature and
+/// Zstandard the `zstd` feature.
+pub fn load_from_compressed_file(filename: &str) -> Result<Arpabet, ArpabetError> {
+  load_from_compressed_file_with_mode(filename, ParseMode::Strict, &ParseLimits::unlimited())
+    .map(|(arpabet, _)| arpabet)
+}
+
+/// Load a dictionary from a file that may be compressed, in the parse mode,
+/// enforcing the supplied limits on the decompressed text. See
+/// `load_from_compressed_file`.
+pub fn load_from_compressed_file_with_mode(filename: &str, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  let load_file = || -> Result<(Arpabet, LoadReport), ArpabetError> {
+    let mut reader = BufReader::new(File::open(filename)?);
+    let header = reader.fill_buf()?;
+    if header.starts_with(&GZIP_MAGIC) {
+      load_gzip(reader, mode, limits)
+    } else if header.starts_with(&ZSTD_MAGIC) {
+      load_zstd(reader, mode, limits)
+    } else {
+      load(&mut reader, mode, limits, None)
+    }
+  };
+  load_file().with_context(|| format!("Loading '{}'", filename))
+}
+
+/// Parse dictionary entries from a reader one line at a time, leaving the
+/// caller to decide whether to skip, repair or give up on each bad line.
+/// Comments, metadata included, are skipped and repeated words are yielded as
+/// they appear.
+///
+/// Malformed lines yield `ArpabetError::InvalidFormat`, lines that aren't
+/// UTF-8 `ArpabetError::Io`, and iteration continues after either. Failing to
+/// read yields the error and ends the iteration.
+pub fn parse_entries<R: BufRead>(reader: R) -> ParseEntries<R> {
+  parse_entries_with_limits(reader, &ParseLimits::unlimited())
+}
+
+/// Parse dictionary entries from a reader, enforcing the line length and
+/// pronunciation length limits. Lines over a limit yield
+/// `ArpabetError::LimitExceeded` and iteration continues; `max_entries` is not
+/// enforced. See `parse_entries`.
+pub fn parse_entries_with_limits<R: BufRead>(reader: R, limits: &ParseLimits) -> ParseEntries<R> {
+  ParseEntries {
+    reader,
+    limits: limits.clone(),
+    line_number: 0,
+    finished: false,
+  }
+}
+
+/// An iterator over the entries of a dictionary. See `parse_entries`.
+#[derive(Debug)]
+pub struct ParseEntries<R> {
+  reader: R,
+  limits: ParseLimits,
+  line_number: usize,
+  finished: bool,
+}
+
+impl<R> ParseEntries<R> {
+  /// The number of the last line read, counting from 1, eg. to report where an
+  /// error occurred.
+  pub fn line_number(&self) -> usize {
+    self.line_number
+  }
+}
+
+impl<R: BufRead> Iterator for ParseEntries<R> {
+  type Item = Result<(Word, Polyphone), ArpabetError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let read_limit = self.limits.max_line_length.saturating_add(2) as u64;
+    while !self.finished {
+      let mut bytes = Vec::new();
+      match (&mut self.reader).take(read_limit).read_until(b'\n', &mut bytes) {
+        Ok(0) => self.finished = true,
+        Ok(_) => {
+          self.line_number += 1;
+          if line_length(&bytes) > self.limits.max_line_length {
+            if !bytes.ends_with(b"\n") {
+              if let Err(err) = skip_line(&mut self.reader) {
+                self.finished = true;
+                return Some(Err(err.into()));
+              }
+            }
+            return Some(Err(ArpabetError::LimitExceeded {
+              limit: "max_line_length",
+              max: self.limits.max_line_length,
+              line_number: self.line_number,
+            }));
+          }
+
+          let parsed = String::from_utf8(bytes)
+            .map_err(|err| ArpabetError::from(io::Error::new(io::ErrorKind::InvalidData, err)))
+            .and_then(|buffer| parse_line(&buffer, self.line_number, &self.limits));
+          match parsed {
+            Ok(None) => {},
+            Ok(Some(entry)) => return Some(Ok(entry)),
+            Err(err) => return Some(Err(err)),
+          }
+        },
+        Err(err) => {
+          self.finished = true;
+          return Some(Err(err.into()));
+        },
+      }
+    }
+    None
+  }
+}
+
+// Discard the rest of the current line.
+fn skip_line<R: BufRead>(reader: &mut R) -> io::Result<()> {
+  loop {
+    let (used, found) = {
+      let buffer = reader.fill_buf()?;
+      if buffer.is_empty() {
+        return Ok(());
+      }
+      match buffer.iter().position(|b| *b == b'\n') {
+        Some(i) => (i + 1, true),
+        None => (buffer.len(), false),
+      }
+    };
+    reader.consume(used);
+    if found {
+      return Ok(());
+    }
+  }
+}
+
+// The length of a line without its line ending.
+fn line_length(bytes: &[u8]) -> usize {
+  bytes.iter()
+    .rposition(|b| *b != b'\r' && *b != b'\n')
+    .map(|i| i + 1)
+    .unwrap_or(0)
+}
+
+// Leading bytes of gzip and Zstandard data.
+const GZIP_MAGIC : [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC : [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[cfg(feature = "flate2")]
+fn load_gzip<R: BufRead>(reader: R, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  let mut reader = BufReader::new(flate2::bufread::MultiGzDecoder::new(reader));
+  load(&mut reader, mode, limits, None)
+}
+
+#[cfg(not(feature = "flate2"))]
+fn load_gzip<R: BufRead>(_reader: R, _mode: ParseMode, _limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  Err(unsupported_compression("gzip", "flate2"))
+}
+
+#[cfg(feature = "zstd")]
+fn load_zstd<R: BufRead>(reader: R, mode: ParseMode, limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  let mut reader = BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?);
+  load(&mut reader, mode, limits, None)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn load_zstd<R: BufRead>(_reader: R, _mode: ParseMode, _limits: &ParseLimits)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
+  Err(unsupported_compression("Zstandard", "zstd"))
+}
+
+#[cfg(any(not(feature = "flate2"), not(feature = "zstd")))]
+fn unsupported_compression(format: &str, feature: &str) -> ArpabetError {
+  let description = format!("Reading {} files needs the '{}' feature", format, feature);
+  ArpabetError::from(io::Error::new(io::ErrorKind::Unsupported, description))
+}
+
+// Load a dictionary, recording the original casing of its words if a casing
+// map is supplied.
+fn load(reader: &mut dyn BufRead, mode: ParseMode, limits: &ParseLimits, casing: Option<&mut CasingMap>)
+  -> Result<(Arpabet, LoadReport), ArpabetError>
+{
   let mut map : HashMap<Word, Polyphone> = HashMap::new();
+  let mut metadata = LexiconMetadata::default();
+  let mut report = LoadReport::default();
 
-  let _r = read_lines(&mut reader, &mut map)?;
+  read_lines(reader, &mut map, &mut metadata, mode, &mut report, limits, casing)?;
 
   if map.is_empty() {
     Err(ArpabetError::EmptyFile)
   } else {
-    Ok(Arpabet::from_map(map))
+    report.entries = map.len();
+    let mut arpabet = Arpabet::from_map(map);
+    arpabet.set_metadata(metadata);
+    Ok((arpabet, report))
   }
 }
 
-fn read_lines(reader: &mut dyn BufRead, map: &mut HashMap<Word, Vec<Phoneme>>)
+fn read_lines(reader: &mut dyn BufRead,
+              map: &mut HashMap<Word, Vec<Phoneme>>,
+              metadata: &mut LexiconMetadata,
+              mode: ParseMode,
+              report: &mut LoadReport,
+              limits: &ParseLimits,
+              mut casing: Option<&mut CasingMap>)
               -> Result<(), ArpabetError> {
 
-  let mut buffer = String::new();
+  let mut bytes = Vec::new();
   let mut line_count = 1;
 
-  while reader.read_line(&mut buffer)? > 0 {
-    if COMMENT_REGEX.is_match(&buffer) {
-      buffer.clear();
-      line_count += 1;
-      continue;
-    }
+  // Never buffer more than the longest allowed line plus its line ending ("\r\n"),
+  // so oversized lines are rejected without being read into memory. Lines are
+  // read as bytes since the cut may fall inside a multi-byte character.
+  let read_limit = limits.max_line_length.saturating_add(2) as u64;
 
-    match FILE_REGEX.captures(&buffer) {
-      None => return Err(ArpabetError::InvalidFormat {
+  while reader.take(read_limit).read_until(b'\n', &mut bytes)? > 0 {
+    if line_length(&bytes) > limits.max_line_length {
+      return Err(ArpabetError::LimitExceeded {
+        limit: "max_line_length",
+        max: limits.max_line_length,
         line_number: line_count,
-        text: buffer.to_string(),
-      }),
-      Some(caps) => {
+      });
+    }
 
-        let word = match caps.get(1) {
-          None => return Err(ArpabetError::InvalidFormat {
-            line_number: line_count,
-            text: buffer.to_string(),
-          }),
-          Some(m) => m.as_str()
-            .to_lowercase(),
-        };
-
-        let phoneme_tokens = match caps.get(2) {
-          None => return Err(ArpabetError::InvalidFormat {
-            line_number: line_count,
-            text: buffer.to_string(),
-          }),
-          Some(m) => m.as_str()
-            .split(" ")
-            .map(|s| s.to_string().to_uppercase())
-            .collect::<Vec<String>>(),
-        };
-
-        if phoneme_tokens.is_empty() {
-          return Err(ArpabetError::InvalidFormat {
+    let parsed = String::from_utf8(std::mem::take(&mut bytes))
+      .map_err(|err| ArpabetError::from(io::Error::new(io::ErrorKind::InvalidData, err)))
+      .and_then(|buffer| {
+        // Metadata comments, as written by `write_dictionary`, head the file.
+        if map.is_empty() {
+          metadata.read_comment_line(&buffer);
+        }
+        parse_line_form(&buffer, line_count, limits)
+      });
+
+    match parsed {
+      Ok(Some((form, phonemes))) => {
+        let word = fold_case(&form);
+        if map.len() >= limits.max_entries && !map.contains_key(&word) {
+          return Err(ArpabetError::LimitExceeded {
+            limit: "max_entries",
+            max: limits.max_entries,
             line_number: line_count,
-            text: buffer.to_string(),
           });
         }
-
-        let mut phonemes = Vec::new();
-
-        for token in phoneme_tokens {
-          match PHONEME_MAP.get(token.as_str()) {
-            None => {
-              return Err(ArpabetError::InvalidFormat {
-                line_number: line_count,
-                text: buffer.to_string(),
-              });
-            },
-            Some(phoneme) => phonemes.push(phoneme.clone()),
-          }
+        if let Some(casing) = casing.as_deref_mut() {
+          casing.insert(&form);
         }
-
         map.insert(word, phonemes);
       },
+      Ok(None) => {},
+      Err(err @ ArpabetError::InvalidFormat { .. }) | Err(err @ ArpabetError::Io(_))
+        if mode == ParseMode::Lenient =>
+      {
+        report.skipped.push(SkippedLine { line_number: line_count, reason: err.to_string() });
+      },
+      Err(err) => return Err(err),
     }
 
-    buffer.clear();
     line_count += 1;
   }
 
   Ok(())
 }
 
+// Parse a line into an entry. Returns None for comments.
+fn parse_line(buffer: &str, line_count: usize, limits: &ParseLimits)
+  -> Result<Option<(Word, Polyphone)>, ArpabetError>
+{
+  parse_line_form(buffer, line_count, limits)
+    .map(|entry| entry.map(|(form, phonemes)| (fold_case(&form), phonemes)))
+}
+
+// Parse a line into an entry, keeping the word as written rather than folding
+// its case. Returns None for comments.
+fn parse_line_form(buffer: &str, line_count: usize, limits: &ParseLimits)
+  -> Result<Option<(String, Polyphone)>, ArpabetError>
+{
+  if COMMENT_REGEX.is_match(buffer) {
+    return Ok(None);
+  }
+
+  let invalid = || ArpabetError::InvalidFormat {
+    line_number: line_count,
+    text: buffer.to_string(),
+  };
+
+  // Trailing comments, eg. "READ  R EH1 D # past tense", are ignored.
+  let entry = buffer.find(" #").map(|end| &buffer[.. end]).unwrap_or(buffer);
+  let caps = FILE_REGEX.captures(entry).ok_or_else(invalid)?;
+
+  let word = match caps.get(1) {
+    None => return Err(invalid()),
+    Some(m) => normalize_apostrophes(m.as_str()).into_owned(),
+  };
+
+  let phoneme_tokens = match caps.get(2) {
+    None => return Err(invalid()),
+    Some(m) => m.as_str()
+      .split(" ")
+      .map(|s| s.to_string().to_uppercase())
+      .collect::<Vec<String>>(),
+  };
+
+  if phoneme_tokens.is_empty() {
+    return Err(invalid());
+  }
+
+  if phoneme_tokens.len() > limits.max_phonemes_per_word {
+    return Err(ArpabetError::LimitExceeded {
+      limit: "max_phonemes_per_word",
+      max: limits.max_phonemes_per_word,
+      line_number: line_count,
+    });
+  }
+
+  let mut phonemes = Vec::new();
+
+  for token in phoneme_tokens {
+    match PHONEME_MAP.get(token.as_str()) {
+      None => return Err(invalid()),
+      Some(phoneme) => phonemes.push(*phoneme),
+    }
+  }
+
+  Ok(Some((word, phonemes)))
+}
+
 #[cfg(test)]
 mod tests {
+  use crate::ParseLimits;
+  use crate::ParseMode;
+  use crate::load_from_compressed_file;
   use crate::load_from_file;
+  use crate::load_from_reader;
+  use crate::load_from_reader_with_limits;
   use crate::load_from_str;
+  use crate::load_from_str_with_casing;
+  use crate::load_from_str_with_limits;
+  use crate::load_from_str_with_mode;
+  use crate::parse_entries;
+  use crate::parse_entries_with_limits;
   use arpabet_types::ArpabetError;
+  use arpabet_types::export::{ExportOptions, write_dictionary};
+  use arpabet_types::metadata::{LanguageTag, LexiconMetadata};
+  use std::fs::File;
+  use std::io::{BufReader, Cursor, Write};
+  use std::path::PathBuf;
+
+  const TEXT : &str = "DOCTOR  D AA1 K T ER0\nMARIO  M AA1 R IY0 OW0\n";
+
+  // Write a file under the temporary directory, returning its path.
+  fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("arpabet_parser_{}_{}", std::process::id(), name));
+    File::create(&path).and_then(|mut f| f.write_all(contents)).expect("File should write");
+    path
+  }
 
   #[test]
   fn test_load_from_file() {
@@ -171,10 +612,12 @@ mod tests {
 
     match result {
       Ok(_) => panic!("Should not be okay!"),
-      Err(err) => match err {
+      Err(err) => match err.root() {
         ArpabetError::InvalidFormat { line_number, text } => {
-          assert_eq!(line_number, 1);
+          assert_eq!(*line_number, 1);
           assert_eq!(text, "this is not arpabet");
+          assert_eq!(err.to_string(),
+            "Loading './tests/bad_file.txt': Invalid format on line 1: this is not arpabet");
         },
         _ => panic!("Wrong error type!")
       },
@@ -197,6 +640,92 @@ mod tests {
                Some(vec!["M", "AA1", "R", "IY0","OW0"]));
   }
 
+  #[test]
+  fn test_load_from_str_apostrophes() {
+    let text = "DON’T  D OW1 N T\n\
+                CAN`T  K AE1 N T\n\
+                WON'T  W OW1 N T";
+
+    let arpabet = load_from_str(text).expect("Text should load");
+
+    assert_eq!(arpabet.get_polyphone_str("don't"), Some(vec!["D", "OW1", "N", "T"]));
+    assert_eq!(arpabet.get_polyphone_str("can't"), Some(vec!["K", "AE1", "N", "T"]));
+    assert_eq!(arpabet.get_polyphone_str("won't"), Some(vec!["W", "OW1", "N", "T"]));
+    assert_eq!(arpabet.get_polyphone_str("don’t"), None);
+  }
+
+  #[test]
+  fn test_load_from_str_turkish_i() {
+    // As written by tools lowercasing or uppercasing in a Turkish locale.
+    let text = "İNSTALL  IH0 N S T AO1 L\n\
+                ınk  IH1 NG K";
+
+    let arpabet = load_from_str(text).expect("Text should load");
+
+    assert_eq!(arpabet.get_polyphone_str("install"), Some(vec!["IH0", "N", "S", "T", "AO1", "L"]));
+    assert_eq!(arpabet.get_polyphone_str("ink"), Some(vec!["IH1", "NG", "K"]));
+  }
+
+  #[test]
+  fn test_load_from_str_with_casing() {
+    let text = "London  L AH1 N D AH0 N\n\
+                London(1)  L AH1 N D AH0 N\n\
+                NASA  N AE1 S AH0\n\
+                doctor  D AA1 K T ER0";
+
+    let (arpabet, casing) = load_from_str_with_casing(text).expect("Text should load");
+
+    assert_eq!(arpabet.get_polyphone_str("london"), Some(vec!["L", "AH1", "N", "D", "AH0", "N"]));
+    assert_eq!(casing.len(), 2);
+    assert_eq!(casing.restore("london(1)"), "London(1)");
+    assert_eq!(casing.restore("nasa"), "NASA");
+    assert_eq!(casing.restore("doctor"), "doctor");
+
+    let options = ExportOptions { casing: Some(&casing), ..Default::default() };
+    let mut output = Vec::new();
+    write_dictionary(&arpabet, &mut output, &options).expect("Dictionary should write");
+    let output = String::from_utf8(output).expect("Output should be UTF-8");
+    assert!(output.contains("NASA  N AE1 S AH0\n"));
+    assert!(output.contains("London  L AH1 N D AH0 N\n"));
+  }
+
+  #[test]
+  fn test_load_from_str_metadata() {
+    let mut arpabet = load_from_str(TEXT).expect("Text should load");
+    assert!(arpabet.metadata().is_empty());
+
+    arpabet.set_metadata(LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").expect("Tag should parse")),
+      version: Some("0.7b".to_string()),
+      license: Some("BSD".to_string()),
+    });
+    let mut output = Vec::new();
+    write_dictionary(&arpabet, &mut output, &ExportOptions::default())
+      .expect("Dictionary should write");
+    let output = String::from_utf8(output).expect("Output should be UTF-8");
+
+    let reloaded = load_from_str(&output).expect("Output should load");
+    assert_eq!(reloaded.metadata(), arpabet.metadata());
+    assert_eq!(reloaded.len(), 2);
+
+    // Only comments heading the file are metadata.
+    let text = ";;; version: 1\nDOCTOR  D AA1 K T ER0\n;;; license: BSD\n";
+    let arpabet = load_from_str(text).expect("Text should load");
+    assert_eq!(arpabet.metadata().version.as_deref(), Some("1"));
+    assert_eq!(arpabet.metadata().license, None);
+  }
+
+  #[test]
+  fn test_load_from_str_trailing_comments() {
+    let text = "READ  R IY1 D # present\n\
+                READ(1)  R EH1 D # v1a2b3c4d";
+
+    let arpabet = load_from_str(text).expect("Text should load");
+
+    assert_eq!(arpabet.get_polyphone_str("read"), Some(vec!["R", "IY1", "D"]));
+    assert_eq!(arpabet.get_polyphone_str("read(1)"), Some(vec!["R", "EH1", "D"]));
+  }
+
   #[test]
   fn test_load_from_str_error() {
     let text = "DOCTOR  D AA1 K T ER0\n\
@@ -214,4 +743,187 @@ mod tests {
       }
     }
   }
+
+  #[test]
+  fn test_load_from_str_limits() {
+    let text = "DOCTOR  D AA1 K T ER0\n\
+                MARIO  M AA1 R IY0 OW0\n\
+                MARIO  M AA1 R IY0 OW0\n\
+                LUIGI  L UW0 IY1 JH IY0\n";
+
+    let limits = ParseLimits::untrusted();
+    assert!(load_from_str_with_limits(text, &limits).is_ok());
+
+    let check = |limits: ParseLimits, expected_limit: &str, expected_line: usize| {
+      match load_from_str_with_limits(text, &limits) {
+        Err(ArpabetError::LimitExceeded { limit, line_number, .. }) => {
+          assert_eq!(limit, expected_limit);
+          assert_eq!(line_number, expected_line);
+        },
+        _ => panic!("Should have exceeded {}", expected_limit),
+      }
+    };
+
+    check(ParseLimits { max_line_length: 21, ..ParseLimits::untrusted() }, "max_line_length", 2);
+    check(ParseLimits { max_entries: 2, ..ParseLimits::untrusted() }, "max_entries", 4);
+    check(ParseLimits { max_phonemes_per_word: 4, ..ParseLimits::untrusted() },
+      "max_phonemes_per_word", 1);
+  }
+
+  #[test]
+  fn test_load_from_str_oversized_line() {
+    let text = format!("{}  AH0\n", "É".repeat(100_000));
+    let limits = ParseLimits { max_line_length: 1024, ..ParseLimits::untrusted() };
+
+    match load_from_str_with_limits(&text, &limits) {
+      Err(ArpabetError::LimitExceeded { limit: "max_line_length", line_number: 1, .. }) => {},
+      _ => panic!("Should have exceeded max_line_length"),
+    }
+  }
+
+  #[test]
+  fn test_load_from_str_lenient() {
+    let text = "DOCTOR  D AA1 K T ER0\n\
+                WAT    \n\
+                ;;; A comment\n\
+                MARIO  M AA1 R IY0 XX0\n\
+                LUIGI  L UW0 IY1 JH IY0\n";
+
+    assert!(load_from_str_with_mode(text, ParseMode::Strict, &ParseLimits::unlimited()).is_err());
+
+    let (arpabet, report) = load_from_str_with_mode(text, ParseMode::Lenient, &ParseLimits::unlimited())
+      .expect("Text should load");
+
+    assert_eq!(arpabet.len(), 2);
+    assert_eq!(arpabet.get_polyphone_str("luigi"), Some(vec!["L", "UW0", "IY1", "JH", "IY0"]));
+    assert_eq!(report.entries, 2);
+    assert_eq!(report.skipped.iter().map(|s| s.line_number).collect::<Vec<usize>>(), vec![2, 4]);
+    assert_eq!(report.skipped[1].reason, "Invalid format on line 4: MARIO  M AA1 R IY0 XX0\n");
+
+    // Limits still apply.
+    let limits = ParseLimits { max_entries: 1, ..ParseLimits::untrusted() };
+    assert!(load_from_str_with_mode(text, ParseMode::Lenient, &limits).is_err());
+
+    match load_from_str_with_mode("WAT\n", ParseMode::Lenient, &ParseLimits::unlimited()) {
+      Err(ArpabetError::EmptyFile) => {},
+      _ => panic!("Should have been empty"),
+    }
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn test_load_report_json() {
+    let text = "DOCTOR  D AA1 K T ER0\n\
+                WAT    \n";
+
+    let (_, report) = load_from_str_with_mode(text, ParseMode::Lenient, &ParseLimits::unlimited())
+      .expect("Text should load");
+
+    let value = serde_json::to_value(&report).expect("Report should serialize");
+    assert_eq!(value["schema_version"], 1);
+    assert_eq!(value["entries"], 1);
+    assert_eq!(value["skipped"][0]["line_number"], 2);
+    assert_eq!(value["skipped"][0]["reason"], report.skipped[0].reason.as_str());
+  }
+
+  #[test]
+  fn test_load_from_reader() {
+    let text = "DOCTOR  D AA1 K T ER0\n\
+                MARIO  M AA1 R IY0 OW0\n";
+
+    let arpabet = load_from_reader(Cursor::new(text)).expect("Text should load");
+    assert_eq!(arpabet.get_polyphone_str("mario"), Some(vec!["M", "AA1", "R", "IY0", "OW0"]));
+
+    let file = File::open("./tests/file_load_test.txt").expect("File should open");
+    let arpabet = load_from_reader(BufReader::new(file)).expect("File should load");
+    assert!(arpabet.get_polyphone_str("pikachu").is_some());
+
+    let limits = ParseLimits { max_entries: 1, ..ParseLimits::untrusted() };
+    assert!(load_from_reader_with_limits(text.as_bytes(), &limits).is_err());
+    assert!(load_from_reader(Cursor::new("")).is_err());
+  }
+
+  #[test]
+  fn test_load_from_compressed_file_plain() {
+    let path = temp_file("plain.txt", TEXT.as_bytes());
+    let arpabet = load_from_compressed_file(path.to_str().unwrap()).expect("File should load");
+    assert_eq!(arpabet.len(), 2);
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[cfg(feature = "flate2")]
+  #[test]
+  fn test_load_from_compressed_file_gzip() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(TEXT.as_bytes()).unwrap();
+    let path = temp_file("dict.gz", &encoder.finish().unwrap());
+
+    let arpabet = load_from_compressed_file(path.to_str().unwrap()).expect("File should load");
+    assert_eq!(arpabet.get_polyphone_str("mario"), Some(vec!["M", "AA1", "R", "IY0", "OW0"]));
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[cfg(feature = "zstd")]
+  #[test]
+  fn test_load_from_compressed_file_zstd() {
+    let path = temp_file("dict.zst", &zstd::encode_all(TEXT.as_bytes(), 0).unwrap());
+
+    let arpabet = load_from_compressed_file(path.to_str().unwrap()).expect("File should load");
+    assert_eq!(arpabet.get_polyphone_str("doctor"), Some(vec!["D", "AA1", "K", "T", "ER0"]));
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[cfg(not(feature = "zstd"))]
+  #[test]
+  fn test_load_from_compressed_file_unsupported() {
+    let path = temp_file("unsupported.zst", &[0x28, 0xb5, 0x2f, 0xfd, 0, 0]);
+    match load_from_compressed_file(path.to_str().unwrap()) {
+      Err(err) => assert!(err.to_string().contains("needs the 'zstd' feature")),
+      Ok(_) => panic!("Should not load without the zstd feature"),
+    }
+    let _ = std::fs::remove_file(path);
+  }
+
+  #[test]
+  fn test_parse_entries() {
+    let text = ";;; comment\n\
+                DOCTOR  D AA1 K T ER0\n\
+                BROKEN  XX\n\
+                MARIO  M AA1 R IY0 OW0\n";
+
+    let mut entries = parse_entries(text.as_bytes());
+    let (word, polyphone) = entries.next().unwrap().unwrap();
+    assert_eq!(word, "doctor");
+    assert_eq!(polyphone.len(), 5);
+    assert_eq!(entries.line_number(), 2);
+
+    match entries.next() {
+      Some(Err(ArpabetError::InvalidFormat { line_number, text })) => {
+        assert_eq!(line_number, 3);
+        assert_eq!(text.trim(), "BROKEN  XX");
+      },
+      other => panic!("Unexpected: {:?}", other),
+    }
+
+    assert_eq!(entries.next().unwrap().unwrap().0, "mario");
+    assert!(entries.next().is_none());
+    assert!(entries.next().is_none());
+  }
+
+  #[test]
+  fn test_parse_entries_recovers() {
+    let mut bytes = b"DOCTOR  D AA1 K T ER0\n".to_vec();
+    bytes.extend_from_slice(&[b'X', 0xff, b' ', b' ', b'D', b'\n']);
+    bytes.extend_from_slice(format!("{}  D\n", "A".repeat(100)).as_bytes());
+    bytes.extend_from_slice(b"MARIO  M AA1 R IY0 OW0");
+
+    let limits = ParseLimits { max_line_length: 50, ..ParseLimits::unlimited() };
+    let results = parse_entries_with_limits(Cursor::new(bytes), &limits).collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(ArpabetError::Io(_))));
+    assert!(matches!(results[2], Err(ArpabetError::LimitExceeded { line_number: 3, .. })));
+    assert_eq!(results[3].as_ref().unwrap().0, "mario");
+  }
 }
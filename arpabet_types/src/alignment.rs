@@ -0,0 +1,282 @@
+//! This module aligns spellings with pronunciations, pairing each chunk of
+//! letters with the phonemes it spells, eg. "knight" as kn|igh|t ->
+//! N|AY1|T, and exports the whole dictionary aligned, eg. to train a neural
+//! grapheme-to-phoneme model on exactly the lexicon deployed.
+//!
+//! Chunks are one to four letters spelling zero to two phonemes. The aligner
+//! prefers known English spellings, eg. "ph" -> F or a vowel letter -> a
+//! vowel, and otherwise pairs single letters with single phonemes. Letters
+//! may be silent, but every phoneme must be spelled, so entries such as
+//! abbreviations, eg. 'mr' -> M IH1 S T ER0, have no alignment.
+
+use crate::casing::fold_case;
+use crate::error::ArpabetError;
+use crate::export::{ordered_entries, ExportOptions};
+use crate::index::strip_variant;
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Letters and the phonemes they spell, one step of an alignment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlignedChunk {
+  /// The letters, lowercase.
+  pub graphemes: String,
+  /// The phonemes spelled, empty for silent letters.
+  pub phonemes: Polyphone,
+}
+
+/// The file formats alignments are exported in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlignmentFormat {
+  /// One line per entry: the word, the letter chunks and the phoneme chunks,
+  /// separated by tabs. Chunks are separated by '|', the phonemes of a chunk
+  /// by ':', and silent letters spell '_', eg. `box\tb|o|x\tB|AA1|K:S`.
+  #[default]
+  Tsv,
+  /// One JSON object per line, eg.
+  /// `{"word":"box","graphemes":["b","o","x"],"phonemes":[["B"],["AA1"],["K","S"]]}`.
+  Jsonl,
+}
+
+// Spellings and the stressless phonemes they commonly spell, besides the
+// vowel letters; see `chunk_cost`.
+const SPELLINGS : [(&str, &str); 98] = [
+  ("b", "B"), ("b", ""), ("bb", "B"), ("c", "K"), ("c", "S"), ("c", "CH"), ("c", "SH"),
+  ("cc", "K"), ("cc", "K S"), ("ch", "CH"), ("ch", "K"), ("ch", "SH"), ("ck", "K"), ("d", "D"),
+  ("d", "T"), ("d", "JH"), ("dd", "D"), ("dg", "JH"), ("dge", "JH"), ("eau", "OW"), ("f", "F"),
+  ("ff", "F"), ("g", "G"), ("g", "JH"), ("g", "ZH"), ("g", ""), ("gg", "G"), ("gh", "G"),
+  ("gh", "F"), ("gh", ""), ("gn", "N"), ("h", "HH"), ("h", ""), ("igh", "AY"), ("j", "JH"),
+  ("j", "Y"), ("j", "HH"), ("k", "K"), ("k", ""), ("kn", "N"), ("l", "L"), ("l", "EL"),
+  ("le", "AH L"), ("le", "EL"), ("ll", "L"), ("m", "M"), ("m", "EM"), ("mm", "M"), ("n", "N"),
+  ("n", "NG"), ("n", "EN"), ("ng", "NG"), ("nn", "N"), ("o", "W AH"), ("ough", "AO"),
+  ("ough", "OW"), ("ough", "UW"), ("p", "P"), ("ph", "F"), ("pp", "P"), ("q", "K"),
+  ("qu", "K W"), ("qu", "K"), ("r", "R"), ("r", "ER"), ("rr", "R"), ("s", "S"), ("s", "Z"),
+  ("s", "SH"), ("s", "ZH"), ("sh", "SH"), ("ss", "S"), ("t", "T"), ("t", "SH"), ("t", "CH"),
+  ("t", "DX"), ("t", ""), ("tch", "CH"), ("th", "TH"), ("th", "DH"), ("tt", "T"), ("u", "Y UW"),
+  ("u", "Y AH"), ("u", "Y UH"), ("u", "Y ER"), ("u", "W"), ("v", "V"), ("w", "W"), ("w", ""),
+  ("wh", "W"), ("wh", "HH"), ("wr", "R"), ("x", "K S"), ("x", "G Z"), ("x", "Z"), ("y", "Y"),
+  ("z", "Z"), ("zz", "Z"),
+];
+
+/// Align a word's spelling with its pronunciation. Returns None if no
+/// alignment spells every phoneme. See the module documentation.
+pub fn align(word: &str, polyphone: &[Phoneme]) -> Option<Vec<AlignedChunk>> {
+  let letters : Vec<char> = fold_case(word).chars().collect();
+  let (n, m) = (letters.len(), polyphone.len());
+
+  // best[i][j] is the cheapest alignment of the first i letters with the first
+  // j phonemes, and the sizes of its last chunk.
+  let mut best : Vec<Vec<Option<(f64, usize, usize)>>> = vec![vec![None; m + 1]; n + 1];
+  best[0][0] = Some((0.0, 0, 0));
+  for i in 0 .. n {
+    for j in 0 ..= m {
+      let cost = match best[i][j] {
+        Some((cost, _, _)) => cost,
+        None => continue,
+      };
+      for letter_count in 1 ..= (n - i).min(4) {
+        let graphemes : String = letters[i .. i + letter_count].iter().collect();
+        for phoneme_count in 0 ..= (m - j).min(2) {
+          let chunk_cost = match chunk_cost(&graphemes, &polyphone[j .. j + phoneme_count]) {
+            Some(chunk_cost) => cost + 1.0 + chunk_cost,
+            None => continue,
+          };
+          let next = &mut best[i + letter_count][j + phoneme_count];
+          if !matches!(next, Some((cost, _, _)) if *cost <= chunk_cost) {
+            *next = Some((chunk_cost, letter_count, phoneme_count));
+          }
+        }
+      }
+    }
+  }
+
+  best[n][m]?;
+  let mut chunks = Vec::new();
+  let (mut i, mut j) = (n, m);
+  while i > 0 {
+    let (_, letter_count, phoneme_count) = best[i][j]?;
+    chunks.push(AlignedChunk {
+      graphemes: letters[i - letter_count .. i].iter().collect(),
+      phonemes: polyphone[j - phoneme_count .. j].to_vec(),
+    });
+    i -= letter_count;
+    j -= phoneme_count;
+  }
+  chunks.reverse();
+  Some(chunks)
+}
+
+/// Write every dictionary entry aligned, in the format. Entries are written in
+/// the order given by `ordered_entries`, without variant markers, and with
+/// lowercase graphemes whatever the options' casing. Entries without an
+/// alignment are skipped. Returns the number of entries written.
+pub fn write_alignments(arpabet: &Arpabet,
+                        writer: &mut dyn Write,
+                        format: AlignmentFormat,
+                        options: &ExportOptions)
+                        -> Result<usize, ArpabetError> {
+  let mut written = 0;
+  for (word, polyphone) in ordered_entries(arpabet, options) {
+    let word = strip_variant(&word);
+    let chunks = match align(word, polyphone) {
+      Some(chunks) => chunks,
+      None => continue,
+    };
+    match format {
+      AlignmentFormat::Tsv => {
+        let graphemes : Vec<&str> = chunks.iter().map(|chunk| chunk.graphemes.as_str()).collect();
+        let phonemes : Vec<String> = chunks.iter()
+          .map(|chunk| {
+            if chunk.phonemes.is_empty() {
+              "_".to_string()
+            } else {
+              phoneme_strs(&chunk.phonemes).join(":")
+            }
+          })
+          .collect();
+        writeln!(writer, "{}\t{}\t{}", word, graphemes.join("|"), phonemes.join("|"))?;
+      },
+      AlignmentFormat::Jsonl => {
+        let graphemes : Vec<String> = chunks.iter()
+          .map(|chunk| json_string(&chunk.graphemes))
+          .collect();
+        let phonemes : Vec<String> = chunks.iter()
+          .map(|chunk| {
+            let phonemes : Vec<String> = phoneme_strs(&chunk.phonemes).iter()
+              .map(|phoneme| json_string(phoneme))
+              .collect();
+            format!("[{}]", phonemes.join(","))
+          })
+          .collect();
+        writeln!(writer, "{{\"word\":{},\"graphemes\":[{}],\"phonemes\":[{}]}}",
+          json_string(word), graphemes.join(","), phonemes.join(","))?;
+      },
+    }
+    written += 1;
+  }
+  Ok(written)
+}
+
+/// Save every dictionary entry aligned to a file. See `write_alignments`.
+pub fn save_alignments(arpabet: &Arpabet,
+                       filename: &str,
+                       format: AlignmentFormat,
+                       options: &ExportOptions)
+                       -> Result<usize, ArpabetError> {
+  let mut writer = BufWriter::new(File::create(filename)?);
+  let written = write_alignments(arpabet, &mut writer, format, options)?;
+  writer.flush()?;
+  Ok(written)
+}
+
+impl Arpabet {
+  /// Align a word's spelling with its pronunciation in the dictionary. See
+  /// `align`.
+  pub fn get_alignment(&self, word: &str) -> Option<Vec<AlignedChunk>> {
+    align(word, self.get_polyphone_ref(word)?)
+  }
+}
+
+// The cost of a chunk beyond the cost of every chunk, 0 for a known spelling,
+// or None if the letters can't spell the phonemes.
+fn chunk_cost(graphemes: &str, phonemes: &[Phoneme]) -> Option<f64> {
+  let symbols = phonemes.iter()
+    .map(|phoneme| match phoneme {
+      Phoneme::Vowel(vowel) => vowel.to_str_stressless(),
+      Phoneme::Consonant(consonant) => consonant.to_str(),
+    })
+    .collect::<Vec<&str>>()
+    .join(" ");
+  if SPELLINGS.contains(&(graphemes, symbols.as_str())) {
+    return Some(0.0);
+  }
+
+  let is_vowel_letter = |c: char| "aeiouy".contains(c);
+  let letters : Vec<char> = graphemes.chars().collect();
+  let single_vowel = matches!(phonemes, [Phoneme::Vowel(_)]);
+  match (letters.as_slice(), phonemes.len()) {
+    // A vowel letter, or two spelling one vowel, eg. "ea", "ow" or "er".
+    ([a], 1) if is_vowel_letter(*a) && single_vowel => Some(0.0),
+    ([a, b], 1) if is_vowel_letter(*a) && (is_vowel_letter(*b) || "wr".contains(*b))
+      && single_vowel => Some(0.0),
+    // Silent vowel letters, eg. the final "e" of "cake".
+    ([a], 0) if is_vowel_letter(*a) => Some(0.5),
+    // Apostrophes, hyphens and the like spell nothing.
+    ([a], 0) if !a.is_alphabetic() => Some(0.0),
+    // Unusual spellings.
+    ([_], 0) | ([_], 1) => Some(3.0),
+    _ => None,
+  }
+}
+
+fn phoneme_strs(polyphone: &[Phoneme]) -> Vec<&'static str> {
+  polyphone.iter().map(|phoneme| phoneme.to_str()).collect()
+}
+
+// Quote a string as JSON.
+fn json_string(text: &str) -> String {
+  let mut quoted = String::with_capacity(text.len() + 2);
+  quoted.push('"');
+  for c in text.chars() {
+    match c {
+      '"' => quoted.push_str("\\\""),
+      '\\' => quoted.push_str("\\\\"),
+      c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+      c => quoted.push(c),
+    }
+  }
+  quoted.push('"');
+  quoted
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  fn aligned(word: &str, pronunciation: &str) -> Option<String> {
+    align(word, &polyphone(pronunciation)).map(|chunks| {
+      chunks.iter()
+        .map(|chunk| format!("{}={}", chunk.graphemes, phoneme_strs(&chunk.phonemes).join(":")))
+        .collect::<Vec<String>>()
+        .join(" ")
+    })
+  }
+
+  #[test]
+  fn alignments() {
+    assert_eq!(aligned("cat", "K AE1 T"), Some("c=K a=AE1 t=T".to_string()));
+    assert_eq!(aligned("knight", "N AY1 T"), Some("kn=N igh=AY1 t=T".to_string()));
+    assert_eq!(aligned("box", "B AA1 K S"), Some("b=B o=AA1 x=K:S".to_string()));
+    assert_eq!(aligned("cake", "K EY1 K"), Some("c=K a=EY1 k=K e=".to_string()));
+    assert_eq!(aligned("phone", "F OW1 N"), Some("ph=F o=OW1 n=N e=".to_string()));
+    assert_eq!(aligned("Thought", "TH AO1 T"), Some("th=TH ough=AO1 t=T".to_string()));
+    assert_eq!(aligned("bottle", "B AA1 T AH0 L"), Some("b=B o=AA1 tt=T le=AH0:L".to_string()));
+    assert_eq!(aligned("don't", "D OW1 N T"), Some("d=D o=OW1 n=N '= t=T".to_string()));
+    assert_eq!(aligned("mr", "M IH1 S T ER0"), None);
+  }
+
+  #[test]
+  fn export() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("box".to_string(), polyphone("B AA1 K S"));
+    arpa.insert("read".to_string(), polyphone("R IY1 D"));
+    arpa.insert("read(1)".to_string(), polyphone("R EH1 D"));
+    arpa.insert("mr".to_string(), polyphone("M IH1 S T ER0"));
+    assert_eq!(arpa.get_alignment("box").map(|chunks| chunks.len()), Some(3));
+
+    let mut tsv = Vec::new();
+    let options = ExportOptions::default();
+    assert_eq!(write_alignments(&arpa, &mut tsv, AlignmentFormat::Tsv, &options).unwrap(), 3);
+    assert_eq!(String::from_utf8(tsv).unwrap(),
+      "box\tb|o|x\tB|AA1|K:S\nread\tr|ea|d\tR|IY1|D\nread\tr|ea|d\tR|EH1|D\n");
+
+    let mut jsonl = Vec::new();
+    write_alignments(&arpa, &mut jsonl, AlignmentFormat::Jsonl, &options).unwrap();
+    assert_eq!(String::from_utf8(jsonl).unwrap().lines().next(),
+      Some(r#"{"word":"box","graphemes":["b","o","x"],"phonemes":[["B"],["AA1"],["K","S"]]}"#));
+    assert_eq!(json_string("a\"b\\\n"), r#""a\"b\\\u000a""#);
+  }
+}
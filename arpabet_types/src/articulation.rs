@@ -0,0 +1,215 @@
+//! This module classifies phonemes by how they are articulated: consonants by
+//! manner, place and voicing, vowels by height, backness and rounding.
+//!
+//! The classes are the coarse ones of General American English. Diphthongs
+//! are classified by the vowel they start from, eg. AY (as in b_i_te) is a low
+//! central vowel.
+
+use crate::phoneme::{Consonant, Vowel};
+
+/// How the airflow is obstructed to produce a consonant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Manner {
+  /// Complete closure then release: B, D, G, K, P, T and the glottal stop Q.
+  Stop,
+  /// A stop released into a fricative: CH, JH.
+  Affricate,
+  /// Turbulent, narrowed airflow: DH, F, HH, S, SH, TH, V, Z, ZH.
+  Fricative,
+  /// Airflow through the nose: M, N, NG, the syllabic EM and EN, and the
+  /// nasal flap NX.
+  Nasal,
+  /// A single quick tap: DX.
+  Flap,
+  /// Airflow around the sides of the tongue: L and the syllabic EL.
+  Lateral,
+  /// R.
+  Rhotic,
+  /// Vowel-like approximants: W, WH, Y.
+  Glide,
+}
+
+/// Where in the mouth a consonant is produced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Place {
+  /// Both lips: B, EM, M, P.
+  Bilabial,
+  /// Both lips with the back of the tongue raised: W, WH.
+  LabioVelar,
+  /// Lower lip and upper teeth: F, V.
+  Labiodental,
+  /// Tongue and teeth: DH, TH.
+  Dental,
+  /// Tongue and the ridge behind the teeth: D, DX, EL, EN, L, N, NX, R, S, T, Z.
+  Alveolar,
+  /// Tongue just behind the alveolar ridge: CH, JH, SH, ZH.
+  Postalveolar,
+  /// Tongue and hard palate: Y.
+  Palatal,
+  /// Back of the tongue and soft palate: G, K, NG.
+  Velar,
+  /// The glottis: HH, Q.
+  Glottal,
+}
+
+/// How high the tongue is for a vowel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Height {
+  /// IH, IX, IY, UH, UW, UX.
+  High,
+  /// AH, AO, AX, AXR, EH, ER, EY, OW, OY.
+  Mid,
+  /// AA, AE, AW, AY.
+  Low,
+}
+
+/// How far forward the tongue is for a vowel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backness {
+  /// AE, EH, EY, IH, IY.
+  Front,
+  /// AH, AW, AX, AXR, AY, ER, IX, UX.
+  Central,
+  /// AA, AO, OW, OY, UH, UW.
+  Back,
+}
+
+impl Consonant {
+  /// Get the manner of articulation.
+  pub fn manner(&self) -> Manner {
+    match self {
+      Consonant::B | Consonant::D | Consonant::G | Consonant::K | Consonant::P | Consonant::Q
+        | Consonant::T => Manner::Stop,
+      Consonant::CH | Consonant::JH => Manner::Affricate,
+      Consonant::DH | Consonant::F | Consonant::HH | Consonant::S | Consonant::SH | Consonant::TH
+        | Consonant::V | Consonant::Z | Consonant::ZH => Manner::Fricative,
+      Consonant::EM | Consonant::EN | Consonant::M | Consonant::N | Consonant::NG
+        | Consonant::NX => Manner::Nasal,
+      Consonant::DX => Manner::Flap,
+      Consonant::EL | Consonant::L => Manner::Lateral,
+      Consonant::R => Manner::Rhotic,
+      Consonant::W | Consonant::WH | Consonant::Y => Manner::Glide,
+    }
+  }
+
+  /// Get the place of articulation.
+  pub fn place(&self) -> Place {
+    match self {
+      Consonant::B | Consonant::EM | Consonant::M | Consonant::P => Place::Bilabial,
+      Consonant::W | Consonant::WH => Place::LabioVelar,
+      Consonant::F | Consonant::V => Place::Labiodental,
+      Consonant::DH | Consonant::TH => Place::Dental,
+      Consonant::D | Consonant::DX | Consonant::EL | Consonant::EN | Consonant::L | Consonant::N
+        | Consonant::NX | Consonant::R | Consonant::S | Consonant::T | Consonant::Z => Place::Alveolar,
+      Consonant::CH | Consonant::JH | Consonant::SH | Consonant::ZH => Place::Postalveolar,
+      Consonant::Y => Place::Palatal,
+      Consonant::G | Consonant::K | Consonant::NG => Place::Velar,
+      Consonant::HH | Consonant::Q => Place::Glottal,
+    }
+  }
+
+  /// Whether the vocal folds vibrate, eg. for Z but not S.
+  pub fn is_voiced(&self) -> bool {
+    !matches!(self,
+      Consonant::CH | Consonant::F | Consonant::HH | Consonant::K | Consonant::P | Consonant::Q
+        | Consonant::S | Consonant::SH | Consonant::T | Consonant::TH | Consonant::WH)
+  }
+}
+
+impl Vowel {
+  /// Get the tongue height. Diphthongs report their starting vowel.
+  pub fn height(&self) -> Height {
+    match self {
+      Vowel::IH(_) | Vowel::IX(_) | Vowel::IY(_) | Vowel::UH(_) | Vowel::UW(_)
+        | Vowel::UX(_) => Height::High,
+      Vowel::AH(_) | Vowel::AO(_) | Vowel::AX(_) | Vowel::AXR(_) | Vowel::EH(_) | Vowel::ER(_)
+        | Vowel::EY(_) | Vowel::OW(_) | Vowel::OY(_) => Height::Mid,
+      Vowel::AA(_) | Vowel::AE(_) | Vowel::AW(_) | Vowel::AY(_) => Height::Low,
+    }
+  }
+
+  /// Get the tongue backness. Diphthongs report their starting vowel.
+  pub fn backness(&self) -> Backness {
+    match self {
+      Vowel::AE(_) | Vowel::EH(_) | Vowel::EY(_) | Vowel::IH(_) | Vowel::IY(_) => Backness::Front,
+      Vowel::AH(_) | Vowel::AW(_) | Vowel::AX(_) | Vowel::AXR(_) | Vowel::AY(_) | Vowel::ER(_)
+        | Vowel::IX(_) | Vowel::UX(_) => Backness::Central,
+      Vowel::AA(_) | Vowel::AO(_) | Vowel::OW(_) | Vowel::OY(_) | Vowel::UH(_)
+        | Vowel::UW(_) => Backness::Back,
+    }
+  }
+
+  /// Whether the lips are rounded: AO, OW, OY, UH, UW and UX.
+  pub fn is_rounded(&self) -> bool {
+    matches!(self,
+      Vowel::AO(_) | Vowel::OW(_) | Vowel::OY(_) | Vowel::UH(_) | Vowel::UW(_) | Vowel::UX(_))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::{ALL_CONSONANTS, ALL_VOWELS};
+  use crate::phoneme::VowelStress;
+
+  #[test]
+  fn consonants() {
+    assert_eq!(Consonant::P.manner(), Manner::Stop);
+    assert_eq!(Consonant::P.place(), Place::Bilabial);
+    assert!(!Consonant::P.is_voiced());
+
+    assert_eq!(Consonant::ZH.manner(), Manner::Fricative);
+    assert_eq!(Consonant::ZH.place(), Place::Postalveolar);
+    assert!(Consonant::ZH.is_voiced());
+
+    assert_eq!(Consonant::EN.manner(), Manner::Nasal);
+    assert_eq!(Consonant::NG.place(), Place::Velar);
+    assert_eq!(Consonant::DX.manner(), Manner::Flap);
+    assert!(Consonant::DX.is_voiced());
+  }
+
+  #[test]
+  fn vowels() {
+    let stress = VowelStress::PrimaryStress;
+    assert_eq!(Vowel::IY(stress).height(), Height::High);
+    assert_eq!(Vowel::IY(stress).backness(), Backness::Front);
+    assert!(!Vowel::IY(stress).is_rounded());
+
+    assert_eq!(Vowel::UW(stress).backness(), Backness::Back);
+    assert!(Vowel::UW(stress).is_rounded());
+
+    assert_eq!(Vowel::AY(stress).height(), Height::Low);
+    assert_eq!(Vowel::AA(VowelStress::NoStress).height(), Height::Low);
+  }
+
+  #[test]
+  fn voiceless_pairs() {
+    // Voiced and voiceless counterparts share manner and place.
+    let pairs = [
+      (Consonant::P, Consonant::B),
+      (Consonant::T, Consonant::D),
+      (Consonant::K, Consonant::G),
+      (Consonant::F, Consonant::V),
+      (Consonant::TH, Consonant::DH),
+      (Consonant::S, Consonant::Z),
+      (Consonant::SH, Consonant::ZH),
+      (Consonant::CH, Consonant::JH),
+      (Consonant::WH, Consonant::W),
+    ];
+    for (voiceless, voiced) in pairs.iter() {
+      assert!(!voiceless.is_voiced() && voiced.is_voiced());
+      assert_eq!(voiceless.manner(), voiced.manner());
+      assert_eq!(voiceless.place(), voiced.place());
+    }
+
+    let voiceless = ALL_CONSONANTS.iter().filter(|consonant| !consonant.is_voiced()).count();
+    assert_eq!(voiceless, 11);
+  }
+
+  #[test]
+  fn rounded_vowels_are_not_front() {
+    for vowel in ALL_VOWELS.iter().filter(|vowel| vowel.is_rounded()) {
+      assert_ne!(vowel.backness(), Backness::Front, "{:?}", vowel);
+    }
+  }
+}
@@ -0,0 +1,714 @@
+//! This module contains a compact binary dictionary format that loads far
+//! faster than parsing CMUdict text, for caching large custom lexicons.
+//!
+//! Layout (integers little-endian):
+//!
+//! * the magic bytes `BINARY_MAGIC` and a u16 `BINARY_FORMAT_VERSION`
+//! * the metadata language, version and license, each a u8 presence flag
+//!   followed, if present, by a string
+//! * a u32 entry count, then each entry as a string key, a u8 phoneme count,
+//!   and one u8 code per phoneme (see `From<Phoneme> for u8`)
+//!
+//! Strings are a u16 byte length followed by UTF-8 bytes.
+//!
+//! The reverse, rhyme and n-gram indices can be saved alongside, so services
+//! with strict cold-start budgets don't rebuild them on every start. An index
+//! file has the magic bytes `INDEX_MAGIC`, a u16 `INDEX_FORMAT_VERSION`, the
+//! u32 entry count and u64 fingerprint of the dictionary it was built from,
+//! then the reverse, rhyme and n-gram indices. Each is a u32 group count, the
+//! u32 byte offset of each group from the start of the file, and the groups in
+//! order of their phoneme codes, each a u8 phoneme count, one u8 code per
+//! phoneme, a u32 word count and the words as strings.
+//!
+//! `IndexView` reads the groups in place, eg. from a memory-mapped file, so
+//! starting up allocates nothing for them. `Arpabet::read_indexes` instead
+//! copies them into the Arpabet's own indices.
+
+use crate::error::{ArpabetError, WithContext};
+use crate::index::{rhyme_key, NGramIndex, ReverseIndex, RhymeIndex};
+use crate::metadata::{LanguageTag, LexiconMetadata};
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone, Word};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+/// Identifies binary dictionary files.
+pub const BINARY_MAGIC : [u8; 4] = *b"ARPB";
+
+/// Version of the binary format. Data of any other version fails to load
+/// with `ArpabetError::BinaryVersionMismatch`.
+pub const BINARY_FORMAT_VERSION : u16 = 1;
+
+/// Identifies index files.
+pub const INDEX_MAGIC : [u8; 4] = *b"ARPI";
+
+/// Version of the index file format. Data of any other version fails to load
+/// with `ArpabetError::BinaryVersionMismatch`. Version 2 added the n-gram
+/// index, and version 3 the group offsets.
+pub const INDEX_FORMAT_VERSION : u16 = 3;
+
+impl Arpabet {
+  /// Write the dictionary and its metadata in the binary format, entries in
+  /// sorted order. Attached frequencies are not included.
+  pub fn write_binary(&self, writer: &mut dyn Write) -> Result<(), ArpabetError> {
+    writer.write_all(&BINARY_MAGIC)?;
+    writer.write_all(&BINARY_FORMAT_VERSION.to_le_bytes())?;
+
+    let metadata = self.metadata();
+    write_optional_string(writer, metadata.language.as_ref().map(|tag| tag.as_str()))?;
+    write_optional_string(writer, metadata.version.as_deref())?;
+    write_optional_string(writer, metadata.license.as_deref())?;
+
+    let mut entries = self.dictionary.iter().collect::<Vec<(&str, &[Phoneme])>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let count = u32::try_from(entries.len())
+      .map_err(|_| invalid("too many entries".to_string()))?;
+    writer.write_all(&count.to_le_bytes())?;
+
+    for (word, polyphone) in entries {
+      write_string(writer, word)?;
+      let length = u8::try_from(polyphone.len())
+        .map_err(|_| invalid(format!("pronunciation of '{}' is too long", word)))?;
+      writer.write_all(&[length])?;
+      writer.write_all(&codes(polyphone))?;
+    }
+
+    Ok(())
+  }
+
+  /// Save the dictionary to a file in the binary format. See `write_binary`.
+  pub fn save_binary(&self, filename: &str) -> Result<(), ArpabetError> {
+    let save = || -> Result<(), ArpabetError> {
+      let mut writer = BufWriter::new(File::create(filename)?);
+      self.write_binary(&mut writer)?;
+      writer.flush()?;
+      Ok(())
+    };
+    save().with_context(|| format!("Saving '{}'", filename))
+  }
+
+  /// Read a dictionary from data in the binary format.
+  pub fn from_binary(data: &[u8]) -> Result<Arpabet, ArpabetError> {
+    let mut reader = Reader { data, position: 0 };
+
+    if reader.take(BINARY_MAGIC.len())? != BINARY_MAGIC {
+      return Err(invalid("not a binary dictionary".to_string()));
+    }
+
+    let version = u16::from_le_bytes([reader.byte()?, reader.byte()?]);
+    if version != BINARY_FORMAT_VERSION {
+      return Err(ArpabetError::BinaryVersionMismatch {
+        found: version,
+        supported: BINARY_FORMAT_VERSION,
+      });
+    }
+
+    let language = match reader.optional_string()? {
+      Some(tag) => Some(LanguageTag::parse(&tag)?),
+      None => None,
+    };
+    let metadata = LexiconMetadata {
+      language,
+      version: reader.optional_string()?,
+      license: reader.optional_string()?,
+    };
+
+    let count = reader.u32()?;
+    let mut map = HashMap::with_capacity(reader.capacity(count, MIN_ENTRY_LENGTH));
+
+    for _ in 0 .. count {
+      let word = reader.string()?;
+      let length = reader.byte()? as usize;
+      let polyphone = reader.take(length)?
+        .iter()
+        .map(|code| Phoneme::try_from(*code))
+        .collect::<Result<Polyphone, ArpabetError>>()?;
+      map.insert(word, polyphone);
+    }
+
+    if reader.position != data.len() {
+      return Err(invalid(format!("{} trailing bytes", data.len() - reader.position)));
+    }
+
+    let mut arpabet = Arpabet::from_map(map);
+    arpabet.set_metadata(metadata);
+    Ok(arpabet)
+  }
+
+  /// Load a dictionary from a file in the binary format. See `write_binary`.
+  pub fn load_binary(filename: &str) -> Result<Arpabet, ArpabetError> {
+    fs::read(filename)
+      .map_err(ArpabetError::from)
+      .and_then(|data| Arpabet::from_binary(&data))
+      .with_context(|| format!("Loading '{}'", filename))
+  }
+
+  /// Write the reverse, rhyme and n-gram indices, building them first if
+  /// needed.
+  /// Groups are written in sorted order.
+  pub fn write_indexes(&self, writer: &mut dyn Write) -> Result<(), ArpabetError> {
+    writer.write_all(&INDEX_MAGIC)?;
+    writer.write_all(&INDEX_FORMAT_VERSION.to_le_bytes())?;
+
+    let count = u32::try_from(self.len())
+      .map_err(|_| invalid("too many entries".to_string()))?;
+    writer.write_all(&count.to_le_bytes())?;
+    writer.write_all(&self.fingerprint().to_le_bytes())?;
+
+    let mut position = INDEX_HEADER_LENGTH;
+    position = write_groups(writer, position, self.reverse_index().iter())?;
+    position = write_groups(writer, position, self.rhyme_index().iter())?;
+    write_groups(writer, position, self.ngram_index().iter())?;
+    Ok(())
+  }
+
+  /// Save the reverse, rhyme and n-gram indices to a file. See
+  /// `write_indexes`.
+  pub fn save_indexes(&self, filename: &str) -> Result<(), ArpabetError> {
+    let save = || -> Result<(), ArpabetError> {
+      let mut writer = BufWriter::new(File::create(filename)?);
+      self.write_indexes(&mut writer)?;
+      writer.flush()?;
+      Ok(())
+    };
+    save().with_context(|| format!("Saving '{}'", filename))
+  }
+
+  /// Install the reverse, rhyme and n-gram indices from data written by
+  /// `write_indexes`. The groups are copied into owned indices, so the data
+  /// needn't outlive the call, but loading allocates as much as building
+  /// would; see `IndexView` to read them in place instead. Indices that are
+  /// already built are kept. Fails with `ArpabetError::InvalidBinary` if the
+  /// indices were built from a different dictionary.
+  pub fn read_indexes(&self, data: &[u8]) -> Result<(), ArpabetError> {
+    let view = IndexView::new(self, data)?;
+
+    // Any index may already be built; keeping it is equivalent.
+    let _ = self.indexes.reverse.set(ReverseIndex::from_entries(view.reverse.to_map()));
+    let _ = self.indexes.rhymes.set(RhymeIndex::from_groups(view.rhymes.to_map()));
+    let _ = self.indexes.ngrams.set(NGramIndex::from_groups(view.ngrams.to_map()));
+    Ok(())
+  }
+
+  /// Install the reverse, rhyme and n-gram indices from a file. See
+  /// `read_indexes`.
+  pub fn load_indexes(&self, filename: &str) -> Result<(), ArpabetError> {
+    fs::read(filename)
+      .map_err(ArpabetError::from)
+      .and_then(|data| self.read_indexes(&data))
+      .with_context(|| format!("Loading '{}'", filename))
+  }
+
+  // An order-independent hash of every entry, to detect stale index files.
+  fn fingerprint(&self) -> u64 {
+    self.dictionary.iter()
+      .map(|(word, polyphone)| {
+        let bytes = word.bytes()
+          .chain(std::iter::once(0xff))
+          .chain(polyphone.iter().map(|phoneme| phoneme_code(*phoneme)));
+        fnv1a(bytes)
+      })
+      .fold(0u64, |sum, hash| sum.wrapping_add(hash))
+  }
+}
+
+/// The reverse, rhyme and n-gram indices of data written by
+/// `Arpabet::write_indexes`, read in place, eg. from a memory-mapped file.
+/// Opening checks the data in one pass without allocating; lookups then
+/// binary search the groups and decode only the one found.
+#[derive(Clone, Copy, Debug)]
+pub struct IndexView<'a> {
+  reverse: GroupTable<'a>,
+  rhymes: GroupTable<'a>,
+  ngrams: GroupTable<'a>,
+}
+
+impl <'a> IndexView<'a> {
+  /// Open index data written for the dictionary. Fails with
+  /// `ArpabetError::InvalidBinary` if the data is malformed or the indices
+  /// were built from a different dictionary.
+  pub fn new(arpabet: &Arpabet, data: &'a [u8]) -> Result<Self, ArpabetError> {
+    let mut reader = Reader { data, position: 0 };
+
+    if reader.take(INDEX_MAGIC.len())? != INDEX_MAGIC {
+      return Err(invalid("not an index file".to_string()));
+    }
+
+    let version = u16::from_le_bytes([reader.byte()?, reader.byte()?]);
+    if version != INDEX_FORMAT_VERSION {
+      return Err(ArpabetError::BinaryVersionMismatch {
+        found: version,
+        supported: INDEX_FORMAT_VERSION,
+      });
+    }
+
+    let count = reader.u32()? as usize;
+    let fingerprint = reader.u64()?;
+    if count != arpabet.len() || fingerprint != arpabet.fingerprint() {
+      return Err(invalid("indices were built from a different dictionary".to_string()));
+    }
+
+    let view = Self {
+      reverse: GroupTable::read(&mut reader)?,
+      rhymes: GroupTable::read(&mut reader)?,
+      ngrams: GroupTable::read(&mut reader)?,
+    };
+
+    if reader.position != data.len() {
+      return Err(invalid(format!("{} trailing bytes", data.len() - reader.position)));
+    }
+    Ok(view)
+  }
+
+  /// Get the words pronounced exactly as the phonemes, in sorted order. See
+  /// `ReverseIndex::get`.
+  pub fn reverse(&self, phonemes: &[Phoneme]) -> Option<IndexWords<'a>> {
+    self.reverse.get(&codes(phonemes))
+  }
+
+  /// Get the words that rhyme with the pronunciation, in sorted order. See
+  /// `RhymeIndex::get`.
+  pub fn rhymes(&self, polyphone: &[Phoneme]) -> Option<IndexWords<'a>> {
+    rhyme_key(polyphone).and_then(|key| self.rhymes.get(&codes(&key)))
+  }
+
+  /// Get the words whose pronunciation contains the n-gram, in sorted order.
+  /// See `NGramIndex::get`.
+  pub fn ngram(&self, ngram: &[Phoneme]) -> Option<IndexWords<'a>> {
+    self.ngrams.get(&codes(ngram))
+  }
+}
+
+/// The words of an index group, borrowed from the index data. See
+/// `IndexView`.
+#[derive(Clone, Debug)]
+pub struct IndexWords<'a> {
+  data: &'a [u8],
+  position: usize,
+  remaining: usize,
+}
+
+impl <'a> Iterator for IndexWords<'a> {
+  type Item = &'a str;
+
+  fn next(&mut self) -> Option<&'a str> {
+    if self.remaining == 0 {
+      return None;
+    }
+    self.remaining -= 1;
+    // NB: Strings were checked when the view was opened.
+    let mut reader = Reader { data: self.data, position: self.position };
+    let word = reader.str().ok();
+    self.position = reader.position;
+    word
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.remaining, Some(self.remaining))
+  }
+}
+
+impl ExactSizeIterator for IndexWords<'_> {}
+
+// One index of an index file: its groups, in order of their phoneme codes,
+// and the offset of each.
+#[derive(Clone, Copy, Debug)]
+struct GroupTable<'a> {
+  data: &'a [u8],
+  offsets: &'a [u8],
+}
+
+impl <'a> GroupTable<'a> {
+  // Read the table at the reader, checking every group, and skip past it.
+  fn read(reader: &mut Reader<'a>) -> Result<Self, ArpabetError> {
+    let count = reader.u32()? as usize;
+    let length = count.checked_mul(4)
+      .ok_or_else(|| invalid("too many index groups".to_string()))?;
+    let table = Self { data: reader.data, offsets: reader.take(length)? };
+
+    let mut previous : Option<&[u8]> = None;
+    for i in 0 .. count {
+      if table.offset(i) != reader.position {
+        return Err(invalid(format!("misplaced index group at byte {}", reader.position)));
+      }
+      let codes = reader.codes()?;
+      if previous.is_some_and(|previous| previous >= codes) {
+        return Err(invalid(format!("unordered index group at byte {}", reader.position)));
+      }
+      previous = Some(codes);
+      for _ in 0 .. reader.u32()? {
+        reader.str()?;
+      }
+    }
+    Ok(table)
+  }
+
+  fn len(&self) -> usize {
+    self.offsets.len() / 4
+  }
+
+  fn offset(&self, i: usize) -> usize {
+    let bytes = &self.offsets[i * 4 .. i * 4 + 4];
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+  }
+
+  // The phoneme codes and words of the group at the index.
+  fn group(&self, i: usize) -> (&'a [u8], IndexWords<'a>) {
+    // NB: Groups were checked when the table was read.
+    let mut reader = Reader { data: self.data, position: self.offset(i) };
+    let codes = reader.codes().unwrap_or_default();
+    let remaining = reader.u32().unwrap_or_default() as usize;
+    (codes, IndexWords { data: self.data, position: reader.position, remaining })
+  }
+
+  fn get(&self, codes: &[u8]) -> Option<IndexWords<'a>> {
+    let (mut low, mut high) = (0, self.len());
+    while low < high {
+      let middle = low + (high - low) / 2;
+      let (group_codes, words) = self.group(middle);
+      match group_codes.cmp(codes) {
+        Ordering::Less => low = middle + 1,
+        Ordering::Greater => high = middle,
+        Ordering::Equal => return Some(words),
+      }
+    }
+    None
+  }
+
+  // Copy the groups out of the data.
+  fn to_map(self) -> HashMap<Polyphone, Vec<Word>> {
+    (0 .. self.len())
+      .map(|i| {
+        let (codes, words) = self.group(i);
+        let polyphone = codes.iter().filter_map(|code| Phoneme::try_from(*code).ok()).collect();
+        (polyphone, words.map(str::to_string).collect())
+      })
+      .collect()
+  }
+}
+
+pub(crate) fn fnv1a<I: Iterator<Item=u8>>(bytes: I) -> u64 {
+  bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// The fewest bytes an entry takes: an empty key and no phonemes.
+const MIN_ENTRY_LENGTH : usize = 3;
+
+// The bytes before the first index: the magic bytes, version, entry count and
+// fingerprint.
+const INDEX_HEADER_LENGTH : usize = 18;
+
+fn phoneme_code(phoneme: Phoneme) -> u8 {
+  match phoneme {
+    Phoneme::Consonant(consonant) => u8::from(consonant),
+    Phoneme::Vowel(vowel) => u8::from(vowel),
+  }
+}
+
+fn invalid(description: String) -> ArpabetError {
+  ArpabetError::InvalidBinary { description }
+}
+
+fn write_string(writer: &mut dyn Write, text: &str) -> Result<(), ArpabetError> {
+  let length = u16::try_from(text.len())
+    .map_err(|_| invalid(format!("string of {} bytes is too long", text.len())))?;
+  writer.write_all(&length.to_le_bytes())?;
+  writer.write_all(text.as_bytes())?;
+  Ok(())
+}
+
+fn write_optional_string(writer: &mut dyn Write, text: Option<&str>) -> Result<(), ArpabetError> {
+  match text {
+    Some(text) => {
+      writer.write_all(&[1])?;
+      write_string(writer, text)
+    },
+    None => {
+      writer.write_all(&[0])?;
+      Ok(())
+    },
+  }
+}
+
+// Write an index's groups, starting at the position in the file, and return
+// the position after them.
+fn write_groups<'a, I>(writer: &mut dyn Write, position: usize, groups: I)
+  -> Result<usize, ArpabetError>
+  where I: Iterator<Item=(&'a Polyphone, &'a Vec<Word>)>
+{
+  let mut groups = groups.collect::<Vec<(&Polyphone, &Vec<Word>)>>();
+  groups.sort_by_key(|(polyphone, _)| codes(polyphone));
+
+  let count = u32::try_from(groups.len())
+    .map_err(|_| invalid("too many index groups".to_string()))?;
+  writer.write_all(&count.to_le_bytes())?;
+
+  let too_large = || invalid("index file is too large".to_string());
+  let mut offset = position + 4 + 4 * groups.len();
+  for (polyphone, words) in groups.iter() {
+    writer.write_all(&u32::try_from(offset).map_err(|_| too_large())?.to_le_bytes())?;
+    offset += 1 + polyphone.len() + 4 + words.iter().map(|word| 2 + word.len()).sum::<usize>();
+  }
+  u32::try_from(offset).map_err(|_| too_large())?;
+
+  for (polyphone, words) in groups {
+    let length = u8::try_from(polyphone.len())
+      .map_err(|_| invalid("indexed pronunciation is too long".to_string()))?;
+    writer.write_all(&[length])?;
+    writer.write_all(&codes(polyphone))?;
+    let count = u32::try_from(words.len())
+      .map_err(|_| invalid("too many words in an index group".to_string()))?;
+    writer.write_all(&count.to_le_bytes())?;
+    for word in words.iter() {
+      write_string(writer, word)?;
+    }
+  }
+  Ok(offset)
+}
+
+fn codes(polyphone: &[Phoneme]) -> Vec<u8> {
+  polyphone.iter().map(|phoneme| phoneme_code(*phoneme)).collect()
+}
+
+// Reads fields from binary data, failing on truncation.
+struct Reader<'a> {
+  data: &'a [u8],
+  position: usize,
+}
+
+impl <'a> Reader<'a> {
+  fn take(&mut self, length: usize) -> Result<&'a [u8], ArpabetError> {
+    let end = self.position + length;
+    let bytes = self.data.get(self.position .. end)
+      .ok_or_else(|| invalid(format!("truncated at byte {}", self.data.len())))?;
+    self.position = end;
+    Ok(bytes)
+  }
+
+  // Capacity for `count` items of at least `min_length` bytes each, bounded by
+  // the bytes left, so a corrupt count can't force a huge allocation.
+  fn capacity(&self, count: u32, min_length: usize) -> usize {
+    (count as usize).min((self.data.len() - self.position) / min_length)
+  }
+
+  fn byte(&mut self) -> Result<u8, ArpabetError> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn u32(&mut self) -> Result<u32, ArpabetError> {
+    Ok(u32::from_le_bytes([self.byte()?, self.byte()?, self.byte()?, self.byte()?]))
+  }
+
+  fn u64(&mut self) -> Result<u64, ArpabetError> {
+    Ok(u64::from(self.u32()?) | u64::from(self.u32()?) << 32)
+  }
+
+  fn string(&mut self) -> Result<String, ArpabetError> {
+    self.str().map(str::to_string)
+  }
+
+  fn str(&mut self) -> Result<&'a str, ArpabetError> {
+    let length = u16::from_le_bytes([self.byte()?, self.byte()?]) as usize;
+    let position = self.position;
+    std::str::from_utf8(self.take(length)?)
+      .map_err(|_| invalid(format!("invalid UTF-8 at byte {}", position)))
+  }
+
+  // A u8 phoneme count and that many phoneme codes, checked.
+  fn codes(&mut self) -> Result<&'a [u8], ArpabetError> {
+    let length = self.byte()? as usize;
+    let codes = self.take(length)?;
+    for code in codes.iter() {
+      Phoneme::try_from(*code)?;
+    }
+    Ok(codes)
+  }
+
+  fn optional_string(&mut self) -> Result<Option<String>, ArpabetError> {
+    match self.byte()? {
+      0 => Ok(None),
+      1 => Ok(Some(self.string()?)),
+      flag => Err(invalid(format!("invalid presence flag {} at byte {}", flag, self.position - 1))),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::index::NGRAM_LENGTH;
+  use crate::phoneme::Consonant;
+  use crate::testing;
+
+  fn dictionary() -> Arpabet {
+    let mut arpa = testing::arpabet(&[
+      ("read", "R IY1 D"),
+      ("read(1)", "R EH1 D"),
+      ("café", "K AE0 F EY1"),
+    ]);
+    arpa.set_metadata(LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").unwrap()),
+      version: Some("0.7b".to_string()),
+      license: None,
+    });
+    arpa
+  }
+
+  fn encode(arpa: &Arpabet) -> Vec<u8> {
+    let mut data = Vec::new();
+    arpa.write_binary(&mut data).unwrap();
+    data
+  }
+
+  #[test]
+  fn round_trip() {
+    let arpa = dictionary();
+    let data = encode(&arpa);
+    assert_eq!(&data[.. 6], b"ARPB\x01\x00");
+
+    let loaded = Arpabet::from_binary(&data).unwrap();
+    assert_eq!(loaded.len(), 3);
+    for key in arpa.keys() {
+      assert_eq!(loaded.get_polyphone_ref(key), arpa.get_polyphone_ref(key));
+    }
+    assert_eq!(loaded.metadata(), arpa.metadata());
+
+    // Output is deterministic.
+    assert_eq!(encode(&loaded), data);
+  }
+
+  #[test]
+  fn rejects_other_versions() {
+    let mut data = encode(&dictionary());
+    data[4] = 2;
+    match Arpabet::from_binary(&data) {
+      Err(ArpabetError::BinaryVersionMismatch { found: 2, supported: 1 }) => {},
+      other => panic!("Unexpected result: {:?}", other.map(|arpa| arpa.len())),
+    }
+  }
+
+  #[test]
+  fn rejects_malformed_data() {
+    let data = encode(&dictionary());
+
+    assert!(matches!(Arpabet::from_binary(b"CMU DICT"), Err(ArpabetError::InvalidBinary { .. })));
+    assert!(matches!(Arpabet::from_binary(&data[.. data.len() - 1]),
+      Err(ArpabetError::InvalidBinary { .. })));
+
+    let mut trailing = data.clone();
+    trailing.push(0);
+    assert!(matches!(Arpabet::from_binary(&trailing), Err(ArpabetError::InvalidBinary { .. })));
+
+    // An entry count far beyond the data fails rather than allocating for it.
+    let mut corrupt_count = b"ARPB\x01\x00\x00\x00\x00".to_vec();
+    corrupt_count.extend_from_slice(&u32::MAX.to_le_bytes());
+    corrupt_count.extend_from_slice(&[1, 0, b'a', 0]);
+    assert!(matches!(Arpabet::from_binary(&corrupt_count),
+      Err(ArpabetError::InvalidBinary { .. })));
+
+    let mut bad_code = data;
+    let last = bad_code.len() - 1;
+    bad_code[last] = 0;
+    assert!(matches!(Arpabet::from_binary(&bad_code), Err(ArpabetError::UnknownCode { code: 0 })));
+  }
+
+  #[test]
+  fn index_round_trip() {
+    let arpa = dictionary();
+    let mut data = Vec::new();
+    arpa.write_indexes(&mut data).unwrap();
+    assert_eq!(&data[.. 6], b"ARPI\x03\x00");
+
+    let loaded = dictionary();
+    loaded.read_indexes(&data).unwrap();
+    assert!(loaded.built_indexes().reverse);
+    assert!(loaded.built_indexes().rhymes);
+    assert!(loaded.built_indexes().ngrams);
+    assert!(!loaded.built_indexes().phoneme_trie);
+
+    let pronunciation = arpa.get_polyphone_ref("read(1)").unwrap();
+    assert_eq!(loaded.reverse_index().get(pronunciation), arpa.reverse_index().get(pronunciation));
+    assert_eq!(loaded.reverse_index().max_length(), 4);
+    assert_eq!(loaded.rhyme_index().get(pronunciation), Some(&vec!["read".to_string()]));
+    assert_eq!(loaded.rhyme_index().len(), arpa.rhyme_index().len());
+    let ngram = &pronunciation[.. NGRAM_LENGTH];
+    assert_eq!(loaded.ngram_index().get(ngram), arpa.ngram_index().get(ngram));
+    assert_eq!(loaded.ngram_index().len(), arpa.ngram_index().len());
+
+    // Output is deterministic.
+    let mut again = Vec::new();
+    loaded.write_indexes(&mut again).unwrap();
+    assert_eq!(again, data);
+  }
+
+  #[test]
+  fn rejects_stale_indexes() {
+    let mut data = Vec::new();
+    dictionary().write_indexes(&mut data).unwrap();
+
+    let mut changed = dictionary();
+    changed.insert("read".to_string(), testing::polyphone("R EH1 D"));
+    assert!(matches!(changed.read_indexes(&data), Err(ArpabetError::InvalidBinary { .. })));
+    assert!(changed.built_indexes().is_empty());
+
+    let arpa = dictionary();
+    assert!(matches!(arpa.read_indexes(&encode(&arpa)), Err(ArpabetError::InvalidBinary { .. })));
+    assert!(matches!(arpa.read_indexes(&data[.. data.len() - 1]),
+      Err(ArpabetError::InvalidBinary { .. })));
+
+    // Files from before the group offsets are rejected by version.
+    let mut old_version = data.clone();
+    old_version[4] = 2;
+    assert!(matches!(arpa.read_indexes(&old_version),
+      Err(ArpabetError::BinaryVersionMismatch { found: 2, supported: 3 })));
+
+    // A group count far beyond the data fails rather than allocating for it.
+    let mut corrupt_count = b"ARPI\x03\x00".to_vec();
+    corrupt_count.extend_from_slice(&[0; 12]);
+    corrupt_count.extend_from_slice(&u32::MAX.to_le_bytes());
+    corrupt_count.extend_from_slice(&[0, 0]);
+    assert!(matches!(Arpabet::new().read_indexes(&corrupt_count),
+      Err(ArpabetError::InvalidBinary { .. })));
+  }
+
+  #[test]
+  fn index_view() {
+    let arpa = dictionary();
+    let mut data = Vec::new();
+    arpa.write_indexes(&mut data).unwrap();
+
+    let view = IndexView::new(&arpa, &data).unwrap();
+    for (_, polyphone) in arpa.dictionary.iter() {
+      let words = view.reverse(polyphone).unwrap().collect::<Vec<&str>>();
+      assert_eq!(words, *arpa.reverse_index().get(polyphone).unwrap());
+      let words = view.rhymes(polyphone).unwrap().collect::<Vec<&str>>();
+      assert_eq!(words, *arpa.rhyme_index().get(polyphone).unwrap());
+      let ngram = &polyphone[.. NGRAM_LENGTH];
+      let words = view.ngram(ngram).unwrap().collect::<Vec<&str>>();
+      assert_eq!(words, *arpa.ngram_index().get(ngram).unwrap());
+    }
+    assert_eq!(view.reverse(arpa.get_polyphone_ref("read(1)").unwrap()).unwrap().len(), 1);
+    assert!(view.reverse(&[Phoneme::Consonant(Consonant::ZH)]).is_none());
+    assert!(view.rhymes(&[Phoneme::Consonant(Consonant::ZH)]).is_none());
+    // Viewing builds nothing.
+    assert!(dictionary().built_indexes().is_empty());
+
+    assert!(matches!(IndexView::new(&Arpabet::new(), &data), Err(ArpabetError::InvalidBinary { .. })));
+
+    // Groups must be where their offsets say, in order.
+    let first_offset = INDEX_HEADER_LENGTH + 4;
+    let mut misplaced = data.clone();
+    misplaced[first_offset] += 1;
+    assert!(matches!(IndexView::new(&arpa, &misplaced), Err(ArpabetError::InvalidBinary { .. })));
+
+    let first_group = u32::from_le_bytes([data[first_offset], data[first_offset + 1],
+      data[first_offset + 2], data[first_offset + 3]]) as usize;
+    let mut unordered = data.clone();
+    unordered[first_group + 1] = u8::from(Consonant::ZH);
+    assert!(matches!(IndexView::new(&arpa, &unordered), Err(ArpabetError::InvalidBinary { .. })));
+  }
+}
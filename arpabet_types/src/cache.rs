@@ -0,0 +1,280 @@
+//! This module memoizes lookups and transcriptions, for servers that
+//! pronounce the same short phrases over and over. It is only built with the
+//! `cache` feature.
+//!
+//! A `CachedArpabet` owns a dictionary and keeps the most recently used word
+//! pronunciations and sentence transcriptions in bounded LRU caches. It can be
+//! shared between threads.
+
+use crate::error::ArpabetError;
+use crate::extensions::SentenceToken;
+use crate::normalization::LookupOptions;
+use crate::sync::ignore_poison;
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone};
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// A map holding at most `capacity` entries, which evicts the least recently
+/// used entry to make room.
+#[derive(Clone, Debug)]
+pub struct LruCache<K, V> {
+  capacity: usize,
+  entries: HashMap<K, (V, u64)>,
+  // Keys by the tick they were last used at, least recent first.
+  recency: BTreeMap<u64, K>,
+  tick: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
+  /// An empty cache holding at most `capacity` entries. A capacity of zero
+  /// holds nothing.
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      entries: HashMap::new(),
+      recency: BTreeMap::new(),
+      tick: 0,
+    }
+  }
+
+  /// Get an entry, marking it most recently used.
+  pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Eq + Hash + ?Sized
+  {
+    let tick = self.next_tick();
+    let (value, used) = self.entries.get_mut(key)?;
+    let key = self.recency.remove(used).expect("Cache entries have a recency.");
+    self.recency.insert(tick, key);
+    *used = tick;
+    Some(value)
+  }
+
+  /// Add or replace an entry, marking it most recently used. Evicts the least
+  /// recently used entry if the cache is full.
+  pub fn insert(&mut self, key: K, value: V) {
+    if self.capacity == 0 {
+      return;
+    }
+    let tick = self.next_tick();
+    if let Some((_, used)) = self.entries.get(&key) {
+      self.recency.remove(used);
+    } else if self.entries.len() == self.capacity {
+      if let Some((_, evicted)) = self.recency.pop_first() {
+        self.entries.remove(&evicted);
+      }
+    }
+    self.recency.insert(tick, key.clone());
+    self.entries.insert(key, (value, tick));
+  }
+
+  /// The number of entries.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Whether there are no entries.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// The most entries the cache holds.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Remove every entry.
+  pub fn clear(&mut self) {
+    self.entries.clear();
+    self.recency.clear();
+  }
+
+  fn next_tick(&mut self) -> u64 {
+    self.tick += 1;
+    self.tick
+  }
+}
+
+/// How often a `CachedArpabet` found results in its caches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+  /// Requests answered from a cache.
+  pub hits: usize,
+  /// Requests computed and then cached.
+  pub misses: usize,
+}
+
+impl CacheStats {
+  /// The fraction of requests answered from a cache. Zero before any request.
+  pub fn hit_rate(&self) -> f64 {
+    match self.hits + self.misses {
+      0 => 0.0,
+      total => self.hits as f64 / total as f64,
+    }
+  }
+}
+
+/// A dictionary that memoizes pronunciations and transcriptions. See the
+/// module documentation.
+pub struct CachedArpabet {
+  arpabet: Arpabet,
+  lookup_options: LookupOptions,
+  transcribe_options: TranscribeOptions,
+  words: Mutex<LruCache<String, Option<Polyphone>>>,
+  sentences: Mutex<LruCache<String, Vec<SentenceToken>>>,
+  stats: Mutex<CacheStats>,
+}
+
+impl CachedArpabet {
+  /// Cache up to `capacity` words and as many sentences, looked up and
+  /// transcribed with the default options.
+  pub fn new(arpabet: Arpabet, capacity: usize) -> Self {
+    Self {
+      arpabet,
+      lookup_options: LookupOptions::default(),
+      transcribe_options: TranscribeOptions::default(),
+      words: Mutex::new(LruCache::new(capacity)),
+      sentences: Mutex::new(LruCache::new(capacity)),
+      stats: Mutex::new(CacheStats::default()),
+    }
+  }
+
+  /// Normalize words with the options before lookup. Clears the caches.
+  pub fn with_lookup_options(mut self, options: LookupOptions) -> Self {
+    self.lookup_options = options;
+    self.clear();
+    self
+  }
+
+  /// Transcribe sentences with the options. Clears the caches.
+  pub fn with_transcribe_options(mut self, options: TranscribeOptions) -> Self {
+    self.transcribe_options = options;
+    self.clear();
+    self
+  }
+
+  /// The dictionary.
+  pub fn arpabet(&self) -> &Arpabet {
+    &self.arpabet
+  }
+
+  /// Get a word's pronunciation, normalized as by
+  /// `Arpabet::get_polyphone_normalized`. Misses are cached too.
+  pub fn get_polyphone(&self, word: &str) -> Option<Polyphone> {
+    let key = self.lookup_options.normalize(word);
+    if let Some(polyphone) = ignore_poison(self.words.lock()).get(&key) {
+      ignore_poison(self.stats.lock()).hits += 1;
+      return polyphone.clone();
+    }
+    let polyphone = self.arpabet.get_polyphone(&key);
+    ignore_poison(self.words.lock()).insert(key, polyphone.clone());
+    ignore_poison(self.stats.lock()).misses += 1;
+    polyphone
+  }
+
+  /// Transcribe text as `Arpabet::transcribe_sentence_with_options` does.
+  /// Errors are not cached.
+  pub fn transcribe_sentence(&self, text: &str) -> Result<Vec<SentenceToken>, ArpabetError> {
+    if let Some(tokens) = ignore_poison(self.sentences.lock()).get(text) {
+      ignore_poison(self.stats.lock()).hits += 1;
+      return Ok(tokens.clone());
+    }
+    let tokens = self.arpabet.transcribe_sentence_with_options(text, &self.transcribe_options)?;
+    ignore_poison(self.sentences.lock()).insert(text.to_string(), tokens.clone());
+    ignore_poison(self.stats.lock()).misses += 1;
+    Ok(tokens)
+  }
+
+  /// How often results were found in the caches.
+  pub fn stats(&self) -> CacheStats {
+    *ignore_poison(self.stats.lock())
+  }
+
+  /// The number of words and sentences cached.
+  pub fn len(&self) -> (usize, usize) {
+    (ignore_poison(self.words.lock()).len(), ignore_poison(self.sentences.lock()).len())
+  }
+
+  /// Whether nothing is cached.
+  pub fn is_empty(&self) -> bool {
+    self.len() == (0, 0)
+  }
+
+  /// Empty the caches and reset the statistics.
+  pub fn clear(&self) {
+    ignore_poison(self.words.lock()).clear();
+    ignore_poison(self.sentences.lock()).clear();
+    *ignore_poison(self.stats.lock()) = CacheStats::default();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+  use std::sync::Arc;
+  use std::thread;
+
+  #[test]
+  fn lru() {
+    let mut cache = LruCache::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+    assert_eq!(cache.get(&"a"), Some(&1));
+    cache.insert("c", 3);
+    assert_eq!(cache.get(&"b"), None);
+    assert_eq!(cache.get(&"a"), Some(&1));
+    assert_eq!(cache.get(&"c"), Some(&3));
+    cache.insert("a", 4);
+    cache.insert("d", 5);
+    assert_eq!(cache.get(&"c"), None);
+    assert_eq!(cache.get(&"a"), Some(&4));
+    assert_eq!(cache.len(), 2);
+
+    let mut empty = LruCache::new(0);
+    empty.insert("a", 1);
+    assert!(empty.is_empty());
+  }
+
+  #[test]
+  fn memoizes() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+    let cached = CachedArpabet::new(arpa, 10);
+
+    assert_eq!(cached.get_polyphone("Cat"), Some(polyphone("K AE1 T")));
+    assert_eq!(cached.get_polyphone("cat,"), Some(polyphone("K AE1 T")));
+    assert_eq!(cached.get_polyphone("dog"), None);
+    assert_eq!(cached.get_polyphone("dog"), None);
+    assert_eq!(cached.stats(), CacheStats { hits: 2, misses: 2 });
+
+    let tokens = cached.transcribe_sentence("Cat.").unwrap();
+    assert_eq!(tokens, cached.arpabet().transcribe_sentence("Cat.").unwrap());
+    assert_eq!(cached.transcribe_sentence("Cat.").unwrap(), tokens);
+    assert!(cached.transcribe_sentence("Dog.").is_err());
+    assert_eq!(cached.len(), (2, 1));
+    assert_eq!(cached.stats().hit_rate(), 3.0 / 6.0);
+
+    cached.clear();
+    assert!(cached.is_empty());
+    assert_eq!(cached.stats(), CacheStats::default());
+  }
+
+  #[test]
+  fn shared_between_threads() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+    let cached = Arc::new(CachedArpabet::new(arpa, 10));
+
+    let handles : Vec<_> = (0 .. 4).map(|_| {
+      let cached = Arc::clone(&cached);
+      thread::spawn(move || cached.get_polyphone("cat"))
+    }).collect();
+    for handle in handles {
+      assert_eq!(handle.join().unwrap(), Some(polyphone("K AE1 T")));
+    }
+    assert_eq!(cached.stats().hits + cached.stats().misses, 4);
+  }
+}
@@ -0,0 +1,195 @@
+//! This module restores conventional casing to the lowercase words the
+//! dictionary stores, eg. 'london' -> 'London', and folds words to the
+//! lowercase form the dictionary stores them in.
+//!
+//! Keys are folded the same way whatever the system locale. Text from
+//! Turkish-locale tools may spell 'I' and 'i' as dotless 'ı' and dotted 'İ';
+//! English folding maps both to 'i', rather than to 'ı' and "i̇" as Unicode
+//! lowercasing does.
+
+use crate::index::strip_variant;
+use std::collections::HashMap;
+
+/// How words are folded to lowercase for dictionary lookup.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseFolding {
+  /// ASCII lowercasing, with Turkish 'İ' and 'ı' read as 'i'. Other letters
+  /// are lowercased when their lowercase is a single character, eg. 'É' -> 'é'.
+  #[default]
+  English,
+  /// Full Unicode lowercasing, as by `str::to_lowercase`.
+  Unicode,
+}
+
+impl CaseFolding {
+  /// Fold a word to lowercase.
+  pub fn fold(&self, word: &str) -> String {
+    match self {
+      CaseFolding::English => word.chars().map(fold_char).collect(),
+      CaseFolding::Unicode => word.to_lowercase(),
+    }
+  }
+}
+
+/// Fold a word to lowercase for dictionary lookup, with `CaseFolding::English`.
+/// Dictionary keys are folded this way when parsed.
+pub fn fold_case(word: &str) -> String {
+  CaseFolding::English.fold(word)
+}
+
+// Fold a character with `CaseFolding::English`.
+pub(crate) fn fold_char(c: char) -> char {
+  match c {
+    'İ' | 'ı' => 'i',
+    c if c.is_ascii() => c.to_ascii_lowercase(),
+    c => {
+      let mut lowercase = c.to_lowercase();
+      match (lowercase.next(), lowercase.next()) {
+        (Some(folded), None) => folded,
+        _ => c,
+      }
+    },
+  }
+}
+
+/// A map from lowercase words to their conventional casing.
+#[derive(Clone, Debug, Default)]
+pub struct CasingMap {
+  forms: HashMap<String, String>,
+}
+
+impl CasingMap {
+  /// Create an empty casing map.
+  pub fn new() -> Self {
+    Self {
+      forms: HashMap::new(),
+    }
+  }
+
+  /// Build a map from explicitly cased forms, eg. ["London", "NASA", "iPhone"].
+  pub fn from_forms<'a, I: IntoIterator<Item=&'a str>>(forms: I) -> Self {
+    let mut map = CasingMap::new();
+    for form in forms {
+      map.insert(form);
+    }
+    map
+  }
+
+  /// Build a map from running text by choosing the most frequent surface form
+  /// of every word. Sentence-initial words are ignored since their capitalization
+  /// is positional rather than conventional. Ties favor the lowercase form.
+  pub fn from_corpus(text: &str) -> Self {
+    let mut counts : HashMap<String, HashMap<String, usize>> = HashMap::new();
+    let mut sentence_start = true;
+
+    for token in text.split_whitespace() {
+      let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+      if !word.is_empty() && !sentence_start {
+        *counts.entry(fold_case(word))
+          .or_default()
+          .entry(word.to_string())
+          .or_insert(0) += 1;
+      }
+      if !word.is_empty() || token.ends_with(['.', '!', '?']) {
+        sentence_start = token.ends_with(['.', '!', '?']);
+      }
+    }
+
+    let mut map = CasingMap::new();
+    for (lowercase, forms) in counts.into_iter() {
+      let best = forms.into_iter()
+        .max_by(|(a_form, a_count), (b_form, b_count)| {
+          a_count.cmp(b_count)
+            .then_with(|| (*a_form == lowercase).cmp(&(*b_form == lowercase)))
+            .then_with(|| b_form.cmp(a_form))
+        })
+        .map(|(form, _)| form);
+      if let Some(form) = best {
+        if form != lowercase {
+          map.forms.insert(lowercase, form);
+        }
+      }
+    }
+    map
+  }
+
+  /// Record the conventional casing of a word. Alternate pronunciation markers
+  /// are ignored, eg. 'London(1)' records 'London'.
+  pub fn insert(&mut self, form: &str) {
+    let form = strip_variant(form);
+    let lowercase = fold_case(form);
+    if lowercase != form {
+      self.forms.insert(lowercase, form.to_string());
+    }
+  }
+
+  /// Restore the casing of a dictionary key. Unknown words are returned unchanged.
+  /// Alternate pronunciation markers are preserved, eg. 'london(1)' -> 'London(1)'.
+  pub fn restore(&self, word: &str) -> String {
+    let base = strip_variant(word);
+    match self.forms.get(base) {
+      None => word.to_string(),
+      Some(form) => format!("{}{}", form, &word[base.len() ..]),
+    }
+  }
+
+  /// Reports the number of words with non-lowercase casing.
+  pub fn len(&self) -> usize {
+    self.forms.len()
+  }
+
+  /// Whether the map contains no words.
+  pub fn is_empty(&self) -> bool {
+    self.forms.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn folding() {
+    assert_eq!(fold_case("LONDON"), "london");
+    assert_eq!(fold_case("İSTANBUL"), "istanbul");
+    assert_eq!(fold_case("ınk"), "ink");
+    assert_eq!(fold_case("CAFÉ"), "café");
+    assert_eq!(CaseFolding::Unicode.fold("ınk"), "ınk");
+    assert_eq!(CaseFolding::Unicode.fold("İ"), "i\u{307}");
+    assert_eq!(CaseFolding::Unicode.fold("CAFÉ"), "café");
+  }
+
+  #[test]
+  fn from_forms() {
+    let map = CasingMap::from_forms(vec!["London", "NASA", "iPhone", "lowercase"]);
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.restore("london"), "London");
+    assert_eq!(map.restore("nasa"), "NASA");
+    assert_eq!(map.restore("iphone"), "iPhone");
+    assert_eq!(map.restore("lowercase"), "lowercase");
+    assert_eq!(map.restore("london(1)"), "London(1)");
+    assert_eq!(map.restore("paris"), "paris");
+
+    let map = CasingMap::from_forms(vec!["Reading(1)"]);
+    assert_eq!(map.restore("reading"), "Reading");
+  }
+
+  #[test]
+  fn from_corpus() {
+    let text = "We flew to London. London is big. The bus went to London, \
+                then to Paris. Apple makes the iPhone and an apple is a fruit. \
+                I ate an apple.";
+
+    let map = CasingMap::from_corpus(text);
+
+    assert_eq!(map.restore("london"), "London");
+    assert_eq!(map.restore("paris"), "Paris");
+    assert_eq!(map.restore("iphone"), "iPhone");
+    // 'Apple' only occurs sentence-initially.
+    assert_eq!(map.restore("apple"), "apple");
+    // 'The' and 'We' are only capitalized sentence-initially.
+    assert_eq!(map.restore("the"), "the");
+    assert_eq!(map.restore("we"), "we");
+  }
+}
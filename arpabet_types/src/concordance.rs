@@ -0,0 +1,67 @@
+//! This module lists the contexts a phoneme occurs in across the dictionary,
+//! eg. for phonological research or for building inventories of
+//! context-dependent synthesis units.
+
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Word};
+
+impl Arpabet {
+  /// Iterate over every occurrence of the phoneme in the dictionary, yielding
+  /// the dictionary key (with any variant marker, eg. "read(1)"), the position
+  /// of the phoneme in that pronunciation, and its left and right neighbors.
+  /// A neighbor is None at the edge of the word. Stress is significant, so AE1
+  /// and AE0 are different phonemes. Entries are visited in arbitrary order.
+  pub fn contexts_of(&self, phoneme: Phoneme)
+    -> impl Iterator<Item=(Word, usize, [Option<Phoneme>; 2])> + '_
+  {
+    self.dictionary.iter()
+      .flat_map(move |(key, polyphone)| {
+        polyphone.iter()
+          .enumerate()
+          .filter(move |(_, candidate)| **candidate == phoneme)
+          .map(move |(position, _)| {
+            let left = position.checked_sub(1).map(|i| polyphone[i]);
+            let right = polyphone.get(position + 1).copied();
+            (key.to_string(), position, [left, right])
+          })
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::PHONEME_MAP;
+  use crate::testing::polyphone;
+
+  fn phoneme(symbol: &str) -> Phoneme {
+    *PHONEME_MAP.get(symbol).unwrap()
+  }
+
+  #[test]
+  fn contexts() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("tact".to_string(), polyphone("T AE1 K T"));
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+    arpa.insert("dog".to_string(), polyphone("D AO1 G"));
+
+    let mut contexts = arpa.contexts_of(phoneme("T")).collect::<Vec<_>>();
+    contexts.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    assert_eq!(contexts, vec![
+      ("cat".to_string(), 2, [Some(phoneme("AE1")), None]),
+      ("tact".to_string(), 0, [None, Some(phoneme("AE1"))]),
+      ("tact".to_string(), 3, [Some(phoneme("K")), None]),
+    ]);
+  }
+
+  #[test]
+  fn stress_is_significant() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+
+    assert_eq!(arpa.contexts_of(phoneme("AE1")).count(), 1);
+    assert_eq!(arpa.contexts_of(phoneme("AE0")).count(), 0);
+    assert_eq!(Arpabet::new().contexts_of(phoneme("K")).count(), 0);
+  }
+}
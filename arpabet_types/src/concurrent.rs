@@ -0,0 +1,189 @@
+//! This module shares a dictionary between threads that read it constantly
+//! and update it occasionally, eg. a service hot-patching pronunciations
+//! while it serves lookups.
+//!
+//! Readers take a snapshot, an `Arc` of the current dictionary, holding a
+//! lock only to clone the `Arc`, so lookups never wait on each other or on a
+//! writer. Writers take turns: each copies the current dictionary, changes
+//! the copy without holding that lock, and publishes it for later snapshots.
+//! Group changes with `ConcurrentArpabet::update` to copy once.
+
+use crate::phoneme::Phoneme;
+use crate::sync::ignore_poison;
+use crate::{Arpabet, Polyphone, Word};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A dictionary for concurrent readers and occasional writers. See the module
+/// documentation.
+#[derive(Default)]
+pub struct ConcurrentArpabet {
+  current: RwLock<Arc<Arpabet>>,
+  // Held by writers for the whole of a change, so none is lost.
+  writer: Mutex<()>,
+}
+
+impl ConcurrentArpabet {
+  /// Share the dictionary.
+  pub fn new(arpabet: Arpabet) -> Self {
+    Self { current: RwLock::new(Arc::new(arpabet)), writer: Mutex::new(()) }
+  }
+
+  /// The current dictionary. Later updates don't change the snapshot.
+  pub fn snapshot(&self) -> Arc<Arpabet> {
+    Arc::clone(&self.read())
+  }
+
+  /// Get a polyphone from the current dictionary.
+  pub fn get_polyphone(&self, word: &str) -> Option<Polyphone> {
+    self.snapshot().get_polyphone(word)
+  }
+
+  /// Whether the current dictionary has the word.
+  pub fn contains_key(&self, word: &str) -> bool {
+    self.snapshot().contains_key(word)
+  }
+
+  /// The number of entries in the current dictionary.
+  pub fn len(&self) -> usize {
+    self.snapshot().len()
+  }
+
+  /// Whether the current dictionary is empty.
+  pub fn is_empty(&self) -> bool {
+    self.snapshot().is_empty()
+  }
+
+  /// Add or replace an entry, returning the pronunciation replaced.
+  pub fn insert(&self, key: Word, value: Vec<Phoneme>) -> Option<Vec<Phoneme>> {
+    self.update(|arpabet| arpabet.insert(key, value))
+  }
+
+  /// Remove an entry, returning its pronunciation.
+  pub fn remove(&self, key: &str) -> Option<Vec<Phoneme>> {
+    self.update(|arpabet| arpabet.remove(key))
+  }
+
+  /// Change the dictionary, eg. to apply a batch of corrections at once.
+  /// Readers see either none or all of the changes, and aren't held up while
+  /// they're made. If `change` panics, none are published. Concurrent updates
+  /// are applied one after the other.
+  pub fn update<F, R>(&self, change: F) -> R
+    where F: FnOnce(&mut Arpabet) -> R
+  {
+    let _writer = self.writer();
+    let mut arpabet = Arpabet::clone(&self.snapshot());
+    let result = change(&mut arpabet);
+    *self.write() = Arc::new(arpabet);
+    result
+  }
+
+  /// Replace the dictionary, eg. with one reloaded from disk, returning the
+  /// previous one.
+  pub fn replace(&self, arpabet: Arpabet) -> Arc<Arpabet> {
+    let _writer = self.writer();
+    std::mem::replace(&mut *self.write(), Arc::new(arpabet))
+  }
+
+  fn writer(&self) -> MutexGuard<'_, ()> {
+    ignore_poison(self.writer.lock())
+  }
+
+  fn read(&self) -> RwLockReadGuard<'_, Arc<Arpabet>> {
+    ignore_poison(self.current.read())
+  }
+
+  fn write(&self) -> RwLockWriteGuard<'_, Arc<Arpabet>> {
+    ignore_poison(self.current.write())
+  }
+}
+
+impl From<Arpabet> for ConcurrentArpabet {
+  fn from(arpabet: Arpabet) -> Self {
+    Self::new(arpabet)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+  use std::thread;
+
+  #[test]
+  fn snapshots() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("tomato".to_string(), polyphone("T AH0 M EY1 T OW2"));
+    let shared = ConcurrentArpabet::new(arpa);
+
+    let before = shared.snapshot();
+    assert_eq!(shared.insert("tomato".to_string(), polyphone("T AH0 M AA1 T OW2")),
+      Some(polyphone("T AH0 M EY1 T OW2")));
+    assert_eq!(before.get_polyphone("tomato"), Some(polyphone("T AH0 M EY1 T OW2")));
+    assert_eq!(shared.get_polyphone("tomato"), Some(polyphone("T AH0 M AA1 T OW2")));
+
+    shared.update(|arpa| {
+      arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+      arpa.insert("dog".to_string(), polyphone("D AO1 G"));
+    });
+    assert_eq!(shared.len(), 3);
+    assert_eq!(shared.remove("dog"), Some(polyphone("D AO1 G")));
+    assert!(!shared.contains_key("dog"));
+
+    let previous = shared.replace(Arpabet::new());
+    assert_eq!(previous.len(), 2);
+    assert!(shared.is_empty());
+  }
+
+  #[test]
+  fn updates_are_private_until_complete() {
+    let shared = ConcurrentArpabet::default();
+    shared.update(|arpa| {
+      arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+      // Readers aren't blocked, and don't see the change yet.
+      assert!(shared.is_empty());
+    });
+    assert_eq!(shared.len(), 1);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      shared.update(|arpa| {
+        arpa.insert("dog".to_string(), polyphone("D AO1 G"));
+        panic!("Abandon the update");
+      })
+    }));
+    assert!(result.is_err());
+    assert!(!shared.contains_key("dog"));
+    shared.insert("dog".to_string(), polyphone("D AO1 G"));
+    assert_eq!(shared.len(), 2);
+  }
+
+  #[test]
+  fn readers_and_writers() {
+    let shared = Arc::new(ConcurrentArpabet::default());
+    let writer = {
+      let shared = Arc::clone(&shared);
+      thread::spawn(move || {
+        for i in 0 .. 100 {
+          shared.insert(format!("word{}", i), polyphone("W ER1 D"));
+        }
+      })
+    };
+    let readers : Vec<_> = (0 .. 4).map(|_| {
+      let shared = Arc::clone(&shared);
+      thread::spawn(move || {
+        let mut seen = 0;
+        for _ in 0 .. 100 {
+          let count = shared.len();
+          assert!(count >= seen);
+          seen = count;
+        }
+      })
+    }).collect();
+
+    writer.join().unwrap();
+    for reader in readers {
+      reader.join().unwrap();
+    }
+    assert_eq!(shared.len(), 100);
+    assert_eq!(shared.get_polyphone("word99"), Some(polyphone("W ER1 D")));
+  }
+}
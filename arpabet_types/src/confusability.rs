@@ -0,0 +1,223 @@
+//! This module measures how easily phrases are confused when spoken, eg. to
+//! check that a wake word and a set of voice commands are far enough apart
+//! before shipping them.
+//!
+//! Distances compare pronunciations phoneme by phoneme, ignoring stress:
+//! substituting a phoneme costs less the more articulatory features it shares
+//! with its replacement (see the `articulation` module), and inserting or
+//! deleting one costs 1. The total is divided by the longer pronunciation's
+//! length, so distances run from 0, sounding the same, to 1.
+
+use crate::phoneme::Phoneme;
+use crate::Arpabet;
+use std::fmt;
+
+/// The cost of substituting one phoneme for another, from 0 for the same
+/// phoneme, ignoring stress, to 1 between a vowel and a consonant. Different
+/// phonemes of the same kind cost 0.2, plus 0.3 each for a different manner
+/// and place, or tongue height and backness, plus 0.2 for different voicing,
+/// or rounding.
+pub fn phoneme_distance(a: Phoneme, b: Phoneme) -> f64 {
+  let differ = |differs: bool, weight: f64| if differs { weight } else { 0.0 };
+  match (a, b) {
+    (Phoneme::Consonant(a), Phoneme::Consonant(b)) if a == b => 0.0,
+    (Phoneme::Consonant(a), Phoneme::Consonant(b)) => 0.2
+      + differ(a.manner() != b.manner(), 0.3)
+      + differ(a.place() != b.place(), 0.3)
+      + differ(a.is_voiced() != b.is_voiced(), 0.2),
+    (Phoneme::Vowel(a), Phoneme::Vowel(b)) if a.to_str_stressless() == b.to_str_stressless() => 0.0,
+    (Phoneme::Vowel(a), Phoneme::Vowel(b)) => 0.2
+      + differ(a.height() != b.height(), 0.3)
+      + differ(a.backness() != b.backness(), 0.3)
+      + differ(a.is_rounded() != b.is_rounded(), 0.2),
+    _ => 1.0,
+  }
+}
+
+/// The distance between two pronunciations, from 0 to 1. See the module
+/// documentation.
+pub fn phonetic_distance(a: &[Phoneme], b: &[Phoneme]) -> f64 {
+  if a.is_empty() && b.is_empty() {
+    return 0.0;
+  }
+  // previous[j] is the cost of turning the first i - 1 phonemes of `a` into
+  // the first j of `b`.
+  let mut previous : Vec<f64> = (0 ..= b.len()).map(|j| j as f64).collect();
+  for (i, pa) in a.iter().enumerate() {
+    let mut current = vec![i as f64 + 1.0; b.len() + 1];
+    for (j, pb) in b.iter().enumerate() {
+      current[j + 1] = (previous[j] + phoneme_distance(*pa, *pb))
+        .min(previous[j + 1] + 1.0)
+        .min(current[j] + 1.0);
+    }
+    previous = current;
+  }
+  previous[b.len()] / a.len().max(b.len()) as f64
+}
+
+/// The pairwise phonetic distances between phrases. See
+/// `Arpabet::phrase_confusability`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfusabilityMatrix {
+  phrases: Vec<String>,
+  // Row by row; None where either phrase can't be pronounced.
+  distances: Vec<Option<f64>>,
+}
+
+/// Two phrases closer than a threshold. See
+/// `ConfusabilityMatrix::confusable_pairs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfusablePair {
+  /// The index of the first phrase.
+  pub a: usize,
+  /// The index of the second phrase, greater than `a`.
+  pub b: usize,
+  /// The distance between them.
+  pub distance: f64,
+}
+
+impl ConfusabilityMatrix {
+  /// The phrases compared, in input order.
+  pub fn phrases(&self) -> &[String] {
+    &self.phrases
+  }
+
+  /// The number of phrases compared.
+  pub fn len(&self) -> usize {
+    self.phrases.len()
+  }
+
+  /// Whether no phrases were compared.
+  pub fn is_empty(&self) -> bool {
+    self.phrases.is_empty()
+  }
+
+  /// The distance between the phrases at two indexes. None if either can't
+  /// be pronounced, or an index is out of bounds.
+  pub fn distance(&self, a: usize, b: usize) -> Option<f64> {
+    if a >= self.len() || b >= self.len() {
+      return None;
+    }
+    self.distances[a * self.len() + b]
+  }
+
+  /// The indexes of the phrases that can't be pronounced, and so weren't
+  /// compared.
+  pub fn unpronounceable(&self) -> Vec<usize> {
+    (0 .. self.len()).filter(|i| self.distance(*i, *i).is_none()).collect()
+  }
+
+  /// The pairs of different phrases closer than the threshold, closest first,
+  /// then in input order.
+  pub fn confusable_pairs(&self, threshold: f64) -> Vec<ConfusablePair> {
+    let mut pairs = Vec::new();
+    for a in 0 .. self.len() {
+      for b in a + 1 .. self.len() {
+        if let Some(distance) = self.distance(a, b).filter(|distance| *distance < threshold) {
+          pairs.push(ConfusablePair { a, b, distance });
+        }
+      }
+    }
+    pairs.sort_by(|x, y| x.distance.total_cmp(&y.distance));
+    pairs
+  }
+}
+
+impl fmt::Display for ConfusabilityMatrix {
+  /// One row per phrase: the phrase, then its distance to each phrase to two
+  /// decimal places, or '-' where unknown, separated by tabs.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for a in 0 .. self.len() {
+      write!(f, "{}", self.phrases[a])?;
+      for b in 0 .. self.len() {
+        match self.distance(a, b) {
+          Some(distance) => write!(f, "\t{:.2}", distance)?,
+          None => write!(f, "\t-")?,
+        }
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+impl Arpabet {
+  /// Compute the phonetic distance between every pair of phrases. Phrases are
+  /// pronounced as by `find_phonetic_duplicates`; where a word has several
+  /// pronunciations, the closest pair counts.
+  pub fn phrase_confusability(&self, phrases: &[&str]) -> ConfusabilityMatrix {
+    let pronunciations : Vec<_> = phrases.iter()
+      .map(|phrase| self.pronunciations_of(phrase))
+      .collect();
+
+    let mut distances = Vec::with_capacity(phrases.len() * phrases.len());
+    for a in pronunciations.iter() {
+      for b in pronunciations.iter() {
+        let closest = a.iter()
+          .flat_map(|a| b.iter().map(move |b| phonetic_distance(a, b)))
+          .min_by(f64::total_cmp);
+        distances.push(closest);
+      }
+    }
+
+    ConfusabilityMatrix {
+      phrases: phrases.iter().map(|phrase| phrase.to_string()).collect(),
+      distances,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::PHONEME_MAP;
+  use crate::testing::{arpabet, polyphone};
+
+  #[test]
+  fn phonemes() {
+    let p = |symbol| *PHONEME_MAP.get(symbol).unwrap();
+    assert_eq!(phoneme_distance(p("AE1"), p("AE0")), 0.0);
+    assert_eq!(phoneme_distance(p("P"), p("B")), 0.4);
+    assert_eq!(phoneme_distance(p("P"), p("K")), 0.5);
+    assert_eq!(phoneme_distance(p("IY1"), p("IH1")), 0.2);
+    assert_eq!(phoneme_distance(p("IY1"), p("T")), 1.0);
+  }
+
+  #[test]
+  fn pronunciations() {
+    assert_eq!(phonetic_distance(&polyphone("K AE1 T"), &polyphone("K AE0 T")), 0.0);
+    assert_eq!(phonetic_distance(&[], &[]), 0.0);
+    assert_eq!(phonetic_distance(&polyphone("K AE1 T"), &[]), 1.0);
+    assert_eq!(phonetic_distance(&polyphone("K AE1 T"), &polyphone("K AE1 T S")), 0.25);
+    let near = phonetic_distance(&polyphone("B IH1 T"), &polyphone("B IY1 T"));
+    let far = phonetic_distance(&polyphone("B IH1 T"), &polyphone("S AA1 K"));
+    assert!(near < far && far <= 1.0);
+  }
+
+  #[test]
+  fn matrix() {
+    let arpa = arpabet(&[
+      ("call", "K AO1 L"),
+      ("mom", "M AA1 M"),
+      ("tom", "T AA1 M"),
+      ("stop", "S T AA1 P"),
+      ("play", "P L EY1"),
+      ("music", "M Y UW1 Z IH0 K"),
+    ]);
+
+    let matrix = arpa.phrase_confusability(&["call mom", "call tom", "play music", "r2d2"]);
+    assert_eq!(matrix.len(), 4);
+    assert_eq!(matrix.distance(0, 0), Some(0.0));
+    assert_eq!(matrix.distance(0, 1), matrix.distance(1, 0));
+    assert_eq!(matrix.distance(0, 3), None);
+    assert_eq!(matrix.distance(0, 4), None);
+    assert_eq!(matrix.unpronounceable(), vec![3]);
+
+    let pairs = matrix.confusable_pairs(0.2);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!((pairs[0].a, pairs[0].b), (0, 1));
+    assert!(matrix.confusable_pairs(1.1).len() == 3);
+    assert!(matrix.to_string().starts_with("call mom\t0.00\t"));
+    assert!(matrix.to_string().ends_with("r2d2\t-\t-\t-\t-\n"));
+  }
+}
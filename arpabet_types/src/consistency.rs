@@ -0,0 +1,97 @@
+//! This module checks a dictionary against a golden set of known
+//! pronunciations, eg. in CI, to catch upstream dictionary changes that would
+//! alter a product's output.
+
+use crate::index::strip_variant;
+use crate::{Arpabet, Polyphone, Word};
+
+/// A golden word whose pronunciations differ in the checked dictionary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+  /// The word, without variant marker.
+  pub word: Word,
+  /// Pronunciations in the golden set, primary first.
+  pub expected: Vec<Polyphone>,
+  /// Pronunciations in the checked dictionary, primary first. Empty if the
+  /// word is missing.
+  pub found: Vec<Polyphone>,
+}
+
+impl Mismatch {
+  /// Whether the word is missing from the checked dictionary entirely.
+  pub fn is_missing(&self) -> bool {
+    self.found.is_empty()
+  }
+}
+
+impl Arpabet {
+  /// Check every word of the golden set against this dictionary. A word
+  /// mismatches unless this dictionary has exactly its golden pronunciations,
+  /// alternates included, in the same order. Words only in this dictionary are
+  /// ignored. Mismatches are sorted by word; none means the check passed.
+  pub fn verify_against(&self, golden: &Arpabet) -> Vec<Mismatch> {
+    let mut words = golden.keys()
+      .map(strip_variant)
+      .collect::<Vec<&str>>();
+    words.sort();
+    words.dedup();
+
+    words.into_iter()
+      .filter_map(|word| {
+        let expected = golden.get_polyphone_variants(word);
+        let found = self.get_polyphone_variants(word);
+        if expected == found {
+          return None;
+        }
+        Some(Mismatch {
+          word: word.to_string(),
+          expected: expected.into_iter().map(|p| p.to_vec()).collect(),
+          found: found.into_iter().map(|p| p.to_vec()).collect(),
+        })
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::testing::{arpabet, polyphone};
+
+  #[test]
+  fn matching_dictionary() {
+    let golden = arpabet(&[("cat", "K AE1 T"), ("read", "R IY1 D"), ("read(1)", "R EH1 D")]);
+    let checked = arpabet(&[
+      ("cat", "K AE1 T"),
+      ("read", "R IY1 D"),
+      ("read(1)", "R EH1 D"),
+      ("dog", "D AO1 G"),
+    ]);
+    assert!(checked.verify_against(&golden).is_empty());
+  }
+
+  #[test]
+  fn mismatches() {
+    let golden = arpabet(&[
+      ("cat", "K AE1 T"),
+      ("read", "R IY1 D"),
+      ("read(1)", "R EH1 D"),
+      ("tomato", "T AH0 M EY1 T OW2"),
+    ]);
+    let checked = arpabet(&[
+      ("cat", "K AE1 T"),
+      ("read", "R EH1 D"),
+      ("read(1)", "R IY1 D"),
+    ]);
+
+    let mismatches = checked.verify_against(&golden);
+    assert_eq!(mismatches.len(), 2);
+
+    assert_eq!(mismatches[0].word, "read");
+    assert_eq!(mismatches[0].expected, vec![polyphone("R IY1 D"), polyphone("R EH1 D")]);
+    assert_eq!(mismatches[0].found, vec![polyphone("R EH1 D"), polyphone("R IY1 D")]);
+    assert!(!mismatches[0].is_missing());
+
+    assert_eq!(mismatches[1].word, "tomato");
+    assert!(mismatches[1].is_missing());
+  }
+}
@@ -126,7 +126,7 @@ pub const ALL_VOWELS : [Vowel; 76] = [
 ];
 
 /// An array of all punctuation.
-pub const ALL_PUNCTUATION: [Punctuation; 10] = [
+pub const ALL_PUNCTUATION: [Punctuation; 12] = [
   Punctuation::StartToken,
   Punctuation::Space,
   Punctuation::Comma,
@@ -136,6 +136,8 @@ pub const ALL_PUNCTUATION: [Punctuation; 10] = [
   Punctuation::Interjection,
   Punctuation::Quote,
   Punctuation::Ellipsis,
+  Punctuation::Break,
+  Punctuation::Annotation,
   Punctuation::EndToken,
 ];
 
@@ -267,7 +269,7 @@ mod tests {
 
   #[test]
   pub fn has_all_punctuation() {
-    expect!(ALL_PUNCTUATION.len()).to(be_eq(10));
+    expect!(ALL_PUNCTUATION.len()).to(be_eq(12));
   }
 
   #[test]
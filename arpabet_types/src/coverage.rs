@@ -0,0 +1,139 @@
+//! This module reports how much of a corpus a dictionary covers, and which
+//! words are out of vocabulary, eg. to find the words of a book that need
+//! pronunciations added before synthesis.
+
+use crate::casing::fold_case;
+use crate::tokenizer::{normalize_apostrophes, tokenize, TokenKind};
+use crate::Arpabet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An out of vocabulary word and the number of times it occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingWord {
+  /// The word, lowercase.
+  pub word: String,
+  /// The number of occurrences.
+  pub count: usize,
+}
+
+/// How many of a corpus's words a dictionary has. See `Arpabet::coverage`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+  /// Occurrences of words in the dictionary.
+  pub hits: usize,
+  /// Occurrences of words missing from the dictionary.
+  pub misses: usize,
+  /// The distinct missing words, most frequent first, then alphabetically.
+  pub out_of_vocabulary: Vec<MissingWord>,
+}
+
+impl CoverageReport {
+  /// The number of words counted.
+  pub fn total(&self) -> usize {
+    self.hits + self.misses
+  }
+
+  /// The fraction of words in the dictionary. One for an empty corpus.
+  pub fn hit_rate(&self) -> f64 {
+    match self.total() {
+      0 => 1.0,
+      total => self.hits as f64 / total as f64,
+    }
+  }
+
+  /// Whether every word is in the dictionary.
+  pub fn is_complete(&self) -> bool {
+    self.misses == 0
+  }
+}
+
+impl fmt::Display for CoverageReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "{} of {} words covered ({:.1}%)", self.hits, self.total(), 100.0 * self.hit_rate())?;
+    for missing in &self.out_of_vocabulary {
+      writeln!(f, "{}\t{}", missing.word, missing.count)?;
+    }
+    Ok(())
+  }
+}
+
+impl Arpabet {
+  /// Count which words are in the dictionary. Words are matched case
+  /// insensitively, with apostrophe variants normalized; empty words are
+  /// skipped. Only the dictionary is consulted, not the fallbacks of
+  /// `transcribe_sentence`.
+  pub fn coverage<'a, I: IntoIterator<Item=&'a str>>(&self, words: I) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    let mut missing : HashMap<String, usize> = HashMap::new();
+
+    for word in words {
+      if word.is_empty() {
+        continue;
+      }
+      let word = fold_case(&normalize_apostrophes(word));
+      if self.contains_key(&word) {
+        report.hits += 1;
+      } else {
+        report.misses += 1;
+        *missing.entry(word).or_insert(0) += 1;
+      }
+    }
+
+    report.out_of_vocabulary = missing.into_iter()
+      .map(|(word, count)| MissingWord { word, count })
+      .collect();
+    report.out_of_vocabulary.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    report
+  }
+
+  /// Count which words of English text are in the dictionary, splitting it
+  /// into words as `transcribe_sentence` does. See `coverage`.
+  pub fn text_coverage(&self, text: &str) -> CoverageReport {
+    let tokens = tokenize(text);
+    self.coverage(tokens.iter().filter_map(|token| match token.kind {
+      TokenKind::Word(ref word) => Some(word.as_str()),
+      _ => None,
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("the", "DH AH0"),
+    ("cat", "K AE1 T"),
+    ("don't", "D OW1 N T"),
+  ];
+
+  #[test]
+  fn coverage() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let report = arpa.coverage(vec!["The", "cat", "Zorp", "", "zorp", "blib", "DON’T"]);
+    assert_eq!(report.hits, 3);
+    assert_eq!(report.misses, 3);
+    assert_eq!(report.total(), 6);
+    assert!((report.hit_rate() - 0.5).abs() < 1e-9);
+    assert!(!report.is_complete());
+    assert_eq!(report.out_of_vocabulary, vec![
+      MissingWord { word: "zorp".to_string(), count: 2 },
+      MissingWord { word: "blib".to_string(), count: 1 },
+    ]);
+    assert_eq!(report.to_string(), "3 of 6 words covered (50.0%)\nzorp\t2\nblib\t1\n");
+  }
+
+  #[test]
+  fn text_coverage() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let report = arpa.text_coverage("The cat? The grumpy cat, don't.");
+    assert_eq!(report.hits, 5);
+    assert_eq!(report.out_of_vocabulary, vec![MissingWord { word: "grumpy".to_string(), count: 1 }]);
+
+    let empty = arpa.text_coverage("...");
+    assert_eq!(empty.hit_rate(), 1.0);
+    assert!(empty.is_complete());
+  }
+}
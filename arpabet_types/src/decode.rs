@@ -0,0 +1,197 @@
+//! This module decodes runs of phonemes back into words, in the manner of a tiny
+//! phonemic speech recognition decoder built over the dictionary.
+
+use crate::{Arpabet, Phoneme, Word};
+use std::cmp::Ordering;
+
+/// Options controlling phoneme-to-word decoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeOptions {
+  /// Number of partial hypotheses kept alive at each phoneme position.
+  pub beam_width: usize,
+  /// Maximum number of complete segmentations returned.
+  pub max_results: usize,
+  /// Score words by the attached unigram frequencies, if any are attached.
+  /// Otherwise every word costs the same and segmentations with fewer words win.
+  pub use_frequencies: bool,
+}
+
+impl Default for DecodeOptions {
+  fn default() -> Self {
+    Self {
+      beam_width: 16,
+      max_results: 5,
+      use_frequencies: true,
+    }
+  }
+}
+
+/// One way of splitting a phoneme sequence into dictionary words.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segmentation {
+  /// The decoded words, in order.
+  pub words: Vec<Word>,
+  /// Log-domain score of the segmentation. Higher is more plausible.
+  pub score: f64,
+}
+
+// Penalty per word when no frequencies are in use.
+const UNWEIGHTED_WORD_COST : f64 = -1.0;
+
+impl Arpabet {
+  /// Decode phonemes into the most plausible word segmentations, best first.
+  pub fn decode_phonemes(&self, phonemes: &[Phoneme]) -> Vec<Segmentation> {
+    self.decode_phonemes_with_options(phonemes, &DecodeOptions::default())
+  }
+
+  /// Decode phonemes into word segmentations with beam search, best first.
+  /// Segmentations must cover every phoneme exactly; partial matches are discarded.
+  pub fn decode_phonemes_with_options(&self, phonemes: &[Phoneme], options: &DecodeOptions)
+    -> Vec<Segmentation>
+  {
+    if phonemes.is_empty() || options.beam_width == 0 || options.max_results == 0 {
+      return Vec::new();
+    }
+
+    let index = self.reverse_index();
+    let frequencies = match options.use_frequencies {
+      true => self.frequencies(),
+      false => None,
+    };
+
+    // Hypotheses grouped by the phoneme position they have consumed up to.
+    let mut frontier : Vec<Vec<Segmentation>> = vec![Vec::new(); phonemes.len() + 1];
+    frontier[0].push(Segmentation { words: Vec::new(), score: 0.0 });
+
+    for start in 0 .. phonemes.len() {
+      let mut beam = std::mem::take(&mut frontier[start]);
+      if beam.is_empty() {
+        continue;
+      }
+      beam.sort_by(compare_segmentations);
+      beam.truncate(options.beam_width);
+
+      let longest = index.max_length().min(phonemes.len() - start);
+
+      for length in 1 ..= longest {
+        let end = start + length;
+        let words = match index.get(&phonemes[start .. end]) {
+          None => continue,
+          Some(words) => words,
+        };
+
+        for word in words.iter() {
+          let cost = match frequencies {
+            Some(frequencies) => frequencies.log_probability(word),
+            None => UNWEIGHTED_WORD_COST,
+          };
+
+          for hypothesis in beam.iter() {
+            let mut extended = hypothesis.words.clone();
+            extended.push(word.clone());
+            frontier[end].push(Segmentation {
+              words: extended,
+              score: hypothesis.score + cost,
+            });
+          }
+        }
+      }
+    }
+
+    let mut results = std::mem::take(&mut frontier[phonemes.len()]);
+    results.sort_by(compare_segmentations);
+    results.truncate(options.max_results);
+    results
+  }
+}
+
+// Best score first, ties broken by the words themselves for deterministic output.
+fn compare_segmentations(a: &Segmentation, b: &Segmentation) -> Ordering {
+  b.score.partial_cmp(&a.score)
+    .unwrap_or(Ordering::Equal)
+    .then_with(|| a.words.cmp(&b.words))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::WordFrequencies;
+  use crate::testing::{self, polyphone};
+
+  fn ice() -> Vec<Phoneme> {
+    polyphone("AY1 S")
+  }
+
+  fn cream() -> Vec<Phoneme> {
+    polyphone("K R IY1 M")
+  }
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("ice", "AY1 S"),
+    ("cream", "K R IY1 M"),
+    ("scream", "S K R IY1 M"),
+    ("i", "AY1"),
+  ];
+
+  #[test]
+  fn decode_without_frequencies() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let input : Vec<Phoneme> = ice().into_iter().chain(cream()).collect();
+
+    let results = arpa.decode_phonemes(&input);
+
+    assert_eq!(results.len(), 2);
+    // Equal word counts tie, so the ordering falls back to the words.
+    assert_eq!(results[0].words, vec!["i", "scream"]);
+    assert_eq!(results[1].words, vec!["ice", "cream"]);
+    assert_eq!(results[0].score, results[1].score);
+  }
+
+  #[test]
+  fn decode_with_frequencies() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    arpa.attach_frequencies(vec![("ice", 50), ("cream", 40), ("i", 5), ("scream", 1)]
+      .into_iter()
+      .collect::<WordFrequencies>());
+
+    let input : Vec<Phoneme> = ice().into_iter().chain(cream()).collect();
+    let results = arpa.decode_phonemes(&input);
+
+    assert_eq!(results[0].words, vec!["ice", "cream"]);
+    assert_eq!(results[1].words, vec!["i", "scream"]);
+
+    let options = DecodeOptions { use_frequencies: false, ..DecodeOptions::default() };
+    let results = arpa.decode_phonemes_with_options(&input, &options);
+    assert_eq!(results[0].words, vec!["i", "scream"]);
+  }
+
+  #[test]
+  fn decode_max_results() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let input : Vec<Phoneme> = ice().into_iter().chain(cream()).collect();
+    let options = DecodeOptions { max_results: 1, ..DecodeOptions::default() };
+
+    assert_eq!(arpa.decode_phonemes_with_options(&input, &options).len(), 1);
+  }
+
+  #[test]
+  fn decode_unmatched() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let input : Vec<Phoneme> = cream().into_iter().chain(polyphone("Z")).collect();
+
+    assert!(arpa.decode_phonemes(&input).is_empty());
+    assert!(arpa.decode_phonemes(&[]).is_empty());
+  }
+
+  #[test]
+  fn decode_sees_inserted_words() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    let zee = polyphone("Z");
+
+    assert!(arpa.decode_phonemes(&zee).is_empty());
+
+    arpa.insert("z".to_string(), zee.clone());
+
+    assert_eq!(arpa.decode_phonemes(&zee)[0].words, vec!["z"]);
+  }
+}
@@ -0,0 +1,169 @@
+//! This module rewrites pronunciations for regional accent features, eg. the
+//! cot-caught merger, so voices for several regions can be generated from one
+//! lexicon.
+//!
+//! A `Dialect` is a set of features compiled to a `SubstitutionTable`. Apply
+//! it lazily, to each pronunciation as it is looked up or transcribed, or
+//! eagerly, to a whole dictionary with `Arpabet::with_dialect`.
+
+use crate::constants::PHONEME_MAP;
+use crate::extensions::SentenceToken;
+use crate::phoneme::Phoneme;
+use crate::substitution::{SubstitutionContext, SubstitutionTable};
+use crate::{Arpabet, Polyphone};
+
+/// An accent feature that rewrites pronunciations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DialectFeature {
+  /// The cot-caught merger: AO is pronounced AA, so 'caught' rhymes with
+  /// 'cot'.
+  CotCaught,
+  /// The pin-pen merger: EH is pronounced IH before nasals, so 'pen' sounds
+  /// like 'pin'.
+  PinPen,
+  /// Non-rhotic speech: R is dropped unless a vowel follows, and the
+  /// r-colored vowels ER and AXR become AH and AX, eg. 'car' -> K AA1.
+  NonRhotic,
+}
+
+impl DialectFeature {
+  // The substitutions realizing the feature.
+  fn rules(&self) -> &'static [(&'static str, Option<&'static str>, SubstitutionContext)] {
+    match self {
+      DialectFeature::CotCaught => &[("AO", Some("AA"), SubstitutionContext::Anywhere)],
+      DialectFeature::PinPen => &[("EH", Some("IH"), SubstitutionContext::BeforeNasal)],
+      DialectFeature::NonRhotic => &[
+        ("R", None, SubstitutionContext::Final),
+        ("R", None, SubstitutionContext::BeforeConsonant),
+        ("ER", Some("AH"), SubstitutionContext::Anywhere),
+        ("AXR", Some("AX"), SubstitutionContext::Anywhere),
+      ],
+    }
+  }
+}
+
+/// A set of accent features. See the module documentation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dialect {
+  features: Vec<DialectFeature>,
+  table: SubstitutionTable,
+}
+
+impl Dialect {
+  /// A dialect without features, which changes nothing.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// A dialect with the features.
+  pub fn with_features(features: &[DialectFeature]) -> Self {
+    features.iter().fold(Self::new(), |dialect, feature| dialect.feature(*feature))
+  }
+
+  /// Add a feature. Adding a feature twice has no further effect.
+  pub fn feature(mut self, feature: DialectFeature) -> Self {
+    if !self.features.contains(&feature) {
+      self.features.push(feature);
+      for (from, to, context) in feature.rules() {
+        self.table = self.table.substitute(phoneme(from), to.map(phoneme), *context);
+      }
+    }
+    self
+  }
+
+  /// The features, in the order added.
+  pub fn features(&self) -> &[DialectFeature] {
+    &self.features
+  }
+
+  /// The substitutions realizing the features.
+  pub fn table(&self) -> &SubstitutionTable {
+    &self.table
+  }
+
+  /// Rewrite a word's pronunciation.
+  pub fn apply(&self, polyphone: &[Phoneme]) -> Polyphone {
+    self.table.apply(polyphone)
+  }
+
+  /// Rewrite a transcription, eg. from `transcribe_sentence`, word by word.
+  pub fn apply_to_tokens(&self, tokens: &[SentenceToken]) -> Vec<SentenceToken> {
+    self.table.apply_to_tokens(tokens)
+  }
+}
+
+impl Arpabet {
+  /// Get a polyphone from the dictionary, rewritten for the dialect.
+  pub fn get_polyphone_in_dialect(&self, word: &str, dialect: &Dialect) -> Option<Polyphone> {
+    self.get_polyphone_ref(word).map(|polyphone| dialect.apply(polyphone))
+  }
+
+  /// A copy of the dictionary with every pronunciation rewritten for the
+  /// dialect. See `with_substitutions`.
+  pub fn with_dialect(&self, dialect: &Dialect) -> Arpabet {
+    self.with_substitutions(dialect.table())
+  }
+}
+
+// Look up a phoneme of the rule tables, which are known to be valid.
+fn phoneme(symbol: &str) -> Phoneme {
+  *PHONEME_MAP.get(symbol).expect("Dialect rules use valid phonemes.")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn features() {
+    let cot_caught = Dialect::new().feature(DialectFeature::CotCaught);
+    assert_eq!(cot_caught.apply(&polyphone("K AO1 T")), polyphone("K AA1 T"));
+
+    let pin_pen = Dialect::new().feature(DialectFeature::PinPen);
+    assert_eq!(pin_pen.apply(&polyphone("P EH1 N")), polyphone("P IH1 N"));
+    assert_eq!(pin_pen.apply(&polyphone("P EH1 T")), polyphone("P EH1 T"));
+
+    let non_rhotic = Dialect::new().feature(DialectFeature::NonRhotic);
+    assert_eq!(non_rhotic.apply(&polyphone("K AA1 R")), polyphone("K AA1"));
+    assert_eq!(non_rhotic.apply(&polyphone("K AA1 R D")), polyphone("K AA1 D"));
+    assert_eq!(non_rhotic.apply(&polyphone("R EH1 D")), polyphone("R EH1 D"));
+    assert_eq!(non_rhotic.apply(&polyphone("K EH1 R IY0")), polyphone("K EH1 R IY0"));
+    assert_eq!(non_rhotic.apply(&polyphone("B ER1 D")), polyphone("B AH1 D"));
+  }
+
+  #[test]
+  fn combined() {
+    let dialect = Dialect::with_features(&[
+      DialectFeature::CotCaught,
+      DialectFeature::NonRhotic,
+      DialectFeature::CotCaught,
+    ]);
+    assert_eq!(dialect.features(), &[DialectFeature::CotCaught, DialectFeature::NonRhotic]);
+    assert_eq!(dialect.table().rules().len(), 5);
+    assert_eq!(dialect.apply(&polyphone("F AO1 R TH")), polyphone("F AA1 TH"));
+    assert_eq!(Dialect::new().apply(&polyphone("F AO1 R TH")), polyphone("F AO1 R TH"));
+  }
+
+  #[test]
+  fn dictionary() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("caught".to_string(), polyphone("K AO1 T"));
+    arpa.insert("car".to_string(), polyphone("K AA1 R"));
+    let dialect = Dialect::with_features(&[DialectFeature::CotCaught, DialectFeature::NonRhotic]);
+
+    assert_eq!(arpa.get_polyphone_in_dialect("caught", &dialect), Some(polyphone("K AA1 T")));
+    assert_eq!(arpa.get_polyphone_in_dialect("cot", &dialect), None);
+
+    let eager = arpa.with_dialect(&dialect);
+    assert_eq!(eager.get_polyphone("car"), Some(polyphone("K AA1")));
+    assert_eq!(arpa.get_polyphone("car"), Some(polyphone("K AA1 R")));
+
+    let tokens = arpa.transcribe_sentence("Car caught.").unwrap();
+    let rewritten : Vec<&str> = dialect.apply_to_tokens(&tokens).iter()
+      .map(|token| token.to_str())
+      .collect();
+    assert_eq!(rewritten,
+      vec!["[start]", "K", "AA1", "[space]", "K", "AA1", "T", "[period]", "[end]"]);
+  }
+}
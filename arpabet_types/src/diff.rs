@@ -0,0 +1,139 @@
+//! This module compares two transcriptions token by token, eg. so golden tests
+//! of a pipeline can show exactly how a normalization or lexicon change
+//! altered the output.
+
+use crate::extensions::SentenceToken;
+use std::fmt;
+
+/// One step of an edit script turning one token stream into another.
+/// Positions index the streams compared, counting from 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenEdit {
+  /// A token of the first stream is removed.
+  Delete {
+    /// The position in the first stream.
+    a: usize,
+    /// The token removed.
+    token: SentenceToken,
+  },
+  /// A token of the second stream is added.
+  Insert {
+    /// The position in the second stream.
+    b: usize,
+    /// The token added.
+    token: SentenceToken,
+  },
+  /// A token is replaced.
+  Substitute {
+    /// The position in the first stream.
+    a: usize,
+    /// The position in the second stream.
+    b: usize,
+    /// The token replaced.
+    from: SentenceToken,
+    /// Its replacement.
+    to: SentenceToken,
+  },
+}
+
+impl fmt::Display for TokenEdit {
+  /// Render as eg. "3: -AE1 +EH1", with the position in the first stream, or
+  /// in the second for insertions.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TokenEdit::Delete { a, token } => write!(f, "{}: -{}", a, token.to_str()),
+      TokenEdit::Insert { b, token } => write!(f, "{}: +{}", b, token.to_str()),
+      TokenEdit::Substitute { a, from, to, .. } =>
+        write!(f, "{}: -{} +{}", a, from.to_str(), to.to_str()),
+    }
+  }
+}
+
+/// Find a shortest edit script turning the tokens `a` into `b`, in stream
+/// order. Empty when the streams are equal. Where several scripts are
+/// shortest, substitutions are preferred, then deletions before insertions.
+pub fn diff_token_streams(a: &[SentenceToken], b: &[SentenceToken]) -> Vec<TokenEdit> {
+  // distances[i][j] is the edit distance between a[i..] and b[j..].
+  let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+  for i in (0 ..= a.len()).rev() {
+    for j in (0 ..= b.len()).rev() {
+      distances[i][j] = if i == a.len() {
+        b.len() - j
+      } else if j == b.len() {
+        a.len() - i
+      } else if a[i] == b[j] {
+        distances[i + 1][j + 1]
+      } else {
+        1 + distances[i + 1][j + 1].min(distances[i + 1][j]).min(distances[i][j + 1])
+      };
+    }
+  }
+
+  let mut edits = Vec::with_capacity(distances[0][0]);
+  let (mut i, mut j) = (0, 0);
+  while i < a.len() || j < b.len() {
+    let distance = distances[i][j];
+    if i < a.len() && j < b.len() && a[i] == b[j] && distance == distances[i + 1][j + 1] {
+      i += 1;
+      j += 1;
+    } else if i < a.len() && j < b.len() && distance == distances[i + 1][j + 1] + 1 {
+      edits.push(TokenEdit::Substitute { a: i, b: j, from: a[i], to: b[j] });
+      i += 1;
+      j += 1;
+    } else if i < a.len() && distance == distances[i + 1][j] + 1 {
+      edits.push(TokenEdit::Delete { a: i, token: a[i] });
+      i += 1;
+    } else {
+      edits.push(TokenEdit::Insert { b: j, token: b[j] });
+      j += 1;
+    }
+  }
+  edits
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::PHONEME_MAP;
+  use crate::extensions::Punctuation;
+
+  fn tokens(text: &str) -> Vec<SentenceToken> {
+    text.split(' ')
+      .map(|token| match token {
+        "," => SentenceToken::Punctuation(Punctuation::Comma),
+        "_" => SentenceToken::Punctuation(Punctuation::Space),
+        phoneme => SentenceToken::Phoneme(*PHONEME_MAP.get(phoneme).unwrap()),
+      })
+      .collect()
+  }
+
+  fn rendered(edits: &[TokenEdit]) -> Vec<String> {
+    edits.iter().map(|edit| edit.to_string()).collect()
+  }
+
+  #[test]
+  fn equal() {
+    assert!(diff_token_streams(&tokens("K AE1 T"), &tokens("K AE1 T")).is_empty());
+    assert!(diff_token_streams(&[], &[]).is_empty());
+  }
+
+  #[test]
+  fn edits() {
+    let a = tokens("R IY1 D , _ IH1 T");
+    let b = tokens("R EH1 D _ IH1 T S");
+    let edits = diff_token_streams(&a, &b);
+    assert_eq!(rendered(&edits), vec!["1: -IY1 +EH1", "3: -[comma]", "6: +S"]);
+    assert_eq!(edits[0], TokenEdit::Substitute { a: 1, b: 1, from: a[1], to: b[1] });
+
+    assert_eq!(rendered(&diff_token_streams(&tokens("K AE1 T"), &[])),
+      vec!["0: -K", "1: -AE1", "2: -T"]);
+    assert_eq!(rendered(&diff_token_streams(&[], &tokens("K AE1"))), vec!["0: +K", "1: +AE1"]);
+  }
+
+  #[test]
+  fn minimal() {
+    // An insertion early shouldn't cascade into substitutions.
+    let edits = diff_token_streams(&tokens("AE1 B K D"), &tokens("S AE1 B K D"));
+    assert_eq!(rendered(&edits), vec!["0: +S"]);
+  }
+}
@@ -0,0 +1,140 @@
+//! This module finds words and phrases that sound alike, eg. to catch
+//! confusable command names such as "write" and "right" when designing a
+//! voice-control grammar.
+
+use crate::casing::fold_case;
+use crate::letter_to_sound::letter_to_sound;
+use crate::stress::PolyphoneStress;
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone};
+use std::collections::HashMap;
+
+impl Arpabet {
+  /// Group the words that share a pronunciation, ignoring stress, by their
+  /// indexes. Every dictionary variant of a word counts, so 'read' shares a
+  /// pronunciation with both 'reed' and 'red', and all three are grouped.
+  ///
+  /// Words missing from the dictionary, and phrases of several words, are
+  /// pronounced as by `transcribe_sentence_with_options` with initialisms and
+  /// numbers read out, eg. "2" sounds like 'two'. Words still unpronounced are
+  /// guessed from their spelling by `letter_to_sound`, so made-up names such
+  /// as "zorblax" and "zorblacks" are grouped too. Words that can't be
+  /// pronounced even so, eg. "r2d2", are never grouped.
+  ///
+  /// Only groups of two or more are returned, each in input order, ordered by
+  /// their first index.
+  pub fn find_phonetic_duplicates(&self, words: &[&str]) -> Vec<Vec<usize>> {
+    // Each word's index is joined with the first word sharing a pronunciation.
+    let mut parents : Vec<usize> = (0 .. words.len()).collect();
+    let mut first_with : HashMap<Vec<&'static str>, usize> = HashMap::new();
+
+    for (i, word) in words.iter().enumerate() {
+      for polyphone in self.pronunciations_of(word) {
+        let first = *first_with.entry(polyphone.to_stressless_strs()).or_insert(i);
+        let (a, b) = (root(&mut parents, first), root(&mut parents, i));
+        parents[a.max(b)] = a.min(b);
+      }
+    }
+
+    let mut groups : Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root = HashMap::new();
+    for i in 0 .. words.len() {
+      let group = *group_of_root.entry(root(&mut parents, i)).or_insert_with(|| {
+        groups.push(Vec::new());
+        groups.len() - 1
+      });
+      groups[group].push(i);
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+  }
+
+  // Every pronunciation of a word or phrase: the dictionary variants of a
+  // word, otherwise its transcription with initialisms and numbers read out
+  // and unknown words guessed from their spelling, if it can be pronounced.
+  pub(crate) fn pronunciations_of(&self, text: &str) -> Vec<Polyphone> {
+    let options = TranscribeOptions {
+      initialisms: true,
+      numbers: true,
+      ..Default::default()
+    };
+    let variants = self.get_polyphone_variants(&fold_case(text.trim()));
+    if !variants.is_empty() {
+      return variants.into_iter().map(|polyphone| polyphone.to_vec()).collect();
+    }
+    let words = self.pronounce_words(text, &options);
+    if words.is_empty() {
+      return Vec::new();
+    }
+    words.into_iter()
+      .map(|(word, polyphone)| polyphone.or_else(|| letter_to_sound(&word)))
+      .collect::<Option<Vec<Polyphone>>>()
+      .map(|polyphones| vec![polyphones.concat()])
+      .unwrap_or_default()
+  }
+}
+
+// The representative of an index's group, compressing the path to it.
+fn root(parents: &mut [usize], i: usize) -> usize {
+  let mut root = i;
+  while parents[root] != root {
+    root = parents[root];
+  }
+  let mut i = i;
+  while parents[i] != root {
+    let next = parents[i];
+    parents[i] = root;
+    i = next;
+  }
+  root
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("write", "R AY1 T"),
+    ("right", "R AY1 T"),
+    ("rite", "R AY2 T"),
+    ("read", "R IY1 D"),
+    ("read(1)", "R EH1 D"),
+    ("reed", "R IY1 D"),
+    ("red", "R EH1 D"),
+    ("stop", "S T AA1 P"),
+    ("to", "T UW1"),
+    ("two", "T UW1"),
+    ("lights", "L AY1 T S"),
+    ("on", "AA1 N"),
+    ("lie", "L AY1"),
+    ("tson", "T S AA1 N"),
+  ];
+
+  #[test]
+  fn groups() {
+    let arpa = testing::arpabet(DICTIONARY);
+    assert_eq!(arpa.find_phonetic_duplicates(&["Write", "stop", "right", "rite"]),
+      vec![vec![0, 2, 3]]);
+    assert_eq!(arpa.find_phonetic_duplicates(&["red", "stop", "reed", "read"]),
+      vec![vec![0, 2, 3]]);
+    assert_eq!(arpa.find_phonetic_duplicates(&["stop", "write", "red"]), Vec::<Vec<usize>>::new());
+    assert_eq!(arpa.find_phonetic_duplicates(&[]), Vec::<Vec<usize>>::new());
+  }
+
+  #[test]
+  fn fallbacks() {
+    let arpa = testing::arpabet(DICTIONARY);
+    assert_eq!(arpa.find_phonetic_duplicates(&["2", "to", "r2d2", "r2d2"]), vec![vec![0, 1]]);
+    assert_eq!(arpa.find_phonetic_duplicates(&["lights on", "stop", "lie tson"]),
+      vec![vec![0, 2]]);
+  }
+
+  #[test]
+  fn out_of_vocabulary() {
+    let arpa = testing::arpabet(DICTIONARY);
+    assert_eq!(arpa.find_phonetic_duplicates(&["zorblax", "stop", "Zorblacks", "fleep"]),
+      vec![vec![0, 2]]);
+    assert_eq!(arpa.find_phonetic_duplicates(&["stop zorblax", "stop zorblacks"]),
+      vec![vec![0, 1]]);
+  }
+}
@@ -1,15 +1,25 @@
 //! Crate errors.
+//!
+//! Errors can carry context, eg. the file being loaded, with
+//! `ArpabetError::context` or the `WithContext` trait on results. Match on
+//! `ArpabetError::root` to see the underlying error through any context.
+//!
+//! `ArpabetError` is a `std::error::Error`, so `?` converts it into an
+//! `anyhow::Error`. With the `anyhow` feature, `anyhow::Error` converts back:
+//! an `ArpabetError` anyhow added no context to is recovered, and anything
+//! else becomes `ArpabetError::Other`.
 
-use std::error::Error;
-use std::fmt;
 use std::io;
+use thiserror::Error;
 
 /// Errors for the Arpabet crate.
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum ArpabetError {
   /// The file or stream being read from was empty.
+  #[error("The file was empty.")]
   EmptyFile,
   /// The file or stream contained invalid syntax.
+  #[error("Invalid format on line {line_number}: {text}")]
   InvalidFormat {
     /// Line where the error occurred.
     line_number: usize,
@@ -17,49 +27,167 @@ pub enum ArpabetError {
     text: String,
   },
   /// Failure to parse the input string.
+  #[error("Parse error: {description}")]
   StringParseError {
     /// Text describing the parse failure.
     description: String,
   },
+  /// A language tag that is not well-formed BCP-47.
+  #[error("Invalid language tag: '{tag}'")]
+  InvalidLanguageTag {
+    /// The rejected tag.
+    tag: String,
+  },
+  /// The input exceeded a configured parser limit.
+  #[error("Limit {limit} of {max} exceeded on line {line_number}")]
+  LimitExceeded {
+    /// Name of the limit, eg. 'max_line_length'.
+    limit: &'static str,
+    /// The configured maximum.
+    max: usize,
+    /// Line where the limit was exceeded.
+    line_number: usize,
+  },
+  /// A word in the text has no known pronunciation.
+  #[error("Unknown word: '{word}'")]
+  UnknownWord {
+    /// The word, as it appeared in the text.
+    word: String,
+  },
+  /// A numeric code that maps to no token.
+  #[error("Unknown token code: {code}")]
+  UnknownCode {
+    /// The unmapped code.
+    code: u8,
+  },
+  /// Malformed binary dictionary data.
+  #[error("Invalid binary dictionary: {description}")]
+  InvalidBinary {
+    /// Text describing the problem.
+    description: String,
+  },
+  /// Binary dictionary data written in a different format version, eg. a stale cache.
+  #[error("Binary dictionary format version {found} is not supported (expected {supported})")]
+  BinaryVersionMismatch {
+    /// Version of the data.
+    found: u16,
+    /// Version this library reads and writes.
+    supported: u16,
+  },
   /// An error during file IO.
-  Io(io::Error),
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  /// An error from outside the crate, eg. converted from an `anyhow::Error`.
+  #[error(transparent)]
+  Other(Box<dyn std::error::Error + Send + Sync>),
+  /// Another error, with a description of what was being done, eg. the file
+  /// being loaded or the word being transcribed.
+  #[error("{context}: {source}")]
+  Context {
+    /// What was being done.
+    context: String,
+    /// The underlying error.
+    #[source]
+    source: Box<ArpabetError>,
+  },
 }
 
-impl fmt::Display for ArpabetError {
-  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match *self {
-      ArpabetError::EmptyFile => write!(f, "The file was empty."),
-      ArpabetError::InvalidFormat { ref line_number, ref text } =>
-          write!(f, "Invalid format on line {}: {}", line_number, text),
-      ArpabetError::StringParseError { ref description } =>
-          write!(f, "Parse error: {}", description),
-      ArpabetError::Io(ref err) => err.fmt(f),
+impl ArpabetError {
+  /// Wrap the error with a description of what was being done.
+  pub fn context<C: Into<String>>(self, context: C) -> ArpabetError {
+    ArpabetError::Context {
+      context: context.into(),
+      source: Box::new(self),
     }
   }
-}
 
-impl Error for ArpabetError {
-  fn description(&self) -> &str {
-    match *self {
-      ArpabetError::EmptyFile => "The file was empty.",
-      ArpabetError::InvalidFormat { .. } => "Invalid format.",
-      ArpabetError::StringParseError { .. } => "Parse error.",
-      ArpabetError::Io(ref err) => err.description(),
+  /// The underlying error, without any context.
+  pub fn root(&self) -> &ArpabetError {
+    match self {
+      ArpabetError::Context { source, .. } => source.root(),
+      _ => self,
     }
   }
+}
 
-  fn cause(&self) -> Option<&dyn Error> {
-    match *self {
-      ArpabetError::EmptyFile => None,
-      ArpabetError::InvalidFormat { .. } => None,
-      ArpabetError::StringParseError { .. } => None,
-      ArpabetError::Io(ref err) => Some(err),
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for ArpabetError {
+  fn from(err: anyhow::Error) -> Self {
+    // anyhow::Error::is also sees through context, which downcasting drops.
+    let outermost : &(dyn std::error::Error + 'static) = &*err;
+    if !outermost.is::<ArpabetError>() {
+      return ArpabetError::Other(err.into());
     }
+    err.downcast::<ArpabetError>().unwrap_or_else(|err| ArpabetError::Other(err.into()))
+  }
+}
+
+/// Adds context to the error of a result. See `ArpabetError::context`.
+pub trait WithContext<T> {
+  /// Wrap an error with a description of what was being done.
+  fn context<C: Into<String>>(self, context: C) -> Result<T, ArpabetError>;
+
+  /// Wrap an error with a description of what was being done, computed only
+  /// when there is an error.
+  fn with_context<C: Into<String>, F: FnOnce() -> C>(self, context: F) -> Result<T, ArpabetError>;
+}
+
+impl <T, E: Into<ArpabetError>> WithContext<T> for Result<T, E> {
+  fn context<C: Into<String>>(self, context: C) -> Result<T, ArpabetError> {
+    self.map_err(|err| err.into().context(context))
+  }
+
+  fn with_context<C: Into<String>, F: FnOnce() -> C>(self, context: F) -> Result<T, ArpabetError> {
+    self.map_err(|err| err.into().context(context()))
   }
 }
 
-impl From<io::Error> for ArpabetError {
-  fn from(err: io::Error) -> ArpabetError {
-    ArpabetError::Io(err)
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn context() {
+    let result : Result<(), io::Error> = Err(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+    let err = result.context("loading 'dict.txt'").unwrap_err();
+
+    assert_eq!(err.to_string(), "loading 'dict.txt': no such file");
+    assert!(matches!(err.root(), ArpabetError::Io(_)));
+    assert_eq!(err.source().unwrap().to_string(), "no such file");
+
+    let err = err.context("starting up");
+    assert_eq!(err.to_string(), "starting up: loading 'dict.txt': no such file");
+    assert!(matches!(err.root(), ArpabetError::Io(_)));
+  }
+
+  #[test]
+  fn lazy_context() {
+    let ok : Result<u8, ArpabetError> = Ok(1);
+    assert_eq!(ok.with_context(|| -> String { panic!("Not needed") }).unwrap(), 1);
+
+    let err : Result<u8, ArpabetError> = Err(ArpabetError::UnknownWord { word: "zzz".to_string() });
+    let err = err.with_context(|| format!("line {}", 3)).unwrap_err();
+    assert_eq!(err.to_string(), "line 3: Unknown word: 'zzz'");
+    assert!(matches!(err.root(), ArpabetError::UnknownWord { .. }));
+  }
+
+  #[cfg(feature = "anyhow")]
+  #[test]
+  fn anyhow() {
+    let unknown = || ArpabetError::UnknownWord { word: "zzz".to_string() };
+
+    let err = ArpabetError::from(anyhow::Error::from(unknown()));
+    assert!(matches!(err, ArpabetError::UnknownWord { .. }));
+
+    let err = ArpabetError::from(anyhow::Error::from(unknown()).context("transcribing"));
+    assert!(matches!(err, ArpabetError::Other(_)));
+    assert_eq!(err.to_string(), "transcribing");
+    assert_eq!(err.source().unwrap().to_string(), "Unknown word: 'zzz'");
+
+    let err = ArpabetError::from(anyhow::anyhow!("callback failed"));
+    assert_eq!(err.to_string(), "callback failed");
   }
 }
+
+
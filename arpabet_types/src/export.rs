@@ -0,0 +1,333 @@
+//! This module contains exporters that write an Arpabet to various file formats.
+
+use crate::casing::CasingMap;
+use crate::index::{strip_variant, variant_number};
+use crate::variant_id::VariantId;
+use crate::error::WithContext;
+use crate::{Arpabet, ArpabetError, Phoneme};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// The order in which exporters write dictionary entries.
+/// Either way the order is total, so repeated exports are byte-identical.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EntryOrder {
+  /// By word, with each word's alternate pronunciations in variant order.
+  #[default]
+  Word,
+  /// By pronunciation, then as for `Word`.
+  Pronunciation,
+}
+
+/// Options shared by the exporters.
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions<'a> {
+  /// Restore conventional casing to the (lowercase) dictionary words on output.
+  /// Words are written lowercase when this is unset. The casing of a loaded file
+  /// is available from `load_from_file_with_casing`.
+  pub casing: Option<&'a CasingMap>,
+  /// The order entries are written in.
+  pub order: EntryOrder,
+  /// Renumber alternate pronunciations consecutively from '(1)', closing any
+  /// gaps left by removed entries. Otherwise variant markers are kept as-is.
+  pub renumber_variants: bool,
+  /// Follow each entry with its stable variant id as a '#' comment, eg.
+  /// "read(1)  R EH1 D # v233774ff". See the `variant_id` module.
+  pub variant_ids: bool,
+}
+
+impl ExportOptions<'_> {
+  // Apply the configured casing to a dictionary word.
+  fn format_word(&self, word: &str) -> String {
+    match self.casing {
+      Some(casing) => casing.restore(word),
+      None => word.to_string(),
+    }
+  }
+}
+
+/// Get the dictionary entries in the order exporters write them, with words
+/// cased and variant markers numbered per the options. Custom exporters should
+/// use this to share the ordering guarantees of the built in ones.
+///
+/// Alternates are ordered by their variant number, so 'read(2)' precedes
+/// 'read(10)'. Markers with the same number, eg. 'read' and 'read(0)', are
+/// ordered by key.
+pub fn ordered_entries<'a>(arpabet: &'a Arpabet, options: &ExportOptions)
+  -> Vec<(String, &'a [Phoneme])>
+{
+  let mut entries = arpabet.keys()
+    .filter_map(|key| {
+      let base = strip_variant(key);
+      arpabet.get_polyphone_ref(key).map(|polyphone| (key, base, variant_number(key), polyphone))
+    })
+    .collect::<Vec<(&str, &str, usize, &[Phoneme])>>();
+
+  entries.sort_by(|a, b| a.1.cmp(b.1).then(a.2.cmp(&b.2)).then_with(|| a.0.cmp(b.0)));
+
+  let mut ordered = Vec::with_capacity(entries.len());
+  let mut previous_base = None;
+  let mut position = 0;
+
+  for (key, base, _, polyphone) in entries.into_iter() {
+    position = if previous_base == Some(base) { position + 1 } else { 0 };
+    previous_base = Some(base);
+
+    let word = if !options.renumber_variants {
+      format!("{}{}", options.format_word(base), &key[base.len() ..])
+    } else if position == 0 {
+      options.format_word(base)
+    } else {
+      format!("{}({})", options.format_word(base), position)
+    };
+    ordered.push((word, polyphone));
+  }
+
+  if options.order == EntryOrder::Pronunciation {
+    // Stable, so ties keep word order.
+    ordered.sort_by_key(|(_, polyphone)| pronunciation(polyphone));
+  }
+
+  ordered
+}
+
+/// Write the dictionary in CMUdict format: the word, two spaces, and the
+/// space-separated phonemes. Metadata is written first as ';;;' comments.
+/// Entries are written in the order given by `ordered_entries`.
+pub fn write_dictionary(arpabet: &Arpabet, writer: &mut dyn Write, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  for line in arpabet.metadata().to_comment_lines() {
+    writeln!(writer, "{}", line)?;
+  }
+
+  for (word, polyphone) in ordered_entries(arpabet, options) {
+    if options.variant_ids {
+      writeln!(writer, "{}  {} # {}", word, pronunciation(polyphone), VariantId::of(polyphone))?;
+    } else {
+      writeln!(writer, "{}  {}", word, pronunciation(polyphone))?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Save the dictionary to a file in CMUdict format. See `write_dictionary`.
+pub fn save_dictionary(arpabet: &Arpabet, filename: &str, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  let save = || -> Result<(), ArpabetError> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_dictionary(arpabet, &mut writer, options)?;
+    writer.flush()?;
+    Ok(())
+  };
+  save().with_context(|| format!("Saving '{}'", filename))
+}
+
+/// Write the reverse dictionary: one line per pronunciation, followed by a tab
+/// and the comma-separated words pronounced that way.
+/// eg. `R EH1 D\tread,red`
+/// Lines are always sorted by pronunciation, and the words on each line
+/// alphabetically, so that output is stable across runs.
+pub fn write_reverse_index(arpabet: &Arpabet, writer: &mut dyn Write, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  let mut lines = arpabet.reverse_index()
+    .iter()
+    .map(|(polyphone, words)| {
+      let words = words.iter()
+        .map(|word| options.format_word(word))
+        .collect::<Vec<String>>();
+      (pronunciation(polyphone), words.join(","))
+    })
+    .collect::<Vec<(String, String)>>();
+
+  lines.sort();
+
+  for (pronunciation, words) in lines.iter() {
+    writeln!(writer, "{}\t{}", pronunciation, words)?;
+  }
+
+  Ok(())
+}
+
+/// Save the reverse dictionary to a file. See `write_reverse_index` for the format.
+pub fn save_reverse_index(arpabet: &Arpabet, filename: &str, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  let save = || -> Result<(), ArpabetError> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_reverse_index(arpabet, &mut writer, options)?;
+    writer.flush()?;
+    Ok(())
+  };
+  save().with_context(|| format!("Saving '{}'", filename))
+}
+
+// Space-separated phoneme string.
+fn pronunciation(polyphone: &[Phoneme]) -> String {
+  polyphone.iter()
+    .map(|phoneme| phoneme.to_str())
+    .collect::<Vec<&str>>()
+    .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::metadata::{LanguageTag, LexiconMetadata};
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("red", "R EH1 D"),
+    ("reed", "R IY1 D"),
+    ("read", "R IY1 D"),
+    ("read(1)", "R EH1 D"),
+  ];
+
+  #[test]
+  fn dictionary_format() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    arpa.set_metadata(LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").unwrap()),
+      version: None,
+      license: None,
+    });
+
+    let mut output = Vec::new();
+    write_dictionary(&arpa, &mut output, &ExportOptions::default()).expect("should write");
+
+    assert_eq!(String::from_utf8(output).unwrap(),
+      ";;; language: en-US\n\
+       read  R IY1 D\n\
+       read(1)  R EH1 D\n\
+       red  R EH1 D\n\
+       reed  R IY1 D\n");
+  }
+
+  #[test]
+  fn dictionary_variant_ids() {
+    let options = ExportOptions { variant_ids: true, ..Default::default() };
+    let mut output = Vec::new();
+    write_dictionary(&testing::arpabet(DICTIONARY), &mut output, &options).expect("should write");
+
+    let text = String::from_utf8(output).unwrap();
+    let red = VariantId::of(&testing::arpabet(DICTIONARY).get_polyphone("red").unwrap());
+    assert!(text.contains(&format!("read(1)  R EH1 D # {}\n", red)));
+    assert!(text.contains(&format!("red  R EH1 D # {}\n", red)));
+  }
+
+  #[test]
+  fn save_errors_name_the_file() {
+    let filename = "./no/such/directory/dictionary.txt";
+    let arpa = testing::arpabet(DICTIONARY);
+    let err = save_dictionary(&arpa, filename, &ExportOptions::default()).unwrap_err();
+    assert!(matches!(err.root(), ArpabetError::Io(_)));
+    assert!(err.to_string().starts_with("Saving './no/such/directory/dictionary.txt': "));
+  }
+
+  #[test]
+  fn dictionary_casing() {
+    let casing = CasingMap::from_forms(vec!["Reed"]);
+    let options = ExportOptions { casing: Some(&casing), ..Default::default() };
+
+    let mut output = Vec::new();
+    write_dictionary(&testing::arpabet(DICTIONARY), &mut output, &options).expect("should write");
+
+    assert!(String::from_utf8(output).unwrap().ends_with("Reed  R IY1 D\n"));
+  }
+
+  #[test]
+  fn dictionary_variant_order() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    let polyphone = arpa.get_polyphone("red").unwrap();
+    for i in 2 ..= 10 {
+      arpa.insert(format!("read({})", i), polyphone.clone());
+    }
+    arpa.remove("read(5)");
+
+    let words = |options: &ExportOptions| {
+      ordered_entries(&arpa, options).into_iter()
+        .map(|(word, _)| word)
+        .collect::<Vec<String>>()
+    };
+
+    let kept = words(&ExportOptions::default());
+    assert_eq!(&kept[.. 6], &["read", "read(1)", "read(2)", "read(3)", "read(4)", "read(6)"]);
+    assert_eq!(&kept[8 ..], &["read(9)", "read(10)", "red", "reed"]);
+
+    let renumbered = words(&ExportOptions { renumber_variants: true, ..Default::default() });
+    assert_eq!(&renumbered[8 ..], &["read(8)", "read(9)", "red", "reed"]);
+  }
+
+  #[test]
+  fn dictionary_variant_ties() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    let polyphone = arpa.get_polyphone("red").unwrap();
+    arpa.insert("read(0)".to_string(), polyphone.clone());
+    arpa.insert("read(99999999999999999999)".to_string(), polyphone);
+
+    let mut output = Vec::new();
+    write_dictionary(&arpa, &mut output, &ExportOptions::default()).expect("should write");
+    let text = String::from_utf8(output).unwrap();
+    let words = text.lines()
+      .map(|line| line.split("  ").next().unwrap())
+      .collect::<Vec<&str>>();
+    assert_eq!(words, vec!["read", "read(0)", "read(99999999999999999999)", "read(1)", "red",
+      "reed"]);
+
+    let options = ExportOptions { renumber_variants: true, ..Default::default() };
+    let renumbered = ordered_entries(&arpa, &options).into_iter()
+      .map(|(word, _)| word)
+      .collect::<Vec<String>>();
+    assert_eq!(&renumbered[.. 4], &["read", "read(1)", "read(2)", "read(3)"]);
+  }
+
+  #[test]
+  fn dictionary_pronunciation_order() {
+    let options = ExportOptions { order: EntryOrder::Pronunciation, ..Default::default() };
+
+    let mut output = Vec::new();
+    write_dictionary(&testing::arpabet(DICTIONARY), &mut output, &options).expect("should write");
+
+    assert_eq!(String::from_utf8(output).unwrap(),
+      "read(1)  R EH1 D\n\
+       red  R EH1 D\n\
+       read  R IY1 D\n\
+       reed  R IY1 D\n");
+  }
+
+  #[test]
+  fn reverse_index_format() {
+    let mut output = Vec::new();
+    write_reverse_index(&testing::arpabet(DICTIONARY), &mut output, &ExportOptions::default())
+      .expect("should write");
+
+    assert_eq!(String::from_utf8(output).unwrap(),
+      "R EH1 D\tread,red\n\
+       R IY1 D\tread,reed\n");
+  }
+
+  #[test]
+  fn reverse_index_casing() {
+    let casing = CasingMap::from_forms(vec!["Reed"]);
+    let options = ExportOptions { casing: Some(&casing), ..Default::default() };
+
+    let mut output = Vec::new();
+    let arpa = testing::arpabet(DICTIONARY);
+    write_reverse_index(&arpa, &mut output, &options).expect("should write");
+
+    assert_eq!(String::from_utf8(output).unwrap(),
+      "R EH1 D\tread,red\n\
+       R IY1 D\tread,Reed\n");
+  }
+
+  #[test]
+  fn reverse_index_empty() {
+    let mut output = Vec::new();
+    write_reverse_index(&Arpabet::new(), &mut output, &ExportOptions::default())
+      .expect("should write");
+    assert!(output.is_empty());
+  }
+}
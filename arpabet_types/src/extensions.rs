@@ -1,15 +1,20 @@
 //! This module contains a few non-standard tokens and mappings that are not declared in CMUDict.
 
+use crate::constants::{ALL_CONSONANTS, ALL_PUNCTUATION, ALL_VOWELS};
+use crate::error::ArpabetError;
 use crate::phoneme::{
   Consonant,
   Phoneme,
   Vowel,
   VowelStress
 };
+use std::convert::TryFrom;
 
 /// Punctuation devices.
 /// These do not belong to Arpabet, but their inclusion can help with ML tasks.
+/// More may be added in minor releases.
 #[derive(Copy,Clone,Debug,PartialEq)]
+#[non_exhaustive]
 pub enum Punctuation {
   /// Denotes the beginning of an utterance.
   /// In a single clip, this is the start of audio.
@@ -30,6 +35,12 @@ pub enum Punctuation {
   Quote,
   /// Denotes an ellipsis (...) within a sentence.
   Ellipsis,
+  /// Denotes a prosodic phrase break (pause) within a sentence.
+  /// These are predicted rather than written, see `phrasing::predict_breaks`.
+  Break,
+  /// Denotes a non-speech annotation, eg. a stage direction such as [laughs].
+  /// These are only emitted when enabled, see `tokenizer::AnnotationPolicy`.
+  Annotation,
   /// Denotes the end of an utterance.
   /// In a single clip, this is the end of audio.
   EndToken,
@@ -48,6 +59,8 @@ impl Punctuation {
       Punctuation::Interjection => "[interjection]",
       Punctuation::Quote => "[quote]",
       Punctuation::Ellipsis => "[ellipsis]",
+      Punctuation::Break => "[break]",
+      Punctuation::Annotation => "[annotation]",
       Punctuation::EndToken => "[end]",
     }
   }
@@ -67,13 +80,17 @@ impl From<Punctuation> for u8 {
       Punctuation::Interjection => 207,
       Punctuation::Quote => 208,
       Punctuation::Ellipsis => 209,
+      Punctuation::Break => 210,
+      Punctuation::Annotation => 211,
       Punctuation::EndToken => 254, // NB: Especially set to 254.
     }
   }
 }
 
 /// A sentence token is any phoneme or punctuation token.
+/// More kinds may be added in minor releases.
 #[derive(Copy,Clone,Debug,PartialEq)]
+#[non_exhaustive]
 pub enum SentenceToken {
   /// A phoneme within a sentence. Runs of phonemes constitute polyphones (words).
   Phoneme(Phoneme),
@@ -268,6 +285,43 @@ impl From<Vowel> for u8 {
   }
 }
 
+impl TryFrom<u8> for Phoneme {
+  type Error = ArpabetError;
+
+  /// Map unsigned ints back to phonemes. The inverse of `u8::from`.
+  fn try_from(code: u8) -> Result<Self, Self::Error> {
+    let phoneme = match code {
+      1 ..= 100 => ALL_CONSONANTS.get(code as usize - 1).map(|c| Phoneme::Consonant(*c)),
+      101 ..= 200 => ALL_VOWELS.get(code as usize - 101).map(|v| Phoneme::Vowel(*v)),
+      _ => None,
+    };
+    phoneme.ok_or(ArpabetError::UnknownCode { code })
+  }
+}
+
+impl TryFrom<u8> for Punctuation {
+  type Error = ArpabetError;
+
+  /// Map unsigned ints back to punctuation. The inverse of `u8::from`.
+  fn try_from(code: u8) -> Result<Self, Self::Error> {
+    ALL_PUNCTUATION.iter()
+      .find(|punctuation| u8::from(**punctuation) == code)
+      .copied()
+      .ok_or(ArpabetError::UnknownCode { code })
+  }
+}
+
+impl TryFrom<u8> for SentenceToken {
+  type Error = ArpabetError;
+
+  /// Map unsigned ints back to sentence tokens. The inverse of `u8::from`.
+  fn try_from(code: u8) -> Result<Self, Self::Error> {
+    Phoneme::try_from(code)
+      .map(SentenceToken::Phoneme)
+      .or_else(|_| Punctuation::try_from(code).map(SentenceToken::Punctuation))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use crate::constants::{ALL_CONSONANTS, ALL_VOWELS};
@@ -285,6 +339,8 @@ mod tests {
     expect!(u8::from(Punctuation::Interjection)).to(be_eq(207));
     expect!(u8::from(Punctuation::Quote)).to(be_eq(208));
     expect!(u8::from(Punctuation::Ellipsis)).to(be_eq(209));
+    expect!(u8::from(Punctuation::Break)).to(be_eq(210));
+    expect!(u8::from(Punctuation::Annotation)).to(be_eq(211));
     expect!(u8::from(Punctuation::EndToken)).to(be_eq(254)); // NB: Exception
   }
 
@@ -299,6 +355,8 @@ mod tests {
     expect!(Punctuation::Interjection.to_str()).to(be_eq("[interjection]"));
     expect!(Punctuation::Quote.to_str()).to(be_eq("[quote]"));
     expect!(Punctuation::Ellipsis.to_str()).to(be_eq("[ellipsis]"));
+    expect!(Punctuation::Break.to_str()).to(be_eq("[break]"));
+    expect!(Punctuation::Annotation.to_str()).to(be_eq("[annotation]"));
     expect!(Punctuation::EndToken.to_str()).to(be_eq("[end]"));
   }
 
@@ -318,6 +376,31 @@ mod tests {
     }
   }
 
+  #[test]
+  fn u8_to_phoneme() {
+    for consonant in ALL_CONSONANTS.iter() {
+      expect!(Phoneme::try_from(u8::from(*consonant)).ok())
+          .to(be_eq(Some(Phoneme::Consonant(*consonant))));
+    }
+    for vowel in ALL_VOWELS.iter() {
+      expect!(Phoneme::try_from(u8::from(*vowel)).ok()).to(be_eq(Some(Phoneme::Vowel(*vowel))));
+    }
+    for code in [0, 32, 100, 177, 201, 254].iter() {
+      expect!(Phoneme::try_from(*code).is_err()).to(be_true());
+    }
+  }
+
+  #[test]
+  fn u8_to_sentence_token() {
+    for punctuation in ALL_PUNCTUATION.iter() {
+      expect!(SentenceToken::try_from(u8::from(*punctuation)).ok())
+          .to(be_eq(Some(SentenceToken::Punctuation(*punctuation))));
+    }
+    expect!(SentenceToken::try_from(1).ok())
+        .to(be_eq(Some(SentenceToken::Phoneme(Phoneme::Consonant(Consonant::B)))));
+    expect!(SentenceToken::try_from(212).is_err()).to(be_true());
+  }
+
   #[test]
   fn sentence_token_to_str() {
     expect!(SentenceToken::Phoneme(Phoneme::Vowel(Vowel::AA(VowelStress::PrimaryStress))).to_str())
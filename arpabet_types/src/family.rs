@@ -0,0 +1,154 @@
+//! This module groups words into families sharing a root, eg. 'run', 'runs',
+//! 'running' and 'runner', by both spelling and pronunciation.
+//!
+//! A word belongs to a root's family when it is spelled like the root plus an
+//! inflection, as undone by `EnglishStemmer`. It must also be pronounced like
+//! the root plus one of the suffixes in `FAMILY_SUFFIXES`. Requiring both
+//! keeps out lookalikes such as 'bead', which sounds like 'bee' plus D but
+//! isn't spelled like it.
+
+use crate::casing::fold_case;
+use crate::constants::PHONEME_MAP;
+use crate::index::strip_variant;
+use crate::lemmatize::{EnglishStemmer, Lemmatizer};
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone};
+
+/// Pronunciations of the standard English suffixes, without stress, eg. 'ing'
+/// as IH NG.
+pub const FAMILY_SUFFIXES : [&str; 22] = [
+  "Z", "S", "IH Z", "AH Z",
+  "D", "T", "IH D", "AH D",
+  "IH NG", "IH NG Z",
+  "ER", "ER Z",
+  "IH S T", "AH S T",
+  "L IY",
+  "N AH S",
+  "AH B AH L",
+  "M AH N T", "M AH N T S",
+  "F AH L",
+  "L AH S",
+  "IY",
+];
+
+impl Arpabet {
+  /// Find the family of a word: its root and the dictionary words formed from
+  /// the root with a standard suffix. The root is the word itself, or the
+  /// lemma it was formed from, eg. 'run' for 'running' and 'runners'. Every
+  /// pronunciation is considered, and stress is ignored. Results include the
+  /// root and the word. They are ranked by attached frequencies, if any, then
+  /// alphabetically. Empty if the word isn't in the dictionary.
+  pub fn word_family(&self, word: &str) -> Vec<&str> {
+    let word = fold_case(word);
+    if !self.contains_key(&word) {
+      return Vec::new();
+    }
+
+    let suffixes = FAMILY_SUFFIXES.iter()
+      .map(|suffix| suffix.split(' ').filter_map(|p| PHONEME_MAP.get(p).copied()).collect())
+      .collect::<Vec<Polyphone>>();
+
+    // Follow lemmas down to the root, eg. 'runners' -> 'runner' -> 'run'.
+    let mut root = word;
+    while let Some(lemma) = spelled_roots(&root)
+      .into_iter()
+      .find(|lemma| self.contains_key(lemma) && self.is_derived(&root, lemma, &suffixes))
+    {
+      root = lemma;
+    }
+
+    let prefix = root.strip_suffix(|c| c == 'e' || c == 'y').unwrap_or(&root);
+
+    let mut family = self.keys()
+      .filter(|key| strip_variant(key) == *key && key.starts_with(prefix))
+      .filter(|key| {
+        *key == root || (is_spelled_from(key, &root) && self.is_derived(key, &root, &suffixes))
+      })
+      .collect::<Vec<&str>>();
+
+    family.sort();
+    self.rank_by_frequency(&mut family);
+    family
+  }
+
+  // Whether a pronunciation of the word is a pronunciation of the root plus
+  // one of the suffixes.
+  fn is_derived(&self, word: &str, root: &str, suffixes: &[Polyphone]) -> bool {
+    let roots = self.get_polyphone_variants(root);
+    self.get_polyphone_variants(word).iter().any(|polyphone| {
+      roots.iter().any(|root| {
+        polyphone.len() > root.len()
+          && same_phonemes(&polyphone[.. root.len()], root)
+          && suffixes.iter().any(|suffix| same_phonemes(&polyphone[root.len() ..], suffix))
+      })
+    })
+  }
+}
+
+// The roots a word may be spelled from: its lemmas, and its lemmas with a
+// silent 'e' restored, eg. 'baker' -> 'bak', 'bake'.
+fn spelled_roots(word: &str) -> Vec<String> {
+  let mut roots = EnglishStemmer.lemmas(word);
+  let restored = roots.iter()
+    .filter(|lemma| !lemma.ends_with('e'))
+    .map(|lemma| format!("{}e", lemma))
+    .filter(|lemma| lemma != word)
+    .collect::<Vec<String>>();
+  roots.extend(restored);
+  roots
+}
+
+// Whether the word is spelled from the root, directly or through an
+// intermediate lemma, eg. 'runners' -> 'runner' -> 'run'.
+fn is_spelled_from(word: &str, root: &str) -> bool {
+  spelled_roots(word).iter()
+    .any(|lemma| lemma == root || spelled_roots(lemma).iter().any(|lemma| lemma == root))
+}
+
+// Whether the pronunciations are the same, regardless of stress.
+fn same_phonemes(a: &[Phoneme], b: &[Phoneme]) -> bool {
+  a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.stressless() == b.stressless())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::testing::arpabet;
+
+  const FAMILIES : &[(&str, &str)] = &[
+    ("run", "R AH1 N"),
+    ("runs", "R AH1 N Z"),
+    ("running", "R AH1 N IH0 NG"),
+    ("runner", "R AH1 N ER0"),
+    ("runners", "R AH1 N ER0 Z"),
+    ("rung", "R AH1 NG"),
+    ("runt", "R AH1 N T"),
+    ("bake", "B EY1 K"),
+    ("baker", "B EY1 K ER0"),
+    ("baked", "B EY1 K T"),
+    ("bee", "B IY1"),
+    ("bead", "B IY1 D"),
+    ("bees", "B IY1 Z"),
+  ];
+
+  #[test]
+  fn family_of_root() {
+    let arpa = arpabet(FAMILIES);
+    // 'runt' sounds like 'run' plus T, but isn't an inflection of it.
+    assert_eq!(arpa.word_family("run"), vec!["run", "runner", "runners", "running", "runs"]);
+    assert_eq!(arpa.word_family("bake"), vec!["bake", "baked", "baker"]);
+    assert_eq!(arpa.word_family("bee"), vec!["bee", "bees"]);
+    assert!(arpa.word_family("unknown").is_empty());
+  }
+
+  #[test]
+  fn family_of_derived_word() {
+    let mut arpa = arpabet(FAMILIES);
+    assert_eq!(arpa.word_family("Running"), arpa.word_family("run"));
+    assert_eq!(arpa.word_family("baker"), vec!["bake", "baked", "baker"]);
+    assert_eq!(arpa.word_family("runners"), arpa.word_family("run"));
+    assert_eq!(arpa.word_family("bead"), vec!["bead"]);
+
+    arpa.attach_frequencies(vec![("baked", 10)].into_iter().collect());
+    assert_eq!(arpa.word_family("bake"), vec!["baked", "bake", "baker"]);
+  }
+}
@@ -0,0 +1,96 @@
+//! This module exports dictionaries as Festival lexicon entries, so custom
+//! dictionaries can drive Festival voices, or Flite voices built with
+//! Festival's tools. Each entry is the word, its part of speech, and its
+//! syllables, each a list of phones with a stress number:
+//!
+//! ```text
+//! ("arpabet" nil (((aa r) 1) ((p ah) 0) ((b eh t) 1)))
+//! ```
+//!
+//! Compile the entries with `lex.compile`, or add them one at a time with
+//! `lex.add.entry`.
+
+use crate::export::{ordered_entries, ExportOptions};
+use crate::index::strip_variant;
+use crate::phoneme::{Phoneme, VowelStress};
+use crate::syllable::{syllabify, syllable_stress};
+use crate::{Arpabet, ArpabetError};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Write a word and its pronunciation as a Festival lexicon entry. The part of
+/// speech is `nil`, as dictionaries don't record it. Phones are lowercase
+/// Arpabet without stress digits; syllables are found by `syllabify`.
+/// Primary and secondary stress are both written 1, as Festival's English
+/// voices only tell stressed syllables from unstressed ones.
+pub fn festival_entry(word: &str, polyphone: &[Phoneme]) -> String {
+  let syllables : Vec<String> = syllabify(polyphone).iter()
+    .map(|syllable| {
+      let phones : Vec<String> = syllable.iter()
+        .map(|phoneme| phoneme.stressless().to_str().to_lowercase())
+        .collect();
+      let stress = match syllable_stress(syllable) {
+        Some(VowelStress::PrimaryStress) | Some(VowelStress::SecondaryStress) => 1,
+        _ => 0,
+      };
+      format!("(({}) {})", phones.join(" "), stress)
+    })
+    .collect();
+  format!("(\"{}\" nil ({}))", word.replace('\\', "\\\\").replace('"', "\\\""), syllables.join(" "))
+}
+
+/// Write every dictionary entry as a Festival lexicon entry, one per line.
+/// Entries are written in the order given by `ordered_entries`, without
+/// variant markers, so alternates follow their primary pronunciation.
+pub fn write_festival_lexicon(arpabet: &Arpabet, writer: &mut dyn Write, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  for (word, polyphone) in ordered_entries(arpabet, options) {
+    writeln!(writer, "{}", festival_entry(strip_variant(&word), polyphone))?;
+  }
+  Ok(())
+}
+
+/// Save the dictionary to a file of Festival lexicon entries. See
+/// `write_festival_lexicon`.
+pub fn save_festival_lexicon(arpabet: &Arpabet, filename: &str, options: &ExportOptions)
+  -> Result<(), ArpabetError>
+{
+  let mut writer = BufWriter::new(File::create(filename)?);
+  write_festival_lexicon(arpabet, &mut writer, options)?;
+  writer.flush()?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn entries() {
+    assert_eq!(festival_entry("arpabet", &polyphone("AA1 R P AH0 B EH2 T")),
+      "(\"arpabet\" nil (((aa r) 1) ((p ah) 0) ((b eh t) 1)))");
+    assert_eq!(festival_entry("extra", &polyphone("EH1 K S T R AH0")),
+      "(\"extra\" nil (((eh k) 1) ((s t r ah) 0)))");
+    assert_eq!(festival_entry("hmm", &polyphone("HH M")), "(\"hmm\" nil (((hh m) 0)))");
+    assert_eq!(festival_entry("\"quoted\"", &polyphone("K W OW1 T")),
+      "(\"\\\"quoted\\\"\" nil (((k w ow t) 1)))");
+  }
+
+  #[test]
+  fn lexicon() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("read".to_string(), polyphone("R IY1 D"));
+    arpa.insert("read(1)".to_string(), polyphone("R EH1 D"));
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+
+    let mut output = Vec::new();
+    write_festival_lexicon(&arpa, &mut output, &ExportOptions::default()).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "\
+(\"cat\" nil (((k ae t) 1)))
+(\"read\" nil (((r iy d) 1)))
+(\"read\" nil (((r eh d) 1)))
+");
+  }
+}
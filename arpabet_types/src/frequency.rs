@@ -0,0 +1,106 @@
+//! This module contains unigram word frequencies that can be attached to an Arpabet
+//! to weight queries towards common words.
+
+use crate::casing::fold_case;
+use crate::memory::HeapSize;
+use crate::Word;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Unigram counts for words, typically gathered from a text corpus.
+#[derive(Clone, Debug, Default)]
+pub struct WordFrequencies {
+  counts: HashMap<Word, u64>,
+  total: u64,
+}
+
+impl WordFrequencies {
+  /// Create an empty frequency table.
+  pub fn new() -> Self {
+    Self {
+      counts: HashMap::new(),
+      total: 0,
+    }
+  }
+
+  /// Add occurrences of a word. Words are stored lowercase.
+  pub fn insert(&mut self, word: &str, count: u64) {
+    *self.counts.entry(fold_case(word)).or_insert(0) += count;
+    self.total += count;
+  }
+
+  /// Get the number of recorded occurrences of a word, in any case.
+  pub fn get(&self, word: &str) -> u64 {
+    self.counts.get(&fold_case(word)).copied().unwrap_or(0)
+  }
+
+  /// Total number of occurrences across all words.
+  pub fn total(&self) -> u64 {
+    self.total
+  }
+
+  /// Reports the number of distinct words in the table.
+  pub fn len(&self) -> usize {
+    self.counts.len()
+  }
+
+  /// Whether the table contains no words.
+  pub fn is_empty(&self) -> bool {
+    self.counts.is_empty()
+  }
+
+  /// Natural log probability of a word, with add-one smoothing so that
+  /// unseen words receive a small but finite probability.
+  pub fn log_probability(&self, word: &str) -> f64 {
+    let vocabulary = self.counts.len() as f64 + 1.0;
+    let count = self.get(word) as f64 + 1.0;
+    (count / (self.total as f64 + vocabulary)).ln()
+  }
+}
+
+impl<'a> FromIterator<(&'a str, u64)> for WordFrequencies {
+  fn from_iter<I: IntoIterator<Item=(&'a str, u64)>>(iter: I) -> Self {
+    let mut frequencies = WordFrequencies::new();
+    for (word, count) in iter {
+      frequencies.insert(word, count);
+    }
+    frequencies
+  }
+}
+
+impl HeapSize for WordFrequencies {
+  fn heap_size(&self) -> usize {
+    self.counts.heap_size()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn insert_and_get() {
+    let mut frequencies = WordFrequencies::new();
+    frequencies.insert("The", 10);
+    frequencies.insert("the", 5);
+    frequencies.insert("cat", 1);
+
+    assert_eq!(frequencies.get("the"), 15);
+    assert_eq!(frequencies.get("The"), 15);
+    assert_eq!(frequencies.get("THE"), 15);
+    assert_eq!(frequencies.get("cat"), 1);
+    assert_eq!(frequencies.get("dog"), 0);
+    assert_eq!(frequencies.total(), 16);
+    assert_eq!(frequencies.len(), 2);
+  }
+
+  #[test]
+  fn log_probability_orders_by_count() {
+    let frequencies : WordFrequencies = vec![("the", 100), ("cat", 3)].into_iter().collect();
+
+    assert!(frequencies.log_probability("the") > frequencies.log_probability("cat"));
+    assert!(frequencies.log_probability("cat") > frequencies.log_probability("dog"));
+    assert!(frequencies.log_probability("dog").is_finite());
+    assert_eq!(frequencies.log_probability("The"), frequencies.log_probability("the"));
+  }
+}
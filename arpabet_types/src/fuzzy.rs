@@ -0,0 +1,157 @@
+//! This module looks up misspelled words, eg. "recieve", by finding the
+//! dictionary words within a small edit distance.
+//!
+//! The distance counts single character insertions, deletions, substitutions
+//! and transpositions of adjacent characters, so common typos such as swapped
+//! letters cost one edit.
+
+use crate::casing::fold_case;
+use crate::index::strip_variant;
+use crate::{Arpabet, Polyphone, Word};
+use std::cmp::{min, Reverse};
+use std::mem;
+
+/// A dictionary word matched to a possibly misspelled one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzyMatch {
+  /// The dictionary word.
+  pub word: Word,
+  /// Pronunciation of the dictionary word.
+  pub polyphone: Polyphone,
+  /// Number of edits between the looked up word and the dictionary word.
+  pub distance: usize,
+}
+
+impl Arpabet {
+  /// Get the pronunciation of the dictionary word closest to the word, within
+  /// `max_distance` edits. An exact (case insensitive) match is preferred, then
+  /// the fewest edits; ties are broken by attached frequencies, if any, then
+  /// alphabetically.
+  pub fn get_polyphone_fuzzy(&self, word: &str, max_distance: usize) -> Option<FuzzyMatch> {
+    let word = fold_case(word);
+    if let Some(polyphone) = self.get_polyphone_ref(&word) {
+      return Some(FuzzyMatch { word, polyphone: polyphone.to_vec(), distance: 0 });
+    }
+    self.fuzzy_matches(&word, max_distance).into_iter().next()
+  }
+
+  /// Find every dictionary word within `max_distance` edits of the word, in
+  /// the order `get_polyphone_fuzzy` prefers them. Alternate pronunciations
+  /// are not listed separately.
+  pub fn fuzzy_matches(&self, word: &str, max_distance: usize) -> Vec<FuzzyMatch> {
+    let target = fold_case(word).chars().collect::<Vec<char>>();
+    let mut buffer = Vec::new();
+
+    let mut matches = self.dictionary.iter()
+      .filter(|(key, _)| strip_variant(key) == *key)
+      .filter_map(|(key, polyphone)| {
+        buffer.clear();
+        buffer.extend(key.chars());
+        bounded_distance(&target, &buffer, max_distance)
+          .map(|distance| (key, polyphone, distance))
+      })
+      .collect::<Vec<_>>();
+
+    matches.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.cmp(b.0)));
+    if let Some(frequencies) = self.frequencies() {
+      // NB: Stable sort keeps alphabetical order among equally common words.
+      matches.sort_by_key(|(key, _, distance)| (*distance, Reverse(frequencies.get(key))));
+    }
+
+    matches.into_iter()
+      .map(|(key, polyphone, distance)| FuzzyMatch {
+        word: key.to_string(),
+        polyphone: polyphone.to_vec(),
+        distance,
+      })
+      .collect()
+  }
+}
+
+/// The number of insertions, deletions, substitutions and adjacent
+/// transpositions turning one word into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let a = a.chars().collect::<Vec<char>>();
+  let b = b.chars().collect::<Vec<char>>();
+  bounded_distance(&a, &b, usize::MAX).unwrap_or(usize::MAX)
+}
+
+// The edit distance if it is at most `max`, computed row by row and abandoned
+// as soon as every entry of a row exceeds `max`.
+fn bounded_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+  if a.len().abs_diff(b.len()) > max {
+    return None;
+  }
+
+  let mut before = Vec::new();
+  let mut previous = (0 ..= b.len()).collect::<Vec<usize>>();
+  let mut current = vec![0; b.len() + 1];
+
+  for i in 1 ..= a.len() {
+    current[0] = i;
+    for j in 1 ..= b.len() {
+      let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+      current[j] = min(min(previous[j] + 1, current[j - 1] + 1), previous[j - 1] + cost);
+      if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        current[j] = min(current[j], before[j - 2] + 1);
+      }
+    }
+    if current.iter().all(|distance| *distance > max) {
+      return None;
+    }
+    before = mem::replace(&mut previous, current.clone());
+  }
+
+  Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::frequency::WordFrequencies;
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("receive", "R IH0 S IY1 V"),
+    ("cat", "K AE1 T"),
+    ("cot", "K AA1 T"),
+    ("cot(1)", "K AO1 T"),
+  ];
+
+  #[test]
+  fn distances() {
+    assert_eq!(edit_distance("receive", "receive"), 0);
+    assert_eq!(edit_distance("recieve", "receive"), 1);
+    assert_eq!(edit_distance("recive", "receive"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+    assert_eq!(bounded_distance(&['a', 'b', 'c'], &['x', 'y', 'z'], 2), None);
+    assert_eq!(bounded_distance(&['a'], &['a', 'b', 'c', 'd'], 2), None);
+  }
+
+  #[test]
+  fn fuzzy_lookup() {
+    let arpa = testing::arpabet(DICTIONARY);
+
+    let found = arpa.get_polyphone_fuzzy("Recieve", 1).unwrap();
+    assert_eq!(found.word, "receive");
+    assert_eq!(found.distance, 1);
+    assert_eq!(found.polyphone, arpa.get_polyphone("receive").unwrap());
+
+    assert_eq!(arpa.get_polyphone_fuzzy("cot", 2).unwrap().distance, 0);
+    assert_eq!(arpa.get_polyphone_fuzzy("recieve", 0), None);
+    assert_eq!(arpa.get_polyphone_fuzzy("xyzzy", 2), None);
+  }
+
+  #[test]
+  fn ranking() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    let words = arpa.fuzzy_matches("cut", 1).into_iter().map(|m| m.word).collect::<Vec<_>>();
+    assert_eq!(words, vec!["cat", "cot"]);
+
+    let mut frequencies = WordFrequencies::new();
+    frequencies.insert("cot", 10);
+    arpa.attach_frequencies(frequencies);
+    assert_eq!(arpa.get_polyphone_fuzzy("cut", 1).unwrap().word, "cot");
+  }
+}
@@ -0,0 +1,200 @@
+//! This module disambiguates heteronyms, words spelled alike but pronounced
+//! differently depending on their part of speech, eg. "record" the noun
+//! (R EH1 K ER0 D) and the verb (R IH0 K AO1 R D).
+//!
+//! The part of speech comes from the caller, eg. from a tagger run over the
+//! text; see `Arpabet::transcribe_sentence_with_pos`.
+
+use crate::casing::fold_case;
+use crate::constants::PHONEME_MAP;
+use crate::error::ArpabetError;
+use crate::extensions::SentenceToken;
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone};
+
+/// The parts of speech that distinguish heteronyms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PartOfSpeech {
+  /// A noun, eg. "a record".
+  Noun,
+  /// A verb other than the past tense, eg. "to record", "records".
+  Verb,
+  /// A verb in the past tense or the past participle, eg. "I read it".
+  PastVerb,
+  /// An adjective, eg. "a live show".
+  Adjective,
+  /// An adverb, eg. "come close".
+  Adverb,
+  /// Any other part of speech, or unknown.
+  Other,
+}
+
+impl PartOfSpeech {
+  /// Map a Penn Treebank tag, as produced by most English taggers, eg. "NNS"
+  /// or "VBD". Unknown tags map to `Other`.
+  pub fn from_penn_tag(tag: &str) -> Self {
+    match tag {
+      "VBD" | "VBN" => PartOfSpeech::PastVerb,
+      _ if tag.starts_with("NN") => PartOfSpeech::Noun,
+      _ if tag.starts_with("VB") || tag == "MD" => PartOfSpeech::Verb,
+      _ if tag.starts_with("JJ") => PartOfSpeech::Adjective,
+      _ if tag.starts_with("RB") => PartOfSpeech::Adverb,
+      _ => PartOfSpeech::Other,
+    }
+  }
+}
+
+/// Heteronyms and their pronunciations by part of speech, sorted by word.
+pub const HETERONYMS : [(&str, PartOfSpeech, &str); 43] = [
+  ("close", PartOfSpeech::Adjective, "K L OW1 S"),
+  ("close", PartOfSpeech::Adverb, "K L OW1 S"),
+  ("close", PartOfSpeech::Verb, "K L OW1 Z"),
+  ("conduct", PartOfSpeech::Noun, "K AA1 N D AH0 K T"),
+  ("conduct", PartOfSpeech::Verb, "K AH0 N D AH1 K T"),
+  ("contract", PartOfSpeech::Noun, "K AA1 N T R AE2 K T"),
+  ("contract", PartOfSpeech::Verb, "K AH0 N T R AE1 K T"),
+  ("desert", PartOfSpeech::Noun, "D EH1 Z ER0 T"),
+  ("desert", PartOfSpeech::Verb, "D IH0 Z ER1 T"),
+  ("estimate", PartOfSpeech::Noun, "EH1 S T AH0 M AH0 T"),
+  ("estimate", PartOfSpeech::Verb, "EH1 S T AH0 M EY2 T"),
+  ("lead", PartOfSpeech::Noun, "L EH1 D"),
+  ("lead", PartOfSpeech::Verb, "L IY1 D"),
+  ("live", PartOfSpeech::Adjective, "L AY1 V"),
+  ("live", PartOfSpeech::Adverb, "L AY1 V"),
+  ("live", PartOfSpeech::Verb, "L IH1 V"),
+  ("minute", PartOfSpeech::Adjective, "M AY0 N UW1 T"),
+  ("minute", PartOfSpeech::Noun, "M IH1 N AH0 T"),
+  ("object", PartOfSpeech::Noun, "AA1 B JH EH0 K T"),
+  ("object", PartOfSpeech::Verb, "AH0 B JH EH1 K T"),
+  ("permit", PartOfSpeech::Noun, "P ER1 M IH2 T"),
+  ("permit", PartOfSpeech::Verb, "P ER0 M IH1 T"),
+  ("present", PartOfSpeech::Adjective, "P R EH1 Z AH0 N T"),
+  ("present", PartOfSpeech::Noun, "P R EH1 Z AH0 N T"),
+  ("present", PartOfSpeech::Verb, "P R IY0 Z EH1 N T"),
+  ("produce", PartOfSpeech::Noun, "P R OW1 D UW0 S"),
+  ("produce", PartOfSpeech::Verb, "P R AH0 D UW1 S"),
+  ("project", PartOfSpeech::Noun, "P R AA1 JH EH0 K T"),
+  ("project", PartOfSpeech::Verb, "P R AA0 JH EH1 K T"),
+  ("read", PartOfSpeech::PastVerb, "R EH1 D"),
+  ("read", PartOfSpeech::Verb, "R IY1 D"),
+  ("record", PartOfSpeech::Noun, "R EH1 K ER0 D"),
+  ("record", PartOfSpeech::Verb, "R IH0 K AO1 R D"),
+  ("refuse", PartOfSpeech::Noun, "R EH1 F Y UW2 Z"),
+  ("refuse", PartOfSpeech::Verb, "R IH0 F Y UW1 Z"),
+  ("separate", PartOfSpeech::Adjective, "S EH1 P ER0 IH0 T"),
+  ("separate", PartOfSpeech::Verb, "S EH1 P ER0 EY2 T"),
+  ("tear", PartOfSpeech::Noun, "T IH1 R"),
+  ("tear", PartOfSpeech::Verb, "T EH1 R"),
+  ("use", PartOfSpeech::Noun, "Y UW1 S"),
+  ("use", PartOfSpeech::Verb, "Y UW1 Z"),
+  ("wind", PartOfSpeech::Noun, "W IH1 N D"),
+  ("wind", PartOfSpeech::Verb, "W AY1 N D"),
+];
+
+/// Whether the word, in any casing, is one of the `HETERONYMS`.
+pub fn is_heteronym(word: &str) -> bool {
+  let word = fold_case(word);
+  HETERONYMS.binary_search_by(|(w, _, _)| w.cmp(&word.as_str())).is_ok()
+}
+
+/// Get the pronunciation of a heteronym as the part of speech, in any casing.
+/// A past tense verb without a pronunciation of its own is pronounced as the
+/// verb. Returns None for other words, and for parts of speech the table
+/// doesn't distinguish.
+pub fn heteronym_pronunciation(word: &str, part_of_speech: PartOfSpeech) -> Option<Polyphone> {
+  let word = fold_case(word);
+  let find = |part_of_speech: PartOfSpeech| {
+    HETERONYMS.iter().find(|(w, pos, _)| *w == word && *pos == part_of_speech)
+  };
+  find(part_of_speech)
+    .or_else(|| match part_of_speech {
+      PartOfSpeech::PastVerb => find(PartOfSpeech::Verb),
+      _ => None,
+    })
+    .map(|(_, _, pronunciation)| {
+      pronunciation.split(' ')
+        .filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied())
+        .collect()
+    })
+}
+
+impl Arpabet {
+  /// Get the pronunciation of a word used as the part of speech: the
+  /// heteronym table's when it distinguishes the word, otherwise the
+  /// dictionary's. See `heteronym_pronunciation`.
+  pub fn get_polyphone_with_pos(&self, word: &str, part_of_speech: PartOfSpeech)
+    -> Option<Polyphone>
+  {
+    heteronym_pronunciation(word, part_of_speech).or_else(|| self.get_polyphone(&fold_case(word)))
+  }
+
+  /// Transcribe English text as `transcribe_sentence_with_options` does, with
+  /// the part of speech of each word token, in order, to pronounce heteronyms.
+  /// Words past the end of the tags are transcribed without one.
+  pub fn transcribe_sentence_with_pos(&self,
+                                      text: &str,
+                                      tags: &[PartOfSpeech],
+                                      options: &TranscribeOptions)
+                                      -> Result<Vec<SentenceToken>, ArpabetError> {
+    self.transcribe_tagged(text, tags, options)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn table() {
+    assert!(HETERONYMS.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    for (_, _, pronunciation) in HETERONYMS.iter() {
+      assert!(pronunciation.split(' ').all(|p| PHONEME_MAP.contains_key(p)), "{}", pronunciation);
+    }
+    assert!(is_heteronym("Record"));
+    assert!(!is_heteronym("cat"));
+  }
+
+  #[test]
+  fn pronunciations() {
+    assert_eq!(heteronym_pronunciation("read", PartOfSpeech::PastVerb), Some(polyphone("R EH1 D")));
+    assert_eq!(heteronym_pronunciation("Read", PartOfSpeech::Verb), Some(polyphone("R IY1 D")));
+    assert_eq!(heteronym_pronunciation("record", PartOfSpeech::Noun),
+      Some(polyphone("R EH1 K ER0 D")));
+    assert_eq!(heteronym_pronunciation("lead", PartOfSpeech::PastVerb), Some(polyphone("L IY1 D")));
+    assert_eq!(heteronym_pronunciation("lead", PartOfSpeech::Adverb), None);
+    assert_eq!(heteronym_pronunciation("cat", PartOfSpeech::Noun), None);
+  }
+
+  #[test]
+  fn penn_tags() {
+    assert_eq!(PartOfSpeech::from_penn_tag("NNS"), PartOfSpeech::Noun);
+    assert_eq!(PartOfSpeech::from_penn_tag("VBZ"), PartOfSpeech::Verb);
+    assert_eq!(PartOfSpeech::from_penn_tag("VBN"), PartOfSpeech::PastVerb);
+    assert_eq!(PartOfSpeech::from_penn_tag("JJR"), PartOfSpeech::Adjective);
+    assert_eq!(PartOfSpeech::from_penn_tag("RB"), PartOfSpeech::Adverb);
+    assert_eq!(PartOfSpeech::from_penn_tag("DT"), PartOfSpeech::Other);
+  }
+
+  #[test]
+  fn with_pos() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("read".to_string(), polyphone("R EH1 D"));
+    arpa.insert("i".to_string(), polyphone("AY1"));
+    arpa.insert("it".to_string(), polyphone("IH1 T"));
+
+    assert_eq!(arpa.get_polyphone_with_pos("read", PartOfSpeech::Verb), Some(polyphone("R IY1 D")));
+    assert_eq!(arpa.get_polyphone_with_pos("It", PartOfSpeech::Other), Some(polyphone("IH1 T")));
+
+    let options = TranscribeOptions::default();
+    let tokens = arpa.transcribe_sentence_with_pos("I read it", &[
+      PartOfSpeech::Other,
+      PartOfSpeech::Verb,
+    ], &options).unwrap();
+    let strings : Vec<&str> = tokens.iter().map(|token| token.to_str()).collect();
+    assert_eq!(strings, vec!["[start]", "AY1", "[space]", "R", "IY1", "D", "[space]", "IH1", "T",
+      "[end]"]);
+    assert_eq!(arpa.transcribe_sentence_with_pos("I read it", &[], &options).unwrap(),
+      arpa.transcribe_sentence_with_options("I read it", &options).unwrap());
+  }
+}
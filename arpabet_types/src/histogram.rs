@@ -0,0 +1,256 @@
+//! This module counts how often each phoneme occurs in a dictionary or a text
+//! corpus, and renders the counts as a report, eg. to check a voice recording
+//! script covers every sound.
+//!
+//! Stress is ignored: AH0, AH1 and AH2 all count as AH. Reports are plain text;
+//! the `svg` feature adds an SVG bar chart.
+
+use crate::phoneme::Phoneme;
+use crate::transcribe::TranscribeOptions;
+use crate::Arpabet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The phonemes of the extended ARPABET that CMUdict doesn't use. These are
+/// left out of `PhonemeHistogram::missing` and only reported once seen.
+pub const EXTENDED_PHONEMES : [&str; 11] =
+  ["AX", "AXR", "DX", "EL", "EM", "EN", "IX", "NX", "Q", "UX", "WH"];
+
+/// The 39 phonemes of CMUdict, without stress.
+pub const CMUDICT_PHONEMES : [&str; 39] = [
+  "AA", "AE", "AH", "AO", "AW", "AY", "B", "CH", "D", "DH", "EH", "ER", "EY", "F", "G", "HH",
+  "IH", "IY", "JH", "K", "L", "M", "N", "NG", "OW", "OY", "P", "R", "S", "SH", "T", "TH", "UH",
+  "UW", "V", "W", "Y", "Z", "ZH",
+];
+
+// Width of the bars in the plain text report.
+const TEXT_BAR_WIDTH : usize = 40;
+
+/// Occurrence counts of each phoneme, ignoring stress.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhonemeHistogram {
+  counts: HashMap<&'static str, usize>,
+  /// Words of the corpus that couldn't be pronounced, in order of appearance.
+  /// They are left out of the counts. Always empty for dictionaries.
+  pub unknown_words: Vec<String>,
+}
+
+impl PhonemeHistogram {
+  /// An empty histogram.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Count the phonemes of a pronunciation.
+  pub fn add(&mut self, polyphone: &[Phoneme]) {
+    for phoneme in polyphone {
+      *self.counts.entry(phoneme.stressless().to_str()).or_insert(0) += 1;
+    }
+  }
+
+  /// The number of occurrences of a phoneme, eg. "AH". Stress digits are
+  /// ignored, so "AH1" counts AH too.
+  pub fn count(&self, phoneme: &str) -> usize {
+    let phoneme = phoneme.trim_end_matches(|c: char| c.is_ascii_digit());
+    self.counts.get(phoneme).copied().unwrap_or(0)
+  }
+
+  /// The total number of phonemes counted.
+  pub fn total(&self) -> usize {
+    self.counts.values().sum()
+  }
+
+  /// The fraction of counted phonemes that are the phoneme. Zero when empty.
+  pub fn fraction(&self, phoneme: &str) -> f64 {
+    match self.total() {
+      0 => 0.0,
+      total => self.count(phoneme) as f64 / total as f64,
+    }
+  }
+
+  /// The phonemes seen, most frequent first, then alphabetically.
+  pub fn ranked(&self) -> Vec<(&'static str, usize)> {
+    let mut ranked = self.counts.iter()
+      .map(|(phoneme, count)| (*phoneme, *count))
+      .collect::<Vec<(&'static str, usize)>>();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+  }
+
+  /// The CMUdict phonemes never seen, alphabetically.
+  pub fn missing(&self) -> Vec<&'static str> {
+    CMUDICT_PHONEMES.iter()
+      .filter(|phoneme| self.count(phoneme) == 0)
+      .copied()
+      .collect()
+  }
+
+  /// Render the plain text report, with bars up to `bar_width` characters.
+  /// The `Display` implementation uses 40.
+  pub fn to_text(&self, bar_width: usize) -> String {
+    let total = self.total();
+    let ranked = self.ranked();
+    let max = ranked.first().map(|(_, count)| *count).unwrap_or(0);
+    let count_width = max.to_string().len();
+
+    let mut report = format!("{} phonemes\n", total);
+    for (phoneme, count) in ranked {
+      let bar = bar_length(count, max, bar_width);
+      report.push_str(&format!("{:<3} {:<bar_width$} {:>count_width$} {:>5.1}%\n",
+        phoneme, "#".repeat(bar), count, 100.0 * self.fraction(phoneme),
+        bar_width = bar_width, count_width = count_width));
+    }
+    let missing = self.missing();
+    if !missing.is_empty() {
+      report.push_str(&format!("missing: {}\n", missing.join(" ")));
+    }
+    if !self.unknown_words.is_empty() {
+      report.push_str(&format!("unknown words: {}\n", self.unknown_words.len()));
+    }
+    report
+  }
+
+  /// Render an SVG bar chart of the phonemes seen, most frequent first.
+  #[cfg(feature = "svg")]
+  pub fn to_svg(&self) -> String {
+    const ROW_HEIGHT : usize = 18;
+    const LABEL_WIDTH : usize = 40;
+    const BAR_WIDTH : usize = 400;
+
+    let ranked = self.ranked();
+    let max = ranked.first().map(|(_, count)| *count).unwrap_or(0);
+    let width = LABEL_WIDTH + BAR_WIDTH + 80;
+    let height = ROW_HEIGHT * (ranked.len() + 1);
+
+    let mut svg = format!(
+      "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">\n",
+      width, height);
+    svg.push_str(&format!("<text x=\"0\" y=\"{}\">{} phonemes</text>\n", ROW_HEIGHT - 5, self.total()));
+    for (i, (phoneme, count)) in ranked.into_iter().enumerate() {
+      let y = ROW_HEIGHT * (i + 1);
+      let bar = bar_length(count, max, BAR_WIDTH);
+      svg.push_str(&format!(
+        "<text x=\"0\" y=\"{}\">{}</text><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"steelblue\"/><text x=\"{}\" y=\"{}\">{}</text>\n",
+        y + ROW_HEIGHT - 5, phoneme, LABEL_WIDTH, y + 2, bar, ROW_HEIGHT - 4,
+        LABEL_WIDTH + bar + 4, y + ROW_HEIGHT - 5, count));
+    }
+    svg.push_str("</svg>\n");
+    svg
+  }
+}
+
+impl fmt::Display for PhonemeHistogram {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.to_text(TEXT_BAR_WIDTH))
+  }
+}
+
+impl Arpabet {
+  /// Count the phonemes of every entry, including alternate pronunciations.
+  pub fn phoneme_histogram(&self) -> PhonemeHistogram {
+    let mut histogram = PhonemeHistogram::new();
+    for polyphone in self.values() {
+      histogram.add(polyphone);
+    }
+    histogram
+  }
+}
+
+/// Count the phonemes of English text. Words are pronounced as by
+/// `Arpabet::transcribe_sentence`; words that can't be pronounced are collected
+/// in `unknown_words` rather than failing the count.
+pub fn corpus_phoneme_histogram(text: &str, arpabet: &Arpabet) -> PhonemeHistogram {
+  let mut histogram = PhonemeHistogram::new();
+  for (word, polyphone) in arpabet.pronounce_words(text, &TranscribeOptions::default()) {
+    match polyphone {
+      Some(polyphone) => histogram.add(&polyphone),
+      None => histogram.unknown_words.push(word),
+    }
+  }
+  histogram
+}
+
+// The length of the bar for the count, scaled so the largest fills the width.
+// Any nonzero count gets at least one unit.
+fn bar_length(count: usize, max: usize, width: usize) -> usize {
+  if max == 0 || count == 0 {
+    return 0;
+  }
+  (count * width / max).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::{ALL_CONSONANTS, ALL_VOWELS};
+  use crate::phoneme::VowelStress;
+  use crate::testing::{self, polyphone};
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("cat", "K AE1 T"),
+    ("tact", "T AE1 K T"),
+    ("a", "AH0"),
+    ("a(1)", "EY1"),
+  ];
+
+  #[test]
+  fn counts() {
+    let histogram = testing::arpabet(DICTIONARY).phoneme_histogram();
+    assert_eq!(histogram.total(), 9);
+    assert_eq!(histogram.count("T"), 3);
+    assert_eq!(histogram.count("AE1"), 2);
+    assert_eq!(histogram.count("AE"), 2);
+    assert_eq!(histogram.count("ZH"), 0);
+    assert!((histogram.fraction("T") - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(histogram.ranked()[..3], [("T", 3), ("AE", 2), ("K", 2)]);
+    assert_eq!(histogram.missing().len(), 39 - 5);
+    assert!(!histogram.missing().contains(&"EY"));
+  }
+
+  #[test]
+  fn corpus() {
+    let histogram = corpus_phoneme_histogram("A cat, a dog.", &testing::arpabet(DICTIONARY));
+    assert_eq!(histogram.total(), 5);
+    assert_eq!(histogram.count("AH"), 2);
+    assert_eq!(histogram.unknown_words, vec!["dog"]);
+  }
+
+  #[test]
+  fn text_report() {
+    let mut histogram = PhonemeHistogram::new();
+    histogram.add(&polyphone("T AE1 K T"));
+    assert_eq!(histogram.to_text(4).lines().take(4).collect::<Vec<&str>>(), vec![
+      "4 phonemes",
+      "T   #### 2  50.0%",
+      "AE  ##   1  25.0%",
+      "K   ##   1  25.0%",
+    ]);
+    assert!(histogram.to_string().contains("missing: AA AH AO"));
+    assert_eq!(PhonemeHistogram::new().to_string().lines().next(), Some("0 phonemes"));
+  }
+
+  #[test]
+  fn inventories() {
+    let mut all = ALL_CONSONANTS.iter()
+      .map(|consonant| consonant.to_str())
+      .chain(ALL_VOWELS.iter()
+        .filter(|vowel| *vowel.get_stress() == VowelStress::UnknownStress)
+        .map(|vowel| vowel.to_str()))
+      .collect::<Vec<&str>>();
+    all.sort();
+
+    let mut expected = CMUDICT_PHONEMES.iter().chain(EXTENDED_PHONEMES.iter())
+      .copied()
+      .collect::<Vec<&str>>();
+    expected.sort();
+    assert_eq!(all, expected);
+  }
+
+  #[cfg(feature = "svg")]
+  #[test]
+  fn svg_report() {
+    let svg = testing::arpabet(DICTIONARY).phoneme_histogram().to_svg();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<rect").count(), 5);
+  }
+}
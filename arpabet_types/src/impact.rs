@@ -0,0 +1,131 @@
+//! This module estimates the impact of switching dictionaries on a text
+//! corpus, eg. to assess the risk of upgrading the embedded CMUdict.
+
+use crate::casing::fold_case;
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone, Word};
+use std::collections::HashMap;
+
+/// A corpus word that would be pronounced differently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PronunciationChange {
+  /// The word, lowercased.
+  pub word: Word,
+  /// How many times the word changes in the corpus.
+  pub occurrences: usize,
+  /// Pronunciation under the old dictionary. None if it couldn't be pronounced.
+  pub old: Option<Polyphone>,
+  /// Pronunciation under the new dictionary. None if it couldn't be pronounced.
+  pub new: Option<Polyphone>,
+}
+
+/// The pronunciation changes across a corpus between two dictionaries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImpactReport {
+  /// Total words in the corpus.
+  pub total_words: usize,
+  /// The changed words, most frequent first, then alphabetically.
+  pub changes: Vec<PronunciationChange>,
+}
+
+impl ImpactReport {
+  /// Number of word occurrences that change.
+  pub fn changed_occurrences(&self) -> usize {
+    self.changes.iter().map(|change| change.occurrences).sum()
+  }
+
+  /// Fraction of the corpus words that change. Zero for an empty corpus.
+  pub fn changed_fraction(&self) -> f64 {
+    if self.total_words == 0 {
+      0.0
+    } else {
+      self.changed_occurrences() as f64 / self.total_words as f64
+    }
+  }
+}
+
+/// List the words of the corpus that would be pronounced differently after
+/// switching from the old dictionary to the new one, and how often they occur.
+/// Words are pronounced in context as by `Arpabet::transcribe_sentence`.
+pub fn impact_report(old: &Arpabet, new: &Arpabet, corpus: &str) -> ImpactReport {
+  let options = TranscribeOptions::default();
+  let before = old.pronounce_words(corpus, &options);
+  let after = new.pronounce_words(corpus, &options);
+
+  let mut changes : HashMap<String, PronunciationChange> = HashMap::new();
+
+  for ((word, old), (_, new)) in before.iter().zip(after) {
+    if *old == new {
+      continue;
+    }
+    let word = fold_case(word);
+    changes.entry(word.clone())
+      .or_insert_with(|| PronunciationChange {
+        word,
+        occurrences: 0,
+        old: old.clone(),
+        new,
+      })
+      .occurrences += 1;
+  }
+
+  let mut changes = changes.into_values().collect::<Vec<PronunciationChange>>();
+  changes.sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then_with(|| a.word.cmp(&b.word)));
+
+  ImpactReport {
+    total_words: before.len(),
+    changes,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{arpabet, polyphone};
+
+  #[test]
+  fn changed_words() {
+    let old = arpabet(&[
+      ("the", "DH AH0"),
+      ("tomato", "T AH0 M EY1 T OW2"),
+      ("route", "R UW1 T"),
+      ("cat", "K AE1 T"),
+    ]);
+    let new = arpabet(&[
+      ("the", "DH AH0"),
+      ("tomato", "T AH0 M AA1 T OW2"),
+      ("route", "R AW1 T"),
+      ("cat", "K AE1 T"),
+      ("gif", "JH IH1 F"),
+    ]);
+
+    let report = impact_report(&old, &new,
+      "The cat ate the tomato. Tomato on the route? A gif!");
+
+    assert_eq!(report.total_words, 11);
+    assert_eq!(report.changes.len(), 3);
+
+    assert_eq!(report.changes[0].word, "tomato");
+    assert_eq!(report.changes[0].occurrences, 2);
+    assert_eq!(report.changes[0].old, Some(polyphone("T AH0 M EY1 T OW2")));
+    assert_eq!(report.changes[0].new, Some(polyphone("T AH0 M AA1 T OW2")));
+
+    assert_eq!(report.changes[1].word, "gif");
+    assert_eq!(report.changes[1].old, None);
+    assert_eq!(report.changes[2].word, "route");
+
+    assert_eq!(report.changed_occurrences(), 4);
+    assert!((report.changed_fraction() - 4.0 / 11.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn no_changes() {
+    let old = arpabet(&[("cat", "K AE1 T")]);
+    let report = impact_report(&old, &old.clone(), "cat cat dog");
+    assert_eq!(report.total_words, 3);
+    assert!(report.changes.is_empty());
+    assert_eq!(report.changed_fraction(), 0.0);
+
+    assert_eq!(impact_report(&old, &old, "").changed_fraction(), 0.0);
+  }
+}
@@ -0,0 +1,981 @@
+//! This module contains lookup indices derived from an Arpabet dictionary.
+//! They are expensive to build, so the Arpabet computes them lazily and caches them.
+//!
+//! Built indices are kept consistent with the dictionary: `Arpabet::insert`
+//! and `Arpabet::remove` update them in place, in time proportional to the
+//! entries sharing the changed pronunciations, while bulk changes such as
+//! `Arpabet::merge_from` drop them to be rebuilt on next use. Both take
+//! `&mut self`, so readers never observe a half-updated index.
+
+use crate::memory::HeapSize;
+use crate::poetry::rhyme_part;
+use crate::{Phoneme, Polyphone, Word};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem::size_of;
+use std::sync::OnceLock;
+
+/// A selection of the indices an Arpabet builds on demand, to prebuild them
+/// with `Arpabet::build_indexes` or report which are built.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexSet {
+  /// The pronunciation -> words index. See `Arpabet::reverse_index`.
+  pub reverse: bool,
+  /// The pronunciation trie. See `Arpabet::phoneme_trie`.
+  pub phoneme_trie: bool,
+  /// The initial phoneme index. See `Arpabet::initial_phoneme_index`.
+  pub initial_phonemes: bool,
+  /// The rhyme index. See `Arpabet::rhyme_index`.
+  pub rhymes: bool,
+  /// The stressless pronunciation index. See `Arpabet::stressless_index`.
+  pub stressless: bool,
+  /// The phoneme n-gram index. See `Arpabet::ngram_index`.
+  pub ngrams: bool,
+}
+
+impl IndexSet {
+  /// Every index.
+  pub fn all() -> Self {
+    Self {
+      reverse: true,
+      phoneme_trie: true,
+      initial_phonemes: true,
+      rhymes: true,
+      stressless: true,
+      ngrams: true,
+    }
+  }
+
+  /// No indices.
+  pub fn none() -> Self {
+    Self::default()
+  }
+
+  /// Whether no index is selected.
+  pub fn is_empty(&self) -> bool {
+    *self == Self::none()
+  }
+}
+
+// The indices of an Arpabet, each built on first use.
+#[derive(Clone, Default)]
+pub(crate) struct Indexes {
+  // Pronunciation -> words.
+  pub(crate) reverse: OnceLock<ReverseIndex>,
+  // Pronunciation trie.
+  pub(crate) phoneme_trie: OnceLock<PhonemeTrie>,
+  // Initial phoneme -> words groups.
+  pub(crate) initial_phonemes: OnceLock<InitialPhonemeIndex>,
+  // Rhyme -> words groups.
+  pub(crate) rhymes: OnceLock<RhymeIndex>,
+  // Stressless pronunciation -> words.
+  pub(crate) stressless: OnceLock<StresslessIndex>,
+  // Phoneme n-gram -> words.
+  pub(crate) ngrams: OnceLock<NGramIndex>,
+  // Base word -> alternate keys. Not selectable, as it backs
+  // `Arpabet::get_polyphone_variants`.
+  pub(crate) alternates: OnceLock<AlternateIndex>,
+}
+
+impl Indexes {
+  // Which indices are built.
+  pub(crate) fn built(&self) -> IndexSet {
+    IndexSet {
+      reverse: self.reverse.get().is_some(),
+      phoneme_trie: self.phoneme_trie.get().is_some(),
+      initial_phonemes: self.initial_phonemes.get().is_some(),
+      rhymes: self.rhymes.get().is_some(),
+      stressless: self.stressless.get().is_some(),
+      ngrams: self.ngrams.get().is_some(),
+    }
+  }
+
+  // Whether any index is built, and must be updated as entries change.
+  pub(crate) fn is_empty(&self) -> bool {
+    self.built().is_empty() && self.alternates.get().is_none()
+  }
+
+  // Update the built indices after the entry for the key changed from `old`
+  // to `new`. `remaining` holds every pronunciation of the key's base word
+  // after the change, which may still place the word where `old` did.
+  pub(crate) fn update(&mut self,
+                       key: &str,
+                       old: Option<&[Phoneme]>,
+                       new: Option<&[Phoneme]>,
+                       remaining: &[Polyphone]) {
+    let word = strip_variant(key);
+    if let Some(index) = self.reverse.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+    if let Some(index) = self.phoneme_trie.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+    if let Some(index) = self.initial_phonemes.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+    if let Some(index) = self.rhymes.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+    if let Some(index) = self.stressless.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+    if let Some(index) = self.ngrams.get_mut() {
+      if let Some(old) = old {
+        index.remove_word(word, old, remaining);
+      }
+      if let Some(new) = new {
+        index.insert_word(word, new);
+      }
+    }
+  }
+}
+
+// Maps base words to the keys of their alternate pronunciations, eg. 'read' ->
+// ['read(1)', 'read(2)'], in variant order. Gaps in the numbering are kept,
+// so 'word(2)' is found without 'word(1)'.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AlternateIndex {
+  entries: HashMap<Word, Vec<Word>>,
+}
+
+impl AlternateIndex {
+  // Build the index from dictionary keys.
+  pub(crate) fn build<'a, I>(keys: I) -> Self
+    where I: Iterator<Item=&'a str>
+  {
+    let mut index = Self::default();
+    for key in keys {
+      index.insert_key(key);
+    }
+    index
+  }
+
+  // Index the key, if it is an alternate.
+  pub(crate) fn insert_key(&mut self, key: &str) {
+    let base = strip_variant(key);
+    if base.len() == key.len() {
+      return;
+    }
+    let keys = self.entries.entry(base.to_string()).or_default();
+    let order = |k: &str| (variant_number(k), k.to_string());
+    if let Err(position) = keys.binary_search_by_key(&order(key), |k| order(k)) {
+      keys.insert(position, key.to_string());
+    }
+  }
+
+  // Unindex the key, if present.
+  pub(crate) fn remove_key(&mut self, key: &str) {
+    let base = strip_variant(key);
+    if let Some(keys) = self.entries.get_mut(base) {
+      keys.retain(|k| k != key);
+      if keys.is_empty() {
+        self.entries.remove(base);
+      }
+    }
+  }
+
+  // The alternate keys of the base word, in variant order.
+  pub(crate) fn get(&self, word: &str) -> &[Word] {
+    self.entries.get(word).map(Vec::as_slice).unwrap_or(&[])
+  }
+}
+
+// Add a word to a sorted list, unless present.
+fn insert_sorted(words: &mut Vec<Word>, word: &str) {
+  if let Err(position) = words.binary_search_by(|w| w.as_str().cmp(word)) {
+    words.insert(position, word.to_string());
+  }
+}
+
+// Remove a word from a sorted list, if present.
+fn remove_sorted(words: &mut Vec<Word>, word: &str) {
+  if let Ok(position) = words.binary_search_by(|w| w.as_str().cmp(word)) {
+    words.remove(position);
+  }
+}
+
+// Remove a word from the group under the key, dropping the group once empty.
+fn remove_from_group<K: Eq + Hash>(groups: &mut HashMap<K, Vec<Word>>, key: &K, word: &str) {
+  if let Some(words) = groups.get_mut(key) {
+    remove_sorted(words, word);
+    if words.is_empty() {
+      groups.remove(key);
+    }
+  }
+}
+
+/// Maps pronunciations back to the words that are pronounced that way.
+/// Alternate pronunciation keys, eg. 'read(1)', are folded into their base word.
+#[derive(Clone, Debug, Default)]
+pub struct ReverseIndex {
+  entries: HashMap<Polyphone, Vec<Word>>,
+  max_length: usize,
+}
+
+impl ReverseIndex {
+  /// Build the index from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut index : HashMap<Polyphone, Vec<Word>> = HashMap::new();
+    let mut max_length = 0;
+
+    for (word, polyphone) in entries {
+      let words = index.entry(polyphone.to_vec()).or_default();
+      let base = strip_variant(word);
+      if !words.iter().any(|w| w == base) {
+        words.push(base.to_string());
+      }
+      max_length = max_length.max(polyphone.len());
+    }
+
+    for words in index.values_mut() {
+      words.sort();
+    }
+
+    Self {
+      entries: index,
+      max_length,
+    }
+  }
+
+  // Reassemble an index from its pronunciation -> words entries.
+  pub(crate) fn from_entries(entries: HashMap<Polyphone, Vec<Word>>) -> Self {
+    let max_length = entries.keys().map(|polyphone| polyphone.len()).max().unwrap_or(0);
+    Self { entries, max_length }
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    insert_sorted(self.entries.entry(polyphone.to_vec()).or_default(), word);
+    self.max_length = self.max_length.max(polyphone.len());
+  }
+
+  // Unindex a pronunciation of the word, unless one of its remaining
+  // pronunciations is the same.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    if remaining.iter().any(|other| other.as_slice() == polyphone) {
+      return;
+    }
+    remove_from_group(&mut self.entries, &polyphone.to_vec(), word);
+    if polyphone.len() == self.max_length && !self.entries.contains_key(polyphone) {
+      self.max_length = self.entries.keys().map(|polyphone| polyphone.len()).max().unwrap_or(0);
+    }
+  }
+
+  /// Get the words pronounced exactly as the supplied phonemes, in sorted order.
+  pub fn get(&self, phonemes: &[Phoneme]) -> Option<&Vec<Word>> {
+    self.entries.get(phonemes)
+  }
+
+  /// Iterate over pronunciations and their words in arbitrary order.
+  pub fn iter(&self) -> impl Iterator<Item=(&Polyphone, &Vec<Word>)> {
+    self.entries.iter()
+  }
+
+  /// The length of the longest indexed pronunciation.
+  pub fn max_length(&self) -> usize {
+    self.max_length
+  }
+
+  /// Reports the number of distinct pronunciations in the index.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Whether the index contains no pronunciations.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+}
+
+/// A trie over pronunciations, used to find words by phoneme prefix.
+/// Alternate pronunciation keys are folded into their base word.
+#[derive(Clone, Debug)]
+pub struct PhonemeTrie {
+  nodes: Vec<TrieNode>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+  children: HashMap<Phoneme, usize>,
+  words: Vec<Word>,
+}
+
+impl PhonemeTrie {
+  /// Build the trie from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut trie = Self { nodes: vec![TrieNode::default()] };
+    for (word, polyphone) in entries {
+      trie.insert_word(strip_variant(word), polyphone);
+    }
+    trie
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    let mut current = 0;
+    for phoneme in polyphone.iter() {
+      current = match self.nodes[current].children.get(phoneme) {
+        Some(next) => *next,
+        None => {
+          self.nodes.push(TrieNode::default());
+          let next = self.nodes.len() - 1;
+          self.nodes[current].children.insert(*phoneme, next);
+          next
+        },
+      };
+    }
+    insert_sorted(&mut self.nodes[current].words, word);
+  }
+
+  // Unindex a pronunciation of the word, unless one of its remaining
+  // pronunciations is the same. Emptied nodes are kept for reuse.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    if remaining.iter().any(|other| other.as_slice() == polyphone) {
+      return;
+    }
+    let mut current = 0;
+    for phoneme in polyphone.iter() {
+      current = match self.nodes[current].children.get(phoneme) {
+        Some(next) => *next,
+        None => return,
+      };
+    }
+    remove_sorted(&mut self.nodes[current].words, word);
+  }
+
+  /// Get every word whose pronunciation begins with the prefix, in sorted order.
+  /// A word with several matching pronunciations is returned once.
+  pub fn words_with_prefix(&self, prefix: &[Phoneme]) -> Vec<&Word> {
+    let mut current = 0;
+    for phoneme in prefix.iter() {
+      current = match self.nodes[current].children.get(phoneme) {
+        Some(next) => *next,
+        None => return Vec::new(),
+      };
+    }
+
+    let mut words = Vec::new();
+    let mut stack = vec![current];
+    while let Some(node) = stack.pop() {
+      words.extend(self.nodes[node].words.iter());
+      stack.extend(self.nodes[node].children.values());
+    }
+
+    words.sort();
+    words.dedup();
+    words
+  }
+}
+
+/// Groups words by the first phoneme of their pronunciation.
+/// A word with several pronunciations may appear in several groups.
+#[derive(Clone, Debug, Default)]
+pub struct InitialPhonemeIndex {
+  groups: HashMap<Phoneme, Vec<Word>>,
+}
+
+impl InitialPhonemeIndex {
+  /// Build the index from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut groups : HashMap<Phoneme, Vec<Word>> = HashMap::new();
+
+    for (word, polyphone) in entries {
+      if let Some(first) = polyphone.first() {
+        groups.entry(*first).or_default().push(strip_variant(word).to_string());
+      }
+    }
+
+    for words in groups.values_mut() {
+      words.sort();
+      words.dedup();
+    }
+
+    Self { groups }
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    if let Some(first) = polyphone.first() {
+      insert_sorted(self.groups.entry(*first).or_default(), word);
+    }
+  }
+
+  // Unindex a pronunciation of the word, unless one of its remaining
+  // pronunciations starts the same.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    if let Some(first) = polyphone.first() {
+      if !remaining.iter().any(|other| other.first() == Some(first)) {
+        remove_from_group(&mut self.groups, first, word);
+      }
+    }
+  }
+
+  /// Get the words starting with the phoneme, in sorted order.
+  pub fn get(&self, phoneme: &Phoneme) -> Option<&Vec<Word>> {
+    self.groups.get(phoneme)
+  }
+
+  /// Get the underlying map of initial phonemes to sorted words.
+  pub fn groups(&self) -> &HashMap<Phoneme, Vec<Word>> {
+    &self.groups
+  }
+}
+
+/// Groups words by their rhyme: the final stressed vowel and the phonemes
+/// after it, compared without stress. See `poetry::rhyme_part`.
+/// A word with several pronunciations may appear in several groups.
+#[derive(Clone, Debug, Default)]
+pub struct RhymeIndex {
+  groups: HashMap<Polyphone, Vec<Word>>,
+}
+
+impl RhymeIndex {
+  /// Build the index from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut groups : HashMap<Polyphone, Vec<Word>> = HashMap::new();
+
+    for (word, polyphone) in entries {
+      if let Some(key) = rhyme_key(polyphone) {
+        groups.entry(key).or_default().push(strip_variant(word).to_string());
+      }
+    }
+
+    for words in groups.values_mut() {
+      words.sort();
+      words.dedup();
+    }
+
+    Self { groups }
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    if let Some(key) = rhyme_key(polyphone) {
+      insert_sorted(self.groups.entry(key).or_default(), word);
+    }
+  }
+
+  // Unindex a pronunciation of the word, unless one of its remaining
+  // pronunciations rhymes the same.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    if let Some(key) = rhyme_key(polyphone) {
+      if !remaining.iter().any(|other| rhyme_key(other).as_ref() == Some(&key)) {
+        remove_from_group(&mut self.groups, &key, word);
+      }
+    }
+  }
+
+  // Reassemble an index from its rhyme -> words groups.
+  pub(crate) fn from_groups(groups: HashMap<Polyphone, Vec<Word>>) -> Self {
+    Self { groups }
+  }
+
+  // Iterate over rhymes and their words in arbitrary order.
+  pub(crate) fn iter(&self) -> impl Iterator<Item=(&Polyphone, &Vec<Word>)> {
+    self.groups.iter()
+  }
+
+  /// Get the words that rhyme with the pronunciation, in sorted order.
+  pub fn get(&self, polyphone: &[Phoneme]) -> Option<&Vec<Word>> {
+    rhyme_key(polyphone).and_then(|key| self.groups.get(&key))
+  }
+
+  /// Reports the number of distinct rhymes in the index.
+  pub fn len(&self) -> usize {
+    self.groups.len()
+  }
+
+  /// Whether the index contains no rhymes.
+  pub fn is_empty(&self) -> bool {
+    self.groups.is_empty()
+  }
+}
+
+/// Maps pronunciations with stress removed to the words and full pronunciations
+/// that reduce to them, eg. 'IH N S AY T' -> insight (IH1 N S AY2 T),
+/// incite (IH0 N S AY1 T). See `Phoneme::stressless`.
+#[derive(Clone, Debug, Default)]
+pub struct StresslessIndex {
+  entries: HashMap<Polyphone, Vec<(Word, Polyphone)>>,
+}
+
+impl StresslessIndex {
+  /// Build the index from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut index : HashMap<Polyphone, Vec<(Word, Polyphone)>> = HashMap::new();
+
+    for (word, polyphone) in entries {
+      let key = polyphone.iter().map(|phoneme| phoneme.stressless()).collect();
+      index.entry(key).or_default().push((strip_variant(word).to_string(), polyphone.to_vec()));
+    }
+
+    for words in index.values_mut() {
+      words.sort_by_cached_key(|(word, polyphone)| {
+        (word.clone(), polyphone.iter().map(|p| p.to_str()).collect::<Vec<&str>>())
+      });
+      words.dedup();
+    }
+
+    Self { entries: index }
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    let key = polyphone.iter().map(|phoneme| phoneme.stressless()).collect();
+    let entries = self.entries.entry(key).or_default();
+    if let Err(position) = entries.binary_search_by(|entry| stressless_order(entry, word, polyphone)) {
+      entries.insert(position, (word.to_string(), polyphone.to_vec()));
+    }
+  }
+
+  // Unindex a pronunciation of the word, unless it remains.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    if remaining.iter().any(|other| other.as_slice() == polyphone) {
+      return;
+    }
+    let key : Polyphone = polyphone.iter().map(|phoneme| phoneme.stressless()).collect();
+    if let Some(entries) = self.entries.get_mut(&key) {
+      if let Ok(position) = entries.binary_search_by(|entry| stressless_order(entry, word, polyphone)) {
+        entries.remove(position);
+      }
+      if entries.is_empty() {
+        self.entries.remove(&key);
+      }
+    }
+  }
+
+  /// Get the words and pronunciations matching the phonemes when stress is
+  /// ignored, sorted by word.
+  pub fn get(&self, phonemes: &[Phoneme]) -> Option<&Vec<(Word, Polyphone)>> {
+    let key : Polyphone = phonemes.iter().map(|phoneme| phoneme.stressless()).collect();
+    self.entries.get(&key)
+  }
+
+  /// Iterate over stressless pronunciations and their entries in arbitrary order.
+  pub fn iter(&self) -> impl Iterator<Item=(&Polyphone, &Vec<(Word, Polyphone)>)> {
+    self.entries.iter()
+  }
+
+  /// Reports the number of distinct stressless pronunciations in the index.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Whether the index contains no pronunciations.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+}
+
+/// The number of phonemes in the sequences the n-gram index is keyed by.
+pub const NGRAM_LENGTH: usize = 3;
+
+/// Maps each run of `NGRAM_LENGTH` phonemes to the words whose pronunciation
+/// contains it, eg. 'AE1 K S' -> axe, taxi. Pronunciations shorter than that
+/// are indexed whole. Used to find words containing a phoneme sequence.
+/// Alternate pronunciation keys are folded into their base word.
+#[derive(Clone, Debug, Default)]
+pub struct NGramIndex {
+  grams: HashMap<Polyphone, Vec<Word>>,
+}
+
+impl NGramIndex {
+  /// Build the index from dictionary entries.
+  pub fn build<'a, I>(entries: I) -> Self
+    where I: Iterator<Item=(&'a str, &'a [Phoneme])>
+  {
+    let mut grams : HashMap<Polyphone, Vec<Word>> = HashMap::new();
+
+    for (word, polyphone) in entries {
+      for gram in ngrams(polyphone) {
+        grams.entry(gram.to_vec()).or_default().push(strip_variant(word).to_string());
+      }
+    }
+
+    for words in grams.values_mut() {
+      words.sort();
+      words.dedup();
+    }
+
+    Self { grams }
+  }
+
+  // Index a pronunciation of the word.
+  fn insert_word(&mut self, word: &str, polyphone: &[Phoneme]) {
+    for gram in ngrams(polyphone) {
+      insert_sorted(self.grams.entry(gram.to_vec()).or_default(), word);
+    }
+  }
+
+  // Unindex a pronunciation of the word, keeping the n-grams one of its
+  // remaining pronunciations also contains.
+  fn remove_word(&mut self, word: &str, polyphone: &[Phoneme], remaining: &[Polyphone]) {
+    for gram in ngrams(polyphone) {
+      if !remaining.iter().any(|other| ngrams(other).any(|other| other == gram)) {
+        remove_from_group(&mut self.grams, &gram.to_vec(), word);
+      }
+    }
+  }
+
+  // Reassemble an index from its n-gram -> words groups.
+  pub(crate) fn from_groups(grams: HashMap<Polyphone, Vec<Word>>) -> Self {
+    Self { grams }
+  }
+
+  // Iterate over n-grams and their words in arbitrary order.
+  pub(crate) fn iter(&self) -> impl Iterator<Item=(&Polyphone, &Vec<Word>)> {
+    self.grams.iter()
+  }
+
+  /// Get the words whose pronunciation contains the n-gram, in sorted order.
+  /// Shorter sequences only match pronunciations of exactly those phonemes.
+  pub fn get(&self, ngram: &[Phoneme]) -> Option<&Vec<Word>> {
+    self.grams.get(ngram)
+  }
+
+  /// Get the words whose pronunciation may contain the phonemes, in sorted
+  /// order. Sequences shorter than `NGRAM_LENGTH` are matched exactly; longer
+  /// ones may return words containing each of their n-grams but not the
+  /// whole sequence, so check the candidates.
+  pub fn candidates(&self, phonemes: &[Phoneme]) -> Vec<&Word> {
+    if phonemes.is_empty() {
+      return Vec::new();
+    }
+    if phonemes.len() >= NGRAM_LENGTH {
+      // The rarest n-gram narrows the search the most.
+      return phonemes.windows(NGRAM_LENGTH)
+        .map(|gram| self.grams.get(gram).map(Vec::as_slice).unwrap_or(&[]))
+        .min_by_key(|words| words.len())
+        .map(|words| words.iter().collect())
+        .unwrap_or_default();
+    }
+    let mut words = self.grams.iter()
+      .filter(|(gram, _)| gram.windows(phonemes.len()).any(|window| window == phonemes))
+      .flat_map(|(_, words)| words.iter())
+      .collect::<Vec<&Word>>();
+    words.sort();
+    words.dedup();
+    words
+  }
+
+  /// Reports the number of distinct n-grams in the index.
+  pub fn len(&self) -> usize {
+    self.grams.len()
+  }
+
+  /// Whether the index contains no n-grams.
+  pub fn is_empty(&self) -> bool {
+    self.grams.is_empty()
+  }
+}
+
+// The n-grams a pronunciation is indexed by: each run of `NGRAM_LENGTH`
+// phonemes, or the whole pronunciation if shorter. None if empty.
+fn ngrams(polyphone: &[Phoneme]) -> impl Iterator<Item=&[Phoneme]> {
+  polyphone.windows(NGRAM_LENGTH.min(polyphone.len()).max(1))
+}
+
+// Compare a stressless index entry with a word and pronunciation, in the order
+// entries are sorted: by word, then by phoneme symbols.
+fn stressless_order(entry: &(Word, Polyphone), word: &str, polyphone: &[Phoneme]) -> Ordering {
+  entry.0.as_str().cmp(word).then_with(|| {
+    entry.1.iter().map(|p| p.to_str()).cmp(polyphone.iter().map(|p| p.to_str()))
+  })
+}
+
+// The stressless rhyme of a pronunciation, if it has a vowel.
+pub(crate) fn rhyme_key(polyphone: &[Phoneme]) -> Option<Polyphone> {
+  let rhyme = rhyme_part(polyphone);
+  if rhyme.is_empty() {
+    None
+  } else {
+    Some(rhyme.iter().map(|phoneme| phoneme.stressless()).collect())
+  }
+}
+
+impl HeapSize for ReverseIndex {
+  fn heap_size(&self) -> usize {
+    self.entries.heap_size()
+  }
+}
+
+impl HeapSize for PhonemeTrie {
+  fn heap_size(&self) -> usize {
+    self.nodes.capacity() * size_of::<TrieNode>()
+      + self.nodes.iter().map(|node| node.children.heap_size() + node.words.heap_size()).sum::<usize>()
+  }
+}
+
+impl HeapSize for InitialPhonemeIndex {
+  fn heap_size(&self) -> usize {
+    self.groups.heap_size()
+  }
+}
+
+impl HeapSize for RhymeIndex {
+  fn heap_size(&self) -> usize {
+    self.groups.heap_size()
+  }
+}
+
+impl HeapSize for StresslessIndex {
+  fn heap_size(&self) -> usize {
+    self.entries.heap_size()
+  }
+}
+
+impl HeapSize for NGramIndex {
+  fn heap_size(&self) -> usize {
+    self.grams.heap_size()
+  }
+}
+
+/// Strip a CMUdict alternate pronunciation marker from a key, eg. 'read(1)' -> 'read'.
+/// Only a trailing marker of digits is stripped, so keys that start with a
+/// parenthesis keep it, eg. '(paren(1)' -> '(paren'.
+pub fn strip_variant(word: &str) -> &str {
+  if word.ends_with(')') {
+    if let Some(open) = word.rfind('(') {
+      let digits = &word[open + 1 .. word.len() - 1];
+      if open > 0 && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        return &word[.. open];
+      }
+    }
+  }
+  word
+}
+
+// The number in a variant marker, eg. 2 for 'read(2)', or 0 for no marker.
+pub(crate) fn variant_number(word: &str) -> usize {
+  let base = strip_variant(word);
+  word[base.len() ..].trim_start_matches('(').trim_end_matches(')')
+    .parse()
+    .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Consonant, Vowel, VowelStress};
+  use crate::testing::polyphone;
+
+  #[test]
+  fn strip_variant_markers() {
+    assert_eq!(strip_variant("read(1)"), "read");
+    assert_eq!(strip_variant("read(12)"), "read");
+    assert_eq!(strip_variant("read"), "read");
+    assert_eq!(strip_variant("(paren)"), "(paren)");
+    assert_eq!(strip_variant("a(b)"), "a(b)");
+    assert_eq!(strip_variant("(paren"), "(paren");
+    assert_eq!(strip_variant("(paren(1)"), "(paren");
+    assert_eq!(strip_variant("(1)"), "(1)");
+  }
+
+  #[test]
+  fn rhyme_index() {
+    let cat = vec![
+      Phoneme::Consonant(Consonant::K),
+      Phoneme::Vowel(Vowel::AE(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+    let acrobat = vec![
+      Phoneme::Vowel(Vowel::AE(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::K),
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::AH(VowelStress::NoStress)),
+      Phoneme::Consonant(Consonant::B),
+      Phoneme::Vowel(Vowel::AE(VowelStress::SecondaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+    let hmm = vec![
+      Phoneme::Consonant(Consonant::HH),
+      Phoneme::Consonant(Consonant::M),
+    ];
+
+    let words = ["cat".to_string(), "acrobat".to_string(), "hmm".to_string()];
+    let polyphones = [cat.clone(), acrobat, hmm.clone()];
+    let index = RhymeIndex::build(words.iter().map(|w| w.as_str()).zip(polyphones.iter().map(|p| p.as_slice())));
+
+    assert_eq!(index.len(), 1);
+    assert_eq!(index.get(&cat), Some(&vec!["acrobat".to_string(), "cat".to_string()]));
+    assert_eq!(index.get(&hmm), None);
+  }
+
+  #[test]
+  fn stressless_index() {
+    let insight = vec![
+      Phoneme::Vowel(Vowel::IH(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::N),
+      Phoneme::Consonant(Consonant::S),
+      Phoneme::Vowel(Vowel::AY(VowelStress::SecondaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+    let incite = vec![
+      Phoneme::Vowel(Vowel::IH(VowelStress::NoStress)),
+      Phoneme::Consonant(Consonant::N),
+      Phoneme::Consonant(Consonant::S),
+      Phoneme::Vowel(Vowel::AY(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+
+    let words = ["insight".to_string(), "incite".to_string(), "incite(1)".to_string()];
+    let polyphones = [insight.clone(), incite.clone(), incite.clone()];
+    let index = StresslessIndex::build(words.iter().map(|w| w.as_str()).zip(polyphones.iter().map(|p| p.as_slice())));
+
+    assert_eq!(index.len(), 1);
+    assert_eq!(index.get(&insight), Some(&vec![
+      ("incite".to_string(), incite),
+      ("insight".to_string(), insight),
+    ]));
+  }
+
+  #[test]
+  fn reverse_index() {
+    let red = vec![
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::EH(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ];
+    let reed = vec![
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::IY(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ];
+    let entries = [
+      ("red".to_string(), red.clone()),
+      ("read".to_string(), reed.clone()),
+      ("read(1)".to_string(), red.clone()),
+      ("reed".to_string(), reed.clone()),
+    ];
+
+    let index = ReverseIndex::build(entries.iter().map(|(w, p)| (w.as_str(), p.as_slice())));
+
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.max_length(), 3);
+    assert_eq!(index.get(&red), Some(&vec!["read".to_string(), "red".to_string()]));
+    assert_eq!(index.get(&reed), Some(&vec!["read".to_string(), "reed".to_string()]));
+    assert_eq!(index.get(&red[..2]), None);
+  }
+
+  #[test]
+  fn initial_phoneme_index() {
+    let cat = vec![
+      Phoneme::Consonant(Consonant::K),
+      Phoneme::Vowel(Vowel::AE(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+    let dog = vec![
+      Phoneme::Consonant(Consonant::D),
+      Phoneme::Vowel(Vowel::AO(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::G),
+    ];
+    let entries = [
+      ("cat".to_string(), cat.clone()),
+      ("cat(1)".to_string(), cat.clone()),
+      ("kat".to_string(), cat.clone()),
+      ("dog".to_string(), dog.clone()),
+      ("empty".to_string(), vec![]),
+    ];
+
+    let index = InitialPhonemeIndex::build(entries.iter().map(|(w, p)| (w.as_str(), p.as_slice())));
+
+    assert_eq!(index.groups().len(), 2);
+    assert_eq!(index.get(&cat[0]), Some(&vec!["cat".to_string(), "kat".to_string()]));
+    assert_eq!(index.get(&dog[0]), Some(&vec!["dog".to_string()]));
+    assert_eq!(index.get(&cat[1]), None);
+  }
+
+  #[test]
+  fn phoneme_trie() {
+    let cat = vec![
+      Phoneme::Consonant(Consonant::K),
+      Phoneme::Vowel(Vowel::AE(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::T),
+    ];
+    let cab = vec![
+      Phoneme::Consonant(Consonant::K),
+      Phoneme::Vowel(Vowel::AE(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::B),
+    ];
+    let dog = vec![
+      Phoneme::Consonant(Consonant::D),
+      Phoneme::Vowel(Vowel::AO(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::G),
+    ];
+    let entries = [
+      ("cat".to_string(), cat.clone()),
+      ("kat".to_string(), cat.clone()),
+      ("cab".to_string(), cab.clone()),
+      ("cab(1)".to_string(), cat.clone()),
+      ("dog".to_string(), dog.clone()),
+    ];
+
+    let trie = PhonemeTrie::build(entries.iter().map(|(w, p)| (w.as_str(), p.as_slice())));
+
+    assert_eq!(trie.words_with_prefix(&cat[..1]), vec!["cab", "cat", "kat"]);
+    assert_eq!(trie.words_with_prefix(&cat), vec!["cab", "cat", "kat"]);
+    assert_eq!(trie.words_with_prefix(&cab), vec!["cab"]);
+    assert_eq!(trie.words_with_prefix(&dog[..2]), vec!["dog"]);
+    assert_eq!(trie.words_with_prefix(&dog[1..]), Vec::<&Word>::new());
+    assert_eq!(trie.words_with_prefix(&[]).len(), 4);
+  }
+
+  #[test]
+  fn ngram_index() {
+    let entries = [
+      ("taxi".to_string(), polyphone("T AE1 K S IY0")),
+      ("axe".to_string(), polyphone("AE1 K S")),
+      ("axe(1)".to_string(), polyphone("AE1 K S")),
+      ("ax".to_string(), polyphone("AE1 K")),
+      ("empty".to_string(), vec![]),
+    ];
+
+    let index = NGramIndex::build(entries.iter().map(|(w, p)| (w.as_str(), p.as_slice())));
+
+    assert_eq!(index.len(), 4);
+    assert_eq!(index.get(&polyphone("AE1 K S")),
+      Some(&vec!["axe".to_string(), "taxi".to_string()]));
+    assert_eq!(index.get(&polyphone("AE1 K")), Some(&vec!["ax".to_string()]));
+    assert_eq!(index.candidates(&polyphone("AE1 K")), vec!["ax", "axe", "taxi"]);
+    assert_eq!(index.candidates(&polyphone("K S IY0")), vec!["taxi"]);
+    assert_eq!(index.candidates(&polyphone("T AE1 K S")), vec!["taxi"]);
+    assert!(index.candidates(&polyphone("S K")).is_empty());
+    assert!(index.candidates(&[]).is_empty());
+  }
+}
@@ -0,0 +1,218 @@
+//! This module pronounces initialisms, words read letter by letter such as
+//! "FBI" or "U.S.", and guesses which words in running text are initialisms.
+//!
+//! Initialisms are conventionally stressed on their final letter: in "FBI"
+//! the I carries primary stress and the F and B secondary stress.
+//!
+//! Acronyms that can be read as words, eg. "NASA" or "SCUBA", are told apart
+//! from those read letter by letter, eg. "HTML", by their spelling. See
+//! `acronym_reading`.
+
+use crate::casing::fold_case;
+use crate::letters::letter_name;
+use crate::phoneme::{Phoneme, VowelStress};
+use crate::{Arpabet, Polyphone};
+
+// Longest undotted word the heuristic reads as an initialism.
+const MAX_INITIALISM_LETTERS : usize = 5;
+
+// Consonant letter clusters that can begin an English word.
+const ONSETS : [&str; 29] = [
+  "bl", "br", "ch", "cl", "cr", "dr", "dw", "fl", "fr", "gl", "gr", "kl", "kn", "kr", "ph",
+  "pl", "pr", "sc", "sh", "sk", "sl", "sm", "sn", "sp", "st", "sw", "th", "tr", "wh",
+];
+
+// Consonant letter clusters that can end an English word.
+const CODAS : [&str; 30] = [
+  "ch", "ck", "ct", "ft", "ld", "lf", "lk", "lm", "lp", "lt", "mb", "mp", "nd", "ng", "nk",
+  "nt", "ph", "pt", "rb", "rd", "rk", "rm", "rn", "rp", "rt", "sh", "sk", "sp", "st", "th",
+];
+
+/// How an acronym is read aloud.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AcronymReading {
+  /// As a word, eg. "NASA".
+  Word,
+  /// Letter by letter, eg. "HTML".
+  Letters,
+}
+
+/// Pronounce a word letter by letter, with primary stress on the final letter
+/// and secondary stress on the others, eg. "FBI" -> EH2 F B IY2 AY1. Periods
+/// are skipped, so "F.B.I." is read the same. Returns None for words with
+/// anything other than the letters a-z and periods, or without letters.
+pub fn initialism_pronunciation(initialism: &str) -> Option<Polyphone> {
+  let letters = initialism_letters(initialism)?;
+  let last = letters.len() - 1;
+
+  let mut polyphone = Vec::new();
+  for (i, letter) in letters.into_iter().enumerate() {
+    let name = letter_name(letter)?;
+    let demote = i < last;
+    polyphone.extend(name.into_iter().map(|phoneme| match phoneme {
+      Phoneme::Vowel(vowel) if demote && *vowel.get_stress() == VowelStress::PrimaryStress =>
+        Phoneme::Vowel(vowel.with_stress(VowelStress::SecondaryStress)),
+      phoneme => phoneme,
+    }));
+  }
+  Some(polyphone)
+}
+
+/// Guess whether a word from running text is an initialism, from its casing,
+/// length and letters:
+///
+/// * dotted capitals, eg. "U.S." or "F.B.I.", always are
+/// * otherwise the word must be two to five capital letters, eg. "FBI", and
+///   have no vowel letters (a, e, i, o, u or y), eg. "BBC" but not "NASA"
+///
+/// Capitalized words with vowels may still be initialisms, eg. "FBI"; these are
+/// only read letter by letter when missing from the dictionary. See
+/// `TranscribeOptions::initialisms`.
+pub fn is_initialism(word: &str) -> bool {
+  is_dotted_initialism(word) || (is_capitalized_initialism(word) && !has_vowel_letters(word))
+}
+
+/// Guess how an acronym is read from its spelling. It is read as a word when
+/// it has vowel letters and its consonant clusters could begin, end or sit
+/// within an English word, eg. "NASA", "SCUBA" or "LASER", and letter by
+/// letter otherwise, eg. "HTML", "FBI" or "NBC". Case is ignored, and words
+/// with anything other than letters are read letter by letter.
+pub fn acronym_reading(acronym: &str) -> AcronymReading {
+  if is_pronounceable(&fold_case(acronym)) {
+    AcronymReading::Word
+  } else {
+    AcronymReading::Letters
+  }
+}
+
+impl Arpabet {
+  /// Pronounce an acronym, and report how it was read. Acronyms read as
+  /// words (see `acronym_reading`) use their dictionary pronunciation, if
+  /// any; others, and those missing from the dictionary, are spelled out as
+  /// by `initialism_pronunciation`. Returns None only for words with
+  /// anything other than letters and periods.
+  pub fn pronounce_acronym(&self, acronym: &str) -> Option<(Polyphone, AcronymReading)> {
+    if acronym_reading(acronym) == AcronymReading::Word {
+      if let Some(polyphone) = self.get_polyphone(&fold_case(acronym)) {
+        return Some((polyphone, AcronymReading::Word));
+      }
+    }
+    initialism_pronunciation(acronym).map(|polyphone| (polyphone, AcronymReading::Letters))
+  }
+}
+
+// Whether a lowercase word's spelling could be read as an English word.
+fn is_pronounceable(word: &str) -> bool {
+  if word.is_empty() || !word.chars().all(|c| c.is_ascii_lowercase()) {
+    return false;
+  }
+  let is_vowel = |c: char| "aeiou".contains(c);
+  let clusters = word.split(is_vowel).collect::<Vec<&str>>();
+  if clusters.len() < 2 {
+    // No vowel letters.
+    return false;
+  }
+
+  let last = clusters.len() - 1;
+  clusters.iter().enumerate().all(|(i, cluster)| {
+    match i {
+      0 => fits(cluster, &ONSETS) || fits_with_s(cluster),
+      i if i == last => {
+        fits(cluster, &CODAS) || cluster.strip_suffix('s').is_some_and(|rest| fits(rest, &CODAS))
+      },
+      _ => cluster.len() <= 3,
+    }
+  })
+}
+
+// Whether a cluster is a single letter or one of the allowed clusters.
+fn fits(cluster: &str, allowed: &[&str]) -> bool {
+  cluster.len() < 2 || allowed.contains(&cluster)
+}
+
+// Whether a cluster is 's' plus a two letter onset, eg. "str" or "spl".
+fn fits_with_s(cluster: &str) -> bool {
+  cluster.len() == 3 && cluster.starts_with('s') && fits(&cluster[1 ..], &ONSETS)
+}
+
+// Whether the word is written in capitals, eg. "FBI", whatever its letters.
+pub(crate) fn is_capitalized_initialism(word: &str) -> bool {
+  let count = word.chars().count();
+  (2 ..= MAX_INITIALISM_LETTERS).contains(&count) && word.chars().all(|c| c.is_ascii_uppercase())
+}
+
+// Whether the word is capital letters each followed by a period, eg. "U.S.".
+fn is_dotted_initialism(word: &str) -> bool {
+  let chars = word.chars().collect::<Vec<char>>();
+  chars.len() >= 4
+    && chars.chunks(2).all(|pair| pair.len() == 2 && pair[0].is_ascii_uppercase() && pair[1] == '.')
+}
+
+fn has_vowel_letters(word: &str) -> bool {
+  word.chars().any(|c| "aeiouyAEIOUY".contains(c))
+}
+
+// The letters of the word, skipping periods.
+fn initialism_letters(word: &str) -> Option<Vec<char>> {
+  let letters = word.chars()
+    .filter(|c| *c != '.')
+    .map(|c| if c.is_ascii_alphabetic() { Some(c) } else { None })
+    .collect::<Option<Vec<char>>>()?;
+  if letters.is_empty() { None } else { Some(letters) }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn pronunciation() {
+    assert_eq!(initialism_pronunciation("FBI"), Some(polyphone("EH2 F B IY2 AY1")));
+    assert_eq!(initialism_pronunciation("f.b.i."), initialism_pronunciation("FBI"));
+    assert_eq!(initialism_pronunciation("US"), Some(polyphone("Y UW2 EH1 S")));
+    assert_eq!(initialism_pronunciation("W"), Some(polyphone("D AH1 B AH0 L Y UW0")));
+    assert_eq!(initialism_pronunciation("WHO"), Some(polyphone("D AH2 B AH0 L Y UW0 EY2 CH OW1")));
+    assert_eq!(initialism_pronunciation("A1"), None);
+    assert_eq!(initialism_pronunciation("..."), None);
+    assert_eq!(initialism_pronunciation(""), None);
+  }
+
+  #[test]
+  fn detection() {
+    assert!(is_initialism("BBC"));
+    assert!(is_initialism("NBC"));
+    assert!(is_initialism("U.S."));
+    assert!(is_initialism("F.B.I."));
+
+    assert!(!is_initialism("FBI"));
+    assert!(!is_initialism("NASA"));
+    assert!(!is_initialism("Bbc"));
+    assert!(!is_initialism("B"));
+    assert!(!is_initialism("BCDFGH"));
+    assert!(!is_initialism("U.S"));
+    assert!(!is_initialism("Mr."));
+
+    assert!(is_capitalized_initialism("FBI"));
+  }
+
+  #[test]
+  fn acronyms() {
+    for word in ["NASA", "SCUBA", "laser", "NATO", "STRIPE", "GIF", "ACTS"].iter() {
+      assert_eq!(acronym_reading(word), AcronymReading::Word, "{}", word);
+    }
+    for word in ["HTML", "FBI", "NBC", "BBC", "TSA", "MP3", ""].iter() {
+      assert_eq!(acronym_reading(word), AcronymReading::Letters, "{}", word);
+    }
+
+    let mut arpa = Arpabet::new();
+    arpa.insert("nasa".to_string(), polyphone("N AE1 S AH0"));
+    arpa.insert("html".to_string(), polyphone("HH T M L"));
+    assert_eq!(arpa.pronounce_acronym("NASA"), Some((polyphone("N AE1 S AH0"), AcronymReading::Word)));
+    assert_eq!(arpa.pronounce_acronym("HTML"),
+      Some((polyphone("EY2 CH T IY2 EH2 M EH1 L"), AcronymReading::Letters)));
+    assert_eq!(arpa.pronounce_acronym("NATO"),
+      Some((polyphone("EH2 N EY2 T IY2 OW1"), AcronymReading::Letters)));
+    assert_eq!(arpa.pronounce_acronym("A-1"), None);
+  }
+}
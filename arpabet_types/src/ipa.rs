@@ -0,0 +1,298 @@
+//! This module converts between ARPABET and the International Phonetic Alphabet (IPA).
+
+use crate::constants::PHONEME_MAP;
+use crate::error::ArpabetError;
+use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+use crate::syllable::{syllabify, syllable_stress};
+use crate::Polyphone;
+
+/// The IPA primary stress mark.
+pub const IPA_PRIMARY_STRESS : char = '\u{02C8}';
+
+/// The IPA secondary stress mark.
+pub const IPA_SECONDARY_STRESS : char = '\u{02CC}';
+
+impl Consonant {
+  /// Get the IPA symbol for the consonant.
+  pub fn to_ipa(&self) -> &'static str {
+    match self {
+      Consonant::B => "b",
+      Consonant::CH => "tʃ",
+      Consonant::D => "d",
+      Consonant::DH => "ð",
+      Consonant::DX => "ɾ",
+      Consonant::EL => "l̩",
+      Consonant::EM => "m̩",
+      Consonant::EN => "n̩",
+      Consonant::F => "f",
+      Consonant::G => "ɡ",
+      Consonant::HH => "h",
+      Consonant::JH => "dʒ",
+      Consonant::K => "k",
+      Consonant::L => "l",
+      Consonant::M => "m",
+      Consonant::N => "n",
+      Consonant::NG => "ŋ",
+      Consonant::NX => "ɾ̃",
+      Consonant::P => "p",
+      Consonant::Q => "ʔ",
+      Consonant::R => "ɹ",
+      Consonant::S => "s",
+      Consonant::SH => "ʃ",
+      Consonant::T => "t",
+      Consonant::TH => "θ",
+      Consonant::V => "v",
+      Consonant::W => "w",
+      Consonant::WH => "ʍ",
+      Consonant::Y => "j",
+      Consonant::Z => "z",
+      Consonant::ZH => "ʒ",
+    }
+  }
+}
+
+impl Vowel {
+  /// Get the IPA symbol for the vowel, without stress marks. Unstressed AH and
+  /// ER are reduced to schwa (ə) and r-colored schwa (ɚ) respectively.
+  pub fn to_ipa(&self) -> &'static str {
+    match self {
+      Vowel::AA(_) => "ɑ",
+      Vowel::AE(_) => "æ",
+      Vowel::AH(VowelStress::NoStress) => "ə",
+      Vowel::AH(_) => "ʌ",
+      Vowel::AO(_) => "ɔ",
+      Vowel::AW(_) => "aʊ",
+      Vowel::AX(_) => "ə",
+      Vowel::AXR(_) => "ɚ",
+      Vowel::AY(_) => "aɪ",
+      Vowel::EH(_) => "ɛ",
+      Vowel::ER(VowelStress::NoStress) => "ɚ",
+      Vowel::ER(_) => "ɝ",
+      Vowel::EY(_) => "eɪ",
+      Vowel::IH(_) => "ɪ",
+      Vowel::IX(_) => "ɨ",
+      Vowel::IY(_) => "i",
+      Vowel::OW(_) => "oʊ",
+      Vowel::OY(_) => "ɔɪ",
+      Vowel::UH(_) => "ʊ",
+      Vowel::UW(_) => "u",
+      Vowel::UX(_) => "ʉ",
+    }
+  }
+}
+
+impl Phoneme {
+  /// Get the IPA symbol for the phoneme, without stress marks.
+  /// Use `polyphone_to_ipa` to transcribe whole words with stress.
+  pub fn to_ipa(&self) -> &'static str {
+    match self {
+      Phoneme::Consonant(consonant) => consonant.to_ipa(),
+      Phoneme::Vowel(vowel) => vowel.to_ipa(),
+    }
+  }
+}
+
+/// Transcribe a pronunciation to IPA, placing stress marks before the
+/// stressed syllables, eg. [EH1 K S T R AH0] -> "ˈɛkstɹə".
+pub fn polyphone_to_ipa(polyphone: &[Phoneme]) -> String {
+  write_symbols(polyphone, IPA_PRIMARY_STRESS, IPA_SECONDARY_STRESS, |p| p.to_ipa())
+}
+
+/// IPA segments and the ARPABET phonemes they are read as. Vowels are listed
+/// without stress. Several IPA spellings may map to the same phoneme, eg. the
+/// British 'ɒ' and the American 'ɑ' both map to AA.
+pub const IPA_SEGMENTS : [(&str, &str); 64] = [
+  // Consonants.
+  ("b", "B"), ("tʃ", "CH"), ("t͡ʃ", "CH"), ("ʧ", "CH"), ("d", "D"), ("ð", "DH"),
+  ("ɾ", "DX"), ("l̩", "EL"), ("m̩", "EM"), ("n̩", "EN"), ("f", "F"), ("ɡ", "G"),
+  ("g", "G"), ("h", "HH"), ("dʒ", "JH"), ("d͡ʒ", "JH"), ("ʤ", "JH"), ("k", "K"),
+  ("l", "L"), ("ɫ", "L"), ("m", "M"), ("n", "N"), ("ŋ", "NG"), ("ɾ̃", "NX"),
+  ("p", "P"), ("ʔ", "Q"), ("ɹ", "R"), ("r", "R"), ("s", "S"), ("ʃ", "SH"),
+  ("t", "T"), ("θ", "TH"), ("v", "V"), ("w", "W"), ("ʍ", "WH"), ("j", "Y"),
+  ("z", "Z"), ("ʒ", "ZH"),
+  // Vowels.
+  ("ɑ", "AA"), ("ɒ", "AA"), ("a", "AA"), ("æ", "AE"), ("ʌ", "AH"), ("ɐ", "AH"),
+  ("ə", "AH"), ("ɔ", "AO"), ("aʊ", "AW"), ("ɚ", "ER"), ("ɝ", "ER"), ("ɜ", "ER"),
+  ("aɪ", "AY"), ("ɛ", "EH"), ("e", "EH"), ("eɪ", "EY"), ("ɪ", "IH"), ("ɨ", "IX"),
+  ("i", "IY"), ("oʊ", "OW"), ("əʊ", "OW"), ("o", "OW"), ("ɔɪ", "OY"), ("ʊ", "UH"),
+  ("u", "UW"), ("ʉ", "UX"),
+];
+
+const IPA_SYMBOLS : SymbolSet = SymbolSet {
+  name: "IPA",
+  segments: &IPA_SEGMENTS,
+  primary_stress: &[IPA_PRIMARY_STRESS, '\''],
+  secondary_stress: &[IPA_SECONDARY_STRESS, ','],
+  // Delimiters, syllable breaks, length marks and aspiration.
+  ignored: &['/', '[', ']', '(', ')', '.', ' ', 'ː', 'ˑ', 'ʰ', '‿'],
+};
+
+/// Parse an IPA transcription, eg. "/ˈɛkstɹə/", into a pronunciation.
+///
+/// Stress marks (ˈ, ˌ, or their ASCII stand-ins ' and ,) stress the next vowel;
+/// other vowels are unstressed, except that the only vowel of an unmarked
+/// transcription ("kæt") receives primary stress. Schwa ('ə') is read as AH0,
+/// or AH1/AH2 when marked. Length marks, syllable breaks and enclosing slashes
+/// or brackets are ignored. Segments with no ARPABET equivalent are an error.
+pub fn parse_ipa(ipa: &str) -> Result<Polyphone, ArpabetError> {
+  parse_symbols(ipa, &IPA_SYMBOLS)
+}
+
+// A phonetic alphabet: its segments and the ARPABET phonemes they are read as,
+// and its stress marks. Shared with the ASCII notations.
+pub(crate) struct SymbolSet {
+  pub(crate) name: &'static str,
+  pub(crate) segments: &'static [(&'static str, &'static str)],
+  pub(crate) primary_stress: &'static [char],
+  pub(crate) secondary_stress: &'static [char],
+  pub(crate) ignored: &'static [char],
+}
+
+// Write a pronunciation syllable by syllable, marking stressed syllables.
+pub(crate) fn write_symbols<F>(polyphone: &[Phoneme], primary: char, secondary: char, symbol: F)
+  -> String
+  where F: Fn(&Phoneme) -> &'static str
+{
+  let mut output = String::new();
+  for syllable in syllabify(polyphone) {
+    match syllable_stress(syllable) {
+      Some(VowelStress::PrimaryStress) => output.push(primary),
+      Some(VowelStress::SecondaryStress) => output.push(secondary),
+      _ => {},
+    }
+    for phoneme in syllable.iter() {
+      output.push_str(symbol(phoneme));
+    }
+  }
+  output
+}
+
+// Parse a transcription by longest match against the symbol set.
+// See `parse_ipa` for the treatment of stress.
+pub(crate) fn parse_symbols(text: &str, symbols: &SymbolSet) -> Result<Polyphone, ArpabetError> {
+  let mut polyphone = Vec::new();
+  let mut pending_stress = None;
+  let mut has_stress_marks = false;
+  let mut rest = text;
+
+  while let Some(c) = rest.chars().next() {
+    if symbols.primary_stress.contains(&c) {
+      pending_stress = Some(VowelStress::PrimaryStress);
+      has_stress_marks = true;
+      rest = &rest[c.len_utf8() ..];
+      continue;
+    }
+    if symbols.secondary_stress.contains(&c) {
+      pending_stress = Some(VowelStress::SecondaryStress);
+      has_stress_marks = true;
+      rest = &rest[c.len_utf8() ..];
+      continue;
+    }
+    if symbols.ignored.contains(&c) {
+      rest = &rest[c.len_utf8() ..];
+      continue;
+    }
+
+    // Longest match, so that 'tʃ' wins over 't'. Among equals, the first listed wins.
+    let mut matched : Option<&(&str, &str)> = None;
+    for entry in symbols.segments.iter() {
+      if rest.starts_with(entry.0) && matched.map(|m| entry.0.len() > m.0.len()).unwrap_or(true) {
+        matched = Some(entry);
+      }
+    }
+
+    let (segment, arpabet) = matched.ok_or_else(|| ArpabetError::StringParseError {
+      description: format!("No ARPAbet equivalent for {} segment '{}' at byte {}",
+        symbols.name, c, text.len() - rest.len()),
+    })?;
+
+    let phoneme = match PHONEME_MAP.get(*arpabet) {
+      Some(Phoneme::Vowel(vowel)) => {
+        Phoneme::Vowel(vowel.with_stress(pending_stress.take().unwrap_or(VowelStress::NoStress)))
+      },
+      Some(consonant) => *consonant,
+      None => unreachable!("segment table maps to a valid phoneme"),
+    };
+
+    polyphone.push(phoneme);
+    rest = &rest[segment.len() ..];
+  }
+
+  let vowels = polyphone.iter()
+    .filter(|phoneme| matches!(phoneme, Phoneme::Vowel(_)))
+    .count();
+
+  if !has_stress_marks && vowels == 1 {
+    for phoneme in polyphone.iter_mut() {
+      if let Phoneme::Vowel(vowel) = phoneme {
+        *vowel = vowel.with_stress(VowelStress::PrimaryStress);
+      }
+    }
+  }
+
+  Ok(polyphone)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  fn ipa(text: &str) -> String {
+    polyphone_to_ipa(&polyphone(text))
+  }
+
+  #[test]
+  fn phoneme_to_ipa() {
+    assert_eq!(Phoneme::Consonant(Consonant::SH).to_ipa(), "ʃ");
+    assert_eq!(Phoneme::Vowel(Vowel::AH(VowelStress::NoStress)).to_ipa(), "ə");
+    assert_eq!(Phoneme::Vowel(Vowel::AH(VowelStress::PrimaryStress)).to_ipa(), "ʌ");
+    assert_eq!(Phoneme::Vowel(Vowel::ER(VowelStress::NoStress)).to_ipa(), "ɚ");
+    assert_eq!(Phoneme::Vowel(Vowel::ER(VowelStress::PrimaryStress)).to_ipa(), "ɝ");
+  }
+
+  #[test]
+  fn stress_marks() {
+    assert_eq!(ipa("EH1 K S T R AH0"), "ˈɛkstɹə");
+    assert_eq!(ipa("AH0 B AE1 N D AH0 N"), "əˈbændən");
+    // 'arpabet'
+    assert_eq!(ipa("AA1 R P AH0 B EH2 T"), "ˈɑɹpəˌbɛt");
+    assert_eq!(ipa("JH AH1 NG G AH0 L"), "ˈdʒʌŋɡəl");
+    assert_eq!(ipa("HH M"), "hm");
+  }
+
+  fn arpabet(ipa: &str) -> String {
+    parse_ipa(ipa).unwrap().iter()
+      .map(|phoneme| phoneme.to_str())
+      .collect::<Vec<&str>>()
+      .join(" ")
+  }
+
+  #[test]
+  fn parse() {
+    assert_eq!(arpabet("/ˈɛkstɹə/"), "EH1 K S T R AH0");
+    assert_eq!(arpabet("[ˈɑɹpəˌbɛt]"), "AA1 R P AH0 B EH2 T");
+    assert_eq!(arpabet("kæt"), "K AE1 T");
+    assert_eq!(arpabet("ˈt͡ʃɜːtʃ"), "CH ER1 CH");
+    assert_eq!(arpabet("/ˈwɔː.tə/"), "W AO1 T AH0");
+    assert_eq!(arpabet("ˈbɒt.l̩"), "B AA1 T EL");
+    assert_eq!(arpabet("ðə"), "DH AH1");
+  }
+
+  #[test]
+  fn parse_errors() {
+    match parse_ipa("ˈbɛx") {
+      Err(ArpabetError::StringParseError { description }) => assert!(description.contains("IPA segment 'x'")),
+      _ => panic!("Should have failed on 'x'"),
+    }
+    assert!(parse_ipa("ʀ").is_err());
+  }
+
+  #[test]
+  fn round_trip() {
+    for text in ["EH1 K S T R AH0", "AH0 B AE1 N D AH0 N", "AA1 R P AH0 B EH2 T",
+        "JH AH1 NG G AH0 L", "S IH1 NG ER0"].iter() {
+      assert_eq!(arpabet(&ipa(text)), *text);
+    }
+  }
+}
@@ -0,0 +1,171 @@
+//! This module contains the iterators over dictionary entries and the standard
+//! collection traits, so an Arpabet composes with ordinary iterator code.
+
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone, Word};
+use regex::Regex;
+use std::collections::hash_map;
+use std::iter::FromIterator;
+
+/// An iterator over borrowed dictionary entries, in arbitrary order.
+pub struct Entries<'a> {
+  inner: Box<dyn Iterator<Item=(&'a str, &'a [Phoneme])> + 'a>,
+}
+
+impl <'a> Iterator for Entries<'a> {
+  type Item = (&'a str, &'a [Phoneme]);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+}
+
+/// An iterator over owned dictionary entries, in arbitrary order.
+pub struct IntoEntries {
+  inner: hash_map::IntoIter<Word, Polyphone>,
+}
+
+impl Iterator for IntoEntries {
+  type Item = (Word, Polyphone);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl Arpabet {
+  /// Iterate over the entries in arbitrary order. Keys include any variant
+  /// marker, eg. "read(1)".
+  pub fn iter(&self) -> Entries<'_> {
+    Entries { inner: self.dictionary.iter() }
+  }
+
+  /// Iterate over the pronunciations in arbitrary order.
+  pub fn values(&self) -> impl Iterator<Item=&[Phoneme]> {
+    self.dictionary.iter().map(|(_, polyphone)| polyphone)
+  }
+
+  /// Iterate over the keys the regex matches, in arbitrary order. Keys include
+  /// any variant marker, so `\(\d+\)$` finds the alternate pronunciations.
+  /// Anchor the regex to match whole keys.
+  pub fn find_words<'a: 'r, 'r>(&'a self, regex: &'r Regex) -> impl Iterator<Item=&'a str> + 'r {
+    self.keys().filter(move |key| regex.is_match(key))
+  }
+}
+
+impl <'a> IntoIterator for &'a Arpabet {
+  type Item = (&'a str, &'a [Phoneme]);
+  type IntoIter = Entries<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl IntoIterator for Arpabet {
+  type Item = (Word, Polyphone);
+  type IntoIter = IntoEntries;
+
+  /// Take the entries. Metadata and frequencies are dropped.
+  fn into_iter(self) -> Self::IntoIter {
+    IntoEntries { inner: self.dictionary.into_owned().into_iter() }
+  }
+}
+
+impl FromIterator<(Word, Polyphone)> for Arpabet {
+  /// Collect entries into a new Arpabet. Later entries for a key replace
+  /// earlier ones.
+  fn from_iter<I: IntoIterator<Item=(Word, Polyphone)>>(entries: I) -> Self {
+    Arpabet::from_map(entries.into_iter().collect())
+  }
+}
+
+impl Extend<(Word, Polyphone)> for Arpabet {
+  /// Insert the entries, replacing existing entries for the same keys.
+  fn extend<I: IntoIterator<Item=(Word, Polyphone)>>(&mut self, entries: I) {
+    self.drop_indexes();
+    self.dictionary.to_mut().extend(entries);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("cat", "K AE1 T"),
+    ("dog", "D AO1 G"),
+  ];
+
+  #[test]
+  fn borrowed_iteration() {
+    let arpa = testing::arpabet(DICTIONARY);
+
+    let mut entries = arpa.iter().collect::<Vec<(&str, &[Phoneme])>>();
+    entries.sort_by_key(|(word, _)| *word);
+    assert_eq!(entries, vec![
+      ("cat", polyphone("K AE1 T").as_slice()),
+      ("dog", polyphone("D AO1 G").as_slice()),
+    ]);
+
+    assert_eq!(arpa.values().map(|p| p.len()).sum::<usize>(), 6);
+
+    let mut words = Vec::new();
+    for (word, _) in &arpa {
+      words.push(word);
+    }
+    words.sort();
+    assert_eq!(words, vec!["cat", "dog"]);
+  }
+
+  #[test]
+  fn find_words() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    arpa.insert("dog(1)".to_string(), polyphone("D AA1 G"));
+    arpa.insert("dog's".to_string(), polyphone("D AO1 G Z"));
+    arpa.insert("hot-dog".to_string(), polyphone("HH AA1 T D AO2 G"));
+
+    let find = |pattern: &str| {
+      let regex = Regex::new(pattern).unwrap();
+      let mut words = arpa.find_words(&regex).collect::<Vec<&str>>();
+      words.sort();
+      words
+    };
+
+    assert_eq!(find("-"), vec!["hot-dog"]);
+    assert_eq!(find("'"), vec!["dog's"]);
+    assert_eq!(find(r"\(\d+\)$"), vec!["dog(1)"]);
+    assert_eq!(find("^dog"), vec!["dog", "dog's", "dog(1)"]);
+    assert!(find("^z").is_empty());
+  }
+
+  #[test]
+  fn owned_iteration() {
+    let mut entries = testing::arpabet(DICTIONARY).into_iter().collect::<Vec<(Word, Polyphone)>>();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(entries[0], ("cat".to_string(), polyphone("K AE1 T")));
+    assert_eq!(entries.len(), 2);
+
+    let arpa = entries.into_iter().collect::<Arpabet>();
+    assert_eq!(arpa.get_polyphone("dog"), Some(polyphone("D AO1 G")));
+  }
+
+  #[test]
+  fn extend() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    assert_eq!(arpa.reverse_index().len(), 2);
+
+    arpa.extend(vec![
+      ("cat".to_string(), polyphone("K AE2 T")),
+      ("cow".to_string(), polyphone("K AW1")),
+    ]);
+    assert_eq!(arpa.len(), 3);
+    assert_eq!(arpa.get_polyphone("cat"), Some(polyphone("K AE2 T")));
+    assert_eq!(arpa.reverse_index().len(), 3);
+  }
+}
@@ -0,0 +1,160 @@
+//! This module exchanges dictionaries as JSON, eg. with web annotation tools.
+//! It needs the `json` feature.
+//!
+//! The schema is an object mapping each word to an array of its
+//! pronunciations, each an array of phoneme strings, alternates in variant
+//! order:
+//!
+//! ```json
+//! {
+//!   "cat": [["K", "AE1", "T"]],
+//!   "read": [["R", "IY1", "D"], ["R", "EH1", "D"]]
+//! }
+//! ```
+//!
+//! Words are written lowercase and sorted. Metadata and attached frequencies
+//! are not included.
+
+use crate::casing::fold_case;
+use crate::error::{ArpabetError, WithContext};
+use crate::export::{ordered_entries, ExportOptions};
+use crate::index::strip_variant;
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+
+impl Arpabet {
+  /// Write the dictionary as JSON. See the module documentation for the
+  /// schema. Gaps in variant numbering are closed.
+  pub fn to_json(&self) -> String {
+    self.json_value().to_string()
+  }
+
+  /// Write the dictionary as indented JSON. See `to_json`.
+  pub fn to_json_pretty(&self) -> String {
+    serde_json::to_string_pretty(&self.json_value()).unwrap_or_default()
+  }
+
+  /// Read a dictionary from JSON. See the module documentation for the schema.
+  /// Words are lowercased. Fails with `ArpabetError::StringParseError` on
+  /// malformed JSON, input not following the schema, or unknown phonemes.
+  pub fn from_json(json: &str) -> Result<Arpabet, ArpabetError> {
+    let value : Value = serde_json::from_str(json)
+      .map_err(|err| schema_error(format!("Invalid JSON: {}", err)))?;
+    let words = value.as_object()
+      .ok_or_else(|| schema_error("Expected an object of words".to_string()))?;
+
+    let mut map = HashMap::new();
+    for (word, pronunciations) in words {
+      let word = fold_case(word);
+      let pronunciations = pronunciations.as_array()
+        .filter(|pronunciations| !pronunciations.is_empty())
+        .ok_or_else(|| schema_error(format!("Expected pronunciations of '{}'", word)))?;
+
+      for (i, pronunciation) in pronunciations.iter().enumerate() {
+        let polyphone = parse_pronunciation(pronunciation)
+          .with_context(|| format!("Reading '{}'", word))?;
+        let key = match i {
+          0 => word.clone(),
+          n => format!("{}({})", word, n),
+        };
+        map.insert(key, polyphone);
+      }
+    }
+
+    Ok(Arpabet::from_map(map))
+  }
+
+  /// Load a dictionary from a JSON file. See `from_json`.
+  pub fn load_json(filename: &str) -> Result<Arpabet, ArpabetError> {
+    fs::read_to_string(filename)
+      .map_err(ArpabetError::from)
+      .and_then(|json| Arpabet::from_json(&json))
+      .with_context(|| format!("Loading '{}'", filename))
+  }
+
+  /// Save the dictionary to a JSON file. See `to_json`.
+  pub fn save_json(&self, filename: &str) -> Result<(), ArpabetError> {
+    fs::write(filename, self.to_json())
+      .map_err(ArpabetError::from)
+      .with_context(|| format!("Saving '{}'", filename))
+  }
+
+  // The dictionary in the JSON schema.
+  fn json_value(&self) -> Value {
+    let options = ExportOptions { renumber_variants: true, ..Default::default() };
+    let mut words = Map::new();
+    for (word, polyphone) in ordered_entries(self, &options) {
+      let phonemes = polyphone.iter()
+        .map(|phoneme| Value::String(phoneme.to_str().to_string()))
+        .collect();
+      let pronunciations = words.entry(strip_variant(&word).to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+      if let Value::Array(pronunciations) = pronunciations {
+        pronunciations.push(Value::Array(phonemes));
+      }
+    }
+    Value::Object(words)
+  }
+}
+
+// Parse one pronunciation, an array of phoneme strings.
+fn parse_pronunciation(pronunciation: &Value) -> Result<Polyphone, ArpabetError> {
+  let phonemes = pronunciation.as_array()
+    .filter(|phonemes| !phonemes.is_empty())
+    .ok_or_else(|| schema_error("Expected an array of phonemes".to_string()))?;
+  phonemes.iter()
+    .map(|phoneme| match phoneme.as_str() {
+      Some(phoneme) => Phoneme::try_from(phoneme),
+      None => Err(schema_error(format!("Expected a phoneme string, found {}", phoneme))),
+    })
+    .collect()
+}
+
+fn schema_error(description: String) -> ArpabetError {
+  ArpabetError::StringParseError { description }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+
+  #[test]
+  fn round_trip() {
+    let arpa = testing::arpabet(&[
+      ("cat", "K AE1 T"),
+      ("read", "R IY1 D"),
+      ("read(1)", "R EH1 D"),
+    ]);
+    let json = arpa.to_json();
+    assert_eq!(json, r#"{"cat":[["K","AE1","T"]],"read":[["R","IY1","D"],["R","EH1","D"]]}"#);
+
+    let loaded = Arpabet::from_json(&json).unwrap();
+    assert_eq!(loaded.len(), 3);
+    assert_eq!(loaded.get_polyphone("read(1)"), Some(polyphone("R EH1 D")));
+    assert_eq!(loaded.to_json(), json);
+    assert_eq!(Arpabet::from_json(&arpa.to_json_pretty()).unwrap().to_json(), json);
+  }
+
+  #[test]
+  fn invalid() {
+    let fails = |json: &str| match Arpabet::from_json(json) {
+      Err(err) => matches!(err.root(), ArpabetError::StringParseError { .. }),
+      Ok(_) => false,
+    };
+    assert!(fails("{"));
+    assert!(fails(r#"[["K"]]"#));
+    assert!(fails(r#"{"cat": []}"#));
+    assert!(fails(r#"{"cat": [[]]}"#));
+    assert!(fails(r#"{"cat": [["K", 1]]}"#));
+    assert!(fails(r#"{"cat": [["K", "XX", "T"]]}"#));
+    assert!(fails(r#"{"cat": ["K AE1 T"]}"#));
+
+    assert_eq!(Arpabet::from_json(r#"{"Cat": [["K", "AE1", "T"]]}"#).unwrap().get_polyphone("cat"),
+      Some(polyphone("K AE1 T")));
+  }
+}
@@ -0,0 +1,188 @@
+//! This module exports dictionaries for training Kaldi (or HTK) speech
+//! recognizers: a `lexicon.txt` of "word phone phone ..." lines, and the
+//! matching `nonsilence_phones.txt` listing every phone the lexicon uses.
+//!
+//! Both files are written with the same `KaldiPhones` settings, so they always
+//! agree. Silence phones, and the optional silence files, are left to the
+//! recipe.
+
+use crate::export::{ordered_entries, ExportOptions};
+use crate::index::strip_variant;
+use crate::phoneme::Phoneme;
+use crate::error::WithContext;
+use crate::{Arpabet, ArpabetError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// How phones are written for Kaldi.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KaldiPhones {
+  /// Drop vowel stress digits, eg. writing "AA" for "AA0", "AA1" and "AA2".
+  pub fold_stress: bool,
+  /// Suffix phones with their position in the word: "_B" at the beginning,
+  /// "_I" inside, "_E" at the end, or "_S" for a word of a single phone. Run
+  /// `prepare_lang.sh` with `--position-dependent-phones false` on such a
+  /// lexicon, as the positions are already marked.
+  pub position_dependent: bool,
+}
+
+impl KaldiPhones {
+  /// The phones of a pronunciation as written in the lexicon.
+  pub fn phones(&self, polyphone: &[Phoneme]) -> Vec<String> {
+    polyphone.iter()
+      .enumerate()
+      .map(|(i, phoneme)| {
+        let phone = if self.fold_stress { phoneme.stressless() } else { *phoneme };
+        if !self.position_dependent {
+          return phone.to_str().to_string();
+        }
+        let position = match (i == 0, i + 1 == polyphone.len()) {
+          (true, true) => "S",
+          (true, false) => "B",
+          (false, true) => "E",
+          (false, false) => "I",
+        };
+        format!("{}_{}", phone.to_str(), position)
+      })
+      .collect()
+  }
+}
+
+/// Write every dictionary entry as a Kaldi lexicon line, eg. "read R IY1 D".
+/// Entries are written in the order given by `ordered_entries`, without
+/// variant markers, so alternates follow their primary pronunciation. Entries
+/// without phonemes are skipped. Returns the number of entries written.
+pub fn write_kaldi_lexicon(arpabet: &Arpabet,
+                           writer: &mut dyn Write,
+                           phones: KaldiPhones,
+                           options: &ExportOptions)
+                           -> Result<usize, ArpabetError> {
+  let mut written = 0;
+  for (word, polyphone) in ordered_entries(arpabet, options) {
+    if polyphone.is_empty() {
+      continue;
+    }
+    writeln!(writer, "{} {}", strip_variant(&word), phones.phones(polyphone).join(" "))?;
+    written += 1;
+  }
+  Ok(written)
+}
+
+/// Save the dictionary to a Kaldi lexicon file. See `write_kaldi_lexicon`.
+pub fn save_kaldi_lexicon(arpabet: &Arpabet,
+                          filename: &str,
+                          phones: KaldiPhones,
+                          options: &ExportOptions)
+                          -> Result<usize, ArpabetError> {
+  let save = || -> Result<usize, ArpabetError> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    let written = write_kaldi_lexicon(arpabet, &mut writer, phones, options)?;
+    writer.flush()?;
+    Ok(written)
+  };
+  save().with_context(|| format!("Saving '{}'", filename))
+}
+
+/// Write the phones the dictionary's Kaldi lexicon uses as
+/// `nonsilence_phones.txt`. Each line holds the variants of one phone, by
+/// stress and position, eg. "AA0 AA1 AA2", so Kaldi shares a decision tree
+/// root between them. Lines and the phones on them are sorted. Returns the
+/// number of lines written.
+pub fn write_nonsilence_phones(arpabet: &Arpabet, writer: &mut dyn Write, phones: KaldiPhones)
+  -> Result<usize, ArpabetError>
+{
+  let mut groups : BTreeMap<&'static str, BTreeSet<String>> = BTreeMap::new();
+  for (_, polyphone) in arpabet.iter() {
+    for (phoneme, phone) in polyphone.iter().zip(phones.phones(polyphone)) {
+      groups.entry(phoneme.stressless().to_str()).or_default().insert(phone);
+    }
+  }
+  for group in groups.values() {
+    writeln!(writer, "{}", group.iter().map(String::as_str).collect::<Vec<&str>>().join(" "))?;
+  }
+  Ok(groups.len())
+}
+
+/// Save the phones the dictionary's Kaldi lexicon uses to a file. See
+/// `write_nonsilence_phones`.
+pub fn save_nonsilence_phones(arpabet: &Arpabet, filename: &str, phones: KaldiPhones)
+  -> Result<usize, ArpabetError>
+{
+  let save = || -> Result<usize, ArpabetError> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    let written = write_nonsilence_phones(arpabet, &mut writer, phones)?;
+    writer.flush()?;
+    Ok(written)
+  };
+  save().with_context(|| format!("Saving '{}'", filename))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("read", "R IY1 D"),
+    ("read(1)", "R EH1 D"),
+    ("a", "AH0"),
+    ("a(1)", "EY1"),
+    ("reed", "R IY1 D"),
+    ("ready", "R EH1 D IY0"),
+  ];
+
+  fn lexicon(phones: KaldiPhones) -> String {
+    let mut output = Vec::new();
+    let arpa = testing::arpabet(DICTIONARY);
+    write_kaldi_lexicon(&arpa, &mut output, phones, &ExportOptions::default()).unwrap();
+    String::from_utf8(output).unwrap()
+  }
+
+  fn nonsilence(phones: KaldiPhones) -> String {
+    let mut output = Vec::new();
+    write_nonsilence_phones(&testing::arpabet(DICTIONARY), &mut output, phones).unwrap();
+    String::from_utf8(output).unwrap()
+  }
+
+  #[test]
+  fn phones() {
+    let cat = polyphone("K AE1 T");
+    let folded = KaldiPhones { fold_stress: true, position_dependent: false };
+    let positions = KaldiPhones { fold_stress: false, position_dependent: true };
+    assert_eq!(KaldiPhones::default().phones(&cat), vec!["K", "AE1", "T"]);
+    assert_eq!(folded.phones(&cat), vec!["K", "AE", "T"]);
+    assert_eq!(positions.phones(&cat), vec!["K_B", "AE1_I", "T_E"]);
+    assert_eq!(positions.phones(&polyphone("AH0")), vec!["AH0_S"]);
+    assert!(positions.phones(&[]).is_empty());
+  }
+
+  #[test]
+  fn lexicons() {
+    assert_eq!(lexicon(KaldiPhones::default()), "\
+a AH0
+a EY1
+read R IY1 D
+read R EH1 D
+ready R EH1 D IY0
+reed R IY1 D
+");
+    assert_eq!(lexicon(KaldiPhones { fold_stress: true, position_dependent: true }), "\
+a AH_S
+a EY_S
+read R_B IY_I D_E
+read R_B EH_I D_E
+ready R_B EH_I D_I IY_E
+reed R_B IY_I D_E
+");
+  }
+
+  #[test]
+  fn nonsilence_phones() {
+    assert_eq!(nonsilence(KaldiPhones::default()), "AH0\nD\nEH1\nEY1\nIY0 IY1\nR\n");
+    assert_eq!(nonsilence(KaldiPhones { fold_stress: true, position_dependent: false }),
+      "AH\nD\nEH\nEY\nIY\nR\n");
+    assert_eq!(nonsilence(KaldiPhones { fold_stress: false, position_dependent: true }),
+      "AH0_S\nD_E D_I\nEH1_I\nEY1_S\nIY0_E IY1_I\nR_B\n");
+  }
+}
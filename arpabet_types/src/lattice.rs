@@ -0,0 +1,253 @@
+//! This module expands a sentence into a lattice of every combination of its
+//! words' pronunciation variants, eg. for forced alignment or keyword spotting
+//! with speech recognition tools.
+//!
+//! The lattice is a phoneme-to-word transducer: each variant of a word is a
+//! chain of phoneme arcs between the states either side of the word, and the
+//! first arc of each chain outputs the word. Lattices export to the OpenFST
+//! text format.
+
+use crate::casing::fold_case;
+use crate::error::ArpabetError;
+use crate::phoneme::Phoneme;
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone};
+use std::fmt;
+
+/// The OpenFST label for arcs without output.
+pub const EPSILON : &str = "<eps>";
+
+/// An arc of a lattice, reading one phoneme.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatticeEdge {
+  /// The state the arc leaves.
+  pub from: usize,
+  /// The state the arc enters.
+  pub to: usize,
+  /// The phoneme read.
+  pub phoneme: Phoneme,
+  /// The word output, on the first arc of each pronunciation.
+  pub word: Option<String>,
+}
+
+/// The pronunciations of a sentence as a lattice. State 0 is the start and the
+/// last state is final. `word_states[i]` is the state before the ith word.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lattice {
+  /// The words of the sentence, lowercased.
+  pub words: Vec<String>,
+  /// The state before each word.
+  pub word_states: Vec<usize>,
+  /// The arcs, in order of their words.
+  pub edges: Vec<LatticeEdge>,
+  states: usize,
+}
+
+impl Lattice {
+  /// The number of states.
+  pub fn state_count(&self) -> usize {
+    self.states
+  }
+
+  /// The start state.
+  pub fn start(&self) -> usize {
+    0
+  }
+
+  /// The final state.
+  pub fn final_state(&self) -> usize {
+    self.states - 1
+  }
+
+  /// The arcs leaving a state.
+  pub fn edges_from(&self, state: usize) -> impl Iterator<Item=&LatticeEdge> {
+    self.edges.iter().filter(move |edge| edge.from == state)
+  }
+
+  /// The number of distinct paths from start to final state, ie. the product
+  /// of each word's variant count. Saturates rather than overflowing.
+  pub fn path_count(&self) -> usize {
+    self.word_states.iter()
+      .map(|state| self.edges_from(*state).filter(|edge| edge.word.is_some()).count())
+      .fold(1, usize::saturating_mul)
+  }
+
+  /// Render in the OpenFST text format: one "from to phoneme word" line per
+  /// arc, with `EPSILON` for arcs without output, then the final state.
+  pub fn to_openfst(&self) -> String {
+    let mut text = String::new();
+    for edge in &self.edges {
+      text.push_str(&format!("{}\t{}\t{}\t{}\n", edge.from, edge.to, edge.phoneme.to_str(),
+        edge.word.as_deref().unwrap_or(EPSILON)));
+    }
+    text.push_str(&format!("{}\n", self.final_state()));
+    text
+  }
+
+  /// The input symbol table for `to_openfst`: `EPSILON` as 0, then each
+  /// phoneme used, in order of appearance.
+  pub fn phoneme_symbols(&self) -> String {
+    symbol_table(self.edges.iter().map(|edge| edge.phoneme.to_str()))
+  }
+
+  /// The output symbol table for `to_openfst`: `EPSILON` as 0, then each word,
+  /// in order of appearance.
+  pub fn word_symbols(&self) -> String {
+    symbol_table(self.words.iter().map(|word| word.as_str()))
+  }
+}
+
+impl fmt::Display for Lattice {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.to_openfst())
+  }
+}
+
+impl Arpabet {
+  /// Expand a sentence into a lattice of all its pronunciations. Every
+  /// dictionary variant of each word is included; words missing from the
+  /// dictionary get their single pronunciation as by `transcribe_sentence`.
+  /// Punctuation and empty pronunciations are skipped, so a word pronounced
+  /// as nothing is left out. Fails with `ArpabetError::UnknownWord` on the
+  /// first unpronounceable word.
+  pub fn transcription_lattice(&self, sentence: &str) -> Result<Lattice, ArpabetError> {
+    let mut lattice = Lattice { states: 1, ..Default::default() };
+
+    for (word, polyphone) in self.pronounce_words(sentence, &TranscribeOptions::default()) {
+      let word = fold_case(&word);
+      let mut variants : Vec<Polyphone> = Vec::new();
+      for variant in self.get_polyphone_variants(&word) {
+        if !variants.iter().any(|seen| seen == variant) {
+          variants.push(variant.to_vec());
+        }
+      }
+      if variants.is_empty() {
+        variants.push(polyphone.ok_or_else(|| ArpabetError::UnknownWord { word: word.clone() })?);
+      }
+      // Every arc reads a phoneme, so empty pronunciations have no path.
+      variants.retain(|variant| !variant.is_empty());
+      if variants.is_empty() {
+        continue;
+      }
+
+      // Number each variant's inner states first, so the word's end state
+      // follows them and the final state is the highest.
+      let from = lattice.states - 1;
+      let inner_states = variants.iter().map(|variant| variant.len() - 1).sum::<usize>();
+      let to = from + inner_states + 1;
+      let mut next_state = from + 1;
+      for variant in variants {
+        let mut state = from;
+        for (i, phoneme) in variant.iter().enumerate() {
+          let next = if i + 1 == variant.len() {
+            to
+          } else {
+            next_state += 1;
+            next_state - 1
+          };
+          lattice.edges.push(LatticeEdge {
+            from: state,
+            to: next,
+            phoneme: *phoneme,
+            word: if i == 0 { Some(word.clone()) } else { None },
+          });
+          state = next;
+        }
+      }
+      lattice.states = to + 1;
+      lattice.word_states.push(from);
+      lattice.words.push(word);
+    }
+
+    Ok(lattice)
+  }
+}
+
+// An OpenFST symbol table, numbering the distinct symbols after epsilon.
+fn symbol_table<'a, I: Iterator<Item=&'a str>>(symbols: I) -> String {
+  let mut seen = vec![EPSILON];
+  for symbol in symbols {
+    if !seen.contains(&symbol) {
+      seen.push(symbol);
+    }
+  }
+  seen.iter()
+    .enumerate()
+    .map(|(i, symbol)| format!("{}\t{}\n", symbol, i))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("read", "R IY1 D"),
+    ("read(1)", "R EH1 D"),
+    ("it", "IH1 T"),
+    ("the", "DH AH0"),
+    ("the(1)", "DH IY1"),
+    ("book", "B UH1 K"),
+  ];
+
+  #[test]
+  fn lattice() {
+    let lattice = testing::arpabet(DICTIONARY).transcription_lattice("Read it.").unwrap();
+    assert_eq!(lattice.words, vec!["read", "it"]);
+    assert_eq!(lattice.word_states, vec![0, 5]);
+    assert_eq!(lattice.state_count(), 8);
+    assert_eq!(lattice.final_state(), 7);
+    assert_eq!(lattice.path_count(), 2);
+    assert_eq!(lattice.edges_from(0).count(), 2);
+    assert_eq!(lattice.to_openfst(), "\
+0\t1\tR\tread
+1\t2\tIY1\t<eps>
+2\t5\tD\t<eps>
+0\t3\tR\tread
+3\t4\tEH1\t<eps>
+4\t5\tD\t<eps>
+5\t6\tIH1\tit
+6\t7\tT\t<eps>
+7
+");
+    assert_eq!(lattice.to_string(), lattice.to_openfst());
+  }
+
+  #[test]
+  fn paths() {
+    let arpa = testing::arpabet(DICTIONARY);
+    assert_eq!(arpa.transcription_lattice("Read the book").unwrap().path_count(), 4);
+    assert_eq!(arpa.transcription_lattice("").unwrap().to_openfst(), "0\n");
+
+    // Empty pronunciations are skipped, alone or beside others.
+    let mut arpa = arpa;
+    arpa.insert("uh".to_string(), Vec::new());
+    arpa.insert("the(2)".to_string(), Vec::new());
+    assert_eq!(arpa.transcription_lattice("uh").unwrap().to_openfst(), "0\n");
+    let lattice = arpa.transcription_lattice("uh the book").unwrap();
+    assert_eq!(lattice.words, vec!["the", "book"]);
+    assert_eq!(lattice.to_openfst(), "\
+0\t1\tDH\tthe
+1\t3\tAH0\t<eps>
+0\t2\tDH\tthe
+2\t3\tIY1\t<eps>
+3\t4\tB\tbook
+4\t5\tUH1\t<eps>
+5\t6\tK\t<eps>
+6
+");
+
+    match arpa.transcription_lattice("read the zzz") {
+      Err(ArpabetError::UnknownWord { word }) => assert_eq!(word, "zzz"),
+      other => panic!("unexpected {:?}", other),
+    }
+  }
+
+  #[test]
+  fn symbols() {
+    let lattice = testing::arpabet(DICTIONARY).transcription_lattice("the book").unwrap();
+    assert_eq!(lattice.word_symbols(), "<eps>\t0\nthe\t1\nbook\t2\n");
+    assert!(lattice.phoneme_symbols().starts_with("<eps>\t0\nDH\t1\nAH0\t2\nIY1\t3\n"));
+  }
+}
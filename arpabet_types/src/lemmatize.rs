@@ -0,0 +1,287 @@
+//! This module contains the lemmatization hook consulted when a word is missing
+//! from the dictionary, eg. 'jumped' is found through 'jump'.
+//!
+//! Transcription consults `TranscribeOptions::lemmatizer` after the dictionary
+//! and pronounces the word as its lemma plus the suffix, eg. 'jumped' as
+//! JH AH1 M P T. See `inflect`.
+
+use crate::casing::fold_case;
+use crate::constants::PHONEME_MAP;
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone};
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+/// Reduces an inflected word to the base forms it may have come from.
+///
+/// Implement this to plug in a better lemmatizer than the built in
+/// [`EnglishStemmer`]. Closures of the form `Fn(&str) -> Vec<String>`
+/// implement it as well. Lemmatizers are shared between threads through
+/// `TranscribeOptions`, so must be `Send` and `Sync`.
+pub trait Lemmatizer: Send + Sync {
+  /// Candidate base forms of a lowercase word, most plausible first.
+  /// The word itself should not be included.
+  fn lemmas(&self, word: &str) -> Vec<String>;
+}
+
+impl <F: Fn(&str) -> Vec<String> + Send + Sync> Lemmatizer for F {
+  fn lemmas(&self, word: &str) -> Vec<String> {
+    self(word)
+  }
+}
+
+impl fmt::Debug for dyn Lemmatizer {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Lemmatizer")
+  }
+}
+
+/// Lemmatizers are equal when they are the same instance, eg. the shared
+/// default of `TranscribeOptions`.
+impl PartialEq for dyn Lemmatizer {
+  fn eq(&self, other: &Self) -> bool {
+    std::ptr::eq(self as *const Self as *const (), other as *const Self as *const ())
+  }
+}
+
+// The shared `EnglishStemmer`, the default `TranscribeOptions::lemmatizer`.
+pub(crate) fn english_stemmer() -> Arc<dyn Lemmatizer> {
+  static STEMMER : OnceLock<Arc<dyn Lemmatizer>> = OnceLock::new();
+  STEMMER.get_or_init(|| Arc::new(EnglishStemmer)).clone()
+}
+
+/// A rule-based English stemmer that undoes common inflectional suffixes:
+/// possessives, plurals, past tenses, participles, comparatives and '-ly'.
+/// Candidates may not be real words; the dictionary decides which ones are.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnglishStemmer;
+
+// Suffixes and what replaces them, tried in order.
+const SUFFIX_RULES : [(&str, &str); 15] = [
+  ("'s", ""),
+  ("s'", "s"),
+  ("ies", "y"),
+  ("ied", "y"),
+  ("ier", "y"),
+  ("iest", "y"),
+  ("ily", "y"),
+  ("es", ""),
+  ("s", ""),
+  ("ed", ""),
+  ("ed", "e"),
+  ("ing", ""),
+  ("ing", "e"),
+  ("est", ""),
+  ("er", ""),
+];
+
+// Shortest stem a rule may leave behind.
+const MIN_STEM_LENGTH : usize = 2;
+
+impl Lemmatizer for EnglishStemmer {
+  fn lemmas(&self, word: &str) -> Vec<String> {
+    let mut lemmas = Vec::new();
+
+    let mut push = |lemma: String| {
+      if lemma.chars().count() >= MIN_STEM_LENGTH && lemma != word && !lemmas.contains(&lemma) {
+        lemmas.push(lemma);
+      }
+    };
+
+    for (suffix, replacement) in SUFFIX_RULES.iter() {
+      if let Some(stem) = word.strip_suffix(suffix) {
+        push(format!("{}{}", stem, replacement));
+
+        // 'running' -> 'run', 'stopped' -> 'stop'
+        if replacement.is_empty() && has_doubled_final_consonant(stem) {
+          push(stem[.. stem.len() - 1].to_string());
+        }
+      }
+    }
+
+    if let Some(stem) = word.strip_suffix("ly") {
+      push(stem.to_string());
+    }
+
+    lemmas
+  }
+}
+
+/// Pronounce an inflected word as its lemma's pronunciation followed by the
+/// suffix, eg. 'jumped' from 'jump' (JH AH1 M P) as JH AH1 M P T. Suffixes
+/// agree with the lemma's last sound, eg. 'cats' ends in S, 'dogs' in Z and
+/// 'horses' in IH0 Z. Returns None when the word isn't spelled as the lemma
+/// plus one of the suffixes `EnglishStemmer` undoes, eg. 'ran' from 'run'.
+pub fn inflect(word: &str, lemma: &str, lemma_polyphone: &[Phoneme]) -> Option<Polyphone> {
+  let suffix = SUFFIX_RULES.iter()
+    .chain([("ly", "")].iter())
+    .find(|(suffix, replacement)| match word.strip_suffix(suffix) {
+      Some(stem) => format!("{}{}", stem, replacement) == lemma
+        || (replacement.is_empty() && has_doubled_final_consonant(stem)
+          && stem[.. stem.len() - 1] == *lemma),
+      None => false,
+    })
+    .map(|(suffix, _)| *suffix)?;
+
+  let last = lemma_polyphone.last()?.stressless();
+  let sounds = match (suffix, last.to_str()) {
+    ("s'", _) => "",
+    ("'s" | "s" | "es" | "ies", "S" | "Z" | "SH" | "ZH" | "CH" | "JH") => "IH0 Z",
+    ("'s" | "s" | "es" | "ies", "P" | "T" | "K" | "F" | "TH") => "S",
+    ("'s" | "s" | "es" | "ies", _) => "Z",
+    ("ed" | "ied", "T" | "D") => "IH0 D",
+    ("ed" | "ied", "P" | "K" | "F" | "TH" | "S" | "SH" | "CH") => "T",
+    ("ed" | "ied", _) => "D",
+    ("ing", _) => "IH0 NG",
+    ("er" | "ier", _) => "ER0",
+    ("est" | "iest", _) => "AH0 S T",
+    ("ly" | "ily", _) => "L IY0",
+    _ => return None,
+  };
+
+  let mut polyphone = lemma_polyphone.to_vec();
+  polyphone.extend(sounds.split_whitespace().filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied()));
+  Some(polyphone)
+}
+
+fn has_doubled_final_consonant(stem: &str) -> bool {
+  let mut chars = stem.chars().rev();
+  match (chars.next(), chars.next()) {
+    (Some(a), Some(b)) => a == b && a.is_ascii_alphabetic() && !"aeiouls".contains(a),
+    _ => false,
+  }
+}
+
+/// A dictionary entry found by way of a lemma.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LemmaMatch<'a> {
+  /// The dictionary word the pronunciation belongs to. This is the word
+  /// itself when it was found without lemmatization.
+  pub lemma: &'a str,
+  /// Pronunciation of the lemma, not of the word, eg. JH AH1 M P for
+  /// 'jumped'. See `inflect` to pronounce the word.
+  pub polyphone: &'a [Phoneme],
+}
+
+impl Arpabet {
+  /// Look up a word, falling back to its lemmas from the [`EnglishStemmer`]
+  /// when the word itself is missing. The pronunciation found is the lemma's,
+  /// without the suffix.
+  pub fn get_polyphone_or_lemma(&self, word: &str) -> Option<LemmaMatch<'_>> {
+    self.get_polyphone_or_lemma_with(word, &EnglishStemmer)
+  }
+
+  /// Look up a word, falling back to the lemmas from the supplied lemmatizer
+  /// when the word itself is missing. The first lemma in the dictionary wins.
+  pub fn get_polyphone_or_lemma_with(&self, word: &str, lemmatizer: &dyn Lemmatizer)
+    -> Option<LemmaMatch<'_>>
+  {
+    let word = fold_case(word);
+
+    if let Some((lemma, polyphone)) = self.dictionary.get_key_value(&word) {
+      return Some(LemmaMatch { lemma, polyphone });
+    }
+
+    lemmatizer.lemmas(&word)
+      .iter()
+      .filter_map(|lemma| self.dictionary.get_key_value(lemma))
+      .map(|(lemma, polyphone)| LemmaMatch { lemma, polyphone })
+      .next()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+  use crate::trace::{LookupStep, PronunciationSource};
+  use crate::transcribe::TranscribeOptions;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("jump", "JH AH1 M P"),
+    ("run", "R AH1 N"),
+    ("bake", "B EY1 K"),
+  ];
+
+  #[test]
+  fn stemmer_candidates() {
+    let stemmer = EnglishStemmer;
+    assert!(stemmer.lemmas("jumped").contains(&"jump".to_string()));
+    assert!(stemmer.lemmas("running").contains(&"run".to_string()));
+    assert!(stemmer.lemmas("baking").contains(&"bake".to_string()));
+    assert!(stemmer.lemmas("ponies").contains(&"pony".to_string()));
+    assert!(stemmer.lemmas("happily").contains(&"happy".to_string()));
+    assert!(stemmer.lemmas("dog's").contains(&"dog".to_string()));
+    assert!(stemmer.lemmas("jump").is_empty());
+  }
+
+  #[test]
+  fn lemma_fallback() {
+    let arpa = testing::arpabet(DICTIONARY);
+
+    // The lemma's pronunciation is returned, without the suffix.
+    let found = arpa.get_polyphone_or_lemma("Jumps").unwrap();
+    assert_eq!(found.lemma, "jump");
+    assert_eq!(found.polyphone, &polyphone("JH AH1 M P")[..]);
+
+    assert_eq!(arpa.get_polyphone_or_lemma("running").unwrap().lemma, "run");
+    assert_eq!(arpa.get_polyphone_or_lemma("baked").unwrap().lemma, "bake");
+    assert_eq!(arpa.get_polyphone_or_lemma("run").unwrap().lemma, "run");
+    assert_eq!(arpa.get_polyphone_or_lemma("xyzzy"), None);
+  }
+
+  #[test]
+  fn inflections() {
+    let jump = polyphone("JH AH1 M P");
+    assert_eq!(inflect("jumped", "jump", &jump), Some(polyphone("JH AH1 M P T")));
+    assert_eq!(inflect("jumps", "jump", &jump), Some(polyphone("JH AH1 M P S")));
+    assert_eq!(inflect("jumping", "jump", &jump), Some(polyphone("JH AH1 M P IH0 NG")));
+    assert_eq!(inflect("running", "run", &polyphone("R AH1 N")), Some(polyphone("R AH1 N IH0 NG")));
+    assert_eq!(inflect("runs", "run", &polyphone("R AH1 N")), Some(polyphone("R AH1 N Z")));
+    assert_eq!(inflect("baked", "bake", &polyphone("B EY1 K")), Some(polyphone("B EY1 K T")));
+    assert_eq!(inflect("hummed", "hum", &polyphone("HH AH1 M")), Some(polyphone("HH AH1 M D")));
+    assert_eq!(inflect("wanted", "want", &polyphone("W AA1 N T")),
+      Some(polyphone("W AA1 N T IH0 D")));
+    assert_eq!(inflect("horses", "horse", &polyphone("HH AO1 R S")),
+      Some(polyphone("HH AO1 R S IH0 Z")));
+    assert_eq!(inflect("ponies", "pony", &polyphone("P OW1 N IY0")),
+      Some(polyphone("P OW1 N IY0 Z")));
+    assert_eq!(inflect("ran", "run", &polyphone("R AH1 N")), None);
+  }
+
+  #[test]
+  fn transcription_fallback() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let words = |options: &TranscribeOptions| arpa.transcribe_words("jumped", options)
+      .map(|words| words[0].polyphone.clone());
+
+    assert_eq!(words(&TranscribeOptions::default()).unwrap(), polyphone("JH AH1 M P T"));
+    let trace = arpa.explain_lookup("jumped");
+    assert_eq!(trace.source, Some(PronunciationSource::Lemma));
+    assert_eq!(trace.steps.last(), Some(&LookupStep::consulted(PronunciationSource::Lemma, "jump", true)));
+
+    let off = TranscribeOptions { lemmatizer: None, ..Default::default() };
+    assert!(words(&off).is_err());
+
+    let irregular : Arc<dyn Lemmatizer> = Arc::new(|word: &str| match word {
+      "jumped" => vec!["jump".to_string()],
+      _ => Vec::new(),
+    });
+    let custom = TranscribeOptions { lemmatizer: Some(irregular), ..Default::default() };
+    assert_eq!(words(&custom).unwrap(), polyphone("JH AH1 M P T"));
+    assert_ne!(custom, TranscribeOptions::default());
+  }
+
+  #[test]
+  fn custom_lemmatizer() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let irregular = |word: &str| match word {
+      "ran" => vec!["run".to_string()],
+      _ => Vec::new(),
+    };
+
+    assert_eq!(arpa.get_polyphone_or_lemma("ran"), None);
+    assert_eq!(arpa.get_polyphone_or_lemma_with("ran", &irregular).unwrap().lemma, "run");
+    assert_eq!(arpa.get_polyphone_or_lemma_with("jumped", &irregular), None);
+  }
+}
@@ -0,0 +1,225 @@
+//! This module guesses pronunciations from spelling with a small set of
+//! English letter-to-sound rules, for words missing from the dictionary, eg.
+//! made-up command names such as "zorblax".
+//!
+//! The rules read letter groups longest first ("tch", "igh", "sh", "ee", ...),
+//! then single letters. A vowel followed by one consonant and a final 'e' is
+//! long and the 'e' silent, eg. "zane" -> Z EY N. 'c' and 'g' are soft before
+//! 'e', 'i' and 'y'. Doubled consonants are read once. Stress is placed by
+//! `assign_stress`. The guesses are rough, but spellings that sound alike are
+//! usually guessed alike.
+
+use crate::constants::PHONEME_MAP;
+use crate::phoneme::Phoneme;
+use crate::stress::assign_stress;
+use crate::Polyphone;
+
+// Letter groups and their sounds, longest first. Vowels are left without
+// stress for `assign_stress`.
+const GRAPHEMES : [(&str, &str); 40] = [
+  ("augh", "AO"),
+  ("eigh", "EY"),
+  ("dge", "JH"),
+  ("igh", "AY"),
+  ("tch", "CH"),
+  ("ai", "EY"),
+  ("ar", "AA R"),
+  ("au", "AO"),
+  ("aw", "AO"),
+  ("ay", "EY"),
+  ("ch", "CH"),
+  ("ck", "K"),
+  ("ea", "IY"),
+  ("ee", "IY"),
+  ("ei", "EY"),
+  ("er", "ER"),
+  ("ew", "UW"),
+  ("ey", "EY"),
+  ("ie", "IY"),
+  ("ir", "ER"),
+  ("ng", "NG"),
+  ("oa", "OW"),
+  ("oi", "OY"),
+  ("oo", "UW"),
+  ("or", "AO R"),
+  ("ou", "AW"),
+  ("ow", "OW"),
+  ("oy", "OY"),
+  ("ph", "F"),
+  ("qu", "K W"),
+  ("sh", "SH"),
+  ("th", "TH"),
+  ("ue", "UW"),
+  ("ur", "ER"),
+  ("wh", "W"),
+  ("a", "AE"),
+  ("e", "EH"),
+  ("i", "IH"),
+  ("o", "AA"),
+  ("u", "AH"),
+];
+
+// Sounds of the remaining single letters. 'c', 'g', 'y' and a final 'e'
+// depend on the letters around them.
+const LETTERS : [(char, &str); 18] = [
+  ('b', "B"),
+  ('d', "D"),
+  ('f', "F"),
+  ('h', "HH"),
+  ('j', "JH"),
+  ('k', "K"),
+  ('l', "L"),
+  ('m', "M"),
+  ('n', "N"),
+  ('p', "P"),
+  ('q', "K"),
+  ('r', "R"),
+  ('s', "S"),
+  ('t', "T"),
+  ('v', "V"),
+  ('w', "W"),
+  ('x', "K S"),
+  ('z', "Z"),
+];
+
+// Long vowels, read when a vowel is followed by one consonant and a final 'e'.
+const LONG_VOWELS : [(char, &str); 5] = [
+  ('a', "EY"),
+  ('e', "IY"),
+  ('i', "AY"),
+  ('o', "OW"),
+  ('u', "UW"),
+];
+
+/// Guess the pronunciation of a word from its spelling. See the module
+/// documentation for the rules. Returns None for words with characters other
+/// than the letters a-z and apostrophes, or without vowels.
+pub fn letter_to_sound(word: &str) -> Option<Polyphone> {
+  let letters = word.chars()
+    .filter(|c| *c != '\'')
+    .map(|c| c.to_ascii_lowercase())
+    .collect::<Vec<char>>();
+  if letters.iter().any(|c| !c.is_ascii_lowercase()) {
+    return None;
+  }
+  let text = letters.iter().collect::<String>();
+
+  let mut polyphone = Vec::new();
+  let mut i = 0;
+  while i < letters.len() {
+    let c = letters[i];
+    let next = letters.get(i + 1).copied();
+
+    // Doubled consonant letters are read once, eg. "zapp".
+    if i > 0 && letters[i - 1] == c && !is_vowel_letter(c) {
+      i += 1;
+      continue;
+    }
+
+    let has_vowel = polyphone.iter().any(|phoneme| matches!(phoneme, Phoneme::Vowel(_)));
+    let magic_e = is_vowel_letter(c) && is_magic_e(&letters, i);
+    let (sounds, length) = if magic_e {
+      (lookup(&LONG_VOWELS, c), 1)
+    } else if c == 'e' && i + 1 == letters.len() && has_vowel {
+      ("", 1)
+    } else if c == 'e' && i + 1 == letters.len() {
+      ("IY", 1)
+    } else if let Some((group, sounds)) = GRAPHEMES.iter()
+      .find(|(group, _)| text[i ..].starts_with(group))
+    {
+      (*sounds, group.len())
+    } else if c == 'c' || c == 'g' {
+      let soft = matches!(next, Some('e') | Some('i') | Some('y'));
+      (match (c, soft) {
+        ('c', true) => "S",
+        ('c', false) => "K",
+        (_, true) => "JH",
+        _ => "G",
+      }, 1)
+    } else if c == 'y' {
+      (match (i, next) {
+        (0, _) => "Y",
+        (_, None) if has_vowel => "IY",
+        (_, None) => "AY",
+        _ => "IH",
+      }, 1)
+    } else {
+      (lookup(&LETTERS, c), 1)
+    };
+
+    polyphone.extend(sounds.split(' ').filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied()));
+    // The consonant before a silent final 'e', which is skipped, eg. "zane".
+    if magic_e {
+      polyphone.extend(consonant_sounds(letters[i + 1]));
+      i += 3;
+    } else {
+      i += length;
+    }
+  }
+
+  if !polyphone.iter().any(|phoneme| matches!(phoneme, Phoneme::Vowel(_))) {
+    return None;
+  }
+  assign_stress(&mut polyphone);
+  Some(polyphone)
+}
+
+fn is_vowel_letter(c: char) -> bool {
+  "aeiou".contains(c)
+}
+
+// Whether the vowel letter at `i` is followed by one consonant and a final
+// 'e', eg. the 'a' of "zane".
+fn is_magic_e(letters: &[char], i: usize) -> bool {
+  i + 3 == letters.len()
+    && letters[i + 2] == 'e'
+    && !is_vowel_letter(letters[i + 1])
+    && letters[i + 1] != 'y'
+    && (i == 0 || !is_vowel_letter(letters[i - 1]))
+}
+
+fn consonant_sounds(c: char) -> Vec<Phoneme> {
+  let sounds = match c {
+    'c' => "S",
+    'g' => "JH",
+    _ => lookup(&LETTERS, c),
+  };
+  sounds.split(' ').filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied()).collect()
+}
+
+fn lookup(table: &[(char, &'static str)], c: char) -> &'static str {
+  table.iter()
+    .find(|(letter, _)| *letter == c)
+    .map(|(_, sounds)| *sounds)
+    .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn rules() {
+    assert_eq!(letter_to_sound("zorblax"), Some(polyphone("Z AO1 R B L AE0 K S")));
+    assert_eq!(letter_to_sound("Zane"), Some(polyphone("Z EY1 N")));
+    assert_eq!(letter_to_sound("fleep"), Some(polyphone("F L IY1 P")));
+    assert_eq!(letter_to_sound("cyber"), Some(polyphone("S IH1 B ER0")));
+    assert_eq!(letter_to_sound("zippy"), Some(polyphone("Z IH1 P IY0")));
+    assert_eq!(letter_to_sound("shtick"), Some(polyphone("SH T IH1 K")));
+  }
+
+  #[test]
+  fn alike_spellings_sound_alike() {
+    assert_eq!(letter_to_sound("zorblax"), letter_to_sound("zorblacks"));
+    assert_eq!(letter_to_sound("fleep"), letter_to_sound("fleap"));
+  }
+
+  #[test]
+  fn unreadable() {
+    assert_eq!(letter_to_sound("hmm"), None);
+    assert_eq!(letter_to_sound("r2d2"), None);
+    assert_eq!(letter_to_sound("café"), None);
+    assert_eq!(letter_to_sound(""), None);
+  }
+}
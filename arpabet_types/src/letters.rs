@@ -0,0 +1,112 @@
+//! This module contains the spoken names of the letters of the English alphabet
+//! and of the digits.
+
+use crate::constants::PHONEME_MAP;
+use crate::Polyphone;
+
+/// Pronunciations of the letter names, eg. 'b' -> "B IY1".
+pub const LETTER_NAMES : [(char, &str); 26] = [
+  ('a', "EY1"),
+  ('b', "B IY1"),
+  ('c', "S IY1"),
+  ('d', "D IY1"),
+  ('e', "IY1"),
+  ('f', "EH1 F"),
+  ('g', "JH IY1"),
+  ('h', "EY1 CH"),
+  ('i', "AY1"),
+  ('j', "JH EY1"),
+  ('k', "K EY1"),
+  ('l', "EH1 L"),
+  ('m', "EH1 M"),
+  ('n', "EH1 N"),
+  ('o', "OW1"),
+  ('p', "P IY1"),
+  ('q', "K Y UW1"),
+  ('r', "AA1 R"),
+  ('s', "EH1 S"),
+  ('t', "T IY1"),
+  ('u', "Y UW1"),
+  ('v', "V IY1"),
+  ('w', "D AH1 B AH0 L Y UW0"),
+  ('x', "EH1 K S"),
+  ('y', "W AY1"),
+  ('z', "Z IY1"),
+];
+
+/// Get the pronunciation of a letter's name, in either case.
+/// Returns None for anything other than the letters a-z.
+pub fn letter_name(letter: char) -> Option<Polyphone> {
+  let letter = letter.to_ascii_lowercase();
+  LETTER_NAMES.iter()
+    .find(|(c, _)| *c == letter)
+    .map(|(_, pronunciation)| {
+      pronunciation.split(' ')
+        .filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied())
+        .collect()
+    })
+}
+
+/// Pronunciations of the digit names, eg. '3' -> "TH R IY1".
+pub const DIGIT_NAMES : [(char, &str); 10] = [
+  ('0', "Z IH1 R OW0"),
+  ('1', "W AH1 N"),
+  ('2', "T UW1"),
+  ('3', "TH R IY1"),
+  ('4', "F AO1 R"),
+  ('5', "F AY1 V"),
+  ('6', "S IH1 K S"),
+  ('7', "S EH1 V AH0 N"),
+  ('8', "EY1 T"),
+  ('9', "N AY1 N"),
+];
+
+/// Get the pronunciation of a digit's name, eg. '3' -> TH R IY1.
+/// Returns None for anything other than the digits 0-9.
+pub fn digit_name(digit: char) -> Option<Polyphone> {
+  DIGIT_NAMES.iter()
+    .find(|(c, _)| *c == digit)
+    .map(|(_, pronunciation)| {
+      pronunciation.split(' ')
+        .filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied())
+        .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+
+  #[test]
+  fn letter_names() {
+    assert_eq!(letter_name('B'), Some(vec![
+      Phoneme::Consonant(Consonant::B),
+      Phoneme::Vowel(Vowel::IY(VowelStress::PrimaryStress)),
+    ]));
+    assert_eq!(letter_name('w').map(|p| p.len()), Some(7));
+    assert_eq!(letter_name('1'), None);
+    assert_eq!(letter_name('é'), None);
+
+    for (letter, pronunciation) in LETTER_NAMES.iter() {
+      let polyphone = letter_name(*letter).unwrap();
+      assert_eq!(polyphone.len(), pronunciation.split(' ').count());
+    }
+  }
+
+  #[test]
+  fn digit_names() {
+    assert_eq!(digit_name('3'), Some(vec![
+      Phoneme::Consonant(Consonant::TH),
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::IY(VowelStress::PrimaryStress)),
+    ]));
+    assert_eq!(digit_name('a'), None);
+    assert_eq!(digit_name('٣'), None);
+
+    for (digit, pronunciation) in DIGIT_NAMES.iter() {
+      let polyphone = digit_name(*digit).unwrap();
+      assert_eq!(polyphone.len(), pronunciation.split(' ').count());
+    }
+  }
+}
@@ -16,17 +16,159 @@
 
 #[cfg(test)] #[macro_use] extern crate expectest;
 
+pub mod alignment;
+pub mod articulation;
+pub mod binary;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod casing;
+pub mod concordance;
+pub mod concurrent;
+pub mod confusability;
+pub mod consistency;
 pub mod constants;
+pub mod coverage;
+pub mod decode;
+pub mod dialect;
+pub mod diff;
+pub mod duplicates;
 pub mod error;
+pub mod export;
 pub mod extensions;
+pub mod family;
+pub mod festival;
+pub mod frequency;
+pub mod fuzzy;
+pub mod heteronym;
+pub mod histogram;
+pub mod impact;
+pub mod index;
+pub mod initialism;
+pub mod ipa;
+pub mod iteration;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod kaldi;
+pub mod lattice;
+pub mod lemmatize;
+pub mod letter_to_sound;
+pub mod letters;
+pub mod lookup;
+pub mod memory;
+pub mod metadata;
+pub mod meter;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock;
+pub mod normalization;
+pub mod notation;
+pub mod numbers;
+pub mod ocr;
+pub mod pattern;
 pub mod phoneme;
-
+pub mod phrasing;
+pub mod pipeline;
+pub mod poetry;
+pub mod prominence;
+pub mod query;
+pub mod readability;
+#[cfg(feature = "json")]
+pub mod report_json;
+pub mod respelling;
+pub mod reversal;
+pub mod sampling;
+pub mod ssml;
+pub mod stats;
+pub mod stress;
+mod store;
+pub mod substitution;
+pub mod syllable;
+pub mod syllable_encoding;
+mod sync;
+#[cfg(test)]
+mod testing;
+pub mod texting;
+pub mod tokenizer;
+pub mod trace;
+pub mod transcribe;
+pub mod validation;
+pub mod variant_id;
+pub mod watermark;
+pub mod weak_forms;
+
+pub use alignment::*;
+pub use articulation::*;
+pub use binary::*;
+#[cfg(feature = "cache")]
+pub use cache::*;
+pub use casing::*;
+pub use concurrent::*;
+pub use confusability::*;
+pub use consistency::*;
 pub use constants::*;
+pub use coverage::*;
+pub use decode::*;
+pub use dialect::*;
+pub use diff::*;
 pub use error::*;
+pub use export::*;
 pub use extensions::*;
+pub use family::*;
+pub use festival::*;
+pub use frequency::*;
+pub use fuzzy::*;
+pub use heteronym::*;
+pub use histogram::*;
+pub use impact::*;
+pub use index::*;
+pub use initialism::*;
+pub use ipa::*;
+pub use iteration::*;
+pub use kaldi::*;
+pub use lattice::*;
+pub use lemmatize::*;
+pub use letter_to_sound::*;
+pub use letters::*;
+pub use lookup::*;
+pub use memory::*;
+pub use metadata::*;
+pub use meter::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use mock::*;
+pub use normalization::*;
+pub use notation::*;
+pub use numbers::*;
+pub use ocr::*;
+pub use pattern::*;
 pub use phoneme::*;
+pub use phrasing::*;
+pub use pipeline::*;
+pub use poetry::*;
+pub use prominence::*;
+pub use query::*;
+pub use readability::*;
+pub use respelling::*;
+pub use reversal::*;
+pub use sampling::*;
+pub use ssml::*;
+pub use stats::*;
+pub use stress::*;
+pub use substitution::*;
+pub use syllable::*;
+pub use syllable_encoding::*;
+pub use texting::*;
+pub use tokenizer::*;
+pub use trace::*;
+pub use transcribe::*;
+pub use validation::*;
+pub use variant_id::*;
+pub use watermark::*;
+pub use weak_forms::*;
+use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::collections::hash_map::Keys;
+use store::Store;
+
+/// The version of this crate, eg. to check it matches the other arpabet crates.
+pub const VERSION : &str = env!("CARGO_PKG_VERSION");
 
 /// A word is a simple string containing no space characters.
 pub type Word = String;
@@ -39,52 +181,52 @@ pub type Polyphone = Vec<Phoneme>;
 pub struct Arpabet {
   /// A map of lowercase words to polyphone breakdown.
   /// eg. 'jungle' -> [JH, AH1, NG, G, AH0, L]
-  dictionary: HashMap<Word, Polyphone>,
+  dictionary: Store,
+  /// Descriptive metadata carried through merges and exports.
+  metadata: LexiconMetadata,
+  /// Optional unigram frequencies used to rank query results.
+  frequencies: Option<WordFrequencies>,
+  /// Query indices, built lazily.
+  indexes: Indexes,
 }
 
 impl Arpabet {
   /// Create an empty Arpabet.
   pub fn new() -> Arpabet {
-    Self {
-      dictionary: HashMap::new(),
-    }
+    Self::from_map(HashMap::new())
   }
 
   /// Create an Arpabet from a map.
   /// Consumes the map.
   pub fn from_map(map: HashMap<Word, Polyphone>) -> Self {
-    Self {
-      dictionary: map
-    }
+    Self::from_store(Store::Owned(map))
   }
 
-  /// Create an Arpabet from a phf::Map.
-  /// Used internally for allocation from codegen.
-  /// Unfortunately this needs to allocate a new HashMap and copy data over.
-  pub fn from_phf_map(map: &phf::Map<&str, &[Phoneme]>) -> Self {
-    // TODO: An internal store over an enum of HashMap / phf::Map would be better.
-    let mut hashmap = HashMap::with_capacity(map.len());
-
-    for (k, v) in map.into_iter() {
-      hashmap.insert(k.to_string(), v.to_vec());
-    }
+  /// Create an Arpabet from a compile-time phf::Map, as generated by codegen.
+  /// The map is used in place without copying; it is only copied to the heap
+  /// when the Arpabet is first modified. Since 3.0 the map must be `'static`;
+  /// copy other maps with `from_map`.
+  pub fn from_phf_map(map: &'static phf::Map<&'static str, &'static [Phoneme]>) -> Self {
+    Self::from_store(Store::Static(map))
+  }
 
+  fn from_store(dictionary: Store) -> Self {
     Self {
-      dictionary: hashmap,
+      dictionary,
+      metadata: LexiconMetadata::default(),
+      frequencies: None,
+      indexes: Indexes::default(),
     }
   }
 
   /// Get a polyphone from the dictionary.
   pub fn get_polyphone(&self, word: &str) -> Option<Polyphone> {
-    self.dictionary.get(word).and_then(|p| {
-      Some(p.iter()
-        .map(|p| p.clone())
-        .collect::<Vec<Phoneme>>())
-    })
+    self.dictionary.get(word).map(|p| p.to_vec())
   }
 
-  /// Get a polyphone from the dictionary.
-  pub fn get_polyphone_ref(&self, word: &str) -> Option<&Polyphone> {
+  /// Get a polyphone from the dictionary without copying it. Since 3.0 this
+  /// is a slice, as static dictionaries hold no `Polyphone` to borrow.
+  pub fn get_polyphone_ref(&self, word: &str) -> Option<&[Phoneme]> {
     self.dictionary.get(word)
   }
 
@@ -98,45 +240,256 @@ impl Arpabet {
       })
   }
 
+  /// Get every pronunciation of a word: the entry itself followed by its
+  /// numbered alternates, eg. 'read', 'read(1)', 'read(2)'. Gaps in the
+  /// numbering are skipped, so 'word(2)' is found without 'word(1)'.
+  pub fn get_polyphone_variants(&self, word: &str) -> Vec<&[Phoneme]> {
+    let alternates = self.indexes.alternates
+      .get_or_init(|| AlternateIndex::build(self.dictionary.iter().map(|(key, _)| key)));
+    self.dictionary.get(word).into_iter()
+      .chain(alternates.get(word).iter().filter_map(|key| self.dictionary.get(key)))
+      .collect()
+  }
+
   /// Combine two Arpabets and return the result.
   /// Items in the second Arpabet take precedence.
+  /// Metadata from the first Arpabet is kept, with unset fields taken from the second.
   pub fn combine(&self, other: &Arpabet) -> Arpabet {
     let mut merged = self.dictionary.clone();
+    let entries = merged.to_mut();
     for (k, v) in other.dictionary.iter() {
-      merged.insert(k.clone(), v.clone());
+      entries.insert(k.to_string(), v.to_vec());
+    }
+    let mut metadata = self.metadata.clone();
+    metadata.fill_from(&other.metadata);
+    Arpabet {
+      dictionary: merged,
+      metadata,
+      frequencies: self.frequencies.clone(),
+      indexes: Indexes::default(),
     }
-    Arpabet { dictionary: merged }
   }
 
   /// Merge the supplied Arpabet into the current one.
   /// Items in the supplied Arpabet override existing entries
-  /// should they already exist.
+  /// should they already exist. Unset metadata fields are taken from the supplied Arpabet.
   pub fn merge_from(&mut self, other: &Arpabet) {
+    let entries = self.dictionary.to_mut();
     for (k, v) in other.dictionary.iter() {
-      self.dictionary.insert(k.clone(), v.clone());
+      entries.insert(k.to_string(), v.to_vec());
     }
+    self.metadata.fill_from(&other.metadata);
+    self.drop_indexes();
   }
 
   /// Insert an entry into the Arpabet. If the entry is already present,
-  /// replace it and return the old value.
+  /// replace it and return the old value. Built indices are updated in place.
   pub fn insert(&mut self, key: Word, value: Vec<Phoneme>) -> Option<Vec<Phoneme>> {
-    self.dictionary.insert(key, value)
+    if self.indexes.is_empty() {
+      return self.dictionary.to_mut().insert(key, value);
+    }
+    let old = self.dictionary.to_mut().insert(key.clone(), value);
+    self.update_indexes(&key, old.as_deref());
+    old
   }
 
   /// Remove an entry from the arpabet. If it is present, it will be returned.
+  /// Built indices are updated in place.
   pub fn remove(&mut self, key: &str) -> Option<Vec<Phoneme>> {
-    self.dictionary.remove(key)
+    let old = self.dictionary.to_mut().remove(key);
+    if old.is_some() && !self.indexes.is_empty() {
+      self.update_indexes(key, old.as_deref());
+    }
+    old
+  }
+
+  // Bring the built indices up to date after the key's entry changed from
+  // `old` to its current value.
+  fn update_indexes(&mut self, key: &str, old: Option<&[Phoneme]>) {
+    let new = self.dictionary.get(key).map(|polyphone| polyphone.to_vec());
+    if let Some(alternates) = self.indexes.alternates.get_mut() {
+      match new {
+        Some(_) => alternates.insert_key(key),
+        None => alternates.remove_key(key),
+      }
+    }
+    if self.indexes.built().is_empty() {
+      return;
+    }
+    // Every key of the base word, gaps in the numbering included.
+    let remaining = self.get_polyphone_variants(strip_variant(key))
+      .into_iter()
+      .map(|polyphone| polyphone.to_vec())
+      .collect::<Vec<Polyphone>>();
+    self.indexes.update(key, old, new.as_deref(), &remaining);
   }
 
-  /// Return a keys iterator that walks the keys in random order.
-  pub fn keys(&self) -> Keys<String, Vec<Phoneme>> {
-    self.dictionary.keys()
+  /// Return a keys iterator that walks the keys in random order. Since 3.0
+  /// this is an opaque iterator of `&str`, over static or owned dictionaries.
+  pub fn keys(&self) -> impl Iterator<Item=&str> {
+    self.dictionary.iter().map(|(key, _)| key)
   }
 
   /// Reports the number of entries in the arpabet.
   pub fn len(&self) -> usize {
     self.dictionary.len()
   }
+
+  /// Reports whether the arpabet has no entries.
+  pub fn is_empty(&self) -> bool {
+    self.dictionary.len() == 0
+  }
+
+  /// Whether the word has a pronunciation. Does not allocate.
+  pub fn contains_key(&self, word: &str) -> bool {
+    self.dictionary.get(word).is_some()
+  }
+
+  /// Get the dictionary metadata.
+  pub fn metadata(&self) -> &LexiconMetadata {
+    &self.metadata
+  }
+
+  /// Replace the dictionary metadata.
+  pub fn set_metadata(&mut self, metadata: LexiconMetadata) {
+    self.metadata = metadata;
+  }
+
+  /// Attach unigram word frequencies, which weight decoding and ranked queries.
+  pub fn attach_frequencies(&mut self, frequencies: WordFrequencies) {
+    self.frequencies = Some(frequencies);
+  }
+
+  /// Get the attached word frequencies, if any.
+  pub fn frequencies(&self) -> Option<&WordFrequencies> {
+    self.frequencies.as_ref()
+  }
+
+  /// Get the pronunciation -> words index. It is built on first use and
+  /// kept up to date as the dictionary changes.
+  pub fn reverse_index(&self) -> &ReverseIndex {
+    self.indexes.reverse.get_or_init(|| ReverseIndex::build(self.dictionary.iter()))
+  }
+
+  /// Get the pronunciation trie. It is built on first use and kept up
+  /// to date as the dictionary changes.
+  pub fn phoneme_trie(&self) -> &PhonemeTrie {
+    self.indexes.phoneme_trie.get_or_init(|| PhonemeTrie::build(self.dictionary.iter()))
+  }
+
+  /// Get words grouped by the first phoneme of their pronunciation, each group
+  /// sorted. The grouping is computed on first use and kept up to date as the
+  /// dictionary changes.
+  pub fn group_by_initial_phoneme(&self) -> &HashMap<Phoneme, Vec<Word>> {
+    self.initial_phoneme_index().groups()
+  }
+
+  /// Get the initial phoneme index. It is built on first use and kept up
+  /// to date as the dictionary changes.
+  pub fn initial_phoneme_index(&self) -> &InitialPhonemeIndex {
+    self.indexes.initial_phonemes.get_or_init(|| InitialPhonemeIndex::build(self.dictionary.iter()))
+  }
+
+  /// Get the rhyme index. It is built on first use and kept up to date as the
+  /// dictionary changes.
+  pub fn rhyme_index(&self) -> &RhymeIndex {
+    self.indexes.rhymes.get_or_init(|| RhymeIndex::build(self.dictionary.iter()))
+  }
+
+  /// Get the stressless pronunciation index. It is built on first use and
+  /// kept up to date as the dictionary changes.
+  pub fn stressless_index(&self) -> &StresslessIndex {
+    self.indexes.stressless.get_or_init(|| StresslessIndex::build(self.dictionary.iter()))
+  }
+
+  /// Get the phoneme n-gram index. It is built on first use and kept up to
+  /// date as the dictionary changes.
+  pub fn ngram_index(&self) -> &NGramIndex {
+    self.indexes.ngrams.get_or_init(|| NGramIndex::build(self.dictionary.iter()))
+  }
+
+  /// Build the selected indices now rather than on first use, eg. at startup.
+  /// Indices that are already built are kept.
+  pub fn build_indexes(&self, indexes: IndexSet) {
+    if indexes.reverse {
+      self.reverse_index();
+    }
+    if indexes.phoneme_trie {
+      self.phoneme_trie();
+    }
+    if indexes.initial_phonemes {
+      self.initial_phoneme_index();
+    }
+    if indexes.rhymes {
+      self.rhyme_index();
+    }
+    if indexes.stressless {
+      self.stressless_index();
+    }
+    if indexes.ngrams {
+      self.ngram_index();
+    }
+  }
+
+  /// Drop every built index to free its memory. Each is rebuilt on next use.
+  pub fn drop_indexes(&mut self) {
+    self.indexes = Indexes::default();
+  }
+
+  /// Report which indices are currently built.
+  pub fn built_indexes(&self) -> IndexSet {
+    self.indexes.built()
+  }
+
+  /// Suggest words whose pronunciation starts with the supplied phonemes.
+  /// When frequencies are attached, common words are ranked first;
+  /// otherwise words are returned in alphabetical order.
+  pub fn words_with_phoneme_prefix(&self, prefix: &[Phoneme]) -> Vec<&Word> {
+    let mut words = self.phoneme_trie().words_with_prefix(prefix);
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find words whose pronunciation contains the supplied phonemes anywhere,
+  /// eg. 'AE1 K S' in 'taxi'. Uses the n-gram index. When frequencies are
+  /// attached, common words are ranked first; otherwise words are returned in
+  /// alphabetical order. Empty for no phonemes.
+  pub fn words_containing_phonemes(&self, phonemes: &[Phoneme]) -> Vec<&Word> {
+    let mut words = self.ngram_index().candidates(phonemes);
+    if phonemes.len() > NGRAM_LENGTH {
+      words.retain(|word| {
+        self.get_polyphone_variants(word).iter()
+          .any(|polyphone| polyphone.windows(phonemes.len()).any(|window| window == phonemes))
+      });
+    }
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  // Find the words with a pronunciation satisfying the predicate, ranked by
+  // attached frequency, then alphabetically.
+  pub(crate) fn words_where<F>(&self, predicate: F) -> Vec<&str>
+    where F: Fn(&[Phoneme]) -> bool
+  {
+    let mut words = self.dictionary.iter()
+      .filter(|(_, polyphone)| predicate(polyphone))
+      .map(|(key, _)| strip_variant(key))
+      .collect::<Vec<&str>>();
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  // Order words by attached frequency, most common first. Without frequencies
+  // the order is unchanged.
+  pub(crate) fn rank_by_frequency<S: AsRef<str>>(&self, words: &mut [S]) {
+    if let Some(frequencies) = self.frequencies() {
+      // NB: Stable sort preserves the existing order among equally common words.
+      words.sort_by_key(|word| Reverse(frequencies.get(word.as_ref())));
+    }
+  }
 }
 
 #[cfg(test)]
@@ -148,6 +501,7 @@ mod tests {
     Vowel,
     VowelStress,
   };
+  use testing::polyphone;
 
   #[test]
   fn insert() {
@@ -250,7 +604,7 @@ mod tests {
       Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
     ]);
 
-    let keys: Vec<String> = arpa.keys().cloned().collect();
+    let keys: Vec<String> = arpa.keys().map(|key| key.to_string()).collect();
     assert_eq!(keys.len(), 2);
 
     // NB: contains is meh, see: https://github.com/rust-lang/rust/issues/42671
@@ -258,6 +612,22 @@ mod tests {
     assert!(keys.iter().any(|x| x == "boo"));
   }
 
+  #[test]
+  fn contains_key_and_is_empty() {
+    let mut arpa = Arpabet::new();
+    assert!(arpa.is_empty());
+    assert!(!arpa.contains_key("foo"));
+
+    arpa.insert("foo".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+    assert!(!arpa.is_empty());
+    assert!(arpa.contains_key("foo"));
+    assert!(!arpa.contains_key("FOO"));
+    assert_eq!(arpa.values().count(), 1);
+  }
+
   #[test]
   fn get_polyphone() {
     let mut a = Arpabet::new();
@@ -290,13 +660,47 @@ mod tests {
       Phoneme::Consonant(Consonant::F),
       Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
     ]);
-    assert_eq!(a.get_polyphone_ref("foo"), Some(&vec![
+    assert_eq!(a.get_polyphone_ref("foo"), Some(&[
       Phoneme::Consonant(Consonant::F),
-      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress))],
-    ));
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ][..]));
     assert_eq!(a.get_polyphone_ref("bar"), None);
   }
 
+  #[test]
+  fn get_polyphone_variants() {
+    let mut a = Arpabet::new();
+    let red = vec![
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::EH(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ];
+    let reed = vec![
+      Phoneme::Consonant(Consonant::R),
+      Phoneme::Vowel(Vowel::IY(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ];
+    a.insert("read".to_string(), reed.clone());
+    a.insert("read(1)".to_string(), red.clone());
+    a.insert("red".to_string(), red.clone());
+
+    assert_eq!(a.get_polyphone_variants("read"), vec![&reed, &red]);
+    assert_eq!(a.get_polyphone_variants("red"), vec![&red]);
+    assert!(a.get_polyphone_variants("bar").is_empty());
+
+    // Gaps in the numbering, before and after the alternates are looked up.
+    let mut b = Arpabet::new();
+    b.insert("word".to_string(), reed.clone());
+    b.insert("word(2)".to_string(), red.clone());
+    b.insert("word(10)".to_string(), reed.clone());
+    assert_eq!(b.get_polyphone_variants("word"), vec![&reed, &red, &reed]);
+    b.remove("word(2)");
+    b.insert("word(3)".to_string(), red.clone());
+    assert_eq!(b.get_polyphone_variants("word"), vec![&reed, &red, &reed]);
+    b.remove("word");
+    assert_eq!(b.get_polyphone_variants("word"), vec![&red, &reed]);
+  }
+
   #[test]
   fn combine() {
     let a = {
@@ -391,4 +795,213 @@ mod tests {
     ]));
     assert_eq!(a.get_polyphone("bin"), None);
   }
+
+  #[test]
+  fn metadata_carried_through_merges() {
+    let mut a = Arpabet::new();
+    a.set_metadata(LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").unwrap()),
+      version: None,
+      license: None,
+    });
+
+    let mut b = Arpabet::new();
+    b.set_metadata(LexiconMetadata {
+      language: Some(LanguageTag::parse("en-GB").unwrap()),
+      version: Some("2".to_string()),
+      license: None,
+    });
+
+    let c = a.combine(&b);
+    assert_eq!(c.metadata().language.as_ref().map(|l| l.as_str()), Some("en-US"));
+    assert_eq!(c.metadata().version.as_deref(), Some("2"));
+
+    let mut d = Arpabet::new();
+    d.merge_from(&b);
+    assert_eq!(d.metadata(), b.metadata());
+  }
+
+  #[test]
+  fn group_by_initial_phoneme() {
+    let mut a = Arpabet::new();
+    a.insert("foo".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+    a.insert("food".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ]);
+
+    let groups = a.group_by_initial_phoneme();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups.get(&Phoneme::Consonant(Consonant::F)),
+      Some(&vec!["foo".to_string(), "food".to_string()]));
+
+    a.insert("boo".to_string(), vec![
+      Phoneme::Consonant(Consonant::B),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+
+    let groups = a.group_by_initial_phoneme();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups.get(&Phoneme::Consonant(Consonant::B)), Some(&vec!["boo".to_string()]));
+  }
+
+  #[test]
+  fn build_and_drop_indexes() {
+    let mut a = Arpabet::new();
+    a.insert("foo".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+    assert!(a.built_indexes().is_empty());
+
+    a.rhyme_index();
+    assert_eq!(a.built_indexes(), IndexSet { rhymes: true, ..IndexSet::none() });
+
+    a.build_indexes(IndexSet { reverse: true, phoneme_trie: true, ..IndexSet::none() });
+    assert_eq!(a.built_indexes(),
+      IndexSet { reverse: true, phoneme_trie: true, rhymes: true, ..IndexSet::none() });
+
+    a.build_indexes(IndexSet::all());
+    assert_eq!(a.built_indexes(), IndexSet::all());
+
+    a.drop_indexes();
+    assert!(a.built_indexes().is_empty());
+
+    // Single entry changes keep the indices; bulk changes drop them.
+    a.build_indexes(IndexSet::all());
+    a.remove("foo");
+    assert_eq!(a.built_indexes(), IndexSet::all());
+    a.merge_from(&Arpabet::new());
+    assert!(a.built_indexes().is_empty());
+  }
+
+  #[test]
+  fn incremental_index_updates() {
+    // Everything the indices report, for comparing against a fresh build.
+    let snapshot = |a: &Arpabet| {
+      let symbols = |p: &[Phoneme]| p.iter().map(|p| p.to_str()).collect::<Vec<&str>>();
+      let mut reverse = a.reverse_index().iter()
+        .map(|(p, w)| (symbols(p), w.clone()))
+        .collect::<Vec<_>>();
+      reverse.sort();
+      let mut rhymes = a.rhyme_index().iter()
+        .map(|(p, w)| (symbols(p), w.clone()))
+        .collect::<Vec<_>>();
+      rhymes.sort();
+      let mut stressless = a.stressless_index().iter()
+        .map(|(p, e)| (symbols(p), e.iter().map(|(w, p)| (w.clone(), symbols(p))).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+      stressless.sort();
+      let mut initial = a.group_by_initial_phoneme().iter()
+        .map(|(p, w)| (p.to_str(), w.clone()))
+        .collect::<Vec<_>>();
+      initial.sort();
+      let trie = a.phoneme_trie().words_with_prefix(&[]).into_iter().cloned().collect::<Vec<_>>();
+      let mut ngrams = a.ngram_index().candidates(&polyphone("R"))
+        .into_iter()
+        .chain(a.ngram_index().candidates(&polyphone("K AE1 T")))
+        .chain(a.ngram_index().candidates(&polyphone("N S")))
+        .cloned()
+        .collect::<Vec<_>>();
+      ngrams.sort();
+      (reverse, a.reverse_index().max_length(), rhymes, stressless, initial, trie, ngrams)
+    };
+
+    let mut a = Arpabet::new();
+    a.insert("read".to_string(), polyphone("R IY1 D"));
+    a.insert("read(1)".to_string(), polyphone("R EH1 D"));
+    a.insert("red".to_string(), polyphone("R EH1 D"));
+    a.insert("reed".to_string(), polyphone("R IY1 D"));
+    a.insert("cat".to_string(), polyphone("K AE1 T"));
+    a.build_indexes(IndexSet::all());
+
+    a.insert("read(1)".to_string(), polyphone("R IY1 D"));
+    a.remove("red");
+    a.insert("incite".to_string(), polyphone("IH0 N S AY1 T"));
+    a.insert("insight".to_string(), polyphone("IH1 N S AY2 T"));
+    a.insert("cat".to_string(), polyphone("K AE1 T S"));
+    a.remove("cat");
+    a.remove("missing");
+    assert_eq!(a.built_indexes(), IndexSet::all());
+
+    let rebuilt = a.iter().map(|(w, p)| (w.to_string(), p.to_vec())).collect::<Arpabet>();
+    assert_eq!(snapshot(&a), snapshot(&rebuilt));
+    assert_eq!(a.reverse_index().get(&polyphone("R IY1 D")),
+      Some(&vec!["read".to_string(), "reed".to_string()]));
+    assert_eq!(a.reverse_index().get(&polyphone("R EH1 D")), None);
+    assert_eq!(a.reverse_index().max_length(), 5);
+
+    // Removing middle variants leaves a gap before the last one.
+    let mut b = Arpabet::new();
+    b.insert("read".to_string(), polyphone("R IY1 D"));
+    b.insert("read(1)".to_string(), polyphone("R EH1 D"));
+    b.insert("read(2)".to_string(), polyphone("R EH1 D"));
+    b.insert("read(3)".to_string(), polyphone("R EH1 D"));
+    b.build_indexes(IndexSet::all());
+    b.remove("read(1)");
+    b.remove("read(2)");
+
+    let rebuilt = b.iter().map(|(w, p)| (w.to_string(), p.to_vec())).collect::<Arpabet>();
+    assert_eq!(snapshot(&b), snapshot(&rebuilt));
+    assert_eq!(b.reverse_index().get(&polyphone("R EH1 D")), Some(&vec!["read".to_string()]));
+  }
+
+  #[test]
+  fn words_with_phoneme_prefix() {
+    let mut a = Arpabet::new();
+    a.insert("foo".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+    a.insert("food".to_string(), vec![
+      Phoneme::Consonant(Consonant::F),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+      Phoneme::Consonant(Consonant::D),
+    ]);
+    a.insert("boo".to_string(), vec![
+      Phoneme::Consonant(Consonant::B),
+      Phoneme::Vowel(Vowel::UW(VowelStress::PrimaryStress)),
+    ]);
+
+    let prefix = [Phoneme::Consonant(Consonant::F)];
+    assert_eq!(a.words_with_phoneme_prefix(&prefix), vec!["foo", "food"]);
+
+    a.attach_frequencies(vec![("food", 10), ("foo", 1)].into_iter().collect());
+    assert_eq!(a.words_with_phoneme_prefix(&prefix), vec!["food", "foo"]);
+
+    a.remove("food");
+    assert_eq!(a.words_with_phoneme_prefix(&prefix), vec!["foo"]);
+    assert!(a.words_with_phoneme_prefix(&[Phoneme::Consonant(Consonant::Z)]).is_empty());
+  }
+
+  #[test]
+  fn words_containing_phonemes() {
+    let mut a = Arpabet::new();
+    a.insert("taxi".to_string(), polyphone("T AE1 K S IY0"));
+    a.insert("axe".to_string(), polyphone("AE1 K S"));
+    a.insert("tacks".to_string(), polyphone("T AE1 K S"));
+    a.insert("stacks".to_string(), polyphone("S T AE1 K S"));
+    a.insert("attack".to_string(), polyphone("AH0 T AE1 K"));
+    a.insert("tack_axe".to_string(), polyphone("T AE1 K AE1 K S"));
+
+    assert_eq!(a.words_containing_phonemes(&polyphone("AE1 K S")),
+      vec!["axe", "stacks", "tack_axe", "tacks", "taxi"]);
+    assert_eq!(a.words_containing_phonemes(&polyphone("T AE1")),
+      vec!["attack", "stacks", "tack_axe", "tacks", "taxi"]);
+    // 'tack axe' has both n-grams of 'T AE1 K S', but not the whole sequence.
+    assert_eq!(a.words_containing_phonemes(&polyphone("T AE1 K S")),
+      vec!["stacks", "tacks", "taxi"]);
+
+    a.attach_frequencies(vec![("taxi", 10), ("tacks", 1)].into_iter().collect());
+    assert_eq!(a.words_containing_phonemes(&polyphone("T AE1 K S")),
+      vec!["taxi", "tacks", "stacks"]);
+
+    a.remove("taxi");
+    assert_eq!(a.words_containing_phonemes(&polyphone("K S IY0")), Vec::<&Word>::new());
+    assert!(a.words_containing_phonemes(&[]).is_empty());
+  }
 }
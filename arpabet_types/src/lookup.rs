@@ -0,0 +1,80 @@
+//! This module contains the read-only lookup interface, so that libraries can
+//! accept any source of pronunciations rather than a concrete dictionary.
+
+use crate::phoneme::Phoneme;
+use crate::Arpabet;
+
+/// A read-only source of pronunciations.
+///
+/// Accept `impl PronunciationLookup` (or `&dyn PronunciationLookup`) in
+/// functions that only look words up, so callers can pass an `Arpabet` or any
+/// other implementation.
+pub trait PronunciationLookup {
+  /// Get the pronunciation of a word, as stored (no case folding).
+  fn lookup(&self, word: &str) -> Option<&[Phoneme]>;
+
+  /// Whether the word has a pronunciation.
+  fn contains(&self, word: &str) -> bool {
+    self.lookup(word).is_some()
+  }
+
+  /// Number of entries, alternate pronunciations included.
+  fn len(&self) -> usize;
+
+  /// Whether there are no entries.
+  fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+impl PronunciationLookup for Arpabet {
+  fn lookup(&self, word: &str) -> Option<&[Phoneme]> {
+    self.get_polyphone_ref(word)
+  }
+
+  fn contains(&self, word: &str) -> bool {
+    self.contains_key(word)
+  }
+
+  fn len(&self) -> usize {
+    Arpabet::len(self)
+  }
+}
+
+impl <T: PronunciationLookup + ?Sized> PronunciationLookup for &T {
+  fn lookup(&self, word: &str) -> Option<&[Phoneme]> {
+    (**self).lookup(word)
+  }
+
+  fn contains(&self, word: &str) -> bool {
+    (**self).contains(word)
+  }
+
+  fn len(&self) -> usize {
+    (**self).len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  fn count_known<L: PronunciationLookup>(lookup: L, words: &[&str]) -> usize {
+    words.iter().filter(|word| lookup.contains(word)).count()
+  }
+
+  #[test]
+  fn arpabet() {
+    let mut arpa = Arpabet::new();
+    assert!(PronunciationLookup::is_empty(&arpa));
+
+    arpa.insert("hi".to_string(), polyphone("HH AY1"));
+
+    let lookup : &dyn PronunciationLookup = &arpa;
+    assert_eq!(lookup.len(), 1);
+    assert_eq!(lookup.lookup("hi"), arpa.get_polyphone_ref("hi"));
+    assert_eq!(lookup.lookup("ho"), None);
+    assert_eq!(count_known(&arpa, &["hi", "ho", "hi"]), 2);
+  }
+}
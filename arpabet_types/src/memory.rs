@@ -0,0 +1,242 @@
+//! This module estimates the memory an Arpabet uses, eg. to compare storage
+//! backends or catch regressions across releases.
+//!
+//! Figures are estimates: strings and vectors count their capacity, and hash
+//! tables one slot plus a control byte per bucket. Allocator overhead is not
+//! counted.
+
+use crate::store::Store;
+use crate::{Arpabet, Phoneme};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem::{size_of, size_of_val};
+
+/// Estimated bytes used by each of an Arpabet's indices. Zero for indices not
+/// built. See `Arpabet::built_indexes`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexMemory {
+  /// The pronunciation -> words index.
+  pub reverse: usize,
+  /// The pronunciation trie.
+  pub phoneme_trie: usize,
+  /// The initial phoneme index.
+  pub initial_phonemes: usize,
+  /// The rhyme index.
+  pub rhymes: usize,
+  /// The stressless pronunciation index.
+  pub stressless: usize,
+  /// The phoneme n-gram index.
+  pub ngrams: usize,
+}
+
+impl IndexMemory {
+  /// Bytes used by all the indices.
+  pub fn total(&self) -> usize {
+    self.reverse + self.phoneme_trie + self.initial_phonemes + self.rhymes + self.stressless
+      + self.ngrams
+  }
+}
+
+/// Estimated bytes used by an Arpabet. See `Arpabet::memory_footprint`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+  /// The entry count.
+  pub entries: usize,
+  /// Bytes of the words.
+  pub keys: usize,
+  /// Bytes of the pronunciations.
+  pub values: usize,
+  /// Bytes of the table holding the entries.
+  pub table: usize,
+  /// Whether the entries are a compile-time map, so `keys`, `values` and
+  /// `table` are read-only data of the program rather than heap.
+  pub static_entries: bool,
+  /// Bytes of the built indices.
+  pub indexes: IndexMemory,
+  /// Bytes of the attached word frequencies, if any.
+  pub frequencies: usize,
+  /// Bytes of pronunciations identical to an earlier entry's, which interning
+  /// pronunciations would save.
+  pub duplicate_values: usize,
+}
+
+impl MemoryReport {
+  /// Bytes used in all.
+  pub fn total(&self) -> usize {
+    self.entries_total() + self.indexes.total() + self.frequencies
+  }
+
+  /// Bytes allocated on the heap, ie. the total less static entries.
+  pub fn heap(&self) -> usize {
+    if self.static_entries {
+      self.total() - self.entries_total()
+    } else {
+      self.total()
+    }
+  }
+
+  fn entries_total(&self) -> usize {
+    self.keys + self.values + self.table
+  }
+}
+
+impl fmt::Display for MemoryReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let storage = if self.static_entries { "static" } else { "heap" };
+    writeln!(f, "entries: {} ({})", self.entries, storage)?;
+    writeln!(f, "keys: {} bytes", self.keys)?;
+    writeln!(f, "values: {} bytes ({} duplicate)", self.values, self.duplicate_values)?;
+    writeln!(f, "table: {} bytes", self.table)?;
+    writeln!(f, "indexes: {} bytes", self.indexes.total())?;
+    writeln!(f, "frequencies: {} bytes", self.frequencies)?;
+    writeln!(f, "total: {} bytes ({} heap)", self.total(), self.heap())
+  }
+}
+
+impl Arpabet {
+  /// Estimate the memory used by the dictionary, its built indices and
+  /// attached frequencies. Takes time proportional to the dictionary size.
+  pub fn memory_footprint(&self) -> MemoryReport {
+    let (keys, values, table) = match &self.dictionary {
+      Store::Static(map) => {
+        let keys = map.keys().map(|key| key.len()).sum();
+        let values = map.values().map(|polyphone| size_of_val(*polyphone)).sum();
+        (keys, values, map.len() * size_of::<(&str, &[Phoneme])>())
+      },
+      Store::Owned(map) => {
+        let keys = map.keys().map(|key| key.heap_size()).sum();
+        let values = map.values().map(|polyphone| polyphone.heap_size()).sum();
+        (keys, values, table_size(map))
+      },
+    };
+
+    let mut seen = HashSet::new();
+    let mut duplicate_values = 0;
+    for (_, polyphone) in self.dictionary.iter() {
+      if !seen.insert(polyphone) {
+        duplicate_values += size_of_val(polyphone);
+      }
+    }
+
+    let indexes = IndexMemory {
+      reverse: self.indexes.reverse.get().map(HeapSize::heap_size).unwrap_or(0),
+      phoneme_trie: self.indexes.phoneme_trie.get().map(HeapSize::heap_size).unwrap_or(0),
+      initial_phonemes: self.indexes.initial_phonemes.get().map(HeapSize::heap_size).unwrap_or(0),
+      rhymes: self.indexes.rhymes.get().map(HeapSize::heap_size).unwrap_or(0),
+      stressless: self.indexes.stressless.get().map(HeapSize::heap_size).unwrap_or(0),
+      ngrams: self.indexes.ngrams.get().map(HeapSize::heap_size).unwrap_or(0),
+    };
+
+    MemoryReport {
+      entries: self.dictionary.len(),
+      keys,
+      values,
+      table,
+      static_entries: matches!(self.dictionary, Store::Static(_)),
+      indexes,
+      frequencies: self.frequencies.as_ref().map(HeapSize::heap_size).unwrap_or(0),
+      duplicate_values,
+    }
+  }
+}
+
+// Bytes a value owns on the heap, not counting its own size.
+pub(crate) trait HeapSize {
+  fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+  fn heap_size(&self) -> usize {
+    self.capacity()
+  }
+}
+
+impl HeapSize for Phoneme {
+  fn heap_size(&self) -> usize {
+    0
+  }
+}
+
+impl HeapSize for u64 {
+  fn heap_size(&self) -> usize {
+    0
+  }
+}
+
+impl HeapSize for usize {
+  fn heap_size(&self) -> usize {
+    0
+  }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+  fn heap_size(&self) -> usize {
+    self.0.heap_size() + self.1.heap_size()
+  }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+  fn heap_size(&self) -> usize {
+    self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+  }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for HashMap<K, V> {
+  fn heap_size(&self) -> usize {
+    table_size(self) + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+  }
+}
+
+// Bytes of a hash table's buckets, excluding what the entries own.
+fn table_size<K, V>(map: &HashMap<K, V>) -> usize {
+  map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::frequency::WordFrequencies;
+  use crate::index::IndexSet;
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("cat", "K AE1 T"),
+    ("kat", "K AE1 T"),
+    ("dog", "D AO1 G"),
+  ];
+
+  #[test]
+  fn footprint() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    let report = arpa.memory_footprint();
+    assert_eq!(report.entries, 3);
+    assert!(report.keys >= 9);
+    assert!(report.values >= 9 * size_of::<Phoneme>());
+    assert_eq!(report.duplicate_values, 3 * size_of::<Phoneme>());
+    assert!(!report.static_entries);
+    assert_eq!(report.indexes.total(), 0);
+    assert_eq!(report.frequencies, 0);
+    assert_eq!(report.heap(), report.total());
+
+    arpa.build_indexes(IndexSet::all());
+    arpa.attach_frequencies(vec![("cat", 3)].into_iter().collect::<WordFrequencies>());
+    let indexed = arpa.memory_footprint();
+    assert!(indexed.indexes.reverse > 0);
+    assert!(indexed.indexes.phoneme_trie > 0);
+    assert!(indexed.indexes.stressless > 0);
+    assert!(indexed.indexes.ngrams > 0);
+    assert!(indexed.frequencies > 0);
+    assert_eq!(indexed.total(), report.total() + indexed.indexes.total() + indexed.frequencies);
+  }
+
+  #[test]
+  fn heap_sizes() {
+    let mut words = Vec::with_capacity(4);
+    words.push("ab".to_string());
+    assert_eq!(words.heap_size(), 4 * size_of::<String>() + 2);
+    assert_eq!(String::new().heap_size(), 0);
+
+    let report = testing::arpabet(DICTIONARY).memory_footprint();
+    assert!(report.to_string().starts_with("entries: 3 (heap)\n"));
+  }
+}
@@ -0,0 +1,276 @@
+//! This module contains descriptive metadata that travels with a dictionary.
+
+use crate::error::ArpabetError;
+use std::fmt;
+
+/// A well-formed BCP-47 language tag, eg. 'en-US' or 'en-GB-oxendict'.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LanguageTag {
+  tag: String,
+}
+
+impl LanguageTag {
+  /// Parse and validate a language tag. Validation checks that the tag is
+  /// well-formed per BCP-47, not that its subtags are registered.
+  pub fn parse(tag: &str) -> Result<Self, ArpabetError> {
+    if is_well_formed(tag) {
+      Ok(Self { tag: tag.to_string() })
+    } else {
+      Err(ArpabetError::InvalidLanguageTag { tag: tag.to_string() })
+    }
+  }
+
+  /// Get the tag as a string.
+  pub fn as_str(&self) -> &str {
+    &self.tag
+  }
+
+  /// Get the primary language subtag, eg. 'en' for 'en-US'.
+  pub fn language(&self) -> &str {
+    self.tag.split('-').next().unwrap_or("")
+  }
+}
+
+impl fmt::Display for LanguageTag {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.tag)
+  }
+}
+
+/// Descriptive metadata for a dictionary, embedded into exported artifacts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LexiconMetadata {
+  /// The language of the dictionary.
+  pub language: Option<LanguageTag>,
+  /// Version or revision of the dictionary, eg. '0.7b'.
+  pub version: Option<String>,
+  /// License the dictionary is distributed under.
+  pub license: Option<String>,
+}
+
+impl LexiconMetadata {
+  /// Whether no metadata fields are set.
+  pub fn is_empty(&self) -> bool {
+    self.language.is_none() && self.version.is_none() && self.license.is_none()
+  }
+
+  /// Fill any unset fields from another set of metadata.
+  /// Fields that are already set take precedence.
+  pub fn fill_from(&mut self, other: &LexiconMetadata) {
+    if self.language.is_none() {
+      self.language = other.language.clone();
+    }
+    if self.version.is_none() {
+      self.version = other.version.clone();
+    }
+    if self.license.is_none() {
+      self.license = other.license.clone();
+    }
+  }
+
+  /// Render the metadata as CMUdict-style comment lines (';;; key: value'),
+  /// which the parser reads back with `read_comment_line`.
+  pub fn to_comment_lines(&self) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(language) = self.language.as_ref() {
+      lines.push(format!(";;; language: {}", language));
+    }
+    if let Some(version) = self.version.as_ref() {
+      lines.push(format!(";;; version: {}", version));
+    }
+    if let Some(license) = self.license.as_ref() {
+      lines.push(format!(";;; license: {}", license));
+    }
+    lines
+  }
+
+  /// Read a comment line written by `to_comment_lines`, eg. ';;; version: 2',
+  /// setting its field unless already set. Returns whether the line held
+  /// metadata. Other comments, and language tags that aren't well-formed, are
+  /// ignored.
+  pub fn read_comment_line(&mut self, line: &str) -> bool {
+    let (key, value) = match line.strip_prefix(";;;").and_then(|rest| rest.split_once(':')) {
+      Some((key, value)) => (key.trim(), value.trim()),
+      None => return false,
+    };
+    if value.is_empty() {
+      return false;
+    }
+    match key {
+      "language" => match LanguageTag::parse(value) {
+        Ok(tag) => { self.language.get_or_insert(tag); },
+        Err(_) => return false,
+      },
+      "version" => { self.version.get_or_insert_with(|| value.to_string()); },
+      "license" => { self.license.get_or_insert_with(|| value.to_string()); },
+      _ => return false,
+    }
+    true
+  }
+}
+
+// Check the BCP-47 'langtag' production, plus the 'privateuse' form and the
+// grandfathered 'i-' tags. Subtags are matched case-insensitively.
+fn is_well_formed(tag: &str) -> bool {
+  let subtags : Vec<&str> = tag.split('-').collect();
+
+  if subtags.iter().any(|s| s.is_empty() || s.len() > 8
+      || !s.chars().all(|c| c.is_ascii_alphanumeric())) {
+    return false;
+  }
+
+  let first = subtags[0];
+
+  if first.eq_ignore_ascii_case("x") || first.eq_ignore_ascii_case("i") {
+    return subtags.len() > 1;
+  }
+
+  if !is_alpha(first) || first.len() < 2 || first.len() == 4 {
+    return false;
+  }
+
+  let mut i = 1;
+
+  // Up to three extended language subtags, only after a 2-3 letter language.
+  if first.len() <= 3 {
+    let mut extlangs = 0;
+    while i < subtags.len() && extlangs < 3 && subtags[i].len() == 3 && is_alpha(subtags[i]) {
+      i += 1;
+      extlangs += 1;
+    }
+  }
+
+  // Script.
+  if i < subtags.len() && subtags[i].len() == 4 && is_alpha(subtags[i]) {
+    i += 1;
+  }
+
+  // Region.
+  if i < subtags.len() && ((subtags[i].len() == 2 && is_alpha(subtags[i]))
+      || (subtags[i].len() == 3 && is_digit(subtags[i]))) {
+    i += 1;
+  }
+
+  // Variants.
+  while i < subtags.len() && is_variant(subtags[i]) {
+    i += 1;
+  }
+
+  // Extensions, each a singleton followed by at least one 2-8 character subtag.
+  while i < subtags.len() && subtags[i].len() == 1 && !subtags[i].eq_ignore_ascii_case("x") {
+    i += 1;
+    let start = i;
+    while i < subtags.len() && subtags[i].len() >= 2 {
+      i += 1;
+    }
+    if i == start {
+      return false;
+    }
+  }
+
+  // Private use, a final 'x' followed by at least one subtag.
+  if i < subtags.len() && subtags[i].eq_ignore_ascii_case("x") {
+    return i + 1 < subtags.len();
+  }
+
+  i == subtags.len()
+}
+
+fn is_alpha(subtag: &str) -> bool {
+  subtag.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_digit(subtag: &str) -> bool {
+  subtag.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_variant(subtag: &str) -> bool {
+  let starts_with_digit = subtag.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false);
+  (subtag.len() >= 5) || (subtag.len() == 4 && starts_with_digit)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn valid_language_tags() {
+    for tag in ["en", "en-US", "EN-us", "en-GB-oxendict", "zh-Hant-TW", "es-419",
+        "sl-rozaj-biske", "de-DE-u-co-phonebk", "en-x-custom", "x-whatever",
+        "i-klingon", "zh-yue-HK", "de-1996"].iter() {
+      assert!(LanguageTag::parse(tag).is_ok(), "{} should be valid", tag);
+    }
+  }
+
+  #[test]
+  fn invalid_language_tags() {
+    for tag in ["", "e", "en-", "en--US", "en-US-u", "en_US", "en-toolongsubtag",
+        "abcd", "en-x", "en-ÜS", "123"].iter() {
+      assert!(LanguageTag::parse(tag).is_err(), "{} should be invalid", tag);
+    }
+  }
+
+  #[test]
+  fn language_subtag() {
+    let tag = LanguageTag::parse("en-US").unwrap();
+    assert_eq!(tag.language(), "en");
+    assert_eq!(tag.to_string(), "en-US");
+  }
+
+  #[test]
+  fn fill_from() {
+    let mut a = LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").unwrap()),
+      version: None,
+      license: None,
+    };
+    let b = LexiconMetadata {
+      language: Some(LanguageTag::parse("en-GB").unwrap()),
+      version: Some("2".to_string()),
+      license: None,
+    };
+
+    a.fill_from(&b);
+
+    assert_eq!(a.language.as_ref().map(|l| l.as_str()), Some("en-US"));
+    assert_eq!(a.version.as_deref(), Some("2"));
+    assert_eq!(a.license, None);
+  }
+
+  #[test]
+  fn comment_lines() {
+    let metadata = LexiconMetadata {
+      language: Some(LanguageTag::parse("en-US").unwrap()),
+      version: Some("0.7b".to_string()),
+      license: Some("BSD".to_string()),
+    };
+
+    assert_eq!(metadata.to_comment_lines(), vec![
+      ";;; language: en-US",
+      ";;; version: 0.7b",
+      ";;; license: BSD",
+    ]);
+    assert!(LexiconMetadata::default().to_comment_lines().is_empty());
+  }
+
+  #[test]
+  fn read_comment_lines() {
+    let mut metadata = LexiconMetadata::default();
+
+    assert!(metadata.read_comment_line(";;; language: en-US\n"));
+    assert!(metadata.read_comment_line(";;; version: 0.7b"));
+    assert!(metadata.read_comment_line(";;; version: 0.8"));
+    assert!(!metadata.read_comment_line(";;; license:"));
+    assert!(!metadata.read_comment_line(";;; A comment"));
+    assert!(!metadata.read_comment_line(";;; note: hello"));
+    assert!(!metadata.read_comment_line("LICENSE  L AY1 S AH0 N S"));
+
+    assert_eq!(metadata.language.as_ref().map(|l| l.as_str()), Some("en-US"));
+    assert_eq!(metadata.version.as_deref(), Some("0.7b"));
+    assert_eq!(metadata.license, None);
+
+    let mut invalid = LexiconMetadata::default();
+    assert!(!invalid.read_comment_line(";;; language: en_US"));
+    assert!(invalid.is_empty());
+  }
+}
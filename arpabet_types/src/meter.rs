@@ -0,0 +1,335 @@
+//! This module scans lines of verse: it transcribes a line, finds the stress
+//! of each syllable, and detects the meter that fits it best, eg. iambic
+//! pentameter, along with the syllables that go against it.
+//!
+//! Polysyllabic words are stressed as the dictionary marks them. Monosyllables
+//! are stressed if they are content words, and may fall either way if they are
+//! function words, as their stress in verse depends on the meter around them.
+
+use crate::error::ArpabetError;
+use crate::phoneme::{Phoneme, VowelStress};
+use crate::prominence::is_function_word;
+use crate::syllable::{syllabify, syllable_stress};
+use crate::transcribe::TranscribeOptions;
+use crate::{Arpabet, Polyphone};
+use std::fmt;
+
+// The names of lines of one to eight feet.
+const MEASURES : [&str; 8] = [
+  "monometer", "dimeter", "trimeter", "tetrameter", "pentameter", "hexameter", "heptameter",
+  "octameter",
+];
+
+/// The stress of a syllable in a line of verse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MetricalStress {
+  /// An unstressed syllable, eg. the second of 'parting'.
+  Unstressed,
+  /// A syllable that may be stressed or not as the meter demands, eg. a
+  /// monosyllabic function word or a syllable with secondary stress.
+  Ambiguous,
+  /// A stressed syllable, eg. the first of 'parting'.
+  Stressed,
+}
+
+impl MetricalStress {
+  /// The conventional scansion mark: '/' for stressed, 'x' for unstressed, and
+  /// '\\' for ambiguous syllables.
+  pub fn mark(&self) -> char {
+    match self {
+      MetricalStress::Unstressed => 'x',
+      MetricalStress::Ambiguous => '\\',
+      MetricalStress::Stressed => '/',
+    }
+  }
+}
+
+/// A metrical foot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Foot {
+  /// Unstressed, stressed, as in 'today'.
+  Iamb,
+  /// Stressed, unstressed, as in 'table'.
+  Trochee,
+  /// Unstressed, unstressed, stressed, as in 'understand'.
+  Anapest,
+  /// Stressed, unstressed, unstressed, as in 'wonderful'.
+  Dactyl,
+}
+
+impl Foot {
+  /// Every foot, in the order meters are preferred when they fit equally well.
+  pub const ALL : [Foot; 4] = [Foot::Iamb, Foot::Trochee, Foot::Anapest, Foot::Dactyl];
+
+  /// The stresses of the foot.
+  pub fn pattern(&self) -> &'static [MetricalStress] {
+    use MetricalStress::{Stressed, Unstressed};
+    match self {
+      Foot::Iamb => &[Unstressed, Stressed],
+      Foot::Trochee => &[Stressed, Unstressed],
+      Foot::Anapest => &[Unstressed, Unstressed, Stressed],
+      Foot::Dactyl => &[Stressed, Unstressed, Unstressed],
+    }
+  }
+
+  /// The adjective for lines of the foot, eg. "iambic".
+  pub fn adjective(&self) -> &'static str {
+    match self {
+      Foot::Iamb => "iambic",
+      Foot::Trochee => "trochaic",
+      Foot::Anapest => "anapestic",
+      Foot::Dactyl => "dactylic",
+    }
+  }
+
+  // Whether the foot ends on its stressed syllable.
+  fn is_rising(&self) -> bool {
+    matches!(self, Foot::Iamb | Foot::Anapest)
+  }
+}
+
+/// How a line ends relative to its last full foot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+  /// The line ends with its last foot.
+  #[default]
+  Regular,
+  /// A line of rising feet ends with an extra unstressed syllable, as in
+  /// "To be, or not to be, that is the question".
+  Feminine,
+  /// A line of falling feet drops the unstressed syllables of its last foot,
+  /// as in "Tyger Tyger, burning bright".
+  Catalectic,
+}
+
+/// A meter: a number of feet of one kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Meter {
+  /// The kind of foot.
+  pub foot: Foot,
+  /// The number of feet, counting a catalectic last foot.
+  pub feet: usize,
+  /// How the line ends.
+  pub ending: LineEnding,
+}
+
+impl Meter {
+  /// The stress of each syllable of a line in the meter.
+  pub fn pattern(&self) -> Vec<MetricalStress> {
+    let mut pattern = self.foot.pattern().repeat(self.feet);
+    match self.ending {
+      LineEnding::Regular => {},
+      LineEnding::Feminine => pattern.push(MetricalStress::Unstressed),
+      LineEnding::Catalectic => {
+        while pattern.last() == Some(&MetricalStress::Unstressed) {
+          pattern.pop();
+        }
+      },
+    }
+    pattern
+  }
+
+  // The meters of lines of the number of syllables.
+  // NB: `usize::is_multiple_of` would raise the minimum supported Rust version.
+  #[allow(clippy::manual_is_multiple_of)]
+  fn candidates(syllables: usize) -> Vec<Meter> {
+    let mut meters = Vec::new();
+    for &ending in [LineEnding::Regular, LineEnding::Feminine, LineEnding::Catalectic].iter() {
+      for &foot in Foot::ALL.iter() {
+        let length = foot.pattern().len();
+        let feet = match ending {
+          LineEnding::Regular =>
+            Some(syllables / length).filter(|_| syllables % length == 0),
+          LineEnding::Feminine if foot.is_rising() && syllables > length =>
+            Some((syllables - 1) / length).filter(|_| (syllables - 1) % length == 0),
+          LineEnding::Catalectic if !foot.is_rising() && syllables > 0 =>
+            Some(syllables.div_ceil(length)).filter(|_| (syllables - 1) % length == 0),
+          _ => None,
+        };
+        match feet {
+          Some(feet) if feet > 0 => meters.push(Meter { foot, feet, ending }),
+          _ => {},
+        }
+      }
+    }
+    meters
+  }
+}
+
+impl fmt::Display for Meter {
+  /// Write the name of the meter, eg. "iambic pentameter", "catalectic
+  /// trochaic tetrameter" or "iambic pentameter with a feminine ending".
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.ending == LineEnding::Catalectic {
+      write!(f, "catalectic ")?;
+    }
+    match MEASURES.get(self.feet.wrapping_sub(1)) {
+      Some(measure) => write!(f, "{} {}", self.foot.adjective(), measure)?,
+      None => write!(f, "{} line of {} feet", self.foot.adjective(), self.feet)?,
+    }
+    if self.ending == LineEnding::Feminine {
+      write!(f, " with a feminine ending")?;
+    }
+    Ok(())
+  }
+}
+
+/// A syllable of a scanned line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineSyllable {
+  /// The word the syllable belongs to, as it appears in the line.
+  pub word: String,
+  /// The phonemes of the syllable.
+  pub phonemes: Polyphone,
+  /// The stress of the syllable.
+  pub stress: MetricalStress,
+}
+
+/// A syllable whose stress goes against the meter: a stressed syllable where
+/// the meter has an unstressed one, or the reverse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deviation {
+  /// The index of the syllable in the line.
+  pub syllable: usize,
+  /// The stress the meter has there.
+  pub expected: MetricalStress,
+}
+
+/// A scanned line of verse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scansion {
+  /// The syllables of the line, in order.
+  pub syllables: Vec<LineSyllable>,
+  /// The meter that fits the line best, or None if it has no syllables.
+  pub meter: Option<Meter>,
+  /// The syllables that go against the meter.
+  pub deviations: Vec<Deviation>,
+}
+
+impl Scansion {
+  /// The stress sequence of the line in scansion marks, eg. "x/x/x/x/x/".
+  /// See `MetricalStress::mark`.
+  pub fn marks(&self) -> String {
+    self.syllables.iter().map(|syllable| syllable.stress.mark()).collect()
+  }
+}
+
+/// Scan a line of syllable stresses: find the meter with the fewest deviations.
+/// Regular lines are preferred to those with feminine or catalectic endings,
+/// then feet in the order of `Foot::ALL`.
+pub fn detect_meter(stresses: &[MetricalStress]) -> Option<(Meter, Vec<Deviation>)> {
+  Meter::candidates(stresses.len()).into_iter()
+    .map(|meter| {
+      let deviations = stresses.iter()
+        .zip(meter.pattern())
+        .enumerate()
+        .filter(|(_, (stress, expected))| {
+          **stress != MetricalStress::Ambiguous && **stress != *expected
+        })
+        .map(|(syllable, (_, expected))| Deviation { syllable, expected })
+        .collect::<Vec<Deviation>>();
+      (meter, deviations)
+    })
+    .min_by_key(|(_, deviations)| deviations.len())
+}
+
+// The stresses of the syllables of a word.
+fn word_stresses(word: &str, syllables: &[&[Phoneme]]) -> Vec<MetricalStress> {
+  if syllables.len() == 1 {
+    let stress = if is_function_word(word) {
+      MetricalStress::Ambiguous
+    } else {
+      MetricalStress::Stressed
+    };
+    return vec![stress];
+  }
+  syllables.iter()
+    .map(|syllable| match syllable_stress(syllable) {
+      Some(VowelStress::PrimaryStress) => MetricalStress::Stressed,
+      Some(VowelStress::SecondaryStress) | Some(VowelStress::UnknownStress) => {
+        MetricalStress::Ambiguous
+      },
+      Some(VowelStress::NoStress) | None => MetricalStress::Unstressed,
+    })
+    .collect()
+}
+
+impl Arpabet {
+  /// Scan a line of verse. Words are pronounced as by `transcribe_words`, and
+  /// unknown words handled the same way.
+  pub fn scan_line(&self, line: &str, options: &TranscribeOptions)
+    -> Result<Scansion, ArpabetError>
+  {
+    let mut syllables = Vec::new();
+    for word in self.transcribe_words(line, options)? {
+      let phonemes = syllabify(&word.polyphone);
+      for (syllable, stress) in phonemes.iter().zip(word_stresses(&word.word, &phonemes)) {
+        let phonemes = syllable.to_vec();
+        syllables.push(LineSyllable { word: word.word.clone(), phonemes, stress });
+      }
+    }
+    let stresses = syllables.iter().map(|syllable| syllable.stress).collect::<Vec<_>>();
+    let (meter, deviations) = match detect_meter(&stresses) {
+      Some((meter, deviations)) => (Some(meter), deviations),
+      None => (None, Vec::new()),
+    };
+    Ok(Scansion { syllables, meter, deviations })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{arpabet, polyphone};
+
+  #[test]
+  fn meters() {
+    let meter = |foot, feet, ending| Meter { foot, feet, ending };
+    assert_eq!(meter(Foot::Iamb, 5, LineEnding::Regular).to_string(), "iambic pentameter");
+    assert_eq!(meter(Foot::Iamb, 5, LineEnding::Feminine).to_string(),
+      "iambic pentameter with a feminine ending");
+    assert_eq!(meter(Foot::Dactyl, 9, LineEnding::Catalectic).to_string(),
+      "catalectic dactylic line of 9 feet");
+
+    use MetricalStress::{Stressed, Unstressed};
+    assert_eq!(meter(Foot::Trochee, 2, LineEnding::Catalectic).pattern(),
+      vec![Stressed, Unstressed, Stressed]);
+    assert_eq!(meter(Foot::Anapest, 1, LineEnding::Feminine).pattern(),
+      vec![Unstressed, Unstressed, Stressed, Unstressed]);
+    assert_eq!(detect_meter(&[]), None);
+  }
+
+  #[test]
+  fn scansion() {
+    let arpa = arpabet(&[
+      ("the", "DH AH0"), ("curfew", "K ER1 F Y UW2"), ("tolls", "T OW1 L Z"),
+      ("knell", "N EH1 L"), ("of", "AH1 V"), ("parting", "P AA1 R T IH0 NG"),
+      ("day", "D EY1"), ("tyger", "T AY1 G ER0"), ("burning", "B ER1 N IH0 NG"),
+      ("bright", "B R AY1 T"), ("shall", "SH AE1 L"), ("i", "AY1"),
+      ("compare", "K AH0 M P EH1 R"), ("thee", "DH IY1"), ("to", "T UW1"), ("a", "AH0"),
+      ("summer", "S AH1 M ER0"),
+    ]);
+    let options = TranscribeOptions::default();
+
+    let line = arpa.scan_line("The curfew tolls the knell of parting day,", &options).unwrap();
+    assert_eq!(line.marks(), "\\/\\/\\/\\/x/");
+    assert_eq!(line.meter.unwrap().to_string(), "iambic pentameter");
+    assert!(line.deviations.is_empty());
+    assert_eq!(line.syllables[1].word, "curfew");
+    assert_eq!(line.syllables[1].phonemes, polyphone("K ER1"));
+
+    let line = arpa.scan_line("Tyger Tyger, burning bright", &options).unwrap();
+    assert_eq!(line.marks(), "/x/x/x/");
+    assert_eq!(line.meter.unwrap().to_string(), "catalectic trochaic tetrameter");
+
+    let line = arpa.scan_line("Shall I compare thee to a summer day?", &options).unwrap();
+    assert_eq!(line.meter.unwrap().to_string(), "iambic pentameter");
+    assert_eq!(line.deviations,
+      vec![Deviation { syllable: 4, expected: MetricalStress::Unstressed }]);
+    assert_eq!(line.syllables[4].word, "thee");
+
+    let line = arpa.scan_line("", &options).unwrap();
+    assert_eq!(line.meter, None);
+    assert!(arpa.scan_line("The xyzzy", &options).is_err());
+  }
+}
@@ -0,0 +1,109 @@
+//! This module contains test doubles for code that depends on this crate. It
+//! is only built with the `test-support` feature.
+
+use crate::lookup::PronunciationLookup;
+use crate::phoneme::Phoneme;
+use crate::sync::ignore_poison;
+use crate::{Polyphone, Word};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// A `PronunciationLookup` with scripted responses that records every word it
+/// is asked for, to test out-of-vocabulary and other paths deterministically.
+/// Words without a scripted response are out of vocabulary unless a default
+/// response is set.
+#[derive(Debug, Default)]
+pub struct MockLexicon {
+  responses: HashMap<Word, Polyphone>,
+  default_response: Option<Polyphone>,
+  calls: Mutex<Vec<Word>>,
+}
+
+impl MockLexicon {
+  /// Create a mock where every word is out of vocabulary.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Script the response for a word.
+  pub fn insert(&mut self, word: &str, polyphone: Polyphone) {
+    self.responses.insert(word.to_string(), polyphone);
+  }
+
+  /// Script the response for words without their own response. None makes
+  /// them out of vocabulary again.
+  pub fn set_default_response(&mut self, polyphone: Option<Polyphone>) {
+    self.default_response = polyphone;
+  }
+
+  /// Every word looked up so far, in order, including repeats.
+  pub fn calls(&self) -> Vec<Word> {
+    self.recorded_calls().clone()
+  }
+
+  /// How many times the word was looked up.
+  pub fn call_count(&self, word: &str) -> usize {
+    self.recorded_calls().iter().filter(|call| *call == word).count()
+  }
+
+  /// Forget the recorded lookups.
+  pub fn clear_calls(&self) {
+    self.recorded_calls().clear();
+  }
+
+  fn recorded_calls(&self) -> MutexGuard<'_, Vec<Word>> {
+    ignore_poison(self.calls.lock())
+  }
+}
+
+impl PronunciationLookup for MockLexicon {
+  fn lookup(&self, word: &str) -> Option<&[Phoneme]> {
+    self.recorded_calls().push(word.to_string());
+    self.responses.get(word)
+      .or(self.default_response.as_ref())
+      .map(|polyphone| polyphone.as_slice())
+  }
+
+  /// Number of scripted responses, not counting the default.
+  fn len(&self) -> usize {
+    self.responses.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  fn hi() -> Polyphone {
+    polyphone("HH AY1")
+  }
+
+  #[test]
+  fn scripted_responses() {
+    let mut mock = MockLexicon::new();
+    mock.insert("hi", hi());
+
+    assert_eq!(mock.lookup("hi"), Some(hi().as_slice()));
+    assert_eq!(mock.lookup("zyzzyva"), None);
+    assert!(!mock.contains("zyzzyva"));
+    assert_eq!(mock.len(), 1);
+
+    mock.set_default_response(Some(hi()));
+    assert_eq!(mock.lookup("anything"), Some(hi().as_slice()));
+  }
+
+  #[test]
+  fn records_calls() {
+    let mock = MockLexicon::new();
+    mock.lookup("a");
+    mock.contains("b");
+    mock.lookup("a");
+
+    assert_eq!(mock.calls(), vec!["a", "b", "a"]);
+    assert_eq!(mock.call_count("a"), 2);
+
+    mock.clear_calls();
+    assert!(mock.calls().is_empty());
+  }
+}
@@ -0,0 +1,143 @@
+//! This module normalizes words at query time, so lookups find an entry
+//! however the query was typed: "Doctor", "doctor," and a decomposed "café"
+//! all find the same key.
+//!
+//! Dictionary keys are folded to lowercase when parsed, but the plain lookups,
+//! eg. `Arpabet::get_polyphone`, take the query as given. The `_normalized`
+//! lookups apply `LookupOptions` to the query first.
+
+use crate::casing::CaseFolding;
+use crate::phoneme::Phoneme;
+use crate::tokenizer::{is_apostrophe, normalize_apostrophes};
+use crate::{Arpabet, Polyphone};
+use unicode_normalization::UnicodeNormalization as _;
+
+/// A Unicode normalization form applied to queries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnicodeForm {
+  /// Leave the query's code points as they are.
+  None,
+  /// Canonical composition, eg. 'e' and a combining acute accent -> 'é'.
+  #[default]
+  Nfc,
+  /// Compatibility composition, which also folds presentation variants, eg.
+  /// the ligature 'ﬁ' -> "fi" and fullwidth 'Ａ' -> 'A'.
+  Nfkc,
+}
+
+/// How a query is normalized before lookup. The default composes (NFC),
+/// folds case as English and strips surrounding punctuation, which suits
+/// keys parsed from CMUdict-style files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LookupOptions {
+  /// How the query is folded to lowercase, or None to keep its case.
+  pub case_folding: Option<CaseFolding>,
+  /// The Unicode normalization form applied.
+  pub unicode: UnicodeForm,
+  /// Strip punctuation and symbols from both ends of the query, eg. "doctor,"
+  /// -> "doctor", and normalize apostrophe variants. Apostrophes at either
+  /// end are kept, as in "'em" and "students'".
+  pub strip_punctuation: bool,
+}
+
+impl Default for LookupOptions {
+  fn default() -> Self {
+    Self {
+      case_folding: Some(CaseFolding::English),
+      unicode: UnicodeForm::Nfc,
+      strip_punctuation: true,
+    }
+  }
+}
+
+impl LookupOptions {
+  /// Options that leave queries as they are, as the plain lookups do.
+  pub fn exact() -> Self {
+    Self {
+      case_folding: None,
+      unicode: UnicodeForm::None,
+      strip_punctuation: false,
+    }
+  }
+
+  /// Normalize a query: Unicode normalization, then case folding, then
+  /// punctuation stripping.
+  pub fn normalize(&self, word: &str) -> String {
+    let mut word = match self.unicode {
+      UnicodeForm::None => word.to_string(),
+      UnicodeForm::Nfc => word.nfc().collect(),
+      UnicodeForm::Nfkc => word.nfkc().collect(),
+    };
+    if let Some(case_folding) = self.case_folding {
+      word = case_folding.fold(&word);
+    }
+    if self.strip_punctuation {
+      let stripped = word.trim_matches(|c: char| !c.is_alphanumeric() && !is_apostrophe(c));
+      word = normalize_apostrophes(stripped).into_owned();
+    }
+    word
+  }
+}
+
+impl Arpabet {
+  /// Get a polyphone from the dictionary, normalizing the query first.
+  pub fn get_polyphone_normalized(&self, word: &str, options: &LookupOptions)
+    -> Option<Polyphone>
+  {
+    self.get_polyphone(&options.normalize(word))
+  }
+
+  /// Get a polyphone from the dictionary by reference, normalizing the query
+  /// first.
+  pub fn get_polyphone_ref_normalized(&self, word: &str, options: &LookupOptions)
+    -> Option<&[Phoneme]>
+  {
+    self.get_polyphone_ref(&options.normalize(word))
+  }
+
+  /// Whether the dictionary has the word, normalizing the query first.
+  pub fn contains_key_normalized(&self, word: &str, options: &LookupOptions) -> bool {
+    self.contains_key(&options.normalize(word))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn normalize() {
+    let options = LookupOptions::default();
+    assert_eq!(options.normalize("Doctor"), "doctor");
+    assert_eq!(options.normalize("\"Doctor,\""), "doctor");
+    assert_eq!(options.normalize("cafe\u{301}"), "caf\u{e9}");
+    assert_eq!(options.normalize("’em"), "'em");
+    assert_eq!(options.normalize("Students’."), "students'");
+    assert_eq!(options.normalize("\u{fb01}ne"), "\u{fb01}ne");
+    assert_eq!(options.normalize("..."), "");
+
+    let nfkc = LookupOptions { unicode: UnicodeForm::Nfkc, ..Default::default() };
+    assert_eq!(nfkc.normalize("\u{fb01}ne"), "fine");
+    assert_eq!(nfkc.normalize("\u{ff24}octor"), "doctor");
+
+    let keep_case = LookupOptions { case_folding: None, ..Default::default() };
+    assert_eq!(keep_case.normalize("Doctor!"), "Doctor");
+    assert_eq!(LookupOptions::exact().normalize("Doctor,"), "Doctor,");
+  }
+
+  #[test]
+  fn lookups() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("doctor".to_string(), polyphone("D AA1 K T ER0"));
+    arpa.insert("caf\u{e9}".to_string(), polyphone("K AE0 F EY1"));
+
+    let options = LookupOptions::default();
+    assert_eq!(arpa.get_polyphone("Doctor"), None);
+    assert_eq!(arpa.get_polyphone_normalized("Doctor", &options), arpa.get_polyphone("doctor"));
+    assert_eq!(arpa.get_polyphone_ref_normalized("CAFE\u{301}!", &options),
+      arpa.get_polyphone_ref("caf\u{e9}"));
+    assert!(arpa.contains_key_normalized("(doctor)", &options));
+    assert!(!arpa.contains_key_normalized("Doctor", &LookupOptions::exact()));
+  }
+}
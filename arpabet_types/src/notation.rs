@@ -0,0 +1,180 @@
+//! This module converts between ARPABET and the ASCII phonetic notations
+//! X-SAMPA and Kirshenbaum, which many legacy synthesis toolchains require.
+
+use crate::error::ArpabetError;
+use crate::ipa::{parse_symbols, write_symbols, SymbolSet};
+use crate::phoneme::Phoneme;
+use crate::Polyphone;
+
+/// An ASCII phonetic notation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Notation {
+  /// Extended Speech Assessment Methods Phonetic Alphabet.
+  /// Stress is marked with '"' (primary) and '%' (secondary).
+  XSampa,
+  /// Kirshenbaum, as used by eSpeak among others.
+  /// Stress is marked with ''' (primary) and ',' (secondary).
+  Kirshenbaum,
+}
+
+/// X-SAMPA symbols and the ARPABET phonemes they are read as. Vowels are listed
+/// without stress, except where an unstressed vowel has its own symbol (AH0).
+/// When writing, the first symbol listed for a phoneme is used; when reading,
+/// the first phoneme listed for a symbol.
+pub const XSAMPA_SEGMENTS : [(&str, &str); 57] = [
+  // Consonants.
+  ("b", "B"), ("tS", "CH"), ("d", "D"), ("D", "DH"), ("4", "DX"), ("l=", "EL"),
+  ("m=", "EM"), ("n=", "EN"), ("f", "F"), ("g", "G"), ("h", "HH"), ("dZ", "JH"),
+  ("k", "K"), ("l", "L"), ("m", "M"), ("n", "N"), ("N", "NG"), ("4~", "NX"),
+  ("p", "P"), ("?", "Q"), ("r\\", "R"), ("r", "R"), ("s", "S"), ("S", "SH"),
+  ("t", "T"), ("T", "TH"), ("v", "V"), ("w", "W"), ("W", "WH"), ("j", "Y"),
+  ("z", "Z"), ("Z", "ZH"),
+  // Vowels.
+  ("A", "AA"), ("Q", "AA"), ("{", "AE"), ("@", "AH0"), ("V", "AH"), ("O", "AO"),
+  ("aU", "AW"), ("@", "AX"), ("@`", "ER0"), ("@`", "AXR"), ("aI", "AY"), ("E", "EH"),
+  ("3`", "ER"), ("3", "ER"), ("eI", "EY"), ("I", "IH"), ("1", "IX"), ("i", "IY"),
+  ("oU", "OW"), ("@U", "OW"), ("OI", "OY"), ("U", "UH"), ("u", "UW"), ("}", "UX"),
+  ("e", "EH"),
+];
+
+/// Kirshenbaum symbols and the ARPABET phonemes they are read as.
+/// See `XSAMPA_SEGMENTS` for how the table is read.
+pub const KIRSHENBAUM_SEGMENTS : [(&str, &str); 53] = [
+  // Consonants.
+  ("b", "B"), ("tS", "CH"), ("d", "D"), ("D", "DH"), ("*", "DX"), ("l-", "EL"),
+  ("m-", "EM"), ("n-", "EN"), ("f", "F"), ("g", "G"), ("h", "HH"), ("dZ", "JH"),
+  ("k", "K"), ("l", "L"), ("m", "M"), ("n", "N"), ("N", "NG"), ("*~", "NX"),
+  ("p", "P"), ("?", "Q"), ("r", "R"), ("s", "S"), ("S", "SH"), ("t", "T"),
+  ("T", "TH"), ("v", "V"), ("w", "W"), ("w<vls>", "WH"), ("j", "Y"), ("z", "Z"),
+  ("Z", "ZH"),
+  // Vowels.
+  ("A", "AA"), ("&", "AE"), ("@", "AH0"), ("V", "AH"), ("O", "AO"), ("aU", "AW"),
+  ("@", "AX"), ("R", "ER"), ("R", "AXR"), ("aI", "AY"), ("E", "EH"), ("eI", "EY"),
+  ("I", "IH"), ("i\"", "IX"), ("i", "IY"), ("oU", "OW"), ("OI", "OY"), ("U", "UH"),
+  ("u", "UW"), ("u\"", "UX"), ("e", "EH"), ("o", "OW"),
+];
+
+const XSAMPA_SYMBOLS : SymbolSet = SymbolSet {
+  name: "X-SAMPA",
+  segments: &XSAMPA_SEGMENTS,
+  primary_stress: &['"'],
+  secondary_stress: &['%'],
+  ignored: &['/', '[', ']', '.', ' ', ':'],
+};
+
+const KIRSHENBAUM_SYMBOLS : SymbolSet = SymbolSet {
+  name: "Kirshenbaum",
+  segments: &KIRSHENBAUM_SEGMENTS,
+  primary_stress: &['\''],
+  secondary_stress: &[','],
+  ignored: &['/', '[', ']', '.', ' ', ':'],
+};
+
+impl Notation {
+  fn symbols(&self) -> &'static SymbolSet {
+    match self {
+      Notation::XSampa => &XSAMPA_SYMBOLS,
+      Notation::Kirshenbaum => &KIRSHENBAUM_SYMBOLS,
+    }
+  }
+
+  fn stress_marks(&self) -> (char, char) {
+    let symbols = self.symbols();
+    (symbols.primary_stress[0], symbols.secondary_stress[0])
+  }
+}
+
+impl Phoneme {
+  /// Get the symbol for the phoneme in the notation, without stress marks.
+  pub fn to_notation(&self, notation: Notation) -> &'static str {
+    let exact = self.to_str();
+    let stressless = match self {
+      Phoneme::Consonant(consonant) => consonant.to_str(),
+      Phoneme::Vowel(vowel) => vowel.to_str_stressless(),
+    };
+    let segments = notation.symbols().segments;
+    segments.iter()
+      .find(|(_, arpabet)| *arpabet == exact)
+      .or_else(|| segments.iter().find(|(_, arpabet)| *arpabet == stressless))
+      .map(|(symbol, _)| *symbol)
+      .unwrap_or_else(|| unreachable!("every phoneme has a symbol"))
+  }
+}
+
+/// Transcribe a pronunciation to the notation, placing stress marks before the
+/// stressed syllables, eg. [EH1 K S T R AH0] -> "\"EkstR\\@" in X-SAMPA.
+pub fn polyphone_to_notation(polyphone: &[Phoneme], notation: Notation) -> String {
+  let (primary, secondary) = notation.stress_marks();
+  write_symbols(polyphone, primary, secondary, |phoneme| phoneme.to_notation(notation))
+}
+
+/// Parse a transcription in the notation into a pronunciation. Stress is read
+/// as in `ipa::parse_ipa`. Symbols with no ARPABET equivalent are an error.
+pub fn parse_notation(text: &str, notation: Notation) -> Result<Polyphone, ArpabetError> {
+  parse_symbols(text, notation.symbols())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constants::{ALL_CONSONANTS, ALL_VOWELS};
+  use crate::testing::polyphone;
+
+  fn arpabet(text: &str, notation: Notation) -> String {
+    parse_notation(text, notation).unwrap().iter()
+      .map(|phoneme| phoneme.to_str())
+      .collect::<Vec<&str>>()
+      .join(" ")
+  }
+
+  #[test]
+  fn every_phoneme_has_a_symbol() {
+    for notation in [Notation::XSampa, Notation::Kirshenbaum].iter() {
+      for consonant in ALL_CONSONANTS.iter() {
+        assert!(!Phoneme::Consonant(*consonant).to_notation(*notation).is_empty());
+      }
+      for vowel in ALL_VOWELS.iter() {
+        assert!(!Phoneme::Vowel(*vowel).to_notation(*notation).is_empty());
+      }
+    }
+  }
+
+  #[test]
+  fn xsampa() {
+    assert_eq!(polyphone_to_notation(&polyphone("EH1 K S T R AH0"), Notation::XSampa),
+      "\"Ekstr\\@");
+    assert_eq!(polyphone_to_notation(&polyphone("AA1 R P AH0 B EH2 T"), Notation::XSampa),
+      "\"Ar\\p@%bEt");
+    assert_eq!(arpabet("\"Ekstr\\@", Notation::XSampa), "EH1 K S T R AH0");
+    assert_eq!(arpabet("/tS3`tS/", Notation::XSampa), "CH ER1 CH");
+  }
+
+  #[test]
+  fn kirshenbaum() {
+    assert_eq!(polyphone_to_notation(&polyphone("EH1 K S T R AH0"), Notation::Kirshenbaum),
+      "'Ekstr@");
+    assert_eq!(polyphone_to_notation(&polyphone("K AE1 T"), Notation::Kirshenbaum), "'k&t");
+    assert_eq!(arpabet("'Ekstr@", Notation::Kirshenbaum), "EH1 K S T R AH0");
+    assert_eq!(arpabet("w<vls>aI", Notation::Kirshenbaum), "WH AY1");
+  }
+
+  #[test]
+  fn round_trip() {
+    for text in ["EH1 K S T R AH0", "AH0 B AE1 N D AH0 N", "AA1 R P AH0 B EH2 T",
+        "JH AH1 NG G AH0 L", "S IH1 NG ER0", "B AA1 T EL"].iter() {
+      for notation in [Notation::XSampa, Notation::Kirshenbaum].iter() {
+        let written = polyphone_to_notation(&polyphone(text), *notation);
+        assert_eq!(arpabet(&written, *notation), *text, "{:?} {}", notation, written);
+      }
+    }
+  }
+
+  #[test]
+  fn parse_errors() {
+    match parse_notation("\"bEx", Notation::XSampa) {
+      Err(ArpabetError::StringParseError { description }) =>
+        assert!(description.contains("X-SAMPA segment 'x'")),
+      _ => panic!("Should have failed on 'x'"),
+    }
+  }
+}
@@ -0,0 +1,241 @@
+//! This module reads numbers as English words, eg. "42" -> 'forty two',
+//! "3.14" -> 'three point one four' and "21st" -> 'twenty first', so they can
+//! be pronounced with the dictionary.
+
+use crate::Arpabet;
+use crate::Polyphone;
+
+/// The names of the numbers below twenty.
+pub const ONES : [&str; 20] = [
+  "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+  "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen",
+  "eighteen", "nineteen",
+];
+
+/// The names of the multiples of ten, indexed by the tens digit. Empty below
+/// twenty.
+pub const TENS : [&str; 10] = [
+  "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// The names of the powers of a thousand, indexed by the exponent. Empty for
+/// units.
+pub const SCALES : [&str; 7] = [
+  "", "thousand", "million", "billion", "trillion", "quadrillion", "quintillion",
+];
+
+// The ordinal of each word a cardinal can end with.
+const ORDINALS : [(&str, &str); 34] = [
+  ("zero", "zeroth"), ("one", "first"), ("two", "second"), ("three", "third"),
+  ("four", "fourth"), ("five", "fifth"), ("six", "sixth"), ("seven", "seventh"),
+  ("eight", "eighth"), ("nine", "ninth"), ("ten", "tenth"), ("eleven", "eleventh"),
+  ("twelve", "twelfth"), ("thirteen", "thirteenth"), ("fourteen", "fourteenth"),
+  ("fifteen", "fifteenth"), ("sixteen", "sixteenth"), ("seventeen", "seventeenth"),
+  ("eighteen", "eighteenth"), ("nineteen", "nineteenth"), ("twenty", "twentieth"),
+  ("thirty", "thirtieth"), ("forty", "fortieth"), ("fifty", "fiftieth"),
+  ("sixty", "sixtieth"), ("seventy", "seventieth"), ("eighty", "eightieth"),
+  ("ninety", "ninetieth"), ("hundred", "hundredth"), ("thousand", "thousandth"),
+  ("million", "millionth"), ("billion", "billionth"), ("trillion", "trillionth"),
+  ("quadrillion", "quadrillionth"),
+];
+
+/// Name a number, eg. 1042 -> "one thousand forty two". Numbers are read in
+/// the American style, without "and".
+pub fn cardinal_words(number: u64) -> Vec<&'static str> {
+  if number == 0 {
+    return vec![ONES[0]];
+  }
+  // Groups of three digits, least significant first.
+  let mut groups = Vec::new();
+  let mut rest = number;
+  while rest > 0 {
+    groups.push((rest % 1000) as usize);
+    rest /= 1000;
+  }
+
+  let mut words = Vec::new();
+  for (scale, group) in groups.into_iter().enumerate().rev() {
+    if group == 0 {
+      continue;
+    }
+    if group >= 100 {
+      words.push(ONES[group / 100]);
+      words.push("hundred");
+    }
+    match group % 100 {
+      0 => {},
+      n if n < 20 => words.push(ONES[n]),
+      n => {
+        words.push(TENS[n / 10]);
+        if n % 10 != 0 {
+          words.push(ONES[n % 10]);
+        }
+      },
+    }
+    if scale > 0 {
+      words.push(SCALES[scale]);
+    }
+  }
+  words
+}
+
+/// Name the ordinal of a number, eg. 21 -> "twenty first".
+pub fn ordinal_words(number: u64) -> Vec<&'static str> {
+  let mut words = cardinal_words(number);
+  if let Some(last) = words.last_mut() {
+    // Quintillions are the largest scale a u64 reaches.
+    *last = ORDINALS.iter()
+      .find(|(cardinal, _)| cardinal == last)
+      .map(|(_, ordinal)| *ordinal)
+      .unwrap_or("quintillionth");
+  }
+  words
+}
+
+/// Read a number written in digits as words. Returns None for anything else.
+///
+/// Accepted are integers, optionally with commas between groups of three
+/// digits ("1,000"); decimals, whose fractional digits are read one by one
+/// ("3.14" -> "three point one four"); and ordinals with a suffix matching
+/// the number ("1st", "22nd", "13th"). Integers with leading zeros are read
+/// digit by digit ("007" -> "zero zero seven"). The text must be lowercase.
+pub fn number_words(text: &str) -> Option<Vec<&'static str>> {
+  let suffix = ["st", "nd", "rd", "th"].iter().find(|suffix| text.ends_with(*suffix));
+  if let Some(suffix) = suffix {
+    let number = parse_integer(&text[.. text.len() - suffix.len()])?;
+    return if ordinal_suffix(number) == *suffix { Some(ordinal_words(number)) } else { None };
+  }
+
+  let (integer, fraction) = match text.find('.') {
+    Some(point) => (&text[.. point], Some(&text[point + 1 ..])),
+    None => (text, None),
+  };
+
+  let mut words = if integer.len() > 1 && integer.starts_with('0') {
+    digit_words(integer)?
+  } else {
+    cardinal_words(parse_integer(integer)?)
+  };
+  if let Some(fraction) = fraction {
+    words.push("point");
+    words.extend(digit_words(fraction)?);
+  }
+  Some(words)
+}
+
+/// The ordinal suffix written after a number in digits, eg. 22 -> "nd".
+pub fn ordinal_suffix(number: u64) -> &'static str {
+  match (number % 10, number % 100) {
+    (_, 11 ..= 13) => "th",
+    (1, _) => "st",
+    (2, _) => "nd",
+    (3, _) => "rd",
+    _ => "th",
+  }
+}
+
+impl Arpabet {
+  /// Pronounce a number written in digits by looking up its words, eg.
+  /// "21st" as 'twenty first'. See `number_words`. Returns None if the text
+  /// isn't a number or a word is missing from the dictionary.
+  pub fn pronounce_number(&self, text: &str) -> Option<Polyphone> {
+    let mut polyphone = Vec::new();
+    for word in number_words(text)? {
+      polyphone.extend(self.get_polyphone(word)?);
+    }
+    Some(polyphone)
+  }
+}
+
+// Parse digits, optionally grouped by commas in threes, eg. "1,000".
+fn parse_integer(text: &str) -> Option<u64> {
+  let mut groups = text.split(',');
+  let first = groups.next()?;
+  let mut digits = first.to_string();
+  for group in groups {
+    if first.is_empty() || first.len() > 3 || group.len() != 3 {
+      return None;
+    }
+    digits.push_str(group);
+  }
+  if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  digits.parse().ok()
+}
+
+// Name each digit, eg. "07" -> "zero seven".
+fn digit_words(text: &str) -> Option<Vec<&'static str>> {
+  if text.is_empty() {
+    return None;
+  }
+  text.chars()
+    .map(|c| c.to_digit(10).map(|digit| ONES[digit as usize]))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{arpabet, polyphone};
+
+  fn read(text: &str) -> Option<String> {
+    number_words(text).map(|words| words.join(" "))
+  }
+
+  #[test]
+  fn cardinals() {
+    assert_eq!(cardinal_words(0), vec!["zero"]);
+    assert_eq!(cardinal_words(13).join(" "), "thirteen");
+    assert_eq!(cardinal_words(42).join(" "), "forty two");
+    assert_eq!(cardinal_words(100).join(" "), "one hundred");
+    assert_eq!(cardinal_words(1042).join(" "), "one thousand forty two");
+    assert_eq!(cardinal_words(2_000_310).join(" "), "two million three hundred ten");
+    assert_eq!(cardinal_words(u64::MAX)[..3], ["eighteen", "quintillion", "four"]);
+  }
+
+  #[test]
+  fn ordinals() {
+    assert_eq!(ordinal_words(1), vec!["first"]);
+    assert_eq!(ordinal_words(21).join(" "), "twenty first");
+    assert_eq!(ordinal_words(40).join(" "), "fortieth");
+    assert_eq!(ordinal_words(112).join(" "), "one hundred twelfth");
+    assert_eq!(ordinal_words(3000).join(" "), "three thousandth");
+    assert_eq!(ordinal_words(2_000_000_000_000_000_000).join(" "), "two quintillionth");
+    assert_eq!(ordinal_suffix(11), "th");
+    assert_eq!(ordinal_suffix(22), "nd");
+    assert_eq!(ordinal_suffix(103), "rd");
+  }
+
+  #[test]
+  fn numbers() {
+    assert_eq!(read("42"), Some("forty two".to_string()));
+    assert_eq!(read("1,000,001"), Some("one million one".to_string()));
+    assert_eq!(read("3.14"), Some("three point one four".to_string()));
+    assert_eq!(read("0.5"), Some("zero point five".to_string()));
+    assert_eq!(read("007"), Some("zero zero seven".to_string()));
+    assert_eq!(read("21st"), Some("twenty first".to_string()));
+    assert_eq!(read("13th"), Some("thirteenth".to_string()));
+    assert_eq!(read("1,000th"), Some("one thousandth".to_string()));
+
+    for text in ["", "abc", "3.", ".5", "1.2.3", "1,00", "1000,000", ",000", "21th", "2st",
+        "12a", "99999999999999999999"].iter() {
+      assert_eq!(read(text), None, "{}", text);
+    }
+  }
+
+  #[test]
+  fn pronounce_number() {
+    let arpa = arpabet(&[
+      ("twenty", "T W EH1 N T IY0"),
+      ("first", "F ER1 S T"),
+      ("one", "W AH1 N"),
+    ]);
+
+    let expected = polyphone("T W EH1 N T IY0 F ER1 S T");
+    assert_eq!(arpa.pronounce_number("21st"), Some(expected));
+    assert!(arpa.pronounce_number("21").is_some());
+    assert_eq!(arpa.pronounce_number("22"), None);
+    assert_eq!(arpa.pronounce_number("twenty"), None);
+  }
+}
@@ -0,0 +1,158 @@
+//! This module recovers dictionary words from text damaged by optical character
+//! recognition, where glyphs are routinely confused ('rn' read as 'm', etc.)
+
+use crate::casing::fold_case;
+use crate::{Arpabet, Polyphone, Word};
+use std::collections::HashSet;
+
+/// Pairs of glyph sequences that OCR commonly mistakes for one another.
+/// Each pair is tried in both directions.
+pub const OCR_CONFUSIONS : [(&str, &str); 10] = [
+  ("rn", "m"),
+  ("cl", "d"),
+  ("vv", "w"),
+  ("li", "h"),
+  ("l", "1"),
+  ("i", "1"),
+  ("l", "i"),
+  ("o", "0"),
+  ("s", "5"),
+  ("e", "c"),
+];
+
+// Maximum number of substitutions applied to a single word.
+const MAX_SUBSTITUTIONS : usize = 2;
+
+/// A dictionary word recovered from an OCR'd token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcrCandidate {
+  /// The dictionary word.
+  pub word: Word,
+  /// Pronunciation of the dictionary word.
+  pub polyphone: Polyphone,
+  /// Human readable description of the corrections applied, empty for exact
+  /// matches. Positions are character offsets into the word looked up.
+  pub notes: Vec<String>,
+}
+
+impl Arpabet {
+  /// Look up an OCR'd word. An exact match is returned alone; otherwise common
+  /// OCR confusions are undone (at most two per word) and every dictionary word
+  /// reached is returned, fewest corrections first.
+  pub fn get_polyphone_ocr(&self, word: &str) -> Vec<OcrCandidate> {
+    let word = fold_case(word);
+
+    if let Some(polyphone) = self.get_polyphone_ref(&word) {
+      return vec![OcrCandidate { word, polyphone: polyphone.to_vec(), notes: Vec::new() }];
+    }
+
+    let mut seen : HashSet<String> = HashSet::new();
+    seen.insert(word.clone());
+
+    // Each text carries the offset in the word of each of its characters, so
+    // notes point into the word even after earlier corrections.
+    let origins = (0 .. word.chars().count()).collect::<Vec<usize>>();
+    let mut frontier = vec![(word, origins, Vec::<String>::new())];
+    let mut candidates = Vec::new();
+
+    for _ in 0 .. MAX_SUBSTITUTIONS {
+      let mut next = Vec::new();
+
+      for (text, origins, notes) in frontier.iter() {
+        for (from, to) in confusion_pairs() {
+          for (position, _) in text.match_indices(from) {
+            let corrected = format!("{}{}{}", &text[.. position], to, &text[position + from.len() ..]);
+            if !seen.insert(corrected.clone()) {
+              continue;
+            }
+
+            let start = text[.. position].chars().count();
+            let origin = origins[start];
+            let mut corrected_origins = origins[.. start].to_vec();
+            corrected_origins.extend(to.chars().map(|_| origin));
+            corrected_origins.extend_from_slice(&origins[start + from.chars().count() ..]);
+
+            let mut corrected_notes = notes.clone();
+            corrected_notes.push(format!("'{}' read as '{}' at {}", to, from, origin));
+
+            if let Some(polyphone) = self.get_polyphone_ref(&corrected) {
+              candidates.push(OcrCandidate {
+                word: corrected.clone(),
+                polyphone: polyphone.to_vec(),
+                notes: corrected_notes.clone(),
+              });
+            }
+
+            next.push((corrected, corrected_origins, corrected_notes));
+          }
+        }
+      }
+
+      frontier = next;
+    }
+
+    candidates.sort_by(|a, b| a.notes.len().cmp(&b.notes.len()).then_with(|| a.word.cmp(&b.word)));
+    candidates
+  }
+}
+
+fn confusion_pairs() -> impl Iterator<Item=(&'static str, &'static str)> {
+  OCR_CONFUSIONS.iter()
+    .flat_map(|(a, b)| vec![(*a, *b), (*b, *a)])
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::testing;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("modern", "M AA1 D ER0 N"),
+    ("hello", "HH AH0 L OW1"),
+    ("tool", "T UW1 L"),
+  ];
+
+  #[test]
+  fn exact_match() {
+    let candidates = testing::arpabet(DICTIONARY).get_polyphone_ocr("Hello");
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].word, "hello");
+    assert!(candidates[0].notes.is_empty());
+  }
+
+  #[test]
+  fn single_confusion() {
+    let candidates = testing::arpabet(DICTIONARY).get_polyphone_ocr("rnodern");
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].word, "modern");
+    assert_eq!(candidates[0].notes, vec!["'m' read as 'rn' at 0"]);
+  }
+
+  #[test]
+  fn double_confusion() {
+    let candidates = testing::arpabet(DICTIONARY).get_polyphone_ocr("he1l0");
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].word, "hello");
+    assert_eq!(candidates[0].notes.len(), 2);
+
+    let candidates = testing::arpabet(DICTIONARY).get_polyphone_ocr("t00l");
+    assert_eq!(candidates[0].word, "tool");
+  }
+
+  #[test]
+  fn positions_in_word() {
+    // 'cl' is at offset 3 of the word, though at 2 of the text left by
+    // correcting 'rn'.
+    let candidates = testing::arpabet(DICTIONARY).get_polyphone_ocr("rnoclern");
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].word, "modern");
+    let mut notes = candidates[0].notes.clone();
+    notes.sort();
+    assert_eq!(notes, vec!["'d' read as 'cl' at 3", "'m' read as 'rn' at 0"]);
+  }
+
+  #[test]
+  fn no_candidates() {
+    assert!(testing::arpabet(DICTIONARY).get_polyphone_ocr("xyzzy").is_empty());
+    assert!(testing::arpabet(DICTIONARY).get_polyphone_ocr("t000l").is_empty());
+  }
+}
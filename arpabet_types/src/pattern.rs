@@ -0,0 +1,302 @@
+//! This module contains a small pattern language over phoneme sequences, for
+//! searching the dictionary by pronunciation.
+//!
+//! A pattern is a space-separated list of elements, each matching one phoneme
+//! unless noted:
+//!
+//! * an ARPABET symbol with stress, eg. `AE1`, matches exactly that phoneme
+//! * a vowel without stress, eg. `AE`, matches the vowel with any stress
+//! * `*` matches any consonant
+//! * `V` matches any vowel; `V0`, `V1` and `V2` match vowels of that stress
+//! * `?` matches any phoneme
+//! * `..` matches any sequence of phonemes, including none
+//!
+//! Patterns match whole pronunciations, eg. "K * V1 .. L" matches K, then any
+//! consonant, then a vowel with primary stress, and anything ending in L.
+//!
+//! Patterns can also be built in code, eg.
+//! `PhonemePattern::new().any_consonant().vowel(vowel).any_sequence()`,
+//! without going through the text syntax.
+
+use crate::constants::PHONEME_MAP;
+use crate::error::ArpabetError;
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::Arpabet;
+use std::fmt;
+
+/// A compiled pattern over phoneme sequences. See the module documentation for
+/// the syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhonemePattern {
+  elements: Vec<Element>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Element {
+  Phoneme(Phoneme),
+  StresslessVowel(&'static str),
+  AnyConsonant,
+  AnyVowel(Option<VowelStress>),
+  AnyPhoneme,
+  AnySequence,
+}
+
+impl PhonemePattern {
+  /// An empty pattern, which only matches an empty pronunciation. Extend it
+  /// with the builder methods below.
+  pub fn new() -> Self {
+    Self { elements: Vec::new() }
+  }
+
+  /// Match exactly the phoneme, including vowel stress.
+  pub fn phoneme(mut self, phoneme: Phoneme) -> Self {
+    self.elements.push(Element::Phoneme(phoneme));
+    self
+  }
+
+  /// Match the vowel with any stress.
+  pub fn vowel(mut self, vowel: Vowel) -> Self {
+    self.elements.push(Element::StresslessVowel(vowel.to_str_stressless()));
+    self
+  }
+
+  /// Match any consonant, like `*`.
+  pub fn any_consonant(mut self) -> Self {
+    self.elements.push(Element::AnyConsonant);
+    self
+  }
+
+  /// Match any vowel, like `V`.
+  pub fn any_vowel(mut self) -> Self {
+    self.elements.push(Element::AnyVowel(None));
+    self
+  }
+
+  /// Match any vowel with the stress, like `V0`, `V1` or `V2`.
+  pub fn any_vowel_stressed(mut self, stress: VowelStress) -> Self {
+    let stress = match stress {
+      VowelStress::UnknownStress => None,
+      stress => Some(stress),
+    };
+    self.elements.push(Element::AnyVowel(stress));
+    self
+  }
+
+  /// Match any phoneme, like `?`.
+  pub fn any_phoneme(mut self) -> Self {
+    self.elements.push(Element::AnyPhoneme);
+    self
+  }
+
+  /// Match any sequence of phonemes, including none, like `..`.
+  pub fn any_sequence(mut self) -> Self {
+    self.elements.push(Element::AnySequence);
+    self
+  }
+
+  /// Compile a pattern, eg. "K * V1 .. L".
+  pub fn parse(pattern: &str) -> Result<Self, ArpabetError> {
+    let elements = pattern.split_whitespace()
+      .map(|element| parse_element(element).ok_or_else(|| ArpabetError::StringParseError {
+        description: format!("Invalid element '{}' in pattern '{}'", element, pattern),
+      }))
+      .collect::<Result<Vec<Element>, ArpabetError>>()?;
+    Ok(Self { elements })
+  }
+
+  /// Whether the whole pronunciation matches the pattern.
+  pub fn matches(&self, polyphone: &[Phoneme]) -> bool {
+    // Wildcard matching, backtracking to the most recent `..` on failure.
+    let mut element = 0;
+    let mut phoneme = 0;
+    let mut backtrack : Option<(usize, usize)> = None;
+
+    while phoneme < polyphone.len() {
+      match self.elements.get(element) {
+        Some(Element::AnySequence) => {
+          element += 1;
+          backtrack = Some((element, phoneme));
+          continue;
+        },
+        Some(current) if current.matches(&polyphone[phoneme]) => {
+          element += 1;
+          phoneme += 1;
+          continue;
+        },
+        _ => {},
+      }
+      match backtrack {
+        Some((after_sequence, start)) => {
+          element = after_sequence;
+          phoneme = start + 1;
+          backtrack = Some((after_sequence, start + 1));
+        },
+        None => return false,
+      }
+    }
+
+    self.elements[element ..].iter().all(|e| *e == Element::AnySequence)
+  }
+}
+
+impl Element {
+  fn matches(&self, phoneme: &Phoneme) -> bool {
+    match (self, phoneme) {
+      (Element::Phoneme(wanted), _) => wanted == phoneme,
+      (Element::StresslessVowel(name), Phoneme::Vowel(vowel)) => vowel.to_str_stressless() == *name,
+      (Element::AnyConsonant, Phoneme::Consonant(_)) => true,
+      (Element::AnyVowel(None), Phoneme::Vowel(_)) => true,
+      (Element::AnyVowel(Some(stress)), Phoneme::Vowel(vowel)) => vowel.get_stress() == stress,
+      (Element::AnyPhoneme, _) => true,
+      _ => false,
+    }
+  }
+}
+
+fn parse_element(element: &str) -> Option<Element> {
+  let parsed = match element {
+    "*" => Element::AnyConsonant,
+    "?" => Element::AnyPhoneme,
+    ".." => Element::AnySequence,
+    "V" => Element::AnyVowel(None),
+    "V0" => Element::AnyVowel(Some(VowelStress::NoStress)),
+    "V1" => Element::AnyVowel(Some(VowelStress::PrimaryStress)),
+    "V2" => Element::AnyVowel(Some(VowelStress::SecondaryStress)),
+    // NB: Vowels without a stress digit parse with unknown stress.
+    _ => match PHONEME_MAP.get(element)? {
+      Phoneme::Vowel(vowel) if *vowel.get_stress() == VowelStress::UnknownStress =>
+        Element::StresslessVowel(vowel.to_str_stressless()),
+      phoneme => Element::Phoneme(*phoneme),
+    },
+  };
+  Some(parsed)
+}
+
+impl fmt::Display for PhonemePattern {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, element) in self.elements.iter().enumerate() {
+      if i > 0 {
+        write!(f, " ")?;
+      }
+      match element {
+        Element::Phoneme(Phoneme::Consonant(consonant)) => write!(f, "{}", consonant.to_str())?,
+        Element::Phoneme(Phoneme::Vowel(vowel)) => write!(f, "{}", vowel.to_str())?,
+        Element::StresslessVowel(name) => write!(f, "{}", name)?,
+        Element::AnyConsonant => write!(f, "*")?,
+        Element::AnyVowel(None) => write!(f, "V")?,
+        Element::AnyVowel(Some(VowelStress::NoStress)) => write!(f, "V0")?,
+        Element::AnyVowel(Some(VowelStress::PrimaryStress)) => write!(f, "V1")?,
+        Element::AnyVowel(Some(VowelStress::SecondaryStress)) => write!(f, "V2")?,
+        Element::AnyVowel(Some(VowelStress::UnknownStress)) => write!(f, "V")?,
+        Element::AnyPhoneme => write!(f, "?")?,
+        Element::AnySequence => write!(f, "..")?,
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Arpabet {
+  /// Find the words with a pronunciation matching the pattern. Results are
+  /// ranked by attached frequencies, if any, then alphabetically.
+  pub fn words_matching(&self, pattern: &PhonemePattern) -> Vec<&str> {
+    self.words_where(|polyphone| pattern.matches(polyphone))
+  }
+
+  /// Find the words with a pronunciation matching the pattern text, eg.
+  /// "K AE1 .." for words starting with K AE1. See `words_matching`.
+  pub fn search_phonemes(&self, pattern: &str) -> Result<Vec<&str>, ArpabetError> {
+    Ok(self.words_matching(&PhonemePattern::parse(pattern)?))
+  }
+}
+
+impl Default for PhonemePattern {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::Consonant;
+  use crate::testing::polyphone;
+
+  fn matches(pattern: &str, pronunciation: &str) -> bool {
+    PhonemePattern::parse(pattern).unwrap().matches(&polyphone(pronunciation))
+  }
+
+  #[test]
+  fn parse() {
+    let pattern = PhonemePattern::parse("K  * V1 .. L ? AE V").unwrap();
+    assert_eq!(pattern.to_string(), "K * V1 .. L ? AE V");
+
+    assert!(PhonemePattern::parse("K X L").is_err());
+    assert!(PhonemePattern::parse("V3").is_err());
+    assert!(PhonemePattern::parse("...").is_err());
+  }
+
+  #[test]
+  fn exact_and_classes() {
+    assert!(matches("K AE1 T", "K AE1 T"));
+    assert!(!matches("K AE1 T", "K AE2 T"));
+    assert!(matches("K AE T", "K AE2 T"));
+    assert!(matches("* V *", "K AE1 T"));
+    assert!(!matches("* V", "K AE1 T"));
+    assert!(!matches("* * *", "K AE1 T"));
+    assert!(matches("? ? ?", "K AE1 T"));
+    assert!(matches("K V0", "K AH0"));
+    assert!(!matches("K V1", "K AH0"));
+  }
+
+  #[test]
+  fn sequences() {
+    // 'clinical'
+    assert!(matches("K * V1 .. L", "K L IH1 N IH0 K AH0 L"));
+    assert!(!matches("K * V1 .. L", "K L IH1 N IH0 K"));
+    assert!(matches("K * V1 .. L", "K R IH1 L"));
+    assert!(matches("..", "K AE1 T"));
+    assert!(PhonemePattern::parse("..").unwrap().matches(&[]));
+    assert!(!PhonemePattern::parse("?").unwrap().matches(&[]));
+    assert!(matches(".. T .. T", "T AH0 M EY1 T OW2 T"));
+    assert!(!matches(".. T .. T ..", "T AH0 M EY1 K"));
+    assert!(matches(".. AE ..", "B L AE1 K"));
+  }
+
+  #[test]
+  fn words_matching() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+    arpa.insert("cattle".to_string(), polyphone("K AE1 T AH0 L"));
+    arpa.insert("clinical".to_string(), polyphone("K L IH1 N IH0 K AH0 L"));
+    arpa.insert("crawl".to_string(), polyphone("K R AO1 L"));
+    arpa.insert("crawl(1)".to_string(), polyphone("K R AA1 L"));
+
+    let pattern = PhonemePattern::parse("K * V1 .. L").unwrap();
+    assert_eq!(arpa.words_matching(&pattern), vec!["clinical", "crawl"]);
+
+    let pattern = PhonemePattern::parse("K AE1 ..").unwrap();
+    assert_eq!(arpa.words_matching(&pattern), vec!["cat", "cattle"]);
+
+    assert_eq!(arpa.search_phonemes("K AE1 *").unwrap(), vec!["cat"]);
+    assert_eq!(arpa.search_phonemes(".. L").unwrap(), vec!["cattle", "clinical", "crawl"]);
+    assert!(arpa.search_phonemes("K AE1 X").is_err());
+  }
+
+  #[test]
+  fn builder() {
+    let pattern = PhonemePattern::new()
+      .phoneme(Phoneme::Consonant(Consonant::K))
+      .any_consonant()
+      .any_vowel_stressed(VowelStress::PrimaryStress)
+      .any_sequence()
+      .vowel(Vowel::AH(VowelStress::NoStress))
+      .any_phoneme()
+      .any_vowel();
+    assert_eq!(pattern, PhonemePattern::parse("K * V1 .. AH ? V").unwrap());
+    assert_eq!(pattern.to_string(), "K * V1 .. AH ? V");
+
+    assert!(PhonemePattern::new().matches(&[]));
+    assert!(!PhonemePattern::new().matches(&polyphone("K")));
+  }
+}
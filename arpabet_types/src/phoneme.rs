@@ -5,7 +5,7 @@ use crate::error::ArpabetError;
 use std::convert::TryFrom;
 
 /// Consonants in ARPABET.
-#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum Consonant {
   /// B, as in _b_uy.
   B,
@@ -111,7 +111,7 @@ impl Consonant {
 }
 
 /// A stress value for a single vowel.
-#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum VowelStress {
   /// An unknown amount of stress, perhaps due to omission.
   /// This is not an included value in ARPABET but is added for the sake of parsing.
@@ -137,7 +137,7 @@ impl VowelStress {
 }
 
 /// Vowels in ARPABET.
-#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum Vowel {
   /// AA, as in b_al_m or b_o_t.
   AA(VowelStress),
@@ -205,6 +205,31 @@ impl Vowel {
     }
   }
 
+  /// Get the same vowel with a different stress.
+  pub fn with_stress(&self, stress: VowelStress) -> Vowel {
+    match self {
+      Vowel::AA(_) => Vowel::AA(stress),
+      Vowel::AE(_) => Vowel::AE(stress),
+      Vowel::AH(_) => Vowel::AH(stress),
+      Vowel::AO(_) => Vowel::AO(stress),
+      Vowel::AW(_) => Vowel::AW(stress),
+      Vowel::AX(_) => Vowel::AX(stress),
+      Vowel::AXR(_) => Vowel::AXR(stress),
+      Vowel::AY(_) => Vowel::AY(stress),
+      Vowel::EH(_) => Vowel::EH(stress),
+      Vowel::ER(_) => Vowel::ER(stress),
+      Vowel::EY(_) => Vowel::EY(stress),
+      Vowel::IH(_) => Vowel::IH(stress),
+      Vowel::IX(_) => Vowel::IX(stress),
+      Vowel::IY(_) => Vowel::IY(stress),
+      Vowel::OW(_) => Vowel::OW(stress),
+      Vowel::OY(_) => Vowel::OY(stress),
+      Vowel::UH(_) => Vowel::UH(stress),
+      Vowel::UW(_) => Vowel::UW(stress),
+      Vowel::UX(_) => Vowel::UX(stress),
+    }
+  }
+
   /// Get the string representation of the vowel phoneme, without the stress.
   pub fn to_str_stressless(&self) -> &'static str {
     match self {
@@ -352,7 +377,7 @@ impl Vowel {
 }
 
 /// All of the phonemes in ARPABET.
-#[derive(Copy,Clone,Debug,PartialEq)]
+#[derive(Copy,Clone,Debug,PartialEq,Eq,Hash)]
 pub enum Phoneme {
   /// ARPABET consonants
   Consonant(Consonant),
@@ -368,6 +393,15 @@ impl Phoneme {
       Phoneme::Vowel(vowel) => vowel.to_str(),
     }
   }
+
+  /// Get the phoneme with any vowel stress removed (set to unknown stress),
+  /// for comparing pronunciations regardless of stress.
+  pub fn stressless(&self) -> Phoneme {
+    match self {
+      Phoneme::Consonant(consonant) => Phoneme::Consonant(*consonant),
+      Phoneme::Vowel(vowel) => Phoneme::Vowel(vowel.with_stress(VowelStress::UnknownStress)),
+    }
+  }
 }
 
 impl TryFrom<&str> for Phoneme {
@@ -603,6 +637,23 @@ mod tests {
     }
   }
 
+  #[test]
+  fn vowel_with_stress() {
+    for vowel in ALL_VOWELS.iter() {
+      let restressed = vowel.with_stress(VowelStress::SecondaryStress);
+      expect!(restressed.get_stress()).to(be_eq(&VowelStress::SecondaryStress));
+      expect!(restressed.to_str_stressless()).to(be_eq(vowel.to_str_stressless()));
+    }
+  }
+
+  #[test]
+  fn phoneme_stressless() {
+    expect!(Phoneme::Vowel(Vowel::AA(VowelStress::PrimaryStress)).stressless())
+      .to(be_eq(Phoneme::Vowel(Vowel::AA(VowelStress::UnknownStress))));
+    expect!(Phoneme::Consonant(Consonant::B).stressless())
+      .to(be_eq(Phoneme::Consonant(Consonant::B)));
+  }
+
   #[test]
   fn phoneme_to_str() {
     expect!(Phoneme::Vowel(Vowel::AA(VowelStress::PrimaryStress)).to_str()).to(be_eq("AA1"));
@@ -0,0 +1,172 @@
+//! This module predicts prosodic phrase breaks (pauses) in transcribed
+//! sentences, which makes long sentences sound more natural when synthesized.
+
+use crate::extensions::{Punctuation, SentenceToken};
+
+/// Options for phrase break prediction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BreakOptions {
+  /// The most words allowed between breaks. Longer stretches without
+  /// punctuation are divided into near-equal phrases. None never divides them.
+  pub max_words_between_breaks: Option<usize>,
+}
+
+impl Default for BreakOptions {
+  fn default() -> Self {
+    Self {
+      max_words_between_breaks: Some(DEFAULT_MAX_WORDS),
+    }
+  }
+}
+
+// Roughly the length of a breath group in read English.
+const DEFAULT_MAX_WORDS : usize = 8;
+
+/// Insert phrase breaks into transcribed sentence tokens, using the default
+/// options. See `predict_breaks_with_options`.
+pub fn predict_breaks(tokens: &[SentenceToken]) -> Vec<SentenceToken> {
+  predict_breaks_with_options(tokens, &BreakOptions::default())
+}
+
+/// Insert phrase breaks into transcribed sentence tokens, eg. the output of
+/// `Arpabet::transcribe_sentence`.
+///
+/// A break follows every clause boundary (comma, full stop, question and
+/// exclamation marks, interjections and ellipses) except at the end of the
+/// utterance. Stretches of too many words between boundaries are divided by
+/// breaks placed before the spaces between words.
+pub fn predict_breaks_with_options(tokens: &[SentenceToken], options: &BreakOptions)
+  -> Vec<SentenceToken>
+{
+  let mut output = Vec::with_capacity(tokens.len());
+  // Positions in the output of the spaces between words of the current stretch.
+  let mut spaces = Vec::new();
+  let mut stretch_has_words = false;
+  let mut pending_break = false;
+
+  for token in tokens.iter() {
+    match token {
+      SentenceToken::Punctuation(Punctuation::Quote) => {},
+      SentenceToken::Punctuation(Punctuation::EndToken) => pending_break = false,
+      SentenceToken::Punctuation(punctuation) if is_clause_boundary(*punctuation) => {
+        divide_stretch(&mut output, &spaces, options);
+        spaces.clear();
+        stretch_has_words = false;
+        pending_break = *punctuation != Punctuation::Break;
+      },
+      _ => {
+        if pending_break {
+          output.push(SentenceToken::Punctuation(Punctuation::Break));
+          pending_break = false;
+        }
+        match token {
+          SentenceToken::Phoneme(_) => stretch_has_words = true,
+          SentenceToken::Punctuation(Punctuation::Space) if stretch_has_words => {
+            spaces.push(output.len());
+          },
+          SentenceToken::Punctuation(_) => {},
+        }
+      },
+    }
+    output.push(*token);
+  }
+
+  divide_stretch(&mut output, &spaces, options);
+  output
+}
+
+fn is_clause_boundary(punctuation: Punctuation) -> bool {
+  match punctuation {
+    Punctuation::Comma
+        | Punctuation::Period
+        | Punctuation::Question
+        | Punctuation::Exclamation
+        | Punctuation::Interjection
+        | Punctuation::Ellipsis
+        | Punctuation::Break
+        | Punctuation::Annotation => true,
+    Punctuation::StartToken
+        | Punctuation::Space
+        | Punctuation::Quote
+        | Punctuation::EndToken => false,
+  }
+}
+
+// Divide a stretch of words, given the output positions of the spaces between
+// them, into the fewest near-equal phrases within the word limit.
+fn divide_stretch(output: &mut Vec<SentenceToken>, spaces: &[usize], options: &BreakOptions) {
+  let max_words = match options.max_words_between_breaks {
+    Some(max_words) if max_words > 0 => max_words,
+    _ => return,
+  };
+
+  let words = spaces.len() + 1;
+  if words <= max_words {
+    return;
+  }
+
+  let phrases = words.div_ceil(max_words);
+
+  // Insert from the back so earlier positions stay valid.
+  for phrase in (1 .. phrases).rev() {
+    let first_word = phrase * words / phrases;
+    output.insert(spaces[first_word - 1], SentenceToken::Punctuation(Punctuation::Break));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Phoneme, Vowel, VowelStress};
+
+  // Build tokens from a compact notation: '_' is a space, 'w' a one-phoneme
+  // word, and other characters punctuation.
+  fn tokens(text: &str) -> Vec<SentenceToken> {
+    text.chars()
+      .map(|c| match c {
+        'w' => SentenceToken::Phoneme(Phoneme::Vowel(Vowel::AH(VowelStress::PrimaryStress))),
+        '_' => SentenceToken::Punctuation(Punctuation::Space),
+        ',' => SentenceToken::Punctuation(Punctuation::Comma),
+        '.' => SentenceToken::Punctuation(Punctuation::Period),
+        '"' => SentenceToken::Punctuation(Punctuation::Quote),
+        '|' => SentenceToken::Punctuation(Punctuation::Break),
+        '<' => SentenceToken::Punctuation(Punctuation::StartToken),
+        '>' => SentenceToken::Punctuation(Punctuation::EndToken),
+        _ => unreachable!(),
+      })
+      .collect()
+  }
+
+  fn with_max(max_words: Option<usize>) -> BreakOptions {
+    BreakOptions { max_words_between_breaks: max_words }
+  }
+
+  #[test]
+  fn clause_boundaries() {
+    assert_eq!(predict_breaks(&tokens("<w_w,_w_w.>")), tokens("<w_w,|_w_w.>"));
+    assert_eq!(predict_breaks(&tokens("<w,\"_w.\">")), tokens("<w,\"|_w.\">"));
+    assert_eq!(predict_breaks(&tokens("<w.>")), tokens("<w.>"));
+    // Existing breaks aren't doubled.
+    assert_eq!(predict_breaks(&tokens("<w|_w>")), tokens("<w|_w>"));
+  }
+
+  #[test]
+  fn long_stretches() {
+    let options = with_max(Some(3));
+    assert_eq!(predict_breaks_with_options(&tokens("<w_w_w>"), &options), tokens("<w_w_w>"));
+    assert_eq!(predict_breaks_with_options(&tokens("<w_w_w_w>"), &options),
+      tokens("<w_w|_w_w>"));
+    assert_eq!(predict_breaks_with_options(&tokens("<w_w_w_w_w_w_w.>"), &options),
+      tokens("<w_w|_w_w|_w_w_w.>"));
+    // Counting restarts at clause boundaries.
+    assert_eq!(predict_breaks_with_options(&tokens("<w_w_w,_w_w_w>"), &options),
+      tokens("<w_w_w,|_w_w_w>"));
+  }
+
+  #[test]
+  fn unlimited_stretches() {
+    let text = "<w_w_w_w_w_w_w_w_w_w_w_w>";
+    assert_eq!(predict_breaks_with_options(&tokens(text), &with_max(None)), tokens(text));
+    assert_eq!(predict_breaks(&tokens(text)), tokens("<w_w_w_w_w_w|_w_w_w_w_w_w>"));
+  }
+}
@@ -0,0 +1,566 @@
+//! This module gathers every setting that affects transcription into one
+//! `PipelineConfig`, so a service's behavior is fully determined by a value
+//! that can be reviewed, versioned and shared between services. With the
+//! `json` feature, configs convert to and from JSON, and any serde format,
+//! eg. TOML:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "numbers": true,
+//!   "unknown_words": "spell",
+//!   "stress": "strip",
+//!   "dialect": ["cot-caught"],
+//!   "punctuation": [["quote", "drop"], ["ellipsis", "comma"]]
+//! }
+//! ```
+//!
+//! Settings left out keep their defaults. Enumerated settings are written in
+//! lowercase with hyphens, eg. "cot-caught" or "utf16"; punctuation by its
+//! token name without brackets, eg. "comma", as a list of [from, to] rules in
+//! the order they apply.
+
+use crate::casing::CaseFolding;
+use crate::dialect::{Dialect, DialectFeature};
+use crate::error::ArpabetError;
+use crate::extensions::{Punctuation, SentenceToken};
+use crate::lemmatize::english_stemmer;
+use crate::phoneme::{Phoneme, VowelStress};
+use crate::stress::{assign_stress, PolyphoneStress};
+use crate::texting::TextingNormalizer;
+use crate::tokenizer::{AnnotationPolicy, OffsetUnit, TokenizerOptions};
+use crate::transcribe::{AlphanumericPolicy, SingleLetterPolicy, TranscribeOptions,
+  TranscribedWord, UnknownWordPolicy};
+use crate::{Arpabet, Polyphone, Word};
+use std::collections::BTreeMap;
+
+/// The version of the configuration schema. Configs of other versions are
+/// rejected rather than read differently.
+pub const PIPELINE_CONFIG_VERSION : u32 = 1;
+
+/// What is done with the stress of transcribed vowels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StressHandling {
+  /// Keep stress as found.
+  #[default]
+  Keep,
+  /// Remove stress, eg. for aligners that ignore it.
+  Strip,
+  /// Assign stress to words with vowels of unknown stress. See
+  /// `stress::assign_stress`.
+  Assign,
+}
+
+/// Every setting of the text-to-phoneme pipeline. See the module
+/// documentation. The default config transcribes as `transcribe_sentence`
+/// does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineConfig {
+  /// How bracketed annotations are tokenized.
+  pub annotations: AnnotationPolicy,
+  /// How words are folded to lowercase for lookup.
+  pub case_folding: CaseFolding,
+  /// How one-character words are pronounced.
+  pub single_letters: SingleLetterPolicy,
+  /// Use the weak forms of function words.
+  pub weak_forms: bool,
+  /// Read texting-speak with the default tables, eg. "gr8" as 'great'.
+  pub texting: bool,
+  /// Read likely initialisms letter by letter.
+  pub initialisms: bool,
+  /// Read numbers as words.
+  pub numbers: bool,
+  /// How words containing digits are pronounced.
+  pub alphanumerics: AlphanumericPolicy,
+  /// Pronunciations of words containing digits, eg. "mp3", that take
+  /// precedence over the dictionary.
+  pub alphanumeric_overlay: BTreeMap<Word, Polyphone>,
+  /// The most words a multi-word entry may span.
+  pub max_phrase_length: usize,
+  /// The unit word locations are reported in.
+  pub offsets: OffsetUnit,
+  /// What becomes of words that can't be pronounced.
+  pub unknown_words: UnknownWordPolicy,
+  /// Pronounce words missing from the dictionary from their lemmas with the
+  /// `EnglishStemmer`, eg. "jumped" from 'jump'.
+  pub lemmatize: bool,
+  /// What is done with stress.
+  pub stress: StressHandling,
+  /// The dialect features pronunciations are rewritten for.
+  pub dialect: Vec<DialectFeature>,
+  /// Punctuation tokens replaced in the output, or dropped where None. The
+  /// first rule for a token applies.
+  pub punctuation: Vec<(Punctuation, Option<Punctuation>)>,
+}
+
+impl Default for PipelineConfig {
+  fn default() -> Self {
+    let options = TranscribeOptions::default();
+    Self {
+      annotations: options.tokenizer.annotations,
+      case_folding: options.case_folding,
+      single_letters: options.single_letters,
+      weak_forms: options.weak_forms,
+      texting: options.texting.is_some(),
+      initialisms: options.initialisms,
+      numbers: options.numbers,
+      alphanumerics: options.alphanumerics,
+      alphanumeric_overlay: options.alphanumeric_overlay.into_iter().collect(),
+      max_phrase_length: options.max_phrase_length,
+      offsets: options.offsets,
+      unknown_words: options.unknown_words,
+      lemmatize: options.lemmatizer.is_some(),
+      stress: StressHandling::default(),
+      dialect: Vec::new(),
+      punctuation: Vec::new(),
+    }
+  }
+}
+
+impl PipelineConfig {
+  /// The transcription options the config sets.
+  pub fn transcribe_options(&self) -> TranscribeOptions {
+    TranscribeOptions {
+      single_letters: self.single_letters,
+      weak_forms: self.weak_forms,
+      tokenizer: TokenizerOptions { annotations: self.annotations },
+      texting: if self.texting { Some(TextingNormalizer::new()) } else { None },
+      initialisms: self.initialisms,
+      numbers: self.numbers,
+      alphanumerics: self.alphanumerics,
+      alphanumeric_overlay: self.alphanumeric_overlay.clone().into_iter().collect(),
+      case_folding: self.case_folding,
+      max_phrase_length: self.max_phrase_length,
+      offsets: self.offsets,
+      unknown_words: self.unknown_words,
+      lemmatizer: if self.lemmatize { Some(english_stemmer()) } else { None },
+    }
+  }
+}
+
+/// A pipeline ready to transcribe, built once from a config.
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+  config: PipelineConfig,
+  options: TranscribeOptions,
+  dialect: Dialect,
+}
+
+impl Pipeline {
+  /// Build the pipeline the config describes.
+  pub fn new(config: PipelineConfig) -> Self {
+    Self {
+      options: config.transcribe_options(),
+      dialect: Dialect::with_features(&config.dialect),
+      config,
+    }
+  }
+
+  /// The config the pipeline was built from.
+  pub fn config(&self) -> &PipelineConfig {
+    &self.config
+  }
+
+  /// The transcription options in use.
+  pub fn options(&self) -> &TranscribeOptions {
+    &self.options
+  }
+
+  /// Transcribe text into sentence tokens, as by
+  /// `Arpabet::transcribe_sentence_with_options`, then rewrite them for the
+  /// dialect, stress handling and punctuation rules, in that order.
+  pub fn transcribe(&self, arpabet: &Arpabet, text: &str)
+    -> Result<Vec<SentenceToken>, ArpabetError>
+  {
+    let tokens = arpabet.transcribe_sentence_with_options(text, &self.options)?;
+    let tokens = self.dialect.apply_to_tokens(&tokens);
+
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut word = Vec::new();
+    for token in tokens {
+      match token {
+        SentenceToken::Phoneme(phoneme) => word.push(phoneme),
+        SentenceToken::Punctuation(punctuation) => {
+          output.extend(self.finish(&mut word).into_iter().map(SentenceToken::Phoneme));
+          if let Some(punctuation) = self.punctuation(punctuation) {
+            output.push(SentenceToken::Punctuation(punctuation));
+          }
+        },
+      }
+    }
+    output.extend(self.finish(&mut word).into_iter().map(SentenceToken::Phoneme));
+    Ok(output)
+  }
+
+  /// Transcribe text word by word, as by `Arpabet::transcribe_words`, then
+  /// rewrite the pronunciations for the dialect and stress handling.
+  pub fn transcribe_words(&self, arpabet: &Arpabet, text: &str)
+    -> Result<Vec<TranscribedWord>, ArpabetError>
+  {
+    let mut words = arpabet.transcribe_words(text, &self.options)?;
+    for word in words.iter_mut() {
+      word.polyphone = self.dialect.apply(&word.polyphone);
+      word.polyphone = self.finish(&mut word.polyphone);
+    }
+    Ok(words)
+  }
+
+  // Apply the stress handling to a word, emptying it.
+  fn finish(&self, word: &mut Polyphone) -> Polyphone {
+    let mut word = std::mem::take(word);
+    match self.config.stress {
+      StressHandling::Keep => {},
+      StressHandling::Strip => word = word.strip_stress(),
+      StressHandling::Assign => {
+        let unknown = word.iter().any(|phoneme| match phoneme {
+          Phoneme::Vowel(vowel) => *vowel.get_stress() == VowelStress::UnknownStress,
+          Phoneme::Consonant(_) => false,
+        });
+        if unknown {
+          assign_stress(&mut word);
+        }
+      },
+    }
+    word
+  }
+
+  // The punctuation token to output for one transcribed, if any.
+  fn punctuation(&self, punctuation: Punctuation) -> Option<Punctuation> {
+    self.config.punctuation.iter()
+      .find(|(from, _)| *from == punctuation)
+      .map_or(Some(punctuation), |(_, to)| *to)
+  }
+}
+
+impl From<PipelineConfig> for Pipeline {
+  fn from(config: PipelineConfig) -> Self {
+    Self::new(config)
+  }
+}
+
+#[cfg(feature = "json")]
+mod json {
+  use super::*;
+  use crate::constants::ALL_PUNCTUATION;
+  use serde::de::Error;
+  use serde::{Deserialize, Deserializer, Serialize, Serializer};
+  use serde_json::{json, Map, Value};
+  use std::convert::TryFrom;
+
+  impl PipelineConfig {
+    /// Write the config as JSON. See the module documentation.
+    pub fn to_json(&self) -> String {
+      self.json_value().to_string()
+    }
+
+    /// Write the config as indented JSON.
+    pub fn to_json_pretty(&self) -> String {
+      serde_json::to_string_pretty(&self.json_value()).unwrap_or_default()
+    }
+
+    /// Read a config from JSON. Fails with `ArpabetError::StringParseError` on
+    /// malformed JSON, unknown settings or values, or another schema version.
+    pub fn from_json(json: &str) -> Result<Self, ArpabetError> {
+      let value : Value = serde_json::from_str(json)
+        .map_err(|err| config_error(format!("Invalid JSON: {}", err)))?;
+      Self::from_json_value(&value)
+    }
+
+    fn json_value(&self) -> Value {
+      let overlay : Map<String, Value> = self.alphanumeric_overlay.iter()
+        .map(|(word, polyphone)| {
+          let phonemes : Vec<&str> = polyphone.iter().map(Phoneme::to_str).collect();
+          (word.clone(), Value::from(phonemes.join(" ")))
+        })
+        .collect();
+      let punctuation : Vec<Value> = self.punctuation.iter()
+        .map(|(from, to)| {
+          let to = to.map_or("drop", |to| punctuation_name(&to));
+          json!([punctuation_name(from), to])
+        })
+        .collect();
+      json!({
+        "version": PIPELINE_CONFIG_VERSION,
+        "annotations": name(&ANNOTATIONS, self.annotations),
+        "case_folding": name(&CASE_FOLDINGS, self.case_folding),
+        "single_letters": name(&SINGLE_LETTERS, self.single_letters),
+        "weak_forms": self.weak_forms,
+        "texting": self.texting,
+        "initialisms": self.initialisms,
+        "numbers": self.numbers,
+        "alphanumerics": name(&ALPHANUMERICS, self.alphanumerics),
+        "alphanumeric_overlay": overlay,
+        "max_phrase_length": self.max_phrase_length,
+        "offsets": name(&OFFSETS, self.offsets),
+        "unknown_words": name(&UNKNOWN_WORDS, self.unknown_words),
+        "lemmatize": self.lemmatize,
+        "stress": name(&STRESS, self.stress),
+        "dialect": self.dialect.iter().map(|feature| name(&DIALECT, *feature)).collect::<Vec<_>>(),
+        "punctuation": punctuation,
+      })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, ArpabetError> {
+      let settings = value.as_object()
+        .ok_or_else(|| config_error("Expected an object of settings".to_string()))?;
+      match settings.get("version").and_then(Value::as_u64) {
+        Some(version) if version == u64::from(PIPELINE_CONFIG_VERSION) => {},
+        _ => return Err(config_error(format!("Expected version {}", PIPELINE_CONFIG_VERSION))),
+      }
+
+      let mut config = Self::default();
+      for (key, value) in settings {
+        match key.as_str() {
+          "version" => {},
+          "annotations" => config.annotations = parse_name(&ANNOTATIONS, key, value)?,
+          "case_folding" => config.case_folding = parse_name(&CASE_FOLDINGS, key, value)?,
+          "single_letters" => config.single_letters = parse_name(&SINGLE_LETTERS, key, value)?,
+          "weak_forms" => config.weak_forms = parse_bool(key, value)?,
+          "texting" => config.texting = parse_bool(key, value)?,
+          "initialisms" => config.initialisms = parse_bool(key, value)?,
+          "numbers" => config.numbers = parse_bool(key, value)?,
+          "alphanumerics" => config.alphanumerics = parse_name(&ALPHANUMERICS, key, value)?,
+          "alphanumeric_overlay" => {
+            for (word, pronunciation) in parse_object(key, value)? {
+              let polyphone = pronunciation.as_str()
+                .map(|pronunciation| pronunciation.split_whitespace()
+                  .map(Phoneme::try_from)
+                  .collect::<Result<Polyphone, ArpabetError>>())
+                .ok_or_else(|| invalid(key, pronunciation))??;
+              config.alphanumeric_overlay.insert(word.clone(), polyphone);
+            }
+          },
+          "max_phrase_length" => {
+            let length = value.as_u64().ok_or_else(|| invalid(key, value))?;
+            config.max_phrase_length = usize::try_from(length).map_err(|_| invalid(key, value))?;
+          },
+          "offsets" => config.offsets = parse_name(&OFFSETS, key, value)?,
+          "unknown_words" => config.unknown_words = parse_name(&UNKNOWN_WORDS, key, value)?,
+          "lemmatize" => config.lemmatize = parse_bool(key, value)?,
+          "stress" => config.stress = parse_name(&STRESS, key, value)?,
+          "dialect" => {
+            config.dialect = value.as_array()
+              .ok_or_else(|| invalid(key, value))?
+              .iter()
+              .map(|feature| parse_name(&DIALECT, key, feature))
+              .collect::<Result<_, _>>()?;
+          },
+          "punctuation" => {
+            for rule in value.as_array().ok_or_else(|| invalid(key, value))? {
+              let (from, to) = match rule.as_array().map(Vec::as_slice) {
+                Some([from, to]) => (from.as_str(), to.as_str()),
+                _ => return Err(invalid(key, rule)),
+              };
+              let from = from.and_then(punctuation_from_name).ok_or_else(|| invalid(key, rule))?;
+              let to = match to {
+                Some("drop") => None,
+                Some(to) => Some(punctuation_from_name(to).ok_or_else(|| invalid(key, rule))?),
+                None => return Err(invalid(key, rule)),
+              };
+              config.punctuation.push((from, to));
+            }
+          },
+          _ => return Err(config_error(format!("Unknown setting '{}'", key))),
+        }
+      }
+      Ok(config)
+    }
+  }
+
+  impl Serialize for PipelineConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      self.json_value().serialize(serializer)
+    }
+  }
+
+  impl<'de> Deserialize<'de> for PipelineConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+      let value = Value::deserialize(deserializer)?;
+      Self::from_json_value(&value).map_err(D::Error::custom)
+    }
+  }
+
+  const ANNOTATIONS : [(&str, AnnotationPolicy); 3] = [
+    ("read", AnnotationPolicy::Read),
+    ("emit", AnnotationPolicy::Emit),
+    ("skip", AnnotationPolicy::Skip),
+  ];
+
+  const CASE_FOLDINGS : [(&str, CaseFolding); 2] = [
+    ("english", CaseFolding::English),
+    ("unicode", CaseFolding::Unicode),
+  ];
+
+  const SINGLE_LETTERS : [(&str, SingleLetterPolicy); 3] = [
+    ("contextual", SingleLetterPolicy::Contextual),
+    ("word", SingleLetterPolicy::Word),
+    ("letter", SingleLetterPolicy::Letter),
+  ];
+
+  const ALPHANUMERICS : [(&str, AlphanumericPolicy); 3] = [
+    ("dictionary", AlphanumericPolicy::Dictionary),
+    ("split", AlphanumericPolicy::Split),
+    ("spell", AlphanumericPolicy::Spell),
+  ];
+
+  const OFFSETS : [(&str, OffsetUnit); 4] = [
+    ("bytes", OffsetUnit::Bytes),
+    ("chars", OffsetUnit::Chars),
+    ("utf16", OffsetUnit::Utf16),
+    ("graphemes", OffsetUnit::Graphemes),
+  ];
+
+  const UNKNOWN_WORDS : [(&str, UnknownWordPolicy); 3] = [
+    ("fail", UnknownWordPolicy::Fail),
+    ("skip", UnknownWordPolicy::Skip),
+    ("spell", UnknownWordPolicy::Spell),
+  ];
+
+  const STRESS : [(&str, StressHandling); 3] = [
+    ("keep", StressHandling::Keep),
+    ("strip", StressHandling::Strip),
+    ("assign", StressHandling::Assign),
+  ];
+
+  const DIALECT : [(&str, DialectFeature); 3] = [
+    ("cot-caught", DialectFeature::CotCaught),
+    ("pin-pen", DialectFeature::PinPen),
+    ("non-rhotic", DialectFeature::NonRhotic),
+  ];
+
+  fn name<T: PartialEq>(names: &[(&'static str, T)], value: T) -> &'static str {
+    names.iter()
+      .find(|(_, named)| *named == value)
+      .map(|(name, _)| *name)
+      .expect("Every value is named.")
+  }
+
+  fn parse_name<T: Copy>(names: &[(&str, T)], key: &str, value: &Value) -> Result<T, ArpabetError> {
+    names.iter()
+      .find(|(name, _)| Some(*name) == value.as_str())
+      .map(|(_, named)| *named)
+      .ok_or_else(|| invalid(key, value))
+  }
+
+  fn parse_bool(key: &str, value: &Value) -> Result<bool, ArpabetError> {
+    value.as_bool().ok_or_else(|| invalid(key, value))
+  }
+
+  fn parse_object<'a>(key: &str, value: &'a Value) -> Result<&'a Map<String, Value>, ArpabetError> {
+    value.as_object().ok_or_else(|| invalid(key, value))
+  }
+
+  // The token name without brackets, eg. "comma".
+  fn punctuation_name(punctuation: &Punctuation) -> &'static str {
+    punctuation.to_str().trim_start_matches('[').trim_end_matches(']')
+  }
+
+  fn punctuation_from_name(name: &str) -> Option<Punctuation> {
+    ALL_PUNCTUATION.iter().copied().find(|punctuation| punctuation_name(punctuation) == name)
+  }
+
+  fn invalid(key: &str, value: &Value) -> ArpabetError {
+    config_error(format!("Invalid value {} for '{}'", value, key))
+  }
+
+  fn config_error(description: String) -> ArpabetError {
+    ArpabetError::StringParseError { description }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+
+  fn strings(tokens: &[SentenceToken]) -> Vec<&'static str> {
+    tokens.iter().map(|token| token.to_str()).collect()
+  }
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("the", "DH AH0"),
+    ("car", "K AA1 R"),
+    ("caught", "K AO1 T"),
+    ("it", "IH1 T"),
+    ("zorp", "Z AO R P"),
+  ];
+
+  #[test]
+  fn default_config() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let pipeline = Pipeline::new(PipelineConfig::default());
+    assert_eq!(pipeline.options(), &TranscribeOptions::default());
+    assert_eq!(pipeline.transcribe(&arpa, "The car, caught it.").unwrap(),
+      arpa.transcribe_sentence("The car, caught it.").unwrap());
+  }
+
+  #[test]
+  fn transcription() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let pipeline = Pipeline::new(PipelineConfig {
+      unknown_words: UnknownWordPolicy::Skip,
+      stress: StressHandling::Assign,
+      dialect: vec![DialectFeature::CotCaught, DialectFeature::NonRhotic],
+      punctuation: vec![(Punctuation::Comma, Some(Punctuation::Break)),
+        (Punctuation::StartToken, None), (Punctuation::EndToken, None)],
+      ..Default::default()
+    });
+    assert_eq!(strings(&pipeline.transcribe(&arpa, "The car, xyzzy zorp.").unwrap()),
+      vec!["DH", "AH0", "[space]", "K", "AA1", "[break]", "[space]", "Z", "AA1", "P", "[period]"]);
+
+    let words = pipeline.transcribe_words(&arpa, "caught zorp").unwrap();
+    assert_eq!(words[0].polyphone, polyphone("K AA1 T"));
+    assert_eq!(words[1].polyphone, polyphone("Z AA1 P"));
+
+    let strip = PipelineConfig { stress: StressHandling::Strip, ..Default::default() };
+    assert_eq!(strings(&Pipeline::from(strip).transcribe(&arpa, "it").unwrap()),
+      vec!["[start]", "IH", "T", "[end]"]);
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn json() {
+    let mut config = PipelineConfig {
+      numbers: true,
+      offsets: OffsetUnit::Utf16,
+      unknown_words: UnknownWordPolicy::Spell,
+      stress: StressHandling::Strip,
+      lemmatize: false,
+      dialect: vec![DialectFeature::CotCaught],
+      punctuation: vec![(Punctuation::Ellipsis, Some(Punctuation::Comma)),
+        (Punctuation::Quote, None)],
+      ..Default::default()
+    };
+    config.alphanumeric_overlay.insert("mp3".to_string(), polyphone("EH2 M P IY1 TH R IY1"));
+
+    let json = config.to_json();
+    assert!(json.contains(r#""dialect":["cot-caught"]"#));
+    assert!(json.contains(r#""lemmatize":false"#));
+    assert!(json.contains(r#""punctuation":[["ellipsis","comma"],["quote","drop"]]"#));
+    assert_eq!(PipelineConfig::from_json(&json).unwrap(), config);
+    assert_eq!(PipelineConfig::from_json(&config.to_json_pretty()).unwrap(), config);
+    assert_eq!(serde_json::from_value::<PipelineConfig>(serde_json::to_value(&config).unwrap())
+      .unwrap(), config);
+
+    // Every rule is kept, so the first for a token still applies after a round trip.
+    let repeated = PipelineConfig {
+      punctuation: vec![(Punctuation::Comma, Some(Punctuation::Break)), (Punctuation::Comma, None)],
+      ..Default::default()
+    };
+    let roundtrip = PipelineConfig::from_json(&repeated.to_json()).unwrap();
+    assert_eq!(roundtrip, repeated);
+    assert_eq!(Pipeline::new(roundtrip).punctuation(Punctuation::Comma), Some(Punctuation::Break));
+
+    let partial = PipelineConfig::from_json(r#"{"version": 1, "stress": "assign"}"#).unwrap();
+    assert_eq!(partial, PipelineConfig { stress: StressHandling::Assign, ..Default::default() });
+
+    for invalid in [r#"{"stress": "keep"}"#, r#"{"version": 2}"#, r#"{"version": 1, "colour": 1}"#,
+        r#"{"version": 1, "stress": "loud"}"#, r#"{"version": 1, "numbers": "yes"}"#,
+        r#"{"version": 1, "punctuation": [["comma", "pause"]]}"#,
+        r#"{"version": 1, "punctuation": {"comma": "break"}}"#,
+        r#"{"version": 1, "punctuation": [["comma"]]}"#,
+        r#"{"version": 1, "max_phrase_length": -1}"#, "[]", "{"].iter() {
+      assert!(PipelineConfig::from_json(invalid).is_err(), "{}", invalid);
+    }
+  }
+}
@@ -0,0 +1,398 @@
+//! This module contains queries for poetic devices such as rhyme, alliteration
+//! and assonance.
+
+use crate::confusability::{phoneme_distance, phonetic_distance};
+use crate::index::strip_variant;
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::syllable::{is_syllabic, syllabify};
+use crate::{Arpabet, Word};
+use std::collections::HashMap;
+
+/// Get the onset of a pronunciation: the consonants before the first vowel.
+pub fn onset(polyphone: &[Phoneme]) -> &[Phoneme] {
+  let end = polyphone.iter()
+    .position(|phoneme| matches!(phoneme, Phoneme::Vowel(_)))
+    .unwrap_or(polyphone.len());
+  &polyphone[.. end]
+}
+
+/// Get the vowel carrying primary stress, or the first vowel if no vowel is
+/// marked with primary stress.
+pub fn stressed_vowel(polyphone: &[Phoneme]) -> Option<Vowel> {
+  let vowels = polyphone.iter().filter_map(|phoneme| match phoneme {
+    Phoneme::Vowel(vowel) => Some(*vowel),
+    Phoneme::Consonant(_) => None,
+  });
+  let mut first = None;
+  for vowel in vowels {
+    if *vowel.get_stress() == VowelStress::PrimaryStress {
+      return Some(vowel);
+    }
+    first = first.or(Some(vowel));
+  }
+  first
+}
+
+/// Get the rhyming part of a pronunciation: the final stressed vowel (primary
+/// or secondary) and everything after it. Without stress marks the final vowel
+/// is used. Empty if there are no vowels.
+pub fn rhyme_part(polyphone: &[Phoneme]) -> &[Phoneme] {
+  let is_vowel = |phoneme: &Phoneme| matches!(phoneme, Phoneme::Vowel(_));
+  let is_stressed = |phoneme: &Phoneme| match phoneme {
+    Phoneme::Vowel(vowel) => matches!(vowel.get_stress(),
+        VowelStress::PrimaryStress | VowelStress::SecondaryStress),
+    Phoneme::Consonant(_) => false,
+  };
+  let start = polyphone.iter().rposition(is_stressed)
+    .or_else(|| polyphone.iter().rposition(is_vowel))
+    .unwrap_or(polyphone.len());
+  &polyphone[start ..]
+}
+
+/// Score how well two pronunciations rhyme, from 0 to 1 for a perfect rhyme.
+/// Their rhyming parts (see `rhyme_part`) are compared, adding:
+///
+/// * 0.4 for the similarity of their vowels, regardless of stress
+/// * 0.4 for the similarity of the phonemes after the vowels, the codas
+/// * 0.1 for vowels of the same stress, and 0.1 for the same number of
+///   syllables after them, eg. not 'cat' and 'cattle'
+///
+/// Similarity is one less the distance of the `confusability` module, so slant
+/// rhymes such as 'shape' and 'late', whose codas differ in place alone, still
+/// score highly. Pronunciations without vowels score 0.
+pub fn rhyme_score(a: &[Phoneme], b: &[Phoneme]) -> f32 {
+  let (a, b) = (rhyme_part(a), rhyme_part(b));
+  let (vowel_a, vowel_b) = match (a.first(), b.first()) {
+    (Some(Phoneme::Vowel(vowel_a)), Some(Phoneme::Vowel(vowel_b))) => (*vowel_a, *vowel_b),
+    _ => return 0.0,
+  };
+  let syllables = |part: &[Phoneme]| part.iter().filter(|phoneme| is_syllabic(phoneme)).count();
+  let vowel = 1.0 - phoneme_distance(a[0], b[0]);
+  let coda = 1.0 - phonetic_distance(&a[1 ..], &b[1 ..]);
+  let stress = if vowel_a.get_stress() == vowel_b.get_stress() { 1.0 } else { 0.0 };
+  let length = if syllables(a) == syllables(b) { 1.0 } else { 0.0 };
+  (0.4 * vowel + 0.4 * coda + 0.1 * stress + 0.1 * length) as f32
+}
+
+/// A near rhyme and its score. See `Arpabet::near_rhymes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearRhyme {
+  /// The rhyming word.
+  pub word: Word,
+  /// How well it rhymes, from 0 to 1. See `rhyme_score`.
+  pub score: f32,
+}
+
+/// A rhyme scored by how well it matches. See `Arpabet::best_rhymes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoredRhyme {
+  /// The rhyming word.
+  pub word: Word,
+  /// Number of final phonemes shared with the word, regardless of stress. At
+  /// least the length of the rhyming part; more for richer rhymes, eg. 'black'
+  /// and 'lack' share L AE K.
+  pub depth: usize,
+  /// Difference in the number of syllables.
+  pub syllable_difference: usize,
+  /// The overall score; higher is better. See `Arpabet::best_rhymes`.
+  pub score: f64,
+}
+
+// The number of final phonemes the pronunciations share, regardless of stress.
+fn rhyme_depth(a: &[Phoneme], b: &[Phoneme]) -> usize {
+  a.iter().rev()
+    .zip(b.iter().rev())
+    .take_while(|(a, b)| a.stressless() == b.stressless())
+    .count()
+}
+
+impl Arpabet {
+  /// Find up to `limit` words that rhyme with the word, as `rhymes_with` does,
+  /// best first. The score adds:
+  ///
+  /// * the rhyme depth, the number of final phonemes shared
+  /// * syllable count similarity, 1 / (1 + the difference in syllables)
+  /// * with attached frequencies, how common the word is, from 0 for unseen
+  ///   words to 1 for the most common candidate on a log scale
+  ///
+  /// so depth weighs most, and similar length and frequency decide among
+  /// equally deep rhymes. Ties are broken alphabetically. Every
+  /// pronunciation of both words is considered, keeping the best score.
+  pub fn best_rhymes(&self, word: &str, limit: usize) -> Vec<ScoredRhyme> {
+    let wanted = self.get_polyphone_variants(word);
+    let candidates = self.rhymes_with(word);
+    let max_frequency = self.frequencies()
+      .and_then(|frequencies| candidates.iter().map(|w| frequencies.get(w)).max())
+      .unwrap_or(0);
+
+    let mut rhymes = candidates.into_iter()
+      .filter_map(|candidate| {
+        let frequency = match self.frequencies() {
+          Some(frequencies) if max_frequency > 0 =>
+            (frequencies.get(&candidate) as f64).ln_1p() / (max_frequency as f64).ln_1p(),
+          _ => 0.0,
+        };
+        let variants = self.get_polyphone_variants(&candidate);
+        wanted.iter()
+          .flat_map(|a| variants.iter().map(move |b| (*a, *b)))
+          .filter(|(a, b)| !rhyme_part(a).is_empty() && rhyme_depth(a, b) >= rhyme_part(a).len())
+          .map(|(a, b)| {
+            let depth = rhyme_depth(a, b);
+            let syllable_difference = syllabify(a).len().abs_diff(syllabify(b).len());
+            let score = depth as f64 + 1.0 / (1.0 + syllable_difference as f64) + frequency;
+            ScoredRhyme { word: candidate.clone(), depth, syllable_difference, score }
+          })
+          .max_by(|a, b| a.score.total_cmp(&b.score))
+      })
+      .collect::<Vec<ScoredRhyme>>();
+
+    rhymes.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.word.cmp(&b.word)));
+    rhymes.truncate(limit);
+    rhymes
+  }
+
+  /// Find words that rhyme with the word at least as well as the threshold, as
+  /// scored by `rhyme_score`, including slant rhymes that `rhymes_with` misses,
+  /// eg. 'shape' for 'late'. Every pronunciation of both words is considered,
+  /// keeping the best score. Results exclude the word itself and are ordered
+  /// best first, then alphabetically.
+  pub fn near_rhymes(&self, word: &str, threshold: f32) -> Vec<NearRhyme> {
+    let wanted = self.get_polyphone_variants(word);
+    if wanted.is_empty() {
+      return Vec::new();
+    }
+    let mut scores : HashMap<&str, f32> = HashMap::new();
+    for (key, polyphone) in self.dictionary.iter() {
+      let candidate = strip_variant(key);
+      if candidate == word {
+        continue;
+      }
+      let score = wanted.iter()
+        .map(|wanted| rhyme_score(wanted, polyphone))
+        .fold(0.0, f32::max);
+      if score >= threshold {
+        let best = scores.entry(candidate).or_insert(score);
+        *best = best.max(score);
+      }
+    }
+
+    let mut rhymes = scores.into_iter()
+      .map(|(word, score)| NearRhyme { word: word.to_string(), score })
+      .collect::<Vec<NearRhyme>>();
+    rhymes.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.word.cmp(&b.word)));
+    rhymes
+  }
+
+  /// Find words that rhyme with the word: they share its final stressed vowel
+  /// and the phonemes after it, regardless of stress marks. Every pronunciation
+  /// of the word is considered. Results exclude the word itself and are ranked
+  /// by attached frequencies, if any, then alphabetically.
+  pub fn rhymes_with(&self, word: &str) -> Vec<Word> {
+    let index = self.rhyme_index();
+
+    let mut words = self.get_polyphone_variants(word)
+      .into_iter()
+      .filter_map(|polyphone| index.get(polyphone))
+      .flatten()
+      .filter(|candidate| *candidate != word)
+      .cloned()
+      .collect::<Vec<Word>>();
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find words that alliterate with the word: their pronunciations share its
+  /// onset (initial consonant cluster). Words starting with a vowel alliterate
+  /// with all other vowel-initial words. Results exclude the word itself and are
+  /// ranked by attached frequencies, if any, then alphabetically.
+  pub fn alliterations_of(&self, word: &str) -> Vec<&str> {
+    let polyphone = match self.get_polyphone_ref(word) {
+      None => return Vec::new(),
+      Some(polyphone) => polyphone,
+    };
+    let wanted = onset(polyphone);
+
+    let mut words = Vec::new();
+
+    for (initial, group) in self.group_by_initial_phoneme().iter() {
+      let candidate_group = match (wanted.first(), initial) {
+        (Some(first), _) => first == initial,
+        (None, Phoneme::Vowel(_)) => true,
+        (None, Phoneme::Consonant(_)) => false,
+      };
+      if !candidate_group {
+        continue;
+      }
+      for candidate in group.iter() {
+        if candidate == word {
+          continue;
+        }
+        let matches = self.get_polyphone_variants(candidate)
+          .iter()
+          .any(|polyphone| onset(polyphone) == wanted);
+        if matches {
+          words.push(candidate.as_str());
+        }
+      }
+    }
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find words that are assonant with the word: their stressed vowel is the
+  /// same vowel, regardless of the stress marker. Results exclude the word itself
+  /// and are ranked by attached frequencies, if any, then alphabetically.
+  pub fn assonant_words(&self, word: &str) -> Vec<&str> {
+    let wanted = match self.get_polyphone_ref(word).and_then(stressed_vowel) {
+      None => return Vec::new(),
+      Some(vowel) => vowel.to_str_stressless(),
+    };
+
+    let mut words = self.words_where(|polyphone| {
+      stressed_vowel(polyphone).map(|v| v.to_str_stressless()) == Some(wanted)
+    });
+    words.retain(|candidate| *candidate != word);
+    words
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::Consonant;
+  use crate::testing::{self, polyphone};
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("big", "B IH1 G"),
+    ("bad", "B AE1 D"),
+    ("black", "B L AE1 K"),
+    ("bear", "B EH1 R"),
+    ("cat", "K AE1 T"),
+    ("apple", "AE1 P AH0 L"),
+    ("eagle", "IY1 G AH0 L"),
+    ("attack", "AH0 T AE1 K"),
+  ];
+
+  #[test]
+  fn rhyme_parts() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let attack = arpa.get_polyphone_ref("attack").unwrap();
+    let apple = arpa.get_polyphone_ref("apple").unwrap();
+
+    assert_eq!(rhyme_part(attack), &attack[2 ..]);
+    assert_eq!(rhyme_part(apple), apple);
+    assert_eq!(rhyme_part(&[Phoneme::Consonant(Consonant::M)]), &[]);
+  }
+
+  #[test]
+  fn rhymes() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    arpa.insert("knack".to_string(), polyphone("N AE2 K"));
+    arpa.insert("lack".to_string(), polyphone("L AE1 K"));
+
+    assert_eq!(arpa.rhymes_with("black"), vec!["attack", "knack", "lack"]);
+    assert_eq!(arpa.rhymes_with("cat"), Vec::<Word>::new());
+    assert_eq!(arpa.rhymes_with("unknown"), Vec::<Word>::new());
+
+    arpa.insert("lack(1)".to_string(), polyphone("L AE1 T"));
+    assert_eq!(arpa.rhymes_with("cat"), vec!["lack"]);
+    assert_eq!(arpa.rhymes_with("lack"), vec!["attack", "black", "cat", "knack"]);
+
+    arpa.attach_frequencies(vec![("knack", 10)].into_iter().collect());
+    assert_eq!(arpa.rhymes_with("black"), vec!["knack", "attack", "lack"]);
+  }
+
+  #[test]
+  fn best_rhymes() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+    arpa.insert("lack".to_string(), polyphone("L AE1 K"));
+    arpa.insert("knack".to_string(), polyphone("N AE1 K"));
+
+    let words = |rhymes: Vec<ScoredRhyme>| rhymes.into_iter().map(|r| r.word).collect::<Vec<Word>>();
+
+    // 'lack' shares L AE K; 'knack' matches the syllable count of 'black'.
+    let rhymes = arpa.best_rhymes("black", 10);
+    assert_eq!(words(rhymes.clone()), vec!["lack", "knack", "attack"]);
+    assert_eq!(rhymes[0].depth, 3);
+    assert_eq!(rhymes[2].syllable_difference, 1);
+    assert_eq!(words(arpa.best_rhymes("black", 1)), vec!["lack"]);
+
+    // Frequency decides among equally deep rhymes.
+    arpa.attach_frequencies(vec![("attack", 1000), ("knack", 1)].into_iter().collect());
+    assert_eq!(words(arpa.best_rhymes("black", 10)), vec!["lack", "attack", "knack"]);
+
+    assert!(arpa.best_rhymes("unknown", 10).is_empty());
+  }
+
+  #[test]
+  fn rhyme_scores() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let score = |a, b| rhyme_score(arpa.get_polyphone_ref(a).unwrap(),
+      arpa.get_polyphone_ref(b).unwrap());
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+
+    assert!(close(score("black", "attack"), 1.0));
+    assert!(close(score("cat", "bad"), 0.84));
+    assert!(close(score("cat", "big"), 0.52));
+    assert!(close(score("cat", "apple"), 0.4 + 0.4 / 6.0 + 0.1));
+    assert!(close(score("cat", "bad"), score("bad", "cat")));
+    let hmm = [Phoneme::Consonant(Consonant::HH), Phoneme::Consonant(Consonant::M)];
+    assert_eq!(rhyme_score(&hmm, arpa.get_polyphone_ref("cat").unwrap()), 0.0);
+  }
+
+  #[test]
+  fn near_rhymes() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let rhymes = arpa.near_rhymes("cat", 0.75);
+    let words = rhymes.iter().map(|rhyme| rhyme.word.as_str()).collect::<Vec<&str>>();
+    assert_eq!(words, vec!["bad", "attack", "black"]);
+    assert!(rhymes[0].score > rhymes[1].score);
+
+    assert_eq!(arpa.near_rhymes("cat", 1.0), Vec::new());
+    assert_eq!(arpa.near_rhymes("unknown", 0.0), Vec::new());
+  }
+
+  #[test]
+  fn onset_and_stressed_vowel() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let black = arpa.get_polyphone_ref("black").unwrap();
+    let attack = arpa.get_polyphone_ref("attack").unwrap();
+
+    assert_eq!(onset(black), &black[..2]);
+    assert!(onset(attack).is_empty());
+    assert_eq!(stressed_vowel(black), Some(Vowel::AE(VowelStress::PrimaryStress)));
+    assert_eq!(stressed_vowel(attack), Some(Vowel::AE(VowelStress::PrimaryStress)));
+    assert_eq!(stressed_vowel(&[Phoneme::Consonant(Consonant::M)]), None);
+  }
+
+  #[test]
+  fn alliterations() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+
+    assert_eq!(arpa.alliterations_of("big"), vec!["bad", "bear"]);
+    assert_eq!(arpa.alliterations_of("black"), Vec::<&str>::new());
+    assert_eq!(arpa.alliterations_of("apple"), vec!["attack", "eagle"]);
+    assert_eq!(arpa.alliterations_of("unknown"), Vec::<&str>::new());
+
+    arpa.attach_frequencies(vec![("bear", 10), ("bad", 1)].into_iter().collect());
+    assert_eq!(arpa.alliterations_of("big"), vec!["bear", "bad"]);
+  }
+
+  #[test]
+  fn assonance() {
+    let mut arpa = testing::arpabet(DICTIONARY);
+
+    assert_eq!(arpa.assonant_words("cat"), vec!["apple", "attack", "bad", "black"]);
+    assert_eq!(arpa.assonant_words("big"), Vec::<&str>::new());
+    assert_eq!(arpa.assonant_words("unknown"), Vec::<&str>::new());
+
+    arpa.attach_frequencies(vec![("black", 10), ("attack", 5)].into_iter().collect());
+    assert_eq!(arpa.assonant_words("cat"), vec!["black", "attack", "apple", "bad"]);
+  }
+}
@@ -0,0 +1,198 @@
+//! This module predicts which words of a sentence are prominent (accented) in
+//! speech, as input to prosody modules.
+
+use crate::casing::fold_case;
+use crate::error::ArpabetError;
+use crate::extensions::Punctuation;
+use crate::tokenizer::{tokenize_with_options, TokenKind};
+use crate::transcribe::{word_context, TranscribeOptions};
+use crate::{Arpabet, Polyphone};
+
+/// English function words: articles, auxiliaries, conjunctions, prepositions
+/// and pronouns. These are normally unaccented in connected speech.
+pub const FUNCTION_WORDS : [&str; 80] = [
+  "a", "about", "after", "am", "an", "and", "are", "as", "at", "be", "been", "but",
+  "by", "can", "could", "did", "do", "does", "for", "from", "had", "has", "have",
+  "he", "her", "him", "his", "i", "if", "in", "into", "is", "it", "its", "may",
+  "me", "might", "must", "my", "nor", "of", "on", "or", "our", "over", "shall",
+  "she", "should", "so", "than", "that", "the", "their", "them", "then", "these",
+  "they", "this", "those", "through", "to", "under", "until", "up", "upon", "us",
+  "was", "we", "were", "what", "when", "which", "while", "who", "will", "with",
+  "would", "yet", "you", "your",
+];
+
+/// How prominent a word is within its phrase, from least to most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Prominence {
+  /// A function word, normally unaccented and often reduced.
+  Reduced,
+  /// A content word carrying a pitch accent.
+  Accented,
+  /// The last accented word of a phrase, carrying the main (nuclear) accent.
+  Nuclear,
+}
+
+impl From<Prominence> for u8 {
+  /// Map prominence levels to unsigned ints, for use as model inputs.
+  fn from(prominence: Prominence) -> Self {
+    match prominence {
+      Prominence::Reduced => 0,
+      Prominence::Accented => 1,
+      Prominence::Nuclear => 2,
+    }
+  }
+}
+
+/// A transcribed word and its predicted prominence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProminentWord {
+  /// The word as it appears in the text.
+  pub word: String,
+  /// Pronunciation of the word.
+  pub polyphone: Polyphone,
+  /// Predicted prominence of the word.
+  pub prominence: Prominence,
+}
+
+/// Whether the word, in any casing, is one of the `FUNCTION_WORDS`.
+pub fn is_function_word(word: &str) -> bool {
+  let word = fold_case(word);
+  FUNCTION_WORDS.binary_search(&word.as_str()).is_ok()
+}
+
+impl Arpabet {
+  /// Transcribe English text word by word, predicting the prominence of each
+  /// word: function words are reduced, content words accented, and the last
+  /// content word before each comma, full stop, etc. carries the nuclear accent.
+  /// A phrase of function words alone puts the nuclear accent on its last word.
+  ///
+  /// Words are pronounced as by `transcribe_sentence_with_options`, and fail the
+  /// same way on unknown words.
+  pub fn transcribe_prominence(&self, text: &str, options: &TranscribeOptions)
+    -> Result<Vec<ProminentWord>, ArpabetError>
+  {
+    let tokens = tokenize_with_options(text, &options.tokenizer);
+    let mut words = Vec::new();
+    let mut phrase_start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+      match token.kind {
+        TokenKind::Punctuation(punctuation) => {
+          if ends_phrase(punctuation) {
+            mark_nucleus(&mut words[phrase_start ..]);
+            phrase_start = words.len();
+          }
+        },
+        TokenKind::Annotation(_) => {
+          mark_nucleus(&mut words[phrase_start ..]);
+          phrase_start = words.len();
+        },
+        TokenKind::Word(ref word) => {
+          let polyphone = self.pronounce_word(word, &word_context(&tokens, i), options)
+            .ok_or_else(|| ArpabetError::UnknownWord { word: word.to_string() })?;
+          let prominence = if is_function_word(word) {
+            Prominence::Reduced
+          } else {
+            Prominence::Accented
+          };
+          words.push(ProminentWord { word: word.to_string(), polyphone, prominence });
+        },
+      }
+    }
+
+    mark_nucleus(&mut words[phrase_start ..]);
+    Ok(words)
+  }
+}
+
+fn ends_phrase(punctuation: Punctuation) -> bool {
+  match punctuation {
+    Punctuation::Comma
+        | Punctuation::Period
+        | Punctuation::Question
+        | Punctuation::Exclamation
+        | Punctuation::Interjection
+        | Punctuation::Ellipsis
+        | Punctuation::Break
+        | Punctuation::Annotation => true,
+    Punctuation::StartToken
+        | Punctuation::Space
+        | Punctuation::Quote
+        | Punctuation::EndToken => false,
+  }
+}
+
+fn mark_nucleus(phrase: &mut [ProminentWord]) {
+  let nucleus = phrase.iter()
+    .rposition(|word| word.prominence == Prominence::Accented)
+    .or_else(|| phrase.len().checked_sub(1));
+  if let Some(i) = nucleus {
+    phrase[i].prominence = Prominence::Nuclear;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::arpabet;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("the", "DH AH0"),
+    ("cat", "K AE1 T"),
+    ("sat", "S AE1 T"),
+    ("on", "AA1 N"),
+    ("mat", "M AE1 T"),
+    ("it", "IH1 T"),
+    ("was", "W AA1 Z"),
+  ];
+
+  fn levels(words: &[ProminentWord]) -> Vec<(&str, Prominence)> {
+    words.iter().map(|word| (word.word.as_str(), word.prominence)).collect()
+  }
+
+  #[test]
+  fn function_words() {
+    assert!(FUNCTION_WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(is_function_word("The"));
+    assert!(is_function_word("would"));
+    assert!(!is_function_word("cat"));
+  }
+
+  #[test]
+  fn prominence() {
+    let words = arpabet(DICTIONARY)
+      .transcribe_prominence("The cat sat on the mat.", &TranscribeOptions::default())
+      .unwrap();
+
+    assert_eq!(levels(&words), vec![
+      ("The", Prominence::Reduced),
+      ("cat", Prominence::Accented),
+      ("sat", Prominence::Accented),
+      ("on", Prominence::Reduced),
+      ("the", Prominence::Reduced),
+      ("mat", Prominence::Nuclear),
+    ]);
+    assert_eq!(words[1].polyphone, arpabet(DICTIONARY).get_polyphone("cat").unwrap());
+  }
+
+  #[test]
+  fn phrases() {
+    let words = arpabet(DICTIONARY)
+      .transcribe_prominence("The cat, it was", &TranscribeOptions::default())
+      .unwrap();
+
+    assert_eq!(levels(&words), vec![
+      ("The", Prominence::Reduced),
+      ("cat", Prominence::Nuclear),
+      ("it", Prominence::Reduced),
+      ("was", Prominence::Nuclear),
+    ]);
+    assert_eq!(u8::from(Prominence::Nuclear), 2);
+  }
+
+  #[test]
+  fn unknown_word() {
+    let arpa = arpabet(DICTIONARY);
+    assert!(arpa.transcribe_prominence("the dog", &TranscribeOptions::default()).is_err());
+  }
+}
@@ -0,0 +1,280 @@
+//! This module contains a small query language over the dictionary, for
+//! searches too involved for a single pattern, eg. from the command line:
+//! `word:~cat syllables:1 -has:K` finds one-syllable rhymes of 'cat' without
+//! a K.
+//!
+//! A query is a list of terms separated by spaces. A word must satisfy every
+//! term, unless terms are split into alternatives by `OR`, in which case it
+//! must satisfy every term of one alternative. A term prefixed with `-` must
+//! not hold. Terms are written `key:value` or `key=value`:
+//!
+//! * `word:cat` is the word 'cat'; `word:cat*` words starting with "cat",
+//!   `word:*cat` words ending with it
+//! * `word:~cat` or `rhymes:cat` rhymes with 'cat', as in `Arpabet::rhymes_with`
+//! * `syllables:2` has two syllables; `syllables:2-4` two to four
+//! * `stress:101` has the stress pattern, as in `StressPattern::parse`
+//! * `has:SH` contains the phoneme; vowels without stress match any stress
+//! * `prefix:K,AE` starts with the phonemes, separated by commas
+//! * `pattern:K,*,V1,..` matches the `PhonemePattern`, with elements separated
+//!   by commas
+
+use crate::casing::fold_case;
+use crate::error::ArpabetError;
+use crate::index::strip_variant;
+use crate::pattern::PhonemePattern;
+use crate::phoneme::Phoneme;
+use crate::poetry::rhyme_part;
+use crate::stress::{stress_pattern, PolyphoneStress};
+use crate::syllable::is_syllabic;
+use crate::Arpabet;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A compiled query. See the module documentation for the syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+  // Alternatives of terms, each term paired with whether it is negated.
+  alternatives: Vec<Vec<(bool, Term)>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Term {
+  Word(String),
+  WordPrefix(String),
+  WordSuffix(String),
+  RhymesWith(String),
+  Syllables(usize, usize),
+  Stress(String),
+  Has(PhonemePattern, String),
+  Prefix(PhonemePattern, String),
+  Pattern(PhonemePattern, String),
+}
+
+impl Query {
+  /// Compile a query, eg. "word:~cat syllables:1". Fails with
+  /// `ArpabetError::StringParseError` on unknown keys or malformed values.
+  pub fn parse(query: &str) -> Result<Self, ArpabetError> {
+    let mut alternatives = vec![Vec::new()];
+    for term in query.split_whitespace() {
+      if term == "OR" {
+        alternatives.push(Vec::new());
+        continue;
+      }
+      let (negated, term) = match term.strip_prefix('-') {
+        Some(term) => (true, term),
+        None => (false, term),
+      };
+      let parsed = parse_term(term).ok_or_else(|| ArpabetError::StringParseError {
+        description: format!("Invalid term '{}' in query '{}'", term, query),
+      })?;
+      alternatives.last_mut().expect("There is always an alternative.").push((negated, parsed));
+    }
+    if alternatives.iter().any(|terms| terms.is_empty()) {
+      return Err(ArpabetError::StringParseError {
+        description: format!("Empty alternative in query '{}'", query),
+      });
+    }
+    Ok(Self { alternatives })
+  }
+
+  // Whether the entry satisfies the query, given the rhyming parts of each
+  // rhyme target's pronunciations.
+  fn matches(&self, word: &str, polyphone: &[Phoneme], rhymes: &HashMap<&str, Vec<Vec<&str>>>)
+    -> bool
+  {
+    self.alternatives.iter().any(|terms| {
+      terms.iter().all(|(negated, term)| term.matches(word, polyphone, rhymes) != *negated)
+    })
+  }
+}
+
+impl Term {
+  fn matches(&self, word: &str, polyphone: &[Phoneme], rhymes: &HashMap<&str, Vec<Vec<&str>>>)
+    -> bool
+  {
+    match self {
+      Term::Word(wanted) => word == wanted,
+      Term::WordPrefix(prefix) => word.starts_with(prefix.as_str()),
+      Term::WordSuffix(suffix) => word.ends_with(suffix.as_str()),
+      Term::RhymesWith(target) => {
+        let part = rhyme_part(polyphone).to_stressless_strs();
+        word != target && !part.is_empty()
+          && matches!(rhymes.get(target.as_str()), Some(parts) if parts.contains(&part))
+      },
+      Term::Syllables(min, max) => {
+        let count = polyphone.iter().filter(|phoneme| is_syllabic(phoneme)).count();
+        *min <= count && count <= *max
+      },
+      Term::Stress(pattern) => stress_pattern(polyphone).to_string() == *pattern,
+      Term::Has(pattern, _) | Term::Prefix(pattern, _) | Term::Pattern(pattern, _) =>
+        pattern.matches(polyphone),
+    }
+  }
+}
+
+fn parse_term(term: &str) -> Option<Term> {
+  let split = term.find([':', '='])?;
+  let (key, value) = (&term[.. split], &term[split + 1 ..]);
+  if value.is_empty() {
+    return None;
+  }
+  let elements = || value.split(',').collect::<Vec<&str>>().join(" ");
+  let parsed = match key {
+    "word" => match value {
+      _ if value.starts_with('~') && value.len() > 1 => Term::RhymesWith(fold_case(&value[1 ..])),
+      _ if value.ends_with('*') => Term::WordPrefix(fold_case(value.trim_end_matches('*'))),
+      _ if value.starts_with('*') => Term::WordSuffix(fold_case(value.trim_start_matches('*'))),
+      _ => Term::Word(fold_case(value)),
+    },
+    "rhymes" => Term::RhymesWith(fold_case(value)),
+    "syllables" => match value.split_once('-') {
+      Some((min, max)) => Term::Syllables(min.parse().ok()?, max.parse().ok()?),
+      None => {
+        let count = value.parse().ok()?;
+        Term::Syllables(count, count)
+      },
+    },
+    "stress" => {
+      if !value.chars().all(|c| "012?".contains(c)) {
+        return None;
+      }
+      Term::Stress(value.to_string())
+    },
+    "has" => {
+      let phoneme = PhonemePattern::parse(value).ok()?.to_string();
+      if phoneme.contains(' ') || phoneme.contains('.') {
+        return None;
+      }
+      Term::Has(PhonemePattern::parse(&format!(".. {} ..", phoneme)).ok()?, phoneme)
+    },
+    "prefix" => {
+      let pattern = PhonemePattern::parse(&format!("{} ..", elements())).ok()?;
+      Term::Prefix(pattern, elements())
+    },
+    "pattern" => Term::Pattern(PhonemePattern::parse(&elements()).ok()?, elements()),
+    _ => return None,
+  };
+  Some(parsed)
+}
+
+impl fmt::Display for Query {
+  /// Write the query back in its text syntax, normalized, eg. `rhymes:` for
+  /// `word:~`.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, terms) in self.alternatives.iter().enumerate() {
+      if i > 0 {
+        write!(f, " OR ")?;
+      }
+      for (j, (negated, term)) in terms.iter().enumerate() {
+        if j > 0 {
+          write!(f, " ")?;
+        }
+        if *negated {
+          write!(f, "-")?;
+        }
+        let commas = |elements: &str| elements.replace(' ', ",");
+        match term {
+          Term::Word(word) => write!(f, "word:{}", word)?,
+          Term::WordPrefix(prefix) => write!(f, "word:{}*", prefix)?,
+          Term::WordSuffix(suffix) => write!(f, "word:*{}", suffix)?,
+          Term::RhymesWith(target) => write!(f, "rhymes:{}", target)?,
+          Term::Syllables(min, max) if min == max => write!(f, "syllables:{}", min)?,
+          Term::Syllables(min, max) => write!(f, "syllables:{}-{}", min, max)?,
+          Term::Stress(pattern) => write!(f, "stress:{}", pattern)?,
+          Term::Has(_, phoneme) => write!(f, "has:{}", phoneme)?,
+          Term::Prefix(_, elements) => write!(f, "prefix:{}", commas(elements))?,
+          Term::Pattern(_, elements) => write!(f, "pattern:{}", commas(elements))?,
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Arpabet {
+  /// Find the words matching the query. A word matches if any of its
+  /// pronunciations does. Results are ranked by attached frequencies, if any,
+  /// then alphabetically.
+  pub fn search(&self, query: &Query) -> Vec<&str> {
+    let mut rhymes : HashMap<&str, Vec<Vec<&str>>> = HashMap::new();
+    for terms in query.alternatives.iter() {
+      for (_, term) in terms.iter() {
+        if let Term::RhymesWith(target) = term {
+          let parts = self.get_polyphone_variants(target)
+            .into_iter()
+            .map(|polyphone| rhyme_part(polyphone).to_stressless_strs())
+            .filter(|part| !part.is_empty())
+            .collect();
+          rhymes.insert(target.as_str(), parts);
+        }
+      }
+    }
+
+    let mut words = self.dictionary.iter()
+      .map(|(key, polyphone)| (strip_variant(key), polyphone))
+      .filter(|(word, polyphone)| query.matches(word, polyphone, &rhymes))
+      .map(|(word, _)| word)
+      .collect::<Vec<&str>>();
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find the words matching the query text. See `search` and the `query`
+  /// module.
+  pub fn search_query(&self, query: &str) -> Result<Vec<&str>, ArpabetError> {
+    Ok(self.search(&Query::parse(query)?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+
+  fn search(query: &str) -> Vec<String> {
+    testing::arpabet(&[
+      ("cat", "K AE1 T"),
+      ("hat", "HH AE1 T"),
+      ("that", "DH AE1 T"),
+      ("acrobat", "AE1 K R AH0 B AE2 T"),
+      ("catalog", "K AE1 T AH0 L AO2 G"),
+      ("cattle", "K AE1 T AH0 L"),
+      ("about", "AH0 B AW1 T"),
+      ("shack", "SH AE1 K"),
+      ("wombat", "W AA1 M B AE2 T"),
+    ]).search_query(query).unwrap().iter().map(|word| word.to_string()).collect()
+  }
+
+  #[test]
+  fn terms() {
+    assert_eq!(search("word:~cat"), vec!["acrobat", "hat", "that", "wombat"]);
+    assert_eq!(search("rhymes:CAT syllables:1"), vec!["hat", "that"]);
+    assert_eq!(search("word:cat*"), vec!["cat", "catalog", "cattle"]);
+    assert_eq!(search("word:*at -word:cat"), vec!["acrobat", "hat", "that", "wombat"]);
+    assert_eq!(search("syllables=2-3 stress:102"), vec!["acrobat", "catalog"]);
+    assert_eq!(search("stress:01"), vec!["about"]);
+    assert_eq!(search("has:SH"), vec!["shack"]);
+    assert_eq!(search("has:AW"), vec!["about"]);
+    assert_eq!(search("prefix:K,AE1,T syllables:2"), vec!["cattle"]);
+    assert_eq!(search("pattern:?,AE1,T"), vec!["cat", "hat", "that"]);
+    assert_eq!(search("word:~cat -has:DH OR word:shack"),
+      vec!["acrobat", "hat", "shack", "wombat"]);
+    assert_eq!(search("word:~nothing"), Vec::<String>::new());
+  }
+
+  #[test]
+  fn parse() {
+    let query = Query::parse("word:~Cat  syllables:1-2 -has:K OR prefix:K,AE pattern:..,T")
+      .unwrap();
+    assert_eq!(query.to_string(), "rhymes:cat syllables:1-2 -has:K OR prefix:K,AE pattern:..,T");
+    assert_eq!(Query::parse(&query.to_string()).unwrap(), query);
+
+    for invalid in ["", "cat", "word:", "syllables:x", "stress:13", "has:K,T", "has:..",
+        "colour:red", "prefix:XX", "word:cat OR", "OR word:cat"].iter() {
+      assert!(Query::parse(invalid).is_err(), "{}", invalid);
+    }
+  }
+}
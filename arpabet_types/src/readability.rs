@@ -0,0 +1,152 @@
+//! This module estimates how hard English text is to read aloud from its
+//! pronunciation rather than its spelling.
+
+use crate::phoneme::Phoneme;
+use crate::syllable::syllabify;
+use crate::transcribe::TranscribeOptions;
+use crate::Arpabet;
+
+/// The least frequent phonemes in running English speech, each accounting for
+/// about one percent of phonemes or less. Stress is ignored.
+pub const RARE_PHONEMES : [&str; 8] = ["AW", "CH", "JH", "NG", "OY", "TH", "UH", "ZH"];
+
+// Words of this many syllables or more count as polysyllabic.
+const POLYSYLLABIC : usize = 3;
+
+/// Pronunciation-based readability measures for a text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhoneticReadability {
+  /// Number of words that could be pronounced.
+  pub words: usize,
+  /// Total syllables across the pronounced words.
+  pub syllables: usize,
+  /// Number of pronounced words with three or more syllables.
+  pub polysyllabic_words: usize,
+  /// Total phonemes across the pronounced words.
+  pub phonemes: usize,
+  /// Number of phonemes listed in `RARE_PHONEMES`.
+  pub rare_phonemes: usize,
+  /// Words that couldn't be pronounced, in order of appearance. They are left
+  /// out of every other measure.
+  pub unknown_words: Vec<String>,
+}
+
+impl PhoneticReadability {
+  /// Average number of syllables per word.
+  pub fn syllables_per_word(&self) -> f64 {
+    ratio(self.syllables, self.words)
+  }
+
+  /// Average number of rare phonemes per word.
+  pub fn rare_phonemes_per_word(&self) -> f64 {
+    ratio(self.rare_phonemes, self.words)
+  }
+
+  /// Fraction of words with three or more syllables.
+  pub fn polysyllabic_ratio(&self) -> f64 {
+    ratio(self.polysyllabic_words, self.words)
+  }
+
+  /// A single difficulty score: syllables per word plus rare phonemes per word.
+  /// Text of common monosyllables scores 1.0; higher is harder. Zero for text
+  /// without pronounceable words.
+  pub fn difficulty(&self) -> f64 {
+    self.syllables_per_word() + self.rare_phonemes_per_word()
+  }
+}
+
+/// Measure the phonetic readability of English text. Words are pronounced as by
+/// `Arpabet::transcribe_sentence`; words that can't be pronounced are collected
+/// in `unknown_words` rather than failing the measurement.
+pub fn readability_phonetic(text: &str, arpabet: &Arpabet) -> PhoneticReadability {
+  let mut readability = PhoneticReadability::default();
+
+  for (word, polyphone) in arpabet.pronounce_words(text, &TranscribeOptions::default()) {
+    let polyphone = match polyphone {
+      Some(polyphone) => polyphone,
+      None => {
+        readability.unknown_words.push(word);
+        continue;
+      },
+    };
+
+    let syllables = syllabify(&polyphone).len();
+
+    readability.words += 1;
+    readability.syllables += syllables;
+    readability.phonemes += polyphone.len();
+    readability.rare_phonemes += polyphone.iter().filter(|p| is_rare(p)).count();
+
+    if syllables >= POLYSYLLABIC {
+      readability.polysyllabic_words += 1;
+    }
+  }
+
+  readability
+}
+
+fn is_rare(phoneme: &Phoneme) -> bool {
+  let stressless = match phoneme {
+    Phoneme::Consonant(consonant) => consonant.to_str(),
+    Phoneme::Vowel(vowel) => vowel.to_str_stressless(),
+  };
+  RARE_PHONEMES.contains(&stressless)
+}
+
+fn ratio(count: usize, words: usize) -> f64 {
+  if words == 0 {
+    0.0
+  } else {
+    count as f64 / words as f64
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::arpabet;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("the", "DH AH0"),
+    ("cat", "K AE1 T"),
+    ("sat", "S AE1 T"),
+    ("thoroughly", "TH ER1 OW0 L IY0"),
+    ("enjoyed", "EH0 N JH OY1 D"),
+    ("unusual", "AH0 N Y UW1 ZH UW0 AH0 L"),
+    ("things", "TH IH1 NG Z"),
+  ];
+
+  #[test]
+  fn simple_text() {
+    let readability = readability_phonetic("The cat sat.", &arpabet(DICTIONARY));
+    assert_eq!(readability.words, 3);
+    assert_eq!(readability.syllables, 3);
+    assert_eq!(readability.rare_phonemes, 0);
+    assert_eq!(readability.difficulty(), 1.0);
+  }
+
+  #[test]
+  fn harder_text() {
+    let readability = readability_phonetic("The cat thoroughly enjoyed unusual things!",
+      &arpabet(DICTIONARY));
+    assert_eq!(readability.words, 6);
+    assert_eq!(readability.syllables, 12);
+    assert_eq!(readability.polysyllabic_words, 2);
+    assert_eq!(readability.phonemes, 27);
+    assert_eq!(readability.rare_phonemes, 6);
+    assert_eq!(readability.rare_phonemes_per_word(), 1.0);
+    assert!(readability.difficulty() > readability_phonetic("The cat sat.", &arpabet(DICTIONARY))
+      .difficulty());
+  }
+
+  #[test]
+  fn unknown_words() {
+    let readability = readability_phonetic("The dog sat", &arpabet(DICTIONARY));
+    assert_eq!(readability.words, 2);
+    assert_eq!(readability.unknown_words, vec!["dog"]);
+
+    let empty = readability_phonetic("", &arpabet(DICTIONARY));
+    assert_eq!(empty.words, 0);
+    assert_eq!(empty.difficulty(), 0.0);
+  }
+}
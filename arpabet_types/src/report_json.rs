@@ -0,0 +1,541 @@
+//! This module serializes the crate's reports as JSON, so CI systems and tools
+//! written in other languages can consume them. It needs the `json` feature.
+//!
+//! Every report implements `serde::Serialize` with a fixed shape, documented
+//! below. Top-level reports carry a `"schema_version"`, currently
+//! `REPORT_SCHEMA_VERSION`; it is raised whenever a field is removed, renamed
+//! or changes meaning. Adding a field does not raise it, so consumers should
+//! ignore fields they don't know.
+//!
+//! Pronunciations are arrays of phoneme strings, eg. `["K", "AE1", "T"]`, or
+//! null when missing. Enumerations are written as snake_case strings in a
+//! `"kind"`, `"op"` or `"step"` field.
+//!
+//! `ImpactReport`:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "total_words": 120,
+//!   "changed_occurrences": 3,
+//!   "changed_fraction": 0.025,
+//!   "changes": [
+//!     { "word": "tomato", "occurrences": 3, "old": ["T", "AH0", "M", "EY1", "T", "OW2"],
+//!       "new": ["T", "AH0", "M", "AA1", "T", "OW2"] }
+//!   ]
+//! }
+//! ```
+//!
+//! `CoverageReport`:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "hits": 98,
+//!   "misses": 2,
+//!   "hit_rate": 0.98,
+//!   "out_of_vocabulary": [{ "word": "zyzzyva", "count": 2 }]
+//! }
+//! ```
+//!
+//! `LookupTrace`, whose `"source"` is a `PronunciationSource` as written by
+//! `to_str`, or null. A `"split"` step holds the traces of the parts:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "word": "Cat",
+//!   "steps": [
+//!     { "step": "normalized", "from": "Cat", "to": "cat" },
+//!     { "step": "consulted", "source": "dictionary", "key": "cat", "found": true },
+//!     { "step": "split", "parts": [] }
+//!   ],
+//!   "pronunciation": ["K", "AE1", "T"],
+//!   "source": "dictionary"
+//! }
+//! ```
+//!
+//! `StreamIssue`, found by `validate_token_stream`, with `"position"` and
+//! `"code"` present only for the kinds that have them:
+//!
+//! ```json
+//! { "kind": "unknown_code", "position": 4, "code": 250, "message": "..." }
+//! ```
+//!
+//! `Mismatch`, found by `Arpabet::check_consistency`:
+//!
+//! ```json
+//! { "word": "read", "missing": false, "expected": [["R", "IY1", "D"]],
+//!   "found": [["R", "EH1", "D"]] }
+//! ```
+//!
+//! `TokenEdit`, found by `diff_token_streams`, with tokens written as by
+//! `SentenceToken::to_str`:
+//!
+//! ```json
+//! { "op": "substitute", "a": 3, "b": 3, "from": "AE1", "to": "EH1" }
+//! { "op": "delete", "a": 5, "token": "[space]" }
+//! { "op": "insert", "b": 5, "token": "T" }
+//! ```
+//!
+//! `PhonemeHistogram`, whose `"counts"` ignore stress and leave out phonemes
+//! never seen:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "total": 6,
+//!   "counts": { "AE": 2, "K": 2, "T": 2 },
+//!   "unknown_words": ["zyzzyva"]
+//! }
+//! ```
+//!
+//! `DictionaryStats`, whose `"phonemes"` is a `PhonemeHistogram` and whose
+//! `"lengths"` has at index n the number of entries with n phonemes:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "entries": 2,
+//!   "vowels": 2,
+//!   "consonants": 4,
+//!   "average_length": 3.0,
+//!   "max_length": 3,
+//!   "stress": { "primary": 2, "secondary": 0, "unstressed": 0, "unknown": 0 },
+//!   "lengths": [0, 0, 0, 2],
+//!   "phonemes": { "schema_version": 1, "total": 6, "counts": { "AE": 2, "K": 2, "T": 2 },
+//!     "unknown_words": [] }
+//! }
+//! ```
+//!
+//! `MemoryReport`, in bytes:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "entries": 2,
+//!   "keys": 6,
+//!   "values": 12,
+//!   "table": 96,
+//!   "static_entries": false,
+//!   "indexes": { "reverse": 0, "phoneme_trie": 0, "initial_phonemes": 0, "rhymes": 0,
+//!     "stressless": 0, "ngrams": 0, "total": 0 },
+//!   "frequencies": 0,
+//!   "duplicate_values": 0,
+//!   "total": 114,
+//!   "heap": 114
+//! }
+//! ```
+//!
+//! `CacheStats`, with the `cache` feature:
+//!
+//! ```json
+//! { "schema_version": 1, "hits": 3, "misses": 1, "hit_rate": 0.75 }
+//! ```
+//!
+//! The parser's `LoadReport` is serialized by the **arpabet_parser** crate, in
+//! the same schema, with its `json` feature:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "entries": 2,
+//!   "skipped": [{ "line_number": 3, "reason": "..." }]
+//! }
+//! ```
+
+#[cfg(feature = "cache")]
+use crate::cache::CacheStats;
+use crate::consistency::Mismatch;
+use crate::coverage::{CoverageReport, MissingWord};
+use crate::diff::TokenEdit;
+use crate::histogram::PhonemeHistogram;
+use crate::impact::{ImpactReport, PronunciationChange};
+use crate::memory::{IndexMemory, MemoryReport};
+use crate::phoneme::Phoneme;
+use crate::stats::{DictionaryStats, StressCounts};
+use crate::trace::{LookupStep, LookupTrace};
+use crate::validation::StreamIssue;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+/// The version of the report shapes. See the module documentation.
+pub const REPORT_SCHEMA_VERSION : u32 = 1;
+
+impl Serialize for ImpactReport {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "total_words": self.total_words,
+      "changed_occurrences": self.changed_occurrences(),
+      "changed_fraction": self.changed_fraction(),
+      "changes": self.changes,
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for PronunciationChange {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "word": self.word,
+      "occurrences": self.occurrences,
+      "old": self.old.as_deref().map(pronunciation),
+      "new": self.new.as_deref().map(pronunciation),
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for CoverageReport {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "hits": self.hits,
+      "misses": self.misses,
+      "hit_rate": self.hit_rate(),
+      "out_of_vocabulary": self.out_of_vocabulary,
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for MissingWord {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({ "word": self.word, "count": self.count }).serialize(serializer)
+  }
+}
+
+impl Serialize for LookupTrace {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "word": self.word,
+      "steps": self.steps,
+      "pronunciation": self.pronunciation.as_deref().map(pronunciation),
+      "source": self.source.map(|source| source.to_str()),
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for LookupStep {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      LookupStep::Normalized { from, to } =>
+        json!({ "step": "normalized", "from": from, "to": to }),
+      LookupStep::Consulted { source, key, found } => json!({
+        "step": "consulted",
+        "source": source.to_str(),
+        "key": key,
+        "found": found,
+      }),
+      LookupStep::Split { parts } => json!({ "step": "split", "parts": parts }),
+    }.serialize(serializer)
+  }
+}
+
+impl Serialize for StreamIssue {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let (kind, position, code) = match self {
+      StreamIssue::Empty => ("empty", None, None),
+      StreamIssue::MissingStartToken => ("missing_start_token", None, None),
+      StreamIssue::MisplacedStartToken { position } =>
+        ("misplaced_start_token", Some(*position), None),
+      StreamIssue::MissingEndToken => ("missing_end_token", None, None),
+      StreamIssue::TokensAfterEndToken { position } =>
+        ("tokens_after_end_token", Some(*position), None),
+      StreamIssue::RepeatedSpace { position } => ("repeated_space", Some(*position), None),
+      StreamIssue::UnknownCode { position, code } =>
+        ("unknown_code", Some(*position), Some(*code)),
+    };
+    let mut value = json!({ "kind": kind, "message": self.to_string() });
+    if let Some(position) = position {
+      value["position"] = json!(position);
+    }
+    if let Some(code) = code {
+      value["code"] = json!(code);
+    }
+    value.serialize(serializer)
+  }
+}
+
+impl Serialize for Mismatch {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "word": self.word,
+      "missing": self.is_missing(),
+      "expected": self.expected.iter().map(|p| pronunciation(p)).collect::<Vec<_>>(),
+      "found": self.found.iter().map(|p| pronunciation(p)).collect::<Vec<_>>(),
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for TokenEdit {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match self {
+      TokenEdit::Delete { a, token } =>
+        json!({ "op": "delete", "a": a, "token": token.to_str() }),
+      TokenEdit::Insert { b, token } =>
+        json!({ "op": "insert", "b": b, "token": token.to_str() }),
+      TokenEdit::Substitute { a, b, from, to } => json!({
+        "op": "substitute",
+        "a": a,
+        "b": b,
+        "from": from.to_str(),
+        "to": to.to_str(),
+      }),
+    }.serialize(serializer)
+  }
+}
+
+impl Serialize for PhonemeHistogram {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let counts = self.ranked().into_iter()
+      .map(|(phoneme, count)| (phoneme.to_string(), json!(count)))
+      .collect::<Map<String, Value>>();
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "total": self.total(),
+      "counts": counts,
+      "unknown_words": self.unknown_words,
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for DictionaryStats {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "entries": self.entries,
+      "vowels": self.vowels,
+      "consonants": self.consonants,
+      "average_length": self.average_length(),
+      "max_length": self.max_length(),
+      "stress": self.stress,
+      "lengths": self.lengths,
+      "phonemes": self.phonemes,
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for StressCounts {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "primary": self.primary,
+      "secondary": self.secondary,
+      "unstressed": self.unstressed,
+      "unknown": self.unknown,
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for MemoryReport {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "entries": self.entries,
+      "keys": self.keys,
+      "values": self.values,
+      "table": self.table,
+      "static_entries": self.static_entries,
+      "indexes": self.indexes,
+      "frequencies": self.frequencies,
+      "duplicate_values": self.duplicate_values,
+      "total": self.total(),
+      "heap": self.heap(),
+    }).serialize(serializer)
+  }
+}
+
+impl Serialize for IndexMemory {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "reverse": self.reverse,
+      "phoneme_trie": self.phoneme_trie,
+      "initial_phonemes": self.initial_phonemes,
+      "rhymes": self.rhymes,
+      "stressless": self.stressless,
+      "ngrams": self.ngrams,
+      "total": self.total(),
+    }).serialize(serializer)
+  }
+}
+
+#[cfg(feature = "cache")]
+impl Serialize for CacheStats {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    json!({
+      "schema_version": REPORT_SCHEMA_VERSION,
+      "hits": self.hits,
+      "misses": self.misses,
+      "hit_rate": self.hit_rate(),
+    }).serialize(serializer)
+  }
+}
+
+// A pronunciation as an array of phoneme strings.
+fn pronunciation(polyphone: &[Phoneme]) -> Value {
+  polyphone.iter().map(|phoneme| phoneme.to_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::extensions::{Punctuation, SentenceToken};
+  use crate::testing::polyphone;
+  use crate::trace::PronunciationSource;
+
+  #[test]
+  fn impact_report() {
+    let report = ImpactReport {
+      total_words: 4,
+      changes: vec![PronunciationChange {
+        word: "cat".to_string(),
+        occurrences: 1,
+        old: None,
+        new: Some(polyphone("K AE1 T")),
+      }],
+    };
+    assert_eq!(serde_json::to_value(&report).unwrap(), json!({
+      "schema_version": 1,
+      "total_words": 4,
+      "changed_occurrences": 1,
+      "changed_fraction": 0.25,
+      "changes": [{ "word": "cat", "occurrences": 1, "old": null, "new": ["K", "AE1", "T"] }],
+    }));
+  }
+
+  #[test]
+  fn coverage_report() {
+    let report = CoverageReport {
+      hits: 3,
+      misses: 1,
+      out_of_vocabulary: vec![MissingWord { word: "zyzzyva".to_string(), count: 1 }],
+    };
+    assert_eq!(serde_json::to_value(&report).unwrap(), json!({
+      "schema_version": 1,
+      "hits": 3,
+      "misses": 1,
+      "hit_rate": 0.75,
+      "out_of_vocabulary": [{ "word": "zyzzyva", "count": 1 }],
+    }));
+  }
+
+  #[test]
+  fn lookup_trace() {
+    let trace = LookupTrace {
+      word: "Cat".to_string(),
+      steps: vec![
+        LookupStep::Normalized { from: "Cat".to_string(), to: "cat".to_string() },
+        LookupStep::consulted(PronunciationSource::Dictionary, "cat", true),
+      ],
+      pronunciation: Some(polyphone("K AE1 T")),
+      source: Some(PronunciationSource::Dictionary),
+    };
+    let split = LookupTrace {
+      word: "x-cat".to_string(),
+      steps: vec![LookupStep::Split { parts: vec![trace.clone()] }],
+      pronunciation: None,
+      source: None,
+    };
+    assert_eq!(serde_json::to_value(&split).unwrap(), json!({
+      "schema_version": 1,
+      "word": "x-cat",
+      "steps": [{ "step": "split", "parts": [{
+        "schema_version": 1,
+        "word": "Cat",
+        "steps": [
+          { "step": "normalized", "from": "Cat", "to": "cat" },
+          { "step": "consulted", "source": "dictionary", "key": "cat", "found": true },
+        ],
+        "pronunciation": ["K", "AE1", "T"],
+        "source": "dictionary",
+      }]}],
+      "pronunciation": null,
+      "source": null,
+    }));
+  }
+
+  #[test]
+  fn issues_and_edits() {
+    let issues = vec![StreamIssue::Empty, StreamIssue::UnknownCode { position: 4, code: 250 }];
+    let value = serde_json::to_value(&issues).unwrap();
+    assert_eq!(value[0]["kind"], "empty");
+    assert!(value[0].get("position").is_none());
+    assert_eq!(value[1]["kind"], "unknown_code");
+    assert_eq!(value[1]["position"], 4);
+    assert_eq!(value[1]["code"], 250);
+    assert_eq!(value[1]["message"], issues[1].to_string());
+
+    let mismatch = Mismatch {
+      word: "read".to_string(),
+      expected: vec![polyphone("R IY1 D")],
+      found: vec![],
+    };
+    assert_eq!(serde_json::to_value(&mismatch).unwrap(),
+      json!({ "word": "read", "missing": true, "expected": [["R", "IY1", "D"]], "found": [] }));
+
+    let edits = vec![
+      TokenEdit::Delete { a: 5, token: SentenceToken::Punctuation(Punctuation::Space) },
+      TokenEdit::Substitute {
+        a: 3,
+        b: 3,
+        from: SentenceToken::Phoneme(polyphone("AE1")[0]),
+        to: SentenceToken::Punctuation(Punctuation::Period),
+      },
+    ];
+    assert_eq!(serde_json::to_value(&edits).unwrap(), json!([
+      { "op": "delete", "a": 5, "token": "[space]" },
+      { "op": "substitute", "a": 3, "b": 3, "from": "AE1", "to": "[period]" },
+    ]));
+  }
+
+  #[test]
+  fn dictionary_stats() {
+    let mut stats = DictionaryStats::from_polyphones(
+      [polyphone("K AE1 T"), polyphone("T AE1 K")].iter().map(|p| p.as_slice()));
+    stats.phonemes.unknown_words.push("zyzzyva".to_string());
+    assert_eq!(serde_json::to_value(&stats).unwrap(), json!({
+      "schema_version": 1,
+      "entries": 2,
+      "vowels": 2,
+      "consonants": 4,
+      "average_length": 3.0,
+      "max_length": 3,
+      "stress": { "primary": 2, "secondary": 0, "unstressed": 0, "unknown": 0 },
+      "lengths": [0, 0, 0, 2],
+      "phonemes": {
+        "schema_version": 1,
+        "total": 6,
+        "counts": { "AE": 2, "K": 2, "T": 2 },
+        "unknown_words": ["zyzzyva"],
+      },
+    }));
+  }
+
+  #[test]
+  fn memory_report() {
+    let report = MemoryReport {
+      entries: 2,
+      keys: 6,
+      values: 12,
+      table: 96,
+      static_entries: true,
+      indexes: IndexMemory { rhymes: 40, ..IndexMemory::default() },
+      frequencies: 0,
+      duplicate_values: 0,
+    };
+    let value = serde_json::to_value(report).unwrap();
+    assert_eq!(value["schema_version"], 1);
+    assert_eq!(value["static_entries"], true);
+    assert_eq!(value["indexes"]["rhymes"], 40);
+    assert_eq!(value["indexes"]["total"], 40);
+    assert_eq!(value["total"], 154);
+    assert_eq!(value["heap"], 40);
+  }
+
+  #[cfg(feature = "cache")]
+  #[test]
+  fn cache_stats() {
+    let stats = CacheStats { hits: 3, misses: 1 };
+    assert_eq!(serde_json::to_value(stats).unwrap(),
+      json!({ "schema_version": 1, "hits": 3, "misses": 1, "hit_rate": 0.75 }));
+  }
+}
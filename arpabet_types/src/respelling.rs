@@ -0,0 +1,304 @@
+//! This module converts informal pronunciation respellings, eg. "win" for
+//! 'nguyen' or "chee" for 'qi', into pronunciations, so that people who don't
+//! know ARPABET can contribute lexicon fixes.
+//!
+//! Respellings follow the common newspaper and dictionary key: syllables are
+//! separated by hyphens or spaces and the stressed syllable is written in
+//! capitals, eg. "uh-BOWT" for 'about'. When no syllable (or every syllable)
+//! is capitalized, the first is stressed. Letters are read by the key below,
+//! longest match first; doubled consonants are read once.
+//!
+//! | Respelling | ARPABET | | Respelling | ARPABET |
+//! |------------|---------|-|------------|---------|
+//! | a          | AE      | | ay         | EY      |
+//! | ah         | AA      | | ar         | AA R    |
+//! | aw         | AO      | | or         | AO R    |
+//! | e          | EH      | | air        | EH R    |
+//! | ee         | IY      | | eer        | IH R    |
+//! | i          | IH      | | eye, igh   | AY      |
+//! | o          | AA      | | oh         | OW      |
+//! | oo         | UW      | | uu         | UH      |
+//! | ow         | AW      | | oy         | OY      |
+//! | u, uh      | AH      | | er, ir, ur | ER      |
+//! | ew         | Y UW    | | x          | K S     |
+//! | ch, tch    | CH      | | sh         | SH      |
+//! | th         | TH      | | dh         | DH      |
+//! | zh         | ZH      | | ng         | NG      |
+//! | j          | JH      | | h          | HH      |
+//! | c, ck, k   | K       | | qu         | K W     |
+//! | ph         | F       | | wh         | W       |
+//!
+//! Other consonant letters read as their ARPABET consonant.
+//!
+//! The override file format has one entry per line, "word: respelling",
+//! with alternative respellings separated by commas. Blank lines and lines
+//! starting with '#' are ignored.
+//!
+//! ```text
+//! # Names our users reported.
+//! nguyen: win
+//! qi: chee
+//! either: EE-dhur, EYE-dhur
+//! ```
+
+use crate::casing::fold_case;
+use crate::error::{ArpabetError, WithContext};
+use crate::index::strip_variant;
+use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+use crate::{Arpabet, Polyphone, Word};
+use std::collections::HashMap;
+use std::fs;
+
+// Respelling letter groups and their sounds, longest first within each first letter.
+const KEY : [(&str, &[Sound]); 46] = [
+  ("tch", &[Sound::Consonant(Consonant::CH)]),
+  ("eye", &[Sound::Vowel(Vowel::AY(VowelStress::UnknownStress))]),
+  ("igh", &[Sound::Vowel(Vowel::AY(VowelStress::UnknownStress))]),
+  ("air", &[Sound::Vowel(Vowel::EH(VowelStress::UnknownStress)), Sound::Consonant(Consonant::R)]),
+  ("eer", &[Sound::Vowel(Vowel::IH(VowelStress::UnknownStress)), Sound::Consonant(Consonant::R)]),
+  ("ay", &[Sound::Vowel(Vowel::EY(VowelStress::UnknownStress))]),
+  ("ah", &[Sound::Vowel(Vowel::AA(VowelStress::UnknownStress))]),
+  ("ar", &[Sound::Vowel(Vowel::AA(VowelStress::UnknownStress)), Sound::Consonant(Consonant::R)]),
+  ("aw", &[Sound::Vowel(Vowel::AO(VowelStress::UnknownStress))]),
+  ("or", &[Sound::Vowel(Vowel::AO(VowelStress::UnknownStress)), Sound::Consonant(Consonant::R)]),
+  ("ee", &[Sound::Vowel(Vowel::IY(VowelStress::UnknownStress))]),
+  ("er", &[Sound::Vowel(Vowel::ER(VowelStress::UnknownStress))]),
+  ("ir", &[Sound::Vowel(Vowel::ER(VowelStress::UnknownStress))]),
+  ("ur", &[Sound::Vowel(Vowel::ER(VowelStress::UnknownStress))]),
+  ("ew", &[Sound::Consonant(Consonant::Y), Sound::Vowel(Vowel::UW(VowelStress::UnknownStress))]),
+  ("oh", &[Sound::Vowel(Vowel::OW(VowelStress::UnknownStress))]),
+  ("oo", &[Sound::Vowel(Vowel::UW(VowelStress::UnknownStress))]),
+  ("ow", &[Sound::Vowel(Vowel::AW(VowelStress::UnknownStress))]),
+  ("oy", &[Sound::Vowel(Vowel::OY(VowelStress::UnknownStress))]),
+  ("uu", &[Sound::Vowel(Vowel::UH(VowelStress::UnknownStress))]),
+  ("uh", &[Sound::Vowel(Vowel::AH(VowelStress::UnknownStress))]),
+  ("ch", &[Sound::Consonant(Consonant::CH)]),
+  ("ck", &[Sound::Consonant(Consonant::K)]),
+  ("dh", &[Sound::Consonant(Consonant::DH)]),
+  ("ng", &[Sound::Consonant(Consonant::NG)]),
+  ("ph", &[Sound::Consonant(Consonant::F)]),
+  ("qu", &[Sound::Consonant(Consonant::K), Sound::Consonant(Consonant::W)]),
+  ("sh", &[Sound::Consonant(Consonant::SH)]),
+  ("th", &[Sound::Consonant(Consonant::TH)]),
+  ("wh", &[Sound::Consonant(Consonant::W)]),
+  ("zh", &[Sound::Consonant(Consonant::ZH)]),
+  ("a", &[Sound::Vowel(Vowel::AE(VowelStress::UnknownStress))]),
+  ("e", &[Sound::Vowel(Vowel::EH(VowelStress::UnknownStress))]),
+  ("i", &[Sound::Vowel(Vowel::IH(VowelStress::UnknownStress))]),
+  ("o", &[Sound::Vowel(Vowel::AA(VowelStress::UnknownStress))]),
+  ("u", &[Sound::Vowel(Vowel::AH(VowelStress::UnknownStress))]),
+  ("c", &[Sound::Consonant(Consonant::K)]),
+  ("h", &[Sound::Consonant(Consonant::HH)]),
+  ("j", &[Sound::Consonant(Consonant::JH)]),
+  ("q", &[Sound::Consonant(Consonant::K)]),
+  ("x", &[Sound::Consonant(Consonant::K), Sound::Consonant(Consonant::S)]),
+  ("y", &[Sound::Consonant(Consonant::Y)]),
+  ("b", &[Sound::Consonant(Consonant::B)]),
+  ("d", &[Sound::Consonant(Consonant::D)]),
+  ("f", &[Sound::Consonant(Consonant::F)]),
+  ("g", &[Sound::Consonant(Consonant::G)]),
+];
+
+// The remaining consonant letters, read as the consonant of the same name.
+const PLAIN_CONSONANTS : [(char, Consonant); 11] = [
+  ('k', Consonant::K),
+  ('l', Consonant::L),
+  ('m', Consonant::M),
+  ('n', Consonant::N),
+  ('p', Consonant::P),
+  ('r', Consonant::R),
+  ('s', Consonant::S),
+  ('t', Consonant::T),
+  ('v', Consonant::V),
+  ('w', Consonant::W),
+  ('z', Consonant::Z),
+];
+
+#[derive(Clone, Copy)]
+enum Sound {
+  Consonant(Consonant),
+  Vowel(Vowel),
+}
+
+/// Convert a respelling, eg. "uh-BOWT", into a pronunciation. See the module
+/// documentation for the key. Fails with `ArpabetError::StringParseError` on
+/// characters outside the key, or if there are no vowels.
+pub fn respelling_to_polyphone(respelling: &str) -> Result<Polyphone, ArpabetError> {
+  let syllables = respelling.split(|c: char| c == '-' || c.is_whitespace())
+    .filter(|syllable| !syllable.is_empty())
+    .collect::<Vec<&str>>();
+
+  let stressed_count = syllables.iter().filter(|syllable| is_capitalized(syllable)).count();
+  let marked = stressed_count > 0 && stressed_count < syllables.len();
+
+  let mut polyphone = Vec::new();
+  for (i, syllable) in syllables.iter().enumerate() {
+    let stressed = if marked { is_capitalized(syllable) } else { i == 0 };
+    let stress = if stressed { VowelStress::PrimaryStress } else { VowelStress::NoStress };
+    let start = polyphone.len();
+    read_syllable(&fold_case(syllable), stress, &mut polyphone)
+      .map_err(|description| ArpabetError::StringParseError {
+        description: format!("{} in respelling '{}'", description, respelling),
+      })?;
+
+    // A consonant doubled across syllables is read once, eg. "WIN-ner".
+    if start > 0 && start < polyphone.len() && polyphone[start - 1] == polyphone[start]
+      && matches!(polyphone[start], Phoneme::Consonant(_))
+    {
+      polyphone.remove(start);
+    }
+  }
+
+  if !polyphone.iter().any(|phoneme| matches!(phoneme, Phoneme::Vowel(_))) {
+    return Err(ArpabetError::StringParseError {
+      description: format!("Respelling '{}' has no vowels", respelling),
+    });
+  }
+  Ok(polyphone)
+}
+
+fn is_capitalized(syllable: &str) -> bool {
+  syllable.chars().all(|c| !c.is_lowercase())
+}
+
+// Read one lowercase syllable. Only its first vowel takes the stress.
+fn read_syllable(syllable: &str, stress: VowelStress, polyphone: &mut Polyphone) -> Result<(), String> {
+  let mut rest = syllable.trim_matches('\'');
+  let mut stress = Some(stress);
+  let mut previous : Option<char> = None;
+
+  while let Some(c) = rest.chars().next() {
+    if c == '\'' {
+      rest = &rest[1 ..];
+      continue;
+    }
+
+    // Doubled consonant letters are read once, eg. "BELL".
+    if previous == Some(c) && !"aeiou".contains(c) {
+      rest = &rest[c.len_utf8() ..];
+      continue;
+    }
+    previous = Some(c);
+
+    if let Some((letters, sounds)) = KEY.iter().find(|(letters, _)| rest.starts_with(letters)) {
+      for sound in sounds.iter() {
+        polyphone.push(match sound {
+          Sound::Consonant(consonant) => Phoneme::Consonant(*consonant),
+          Sound::Vowel(vowel) => Phoneme::Vowel(vowel.with_stress(stress.take().unwrap_or(VowelStress::NoStress))),
+        });
+      }
+      rest = &rest[letters.len() ..];
+      continue;
+    }
+
+    match PLAIN_CONSONANTS.iter().find(|(letter, _)| *letter == c) {
+      Some((_, consonant)) => polyphone.push(Phoneme::Consonant(*consonant)),
+      None => return Err(format!("Unexpected character '{}'", c)),
+    }
+    rest = &rest[c.len_utf8() ..];
+  }
+  Ok(())
+}
+
+impl Arpabet {
+  /// Read overrides in the "word: respelling" format described in the module
+  /// documentation. Alternative respellings become alternate pronunciations,
+  /// eg. 'either(1)'. A word listed on several lines takes its last entry.
+  /// Merge the result into a dictionary with `merge_from`.
+  pub fn from_respellings(text: &str) -> Result<Arpabet, ArpabetError> {
+    let mut map : HashMap<Word, Polyphone> = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let invalid = || ArpabetError::InvalidFormat { line_number: i + 1, text: line.to_string() };
+      let (word, respellings) = line.split_once(':').ok_or_else(invalid)?;
+      let word = fold_case(word.trim());
+      if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(invalid());
+      }
+
+      map.retain(|key, _| strip_variant(key) != word);
+      for (variant, respelling) in respellings.split(',').enumerate() {
+        let polyphone = respelling_to_polyphone(respelling.trim())
+          .with_context(|| format!("Line {}", i + 1))?;
+        let key = match variant {
+          0 => word.clone(),
+          n => format!("{}({})", word, n),
+        };
+        map.insert(key, polyphone);
+      }
+    }
+
+    Ok(Arpabet::from_map(map))
+  }
+
+  /// Load overrides from a file in the "word: respelling" format. See
+  /// `from_respellings`.
+  pub fn load_respellings(filename: &str) -> Result<Arpabet, ArpabetError> {
+    fs::read_to_string(filename)
+      .map_err(ArpabetError::from)
+      .and_then(|text| Arpabet::from_respellings(&text))
+      .with_context(|| format!("Loading '{}'", filename))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn respellings() {
+    assert_eq!(respelling_to_polyphone("win").unwrap(), polyphone("W IH1 N"));
+    assert_eq!(respelling_to_polyphone("chee").unwrap(), polyphone("CH IY1"));
+    assert_eq!(respelling_to_polyphone("uh-BOWT").unwrap(), polyphone("AH0 B AW1 T"));
+    assert_eq!(respelling_to_polyphone("EYE-dhur").unwrap(), polyphone("AY1 DH ER0"));
+    assert_eq!(respelling_to_polyphone("WIN-ner").unwrap(), polyphone("W IH1 N ER0"));
+    assert_eq!(respelling_to_polyphone("SHAHR-lot").unwrap(), polyphone("SH AA1 R L AA0 T"));
+    assert_eq!(respelling_to_polyphone("kew").unwrap(), polyphone("K Y UW1"));
+    assert_eq!(respelling_to_polyphone("zoo").unwrap(), polyphone("Z UW1"));
+    assert_eq!(respelling_to_polyphone("ROH-zuh").unwrap(), polyphone("R OW1 Z AH0"));
+    assert_eq!(respelling_to_polyphone("KWIK SIL-ver").unwrap(), polyphone("K W IH1 K S IH1 L V ER0"));
+  }
+
+  #[test]
+  fn invalid_respellings() {
+    assert!(matches!(respelling_to_polyphone("w1n"), Err(ArpabetError::StringParseError { .. })));
+    assert!(matches!(respelling_to_polyphone("shh"), Err(ArpabetError::StringParseError { .. })));
+    assert!(matches!(respelling_to_polyphone(""), Err(ArpabetError::StringParseError { .. })));
+  }
+
+  #[test]
+  fn overrides() {
+    let text = "# Reported names.\n\
+                Nguyen: win\n\
+                \n\
+                qi: chee\n\
+                either: EE-dhur, EYE-dhur\n";
+    let arpa = Arpabet::from_respellings(text).unwrap();
+
+    assert_eq!(arpa.len(), 4);
+    assert_eq!(arpa.get_polyphone("nguyen"), Some(polyphone("W IH1 N")));
+    assert_eq!(arpa.get_polyphone("qi"), Some(polyphone("CH IY1")));
+    assert_eq!(arpa.get_polyphone("either(1)"), Some(polyphone("AY1 DH ER0")));
+
+    let replaced = Arpabet::from_respellings("either: EE-dhur, EYE-dhur\neither: EE-dhur").unwrap();
+    assert_eq!(replaced.len(), 1);
+  }
+
+  #[test]
+  fn invalid_overrides() {
+    match Arpabet::from_respellings("qi: chee\nnguyen win") {
+      Err(ArpabetError::InvalidFormat { line_number: 2, .. }) => {},
+      other => panic!("Unexpected result: {:?}", other.map(|arpa| arpa.len())),
+    }
+
+    let err = Arpabet::from_respellings("qi: chee\nnguyen: w1n").err().unwrap();
+    assert!(matches!(err.root(), ArpabetError::StringParseError { .. }));
+    assert!(err.to_string().starts_with("Line 2: "));
+  }
+}
@@ -0,0 +1,137 @@
+//! This module finds words whose pronunciations read the same backwards, and
+//! pairs of words that are each other's pronunciation reversed, eg. for word
+//! games. Stress is significant throughout: 'tip' (T IH1 P) reverses to 'pit'
+//! (P IH1 T), but T IH1 P does not reverse to P IH0 T.
+
+use crate::phoneme::Phoneme;
+use crate::{Arpabet, Polyphone};
+
+/// Whether a pronunciation of two or more phonemes reads the same backwards.
+pub fn is_palindrome(polyphone: &[Phoneme]) -> bool {
+  polyphone.len() > 1 && polyphone.iter().eq(polyphone.iter().rev())
+}
+
+/// A pair of words, each pronounced as the other reversed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReversiblePair<'a> {
+  /// The alphabetically first word.
+  pub first: &'a str,
+  /// The alphabetically second word.
+  pub second: &'a str,
+}
+
+impl Arpabet {
+  /// Whether any pronunciation of the word reads the same backwards, eg. 'bob'
+  /// (B AA1 B). 'kayak' (K AY1 AE0 K) is a palindrome in spelling only.
+  pub fn is_phonetic_palindrome(&self, word: &str) -> bool {
+    self.get_polyphone_variants(word)
+      .into_iter()
+      .any(is_palindrome)
+  }
+
+  /// Find all words with a pronunciation that reads the same backwards.
+  /// Results are ranked by attached frequencies, if any, then alphabetically.
+  pub fn phonetic_palindromes(&self) -> Vec<&str> {
+    let mut words = self.reverse_index()
+      .iter()
+      .filter(|(polyphone, _)| is_palindrome(polyphone))
+      .flat_map(|(_, words)| words.iter().map(|word| word.as_str()))
+      .collect::<Vec<&str>>();
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find the words pronounced as a pronunciation of the word reversed, eg.
+  /// 'pit' for 'tip'. A palindrome's reversal includes the word itself.
+  /// Results are ranked by attached frequencies, if any, then alphabetically.
+  pub fn reversals_of(&self, word: &str) -> Vec<&str> {
+    let index = self.reverse_index();
+
+    let mut words = self.get_polyphone_variants(word)
+      .into_iter()
+      .filter_map(|polyphone| index.get(&reversed(polyphone)))
+      .flatten()
+      .map(|word| word.as_str())
+      .collect::<Vec<&str>>();
+
+    words.sort();
+    words.dedup();
+    self.rank_by_frequency(&mut words);
+    words
+  }
+
+  /// Find all pairs of different words where a pronunciation of one is a
+  /// pronunciation of the other reversed. Homophones of palindromes, eg. 'tot'
+  /// and 'taught', are not paired. Pairs are sorted by their first word, then
+  /// their second.
+  pub fn reversible_pairs(&self) -> Vec<ReversiblePair<'_>> {
+    let index = self.reverse_index();
+    let mut pairs = Vec::new();
+
+    for (polyphone, words) in index.iter().filter(|(polyphone, _)| !is_palindrome(polyphone)) {
+      let partners = match index.get(&reversed(polyphone)) {
+        Some(partners) => partners,
+        None => continue,
+      };
+      for first in words.iter() {
+        for second in partners.iter().filter(|second| first < *second) {
+          pairs.push(ReversiblePair { first, second });
+        }
+      }
+    }
+
+    pairs.sort_by(|a, b| a.first.cmp(b.first).then_with(|| a.second.cmp(b.second)));
+    pairs.dedup();
+    pairs
+  }
+}
+
+fn reversed(polyphone: &[Phoneme]) -> Polyphone {
+  polyphone.iter().rev().copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::arpabet;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("tip", "T IH1 P"),
+    ("pit", "P IH1 T"),
+    ("tot", "T AA1 T"),
+    ("taught", "T AA1 T"),
+    ("top", "T AA1 P"),
+    ("pot", "P AA1 T"),
+    ("pot(1)", "P AO1 T"),
+    ("a", "AH0"),
+    ("cat", "K AE1 T"),
+  ];
+
+  #[test]
+  fn palindromes() {
+    let arpa = arpabet(DICTIONARY);
+    assert!(arpa.is_phonetic_palindrome("tot"));
+    assert!(!arpa.is_phonetic_palindrome("tip"));
+    assert!(!arpa.is_phonetic_palindrome("a"));
+    assert!(!arpa.is_phonetic_palindrome("missing"));
+    assert_eq!(arpa.phonetic_palindromes(), vec!["taught", "tot"]);
+  }
+
+  #[test]
+  fn reversals() {
+    let arpa = arpabet(DICTIONARY);
+    assert_eq!(arpa.reversals_of("tip"), vec!["pit"]);
+    assert_eq!(arpa.reversals_of("pot"), vec!["top"]);
+    assert_eq!(arpa.reversals_of("tot"), vec!["taught", "tot"]);
+    assert!(arpa.reversals_of("cat").is_empty());
+
+    let pairs = arpa.reversible_pairs();
+    assert_eq!(pairs, vec![
+      ReversiblePair { first: "pit", second: "tip" },
+      ReversiblePair { first: "pot", second: "top" },
+    ]);
+  }
+}
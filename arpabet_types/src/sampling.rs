@@ -0,0 +1,179 @@
+//! This module draws samples of the dictionary balanced across phonetic
+//! properties, eg. to build listening-test or recording sets that cover short
+//! and long words, every initial sound and every stress pattern alike rather
+//! than in their dictionary proportions.
+//!
+//! Samples are reproducible: the same dictionary, specification and seed
+//! always draw the same words.
+
+use crate::index::strip_variant;
+use crate::stress::{stress_pattern, PolyphoneStress};
+use crate::syllable::is_syllabic;
+use crate::Arpabet;
+use std::collections::BTreeMap;
+
+/// What to balance a sample across. See `Arpabet::stratified_sample`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SamplingSpec {
+  /// The number of words to draw. Fewer are drawn if fewer words qualify.
+  pub size: usize,
+  /// Balance across syllable counts.
+  pub by_syllables: bool,
+  /// Balance across the first phoneme, ignoring stress.
+  pub by_initial_phoneme: bool,
+  /// Balance across stress patterns, eg. "10" and "01".
+  pub by_stress_pattern: bool,
+  /// Only draw words with at least this many syllables.
+  pub min_syllables: Option<usize>,
+  /// Only draw words with at most this many syllables, eg. to keep prompts
+  /// short.
+  pub max_syllables: Option<usize>,
+}
+
+// The properties a word is grouped by; None for those not balanced.
+type Stratum = (Option<usize>, Option<&'static str>, Option<String>);
+
+impl Arpabet {
+  /// Draw a sample of words balanced across the properties in the
+  /// specification. Words are grouped by every property balanced, and drawn
+  /// from each group in turn, so every group contributes equally until it
+  /// runs out. With no property balanced, this is a plain random sample.
+  ///
+  /// Only each word's primary pronunciation is considered, and each word is
+  /// drawn at most once. Words are returned in the order drawn.
+  pub fn stratified_sample(&self, spec: &SamplingSpec, seed: u64) -> Vec<&str> {
+    let mut strata : BTreeMap<Stratum, Vec<&str>> = BTreeMap::new();
+    for (key, polyphone) in self.dictionary.iter() {
+      if strip_variant(key) != key {
+        continue;
+      }
+      let syllables = polyphone.iter().filter(|phoneme| is_syllabic(phoneme)).count();
+      if matches!(spec.min_syllables, Some(min) if syllables < min)
+        || matches!(spec.max_syllables, Some(max) if syllables > max)
+      {
+        continue;
+      }
+      let stratum = (
+        Some(syllables).filter(|_| spec.by_syllables),
+        Some(polyphone).filter(|_| spec.by_initial_phoneme)
+          .and_then(|polyphone| polyphone.to_stressless_strs().first().copied()),
+        Some(stress_pattern(polyphone).to_string()).filter(|_| spec.by_stress_pattern),
+      );
+      strata.entry(stratum).or_default().push(key);
+    }
+
+    let mut rng = SplitMix64(seed);
+    let mut strata : Vec<Vec<&str>> = strata.into_values()
+      .map(|mut words| {
+        // Sort first, as the dictionary's iteration order isn't fixed.
+        words.sort_unstable();
+        rng.shuffle(&mut words);
+        words
+      })
+      .collect();
+
+    // Take one word from each group per round, visiting the groups in a new
+    // order each round so a final partial round favours none of them.
+    let mut sample = Vec::with_capacity(spec.size);
+    let mut round = 0;
+    while sample.len() < spec.size {
+      strata.retain(|words| words.len() > round);
+      if strata.is_empty() {
+        break;
+      }
+      let mut order : Vec<usize> = (0 .. strata.len()).collect();
+      rng.shuffle(&mut order);
+      for i in order.into_iter().take(spec.size - sample.len()) {
+        sample.push(strata[i][round]);
+      }
+      round += 1;
+    }
+    sample
+  }
+}
+
+// A small, fast generator, so samples don't depend on an external crate's
+// algorithm staying the same between versions.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+  fn next(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  // Fisher-Yates. The modulo bias is negligible for dictionary sizes.
+  fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1 .. items.len()).rev() {
+      let j = (self.next() % (i as u64 + 1)) as usize;
+      items.swap(i, j);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+  use std::collections::HashMap;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("cat", "K AE1 T"),
+    ("cap", "K AE1 P"),
+    ("can", "K AE1 N"),
+    ("cab", "K AE1 B"),
+    ("cad", "K AE1 D"),
+    ("cam", "K AE1 M"),
+    ("sat", "S AE1 T"),
+    ("sap", "S AE1 P"),
+    ("cattle", "K AE1 T AH0 L"),
+    ("about", "AH0 B AW1 T"),
+    ("about(1)", "AH0 B AW1"),
+    ("catalog", "K AE1 T AH0 L AO2 G"),
+  ];
+
+  #[test]
+  fn balanced() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let spec = SamplingSpec { size: 3, by_syllables: true, ..Default::default() };
+    let mut sample = arpa.stratified_sample(&spec, 7);
+    let syllables = |word: &str| arpa.get_polyphone_ref(word).unwrap().iter()
+      .filter(|phoneme| is_syllabic(phoneme))
+      .count();
+    sample.sort_by_key(|word| syllables(word));
+    assert_eq!(sample.iter().map(|word| syllables(word)).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    let spec = SamplingSpec { size: 4, by_initial_phoneme: true, max_syllables: Some(1),
+      ..Default::default() };
+    let mut initials : HashMap<char, usize> = HashMap::new();
+    for word in arpa.stratified_sample(&spec, 7) {
+      *initials.entry(word.chars().next().unwrap()).or_default() += 1;
+    }
+    assert_eq!(initials.get(&'c'), Some(&2));
+    assert_eq!(initials.get(&'s'), Some(&2));
+  }
+
+  #[test]
+  fn reproducible() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let spec = SamplingSpec { size: 5, by_stress_pattern: true, ..Default::default() };
+    assert_eq!(arpa.stratified_sample(&spec, 1), arpa.stratified_sample(&spec, 1));
+    assert_ne!(arpa.stratified_sample(&spec, 1), arpa.stratified_sample(&spec, 2));
+
+    let all = SamplingSpec { size: 100, ..Default::default() };
+    let mut sample = arpa.stratified_sample(&all, 1);
+    assert_eq!(sample.len(), 11);
+    sample.sort_unstable();
+    sample.dedup();
+    assert_eq!(sample.len(), 11);
+
+    let short = SamplingSpec { size: 100, min_syllables: Some(2), max_syllables: Some(2),
+      ..Default::default() };
+    let mut sample = arpa.stratified_sample(&short, 1);
+    sample.sort_unstable();
+    assert_eq!(sample, vec!["about", "cattle"]);
+  }
+}
@@ -0,0 +1,129 @@
+//! This module renders pronunciations as SSML `<phoneme>` markup, eg. to
+//! control how a cloud speech service such as Amazon Polly or Azure reads
+//! words it would otherwise guess at:
+//!
+//! ```xml
+//! <phoneme alphabet="ipa" ph="ˈtɛst">test</phoneme>
+//! ```
+//!
+//! Output is an SSML fragment; wrap it in `<speak>` tags before sending it.
+//! Text and attribute values are escaped.
+
+use crate::error::ArpabetError;
+use crate::ipa::polyphone_to_ipa;
+use crate::phoneme::Phoneme;
+use crate::tokenizer::OffsetUnit;
+use crate::transcribe::TranscribeOptions;
+use crate::Arpabet;
+
+/// The phonetic alphabet `<phoneme>` tags are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SsmlAlphabet {
+  /// The International Phonetic Alphabet, "ipa", which most services accept.
+  #[default]
+  Ipa,
+  /// Arpabet symbols with stress digits, "x-arpabet", eg. "T EH1 S T".
+  Arpabet,
+}
+
+impl SsmlAlphabet {
+  /// The value of the `alphabet` attribute.
+  pub fn to_str(&self) -> &'static str {
+    match self {
+      SsmlAlphabet::Ipa => "ipa",
+      SsmlAlphabet::Arpabet => "x-arpabet",
+    }
+  }
+
+  // The pronunciation written in the alphabet.
+  fn transcribe(&self, polyphone: &[Phoneme]) -> String {
+    match self {
+      SsmlAlphabet::Ipa => polyphone_to_ipa(polyphone),
+      SsmlAlphabet::Arpabet => {
+        polyphone.iter().map(Phoneme::to_str).collect::<Vec<&str>>().join(" ")
+      },
+    }
+  }
+}
+
+/// Render a word and its pronunciation as a `<phoneme>` element, eg.
+/// `<phoneme alphabet="x-arpabet" ph="T EH1 S T">test</phoneme>`.
+pub fn polyphone_to_ssml(word: &str, polyphone: &[Phoneme], alphabet: SsmlAlphabet) -> String {
+  format!("<phoneme alphabet=\"{}\" ph=\"{}\">{}</phoneme>",
+    alphabet.to_str(), escape_ssml(&alphabet.transcribe(polyphone)), escape_ssml(word))
+}
+
+impl Arpabet {
+  /// Render text as SSML with each word wrapped in a `<phoneme>` element.
+  /// Words are pronounced as by `transcribe_words`; the text between them,
+  /// and words left out under `options.unknown_words`, is kept as it is.
+  pub fn transcribe_ssml(&self, text: &str, alphabet: SsmlAlphabet, options: &TranscribeOptions)
+    -> Result<String, ArpabetError>
+  {
+    let options = TranscribeOptions { offsets: OffsetUnit::Bytes, ..options.clone() };
+    let mut ssml = String::with_capacity(text.len() * 4);
+    let mut end = 0;
+    for word in self.transcribe_words(text, &options)? {
+      ssml.push_str(&escape_ssml(&text[end .. word.span.start]));
+      ssml.push_str(&polyphone_to_ssml(&text[word.span.start .. word.span.end], &word.polyphone,
+        alphabet));
+      end = word.span.end;
+    }
+    ssml.push_str(&escape_ssml(&text[end ..]));
+    Ok(ssml)
+  }
+}
+
+/// Escape text for SSML content or attribute values.
+pub fn escape_ssml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+  use crate::transcribe::UnknownWordPolicy;
+
+  #[test]
+  fn elements() {
+    assert_eq!(polyphone_to_ssml("test", &polyphone("T EH1 S T"), SsmlAlphabet::Arpabet),
+      "<phoneme alphabet=\"x-arpabet\" ph=\"T EH1 S T\">test</phoneme>");
+    assert_eq!(polyphone_to_ssml("test", &polyphone("T EH1 S T"), SsmlAlphabet::Ipa),
+      "<phoneme alphabet=\"ipa\" ph=\"ˈtɛst\">test</phoneme>");
+    assert_eq!(polyphone_to_ssml("<&>", &polyphone("AE1 N D"), SsmlAlphabet::Arpabet),
+      "<phoneme alphabet=\"x-arpabet\" ph=\"AE1 N D\">&lt;&amp;&gt;</phoneme>");
+    assert_eq!(escape_ssml("\"Rock 'n' roll\""), "&quot;Rock &apos;n&apos; roll&quot;");
+  }
+
+  #[test]
+  fn sentences() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("tom".to_string(), polyphone("T AA1 M"));
+    arpa.insert("jerry".to_string(), polyphone("JH EH1 R IY0"));
+
+    assert_eq!(arpa.transcribe_ssml("Tom & Jerry!", SsmlAlphabet::Arpabet,
+        &TranscribeOptions::default()).unwrap(),
+      "<phoneme alphabet=\"x-arpabet\" ph=\"T AA1 M\">Tom</phoneme> &amp; \
+        <phoneme alphabet=\"x-arpabet\" ph=\"JH EH1 R IY0\">Jerry</phoneme>!");
+
+    let options = TranscribeOptions {
+      unknown_words: UnknownWordPolicy::Skip,
+      ..Default::default()
+    };
+    assert_eq!(arpa.transcribe_ssml("Tom <xyzzy>", SsmlAlphabet::Ipa, &options).unwrap(),
+      "<phoneme alphabet=\"ipa\" ph=\"ˈtɑm\">Tom</phoneme> &lt;xyzzy&gt;");
+    assert!(arpa.transcribe_ssml("Tom <xyzzy>", SsmlAlphabet::Ipa, &Default::default()).is_err());
+  }
+}
@@ -0,0 +1,351 @@
+//! This module summarizes a dictionary's pronunciations: how often each
+//! phoneme occurs, how long pronunciations are, and how stress is distributed,
+//! eg. to balance training data for a grapheme-to-phoneme model.
+//!
+//! A `VowelProfile` breaks the vowels down further, by vowel, category and
+//! stress, to compare lexicons: a custom lexicon whose proportions differ
+//! markedly from CMUdict's likely follows different transcription conventions,
+//! eg. AH0 where CMUdict has IH0.
+//!
+//! The statistics take a pass over every entry, so compute them once and keep
+//! the result rather than calling `Arpabet::stats` repeatedly.
+
+use crate::articulation::{Backness, Height};
+use crate::histogram::PhonemeHistogram;
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::Arpabet;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// The number of vowels with each level of stress.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StressCounts {
+  /// Vowels with primary stress, eg. AH1.
+  pub primary: usize,
+  /// Vowels with secondary stress, eg. AH2.
+  pub secondary: usize,
+  /// Unstressed vowels, eg. AH0.
+  pub unstressed: usize,
+  /// Vowels written without a stress digit, eg. AH.
+  pub unknown: usize,
+}
+
+impl StressCounts {
+  /// The number of vowels counted.
+  pub fn total(&self) -> usize {
+    self.primary + self.secondary + self.unstressed + self.unknown
+  }
+
+  /// Count a vowel's stress.
+  pub fn add(&mut self, stress: &VowelStress) {
+    match stress {
+      VowelStress::PrimaryStress => self.primary += 1,
+      VowelStress::SecondaryStress => self.secondary += 1,
+      VowelStress::NoStress => self.unstressed += 1,
+      VowelStress::UnknownStress => self.unknown += 1,
+    }
+  }
+}
+
+/// Statistics of a dictionary's pronunciations. Alternate pronunciations count
+/// as entries of their own. See `Arpabet::stats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DictionaryStats {
+  /// The number of entries.
+  pub entries: usize,
+  /// Occurrences of each phoneme, ignoring stress.
+  pub phonemes: PhonemeHistogram,
+  /// The number of vowels.
+  pub vowels: usize,
+  /// The number of consonants.
+  pub consonants: usize,
+  /// The stress of the vowels.
+  pub stress: StressCounts,
+  /// The number of entries of each pronunciation length: `lengths[n]` entries
+  /// have n phonemes.
+  pub lengths: Vec<usize>,
+}
+
+impl DictionaryStats {
+  /// Gather statistics of pronunciations.
+  pub fn from_polyphones<'a, I: IntoIterator<Item=&'a [Phoneme]>>(polyphones: I) -> Self {
+    let mut stats = Self::default();
+    for polyphone in polyphones {
+      stats.add(polyphone);
+    }
+    stats
+  }
+
+  /// Count a pronunciation.
+  pub fn add(&mut self, polyphone: &[Phoneme]) {
+    self.entries += 1;
+    self.phonemes.add(polyphone);
+    for phoneme in polyphone {
+      match phoneme {
+        Phoneme::Vowel(vowel) => {
+          self.vowels += 1;
+          self.stress.add(vowel.get_stress());
+        },
+        Phoneme::Consonant(_) => self.consonants += 1,
+      }
+    }
+    if self.lengths.len() <= polyphone.len() {
+      self.lengths.resize(polyphone.len() + 1, 0);
+    }
+    self.lengths[polyphone.len()] += 1;
+  }
+
+  /// The number of phonemes counted.
+  pub fn total_phonemes(&self) -> usize {
+    self.vowels + self.consonants
+  }
+
+  /// The mean number of phonemes per entry. Zero when empty.
+  pub fn average_length(&self) -> f64 {
+    ratio(self.total_phonemes(), self.entries)
+  }
+
+  /// The length of the longest pronunciation.
+  pub fn max_length(&self) -> usize {
+    self.lengths.len().saturating_sub(1)
+  }
+
+  /// The fraction of phonemes that are vowels. Zero when empty.
+  pub fn vowel_fraction(&self) -> f64 {
+    ratio(self.vowels, self.total_phonemes())
+  }
+
+  /// The fraction of phonemes that are consonants. Zero when empty.
+  pub fn consonant_fraction(&self) -> f64 {
+    ratio(self.consonants, self.total_phonemes())
+  }
+
+  /// The number of vowels per consonant. Zero without consonants.
+  pub fn vowel_consonant_ratio(&self) -> f64 {
+    ratio(self.vowels, self.consonants)
+  }
+
+  /// The fraction of vowels with primary stress. Zero without vowels.
+  pub fn primary_stress_fraction(&self) -> f64 {
+    ratio(self.stress.primary, self.stress.total())
+  }
+}
+
+impl fmt::Display for DictionaryStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "entries: {}", self.entries)?;
+    writeln!(f, "phonemes: {} ({:.2} per entry, longest {})",
+      self.total_phonemes(), self.average_length(), self.max_length())?;
+    writeln!(f, "vowels: {} ({:.1}%)", self.vowels, 100.0 * self.vowel_fraction())?;
+    writeln!(f, "consonants: {} ({:.1}%)", self.consonants, 100.0 * self.consonant_fraction())?;
+    writeln!(f, "stress: primary {}, secondary {}, unstressed {}, unknown {}",
+      self.stress.primary, self.stress.secondary, self.stress.unstressed, self.stress.unknown)
+  }
+}
+
+/// The vowels of a set of pronunciations by vowel, category and stress. See
+/// `Arpabet::vowel_profile`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VowelProfile {
+  /// The stress of each vowel's occurrences, by the vowel without stress, eg.
+  /// "AH".
+  pub vowels: BTreeMap<&'static str, StressCounts>,
+  /// The stress of all the vowels.
+  pub stress: StressCounts,
+  /// The number of vowels of each tongue height.
+  pub heights: HashMap<Height, usize>,
+  /// The number of vowels of each tongue backness.
+  pub backness: HashMap<Backness, usize>,
+  /// The number of rounded vowels.
+  pub rounded: usize,
+  /// The number of diphthongs: AW, AY, EY, OW and OY.
+  pub diphthongs: usize,
+}
+
+impl VowelProfile {
+  /// Profile the vowels of pronunciations.
+  pub fn from_polyphones<'a, I: IntoIterator<Item=&'a [Phoneme]>>(polyphones: I) -> Self {
+    let mut profile = Self::default();
+    for polyphone in polyphones {
+      profile.add(polyphone);
+    }
+    profile
+  }
+
+  /// Count the vowels of a pronunciation.
+  pub fn add(&mut self, polyphone: &[Phoneme]) {
+    for phoneme in polyphone {
+      if let Phoneme::Vowel(vowel) = phoneme {
+        self.vowels.entry(vowel.to_str_stressless()).or_default().add(vowel.get_stress());
+        self.stress.add(vowel.get_stress());
+        *self.heights.entry(vowel.height()).or_insert(0) += 1;
+        *self.backness.entry(vowel.backness()).or_insert(0) += 1;
+        if vowel.is_rounded() {
+          self.rounded += 1;
+        }
+        if is_diphthong(vowel) {
+          self.diphthongs += 1;
+        }
+      }
+    }
+  }
+
+  /// The number of vowels counted.
+  pub fn total(&self) -> usize {
+    self.stress.total()
+  }
+
+  /// The fraction of vowels that are the vowel, eg. "AH", in any stress.
+  /// Zero without vowels.
+  pub fn fraction(&self, vowel: &str) -> f64 {
+    ratio(self.vowels.get(vowel).map(StressCounts::total).unwrap_or(0), self.total())
+  }
+
+  /// The fraction of the vowel's occurrences, eg. of "AH", that are
+  /// unstressed. Zero if it doesn't occur.
+  pub fn unstressed_fraction(&self, vowel: &str) -> f64 {
+    self.vowels.get(vowel)
+      .map(|stress| ratio(stress.unstressed, stress.total()))
+      .unwrap_or(0.0)
+  }
+
+  /// The fraction of vowels of the tongue height. Zero without vowels.
+  pub fn height_fraction(&self, height: Height) -> f64 {
+    ratio(self.heights.get(&height).copied().unwrap_or(0), self.total())
+  }
+
+  /// The fraction of vowels of the tongue backness. Zero without vowels.
+  pub fn backness_fraction(&self, backness: Backness) -> f64 {
+    ratio(self.backness.get(&backness).copied().unwrap_or(0), self.total())
+  }
+
+  /// The fraction of vowels that are rounded. Zero without vowels.
+  pub fn rounded_fraction(&self) -> f64 {
+    ratio(self.rounded, self.total())
+  }
+
+  /// The fraction of vowels that are diphthongs. Zero without vowels.
+  pub fn diphthong_fraction(&self) -> f64 {
+    ratio(self.diphthongs, self.total())
+  }
+
+  /// How the proportion of each vowel differs from another profile's, eg. a
+  /// custom lexicon's from CMUdict's: this profile's fraction minus the
+  /// other's, for the vowels of either, largest difference first.
+  pub fn differences(&self, other: &VowelProfile) -> Vec<(&'static str, f64)> {
+    let mut vowels : Vec<&'static str> = self.vowels.keys()
+      .chain(other.vowels.keys())
+      .copied()
+      .collect();
+    vowels.sort_unstable();
+    vowels.dedup();
+    let mut differences : Vec<(&'static str, f64)> = vowels.into_iter()
+      .map(|vowel| (vowel, self.fraction(vowel) - other.fraction(vowel)))
+      .collect();
+    differences.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then_with(|| a.0.cmp(b.0)));
+    differences
+  }
+}
+
+impl fmt::Display for VowelProfile {
+  /// One line per vowel: its share of the vowels, then its counts of primary,
+  /// secondary, no and unknown stress.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (vowel, stress) in &self.vowels {
+      writeln!(f, "{}\t{:.1}%\t{}\t{}\t{}\t{}", vowel, 100.0 * self.fraction(vowel),
+        stress.primary, stress.secondary, stress.unstressed, stress.unknown)?;
+    }
+    Ok(())
+  }
+}
+
+impl Arpabet {
+  /// Gather statistics of every entry's pronunciation, including alternates.
+  pub fn stats(&self) -> DictionaryStats {
+    DictionaryStats::from_polyphones(self.values())
+  }
+
+  /// Profile the vowels of every entry's pronunciation, including alternates.
+  pub fn vowel_profile(&self) -> VowelProfile {
+    VowelProfile::from_polyphones(self.values())
+  }
+}
+
+fn is_diphthong(vowel: &Vowel) -> bool {
+  matches!(vowel, Vowel::AW(_) | Vowel::AY(_) | Vowel::EY(_) | Vowel::OW(_) | Vowel::OY(_))
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+  match denominator {
+    0 => 0.0,
+    denominator => numerator as f64 / denominator as f64,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{self, polyphone};
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("cat", "K AE1 T"),
+    ("a", "AH0"),
+    ("a(1)", "EY1"),
+    ("sofa", "S OW1 F AH0"),
+    ("into", "IH2 N T UW"),
+  ];
+
+  #[test]
+  fn stats() {
+    let stats = testing::arpabet(DICTIONARY).stats();
+    assert_eq!(stats.entries, 5);
+    assert_eq!(stats.total_phonemes(), 13);
+    assert_eq!(stats.vowels, 7);
+    assert_eq!(stats.consonants, 6);
+    assert_eq!(stats.stress, StressCounts { primary: 3, secondary: 1, unstressed: 2, unknown: 1 });
+    assert_eq!(stats.lengths, vec![0, 2, 0, 1, 2]);
+    assert_eq!(stats.max_length(), 4);
+    assert!((stats.average_length() - 2.6).abs() < 1e-9);
+    assert!((stats.vowel_fraction() + stats.consonant_fraction() - 1.0).abs() < 1e-9);
+    assert!((stats.vowel_consonant_ratio() - 7.0 / 6.0).abs() < 1e-9);
+    assert!((stats.primary_stress_fraction() - 3.0 / 7.0).abs() < 1e-9);
+    assert_eq!(stats.phonemes.count("AH"), 2);
+    assert_eq!(stats.phonemes.count("T"), 2);
+  }
+
+  #[test]
+  fn vowel_profile() {
+    let profile = testing::arpabet(DICTIONARY).vowel_profile();
+    assert_eq!(profile.total(), 7);
+    assert_eq!(profile.vowels["AH"], StressCounts { unstressed: 2, ..Default::default() });
+    assert_eq!(profile.vowels.keys().copied().collect::<Vec<_>>(),
+      vec!["AE", "AH", "EY", "IH", "OW", "UW"]);
+    assert!((profile.fraction("AH") - 2.0 / 7.0).abs() < 1e-9);
+    assert_eq!(profile.fraction("IY"), 0.0);
+    assert_eq!(profile.unstressed_fraction("AH"), 1.0);
+    assert_eq!(profile.heights[&Height::High], 2);
+    assert!((profile.backness_fraction(Backness::Front) - 3.0 / 7.0).abs() < 1e-9);
+    assert_eq!(profile.rounded, 2);
+    assert_eq!(profile.diphthongs, 2);
+    assert!(profile.to_string().starts_with("AE\t14.3%\t1\t0\t0\t0\n"));
+
+    let mut other = Arpabet::new();
+    other.insert("cat".to_string(), polyphone("K AE1 T"));
+    other.insert("sofa".to_string(), polyphone("S OW1 F IH0"));
+    let differences = profile.differences(&other.vowel_profile());
+    assert_eq!(differences[0].0, "AH");
+    assert!((differences[0].1 - 2.0 / 7.0).abs() < 1e-9);
+    assert_eq!(differences.len(), 6);
+  }
+
+  #[test]
+  fn empty() {
+    let stats = Arpabet::new().stats();
+    assert_eq!(stats.entries, 0);
+    assert_eq!(stats.average_length(), 0.0);
+    assert_eq!(stats.vowel_fraction(), 0.0);
+    assert_eq!(stats.max_length(), 0);
+    assert!(stats.to_string().starts_with("entries: 0\n"));
+    assert_eq!(Arpabet::new().vowel_profile().rounded_fraction(), 0.0);
+  }
+}
@@ -0,0 +1,121 @@
+//! This module contains the storage behind an Arpabet. A dictionary generated
+//! at compile time is used in place, and only copied into a heap map once it
+//! is modified.
+
+use crate::{Phoneme, Polyphone, Word};
+use std::collections::HashMap;
+
+/// A compile-time map of words to pronunciations, as generated with phf_codegen.
+pub(crate) type StaticDictionary = phf::Map<&'static str, &'static [Phoneme]>;
+
+/// Dictionary entries, either borrowed from a compile-time map or owned.
+#[derive(Clone)]
+pub(crate) enum Store {
+  /// Entries of a compile-time map, which are never copied.
+  Static(&'static StaticDictionary),
+  /// Entries on the heap.
+  Owned(HashMap<Word, Polyphone>),
+}
+
+impl Default for Store {
+  fn default() -> Self {
+    Store::Owned(HashMap::new())
+  }
+}
+
+impl Store {
+  pub(crate) fn get(&self, word: &str) -> Option<&[Phoneme]> {
+    match self {
+      Store::Static(map) => map.get(word).copied(),
+      Store::Owned(map) => map.get(word).map(|polyphone| polyphone.as_slice()),
+    }
+  }
+
+  pub(crate) fn get_key_value(&self, word: &str) -> Option<(&str, &[Phoneme])> {
+    match self {
+      Store::Static(map) => map.get_entry(word).map(|(key, polyphone)| (*key, *polyphone)),
+      Store::Owned(map) => map.get_key_value(word)
+        .map(|(key, polyphone)| (key.as_str(), polyphone.as_slice())),
+    }
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    match self {
+      Store::Static(map) => map.len(),
+      Store::Owned(map) => map.len(),
+    }
+  }
+
+  /// Iterate over entries in arbitrary order.
+  pub(crate) fn iter(&self) -> Box<dyn Iterator<Item=(&str, &[Phoneme])> + '_> {
+    match self {
+      Store::Static(map) => Box::new(map.entries().map(|(key, polyphone)| (*key, *polyphone))),
+      Store::Owned(map) => Box::new(map.iter()
+        .map(|(key, polyphone)| (key.as_str(), polyphone.as_slice()))),
+    }
+  }
+
+  /// Get the entries for modification, first copying a compile-time map to the heap.
+  pub(crate) fn to_mut(&mut self) -> &mut HashMap<Word, Polyphone> {
+    if let Store::Static(map) = self {
+      let owned = map.entries()
+        .map(|(key, polyphone)| (key.to_string(), polyphone.to_vec()))
+        .collect();
+      *self = Store::Owned(owned);
+    }
+    match self {
+      Store::Owned(map) => map,
+      Store::Static(_) => unreachable!("promoted to an owned map above"),
+    }
+  }
+
+  /// Take the entries, copying a compile-time map to the heap.
+  pub(crate) fn into_owned(mut self) -> HashMap<Word, Polyphone> {
+    self.to_mut();
+    match self {
+      Store::Owned(map) => map,
+      Store::Static(_) => unreachable!("promoted to an owned map above"),
+    }
+  }
+
+  /// Whether the entries are borrowed from a compile-time map.
+  #[cfg(test)]
+  pub(crate) fn is_static(&self) -> bool {
+    matches!(self, Store::Static(_))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Consonant, Vowel, VowelStress};
+  use crate::Arpabet;
+  use phf::phf_map;
+
+  static DICTIONARY : StaticDictionary = phf_map! {
+    "hi" => &[
+      Phoneme::Consonant(Consonant::HH),
+      Phoneme::Vowel(Vowel::AY(VowelStress::PrimaryStress)),
+    ],
+  };
+
+  #[test]
+  fn static_until_modified() {
+    let mut arpa = Arpabet::from_phf_map(&DICTIONARY);
+    assert!(arpa.dictionary.is_static());
+    assert_eq!(arpa.len(), 1);
+    assert_eq!(arpa.get_polyphone_ref("hi"), DICTIONARY.get("hi").copied());
+
+    let clone = arpa.clone();
+    assert!(clone.dictionary.is_static());
+
+    arpa.insert("ho".to_string(), vec![
+      Phoneme::Consonant(Consonant::HH),
+      Phoneme::Vowel(Vowel::OW(VowelStress::PrimaryStress)),
+    ]);
+    assert!(!arpa.dictionary.is_static());
+    assert_eq!(arpa.len(), 2);
+    assert_eq!(arpa.get_polyphone_ref("hi"), DICTIONARY.get("hi").copied());
+    assert_eq!(clone.len(), 1);
+  }
+}
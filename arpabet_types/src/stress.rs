@@ -0,0 +1,369 @@
+//! This module compares pronunciations by where they place stress, assigns
+//! stress to pronunciations that lack it, and strips it from those that have
+//! it, eg. for ASR lexicons and aligners that ignore stress.
+
+use crate::error::ArpabetError;
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::syllable::syllabify;
+use crate::{Arpabet, Polyphone};
+use std::fmt;
+
+/// The stress of each vowel of a pronunciation, in order. Written as digits in
+/// the manner of CMUdict, eg. "102" for 'arpabet' (AA1 R P AH0 B EH2 T), with
+/// '?' for vowels of unknown stress.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StressPattern {
+  stresses: Vec<VowelStress>,
+}
+
+impl StressPattern {
+  /// Parse a pattern of '0' (no stress), '1' (primary), '2' (secondary) and
+  /// '?' (unknown) digits, eg. "10" for a trochee or "01" for an iamb.
+  pub fn parse(pattern: &str) -> Result<Self, ArpabetError> {
+    let stresses = pattern.chars()
+      .map(|c| match c {
+        '0' => Ok(VowelStress::NoStress),
+        '1' => Ok(VowelStress::PrimaryStress),
+        '2' => Ok(VowelStress::SecondaryStress),
+        '?' => Ok(VowelStress::UnknownStress),
+        _ => Err(ArpabetError::StringParseError {
+          description: format!("Invalid stress '{}' in pattern '{}'", c, pattern),
+        }),
+      })
+      .collect::<Result<Vec<VowelStress>, ArpabetError>>()?;
+    Ok(Self { stresses })
+  }
+
+  /// Get the stress of each vowel.
+  pub fn stresses(&self) -> &[VowelStress] {
+    &self.stresses
+  }
+
+  /// Number of vowels in the pattern.
+  pub fn len(&self) -> usize {
+    self.stresses.len()
+  }
+
+  /// Whether the pattern has no vowels.
+  pub fn is_empty(&self) -> bool {
+    self.stresses.is_empty()
+  }
+}
+
+impl fmt::Display for StressPattern {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for stress in self.stresses.iter() {
+      let digit = match stress {
+        VowelStress::NoStress => '0',
+        VowelStress::PrimaryStress => '1',
+        VowelStress::SecondaryStress => '2',
+        VowelStress::UnknownStress => '?',
+      };
+      write!(f, "{}", digit)?;
+    }
+    Ok(())
+  }
+}
+
+/// Get the stress pattern of a pronunciation.
+pub fn stress_pattern(polyphone: &[Phoneme]) -> StressPattern {
+  let stresses = polyphone.iter()
+    .filter_map(|phoneme| match phoneme {
+      Phoneme::Vowel(vowel) => Some(*vowel.get_stress()),
+      Phoneme::Consonant(_) => None,
+    })
+    .collect();
+  StressPattern { stresses }
+}
+
+/// Stress-insensitive operations on pronunciations. Implemented for phoneme
+/// slices, so they apply to a `Polyphone` too.
+pub trait PolyphoneStress {
+  /// The pronunciation with every vowel's stress removed (set to unknown
+  /// stress), eg. AA1 R P AH0 B EH2 T -> AA R P AH B EH T.
+  fn strip_stress(&self) -> Polyphone;
+
+  /// The phoneme symbols without stress digits, eg. ["AA", "R", "P", ...].
+  fn to_stressless_strs(&self) -> Vec<&'static str>;
+
+  /// Whether two pronunciations are the same but for stress.
+  fn eq_ignoring_stress(&self, other: &[Phoneme]) -> bool;
+}
+
+impl PolyphoneStress for [Phoneme] {
+  fn strip_stress(&self) -> Polyphone {
+    self.iter().map(Phoneme::stressless).collect()
+  }
+
+  fn to_stressless_strs(&self) -> Vec<&'static str> {
+    self.iter()
+      .map(|phoneme| match phoneme {
+        Phoneme::Consonant(consonant) => consonant.to_str(),
+        Phoneme::Vowel(vowel) => vowel.to_str_stressless(),
+      })
+      .collect()
+  }
+
+  fn eq_ignoring_stress(&self, other: &[Phoneme]) -> bool {
+    self.len() == other.len()
+      && self.iter().zip(other).all(|(a, b)| a.stressless() == b.stressless())
+  }
+}
+
+/// Two different words whose pronunciations differ only in stress placement,
+/// eg. 'insight' (IH1 N S AY2 T) and 'incite' (IH0 N S AY1 T).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressMinimalPair<'a> {
+  /// The alphabetically first word.
+  pub first: &'a str,
+  /// Pronunciation of the first word.
+  pub first_polyphone: &'a Polyphone,
+  /// The alphabetically second word.
+  pub second: &'a str,
+  /// Pronunciation of the second word.
+  pub second_polyphone: &'a Polyphone,
+}
+
+impl Arpabet {
+  /// Get a polyphone from the dictionary without stress. See
+  /// `PolyphoneStress::strip_stress`.
+  pub fn get_polyphone_stressless(&self, word: &str) -> Option<Polyphone> {
+    self.get_polyphone_ref(word).map(|polyphone| polyphone.strip_stress())
+  }
+
+  /// Find all pairs of words with identical phonemes but different stress.
+  /// Alternate pronunciations of a word are never paired with each other.
+  /// Pairs are sorted by their first word, then their second.
+  pub fn stress_minimal_pairs(&self) -> Vec<StressMinimalPair<'_>> {
+    let mut pairs = Vec::new();
+
+    for (_, entries) in self.stressless_index().iter() {
+      for (i, (first, first_polyphone)) in entries.iter().enumerate() {
+        for (second, second_polyphone) in entries[i + 1 ..].iter() {
+          if first != second && first_polyphone != second_polyphone {
+            pairs.push(StressMinimalPair {
+              first,
+              first_polyphone,
+              second,
+              second_polyphone,
+            });
+          }
+        }
+      }
+    }
+
+    pairs.sort_by(|a, b| a.first.cmp(b.first).then_with(|| a.second.cmp(b.second)));
+    pairs
+  }
+
+  /// Find the words with a pronunciation of the stress pattern, written as
+  /// for `StressPattern::parse`, eg. "10" for trochees like 'table' or "01"
+  /// for iambs like 'about'. A malformed pattern matches no words. Results are
+  /// ranked by attached frequencies, if any, then alphabetically.
+  pub fn words_with_stress_pattern(&self, pattern: &str) -> Vec<&str> {
+    self.words_where(|polyphone| stress_pattern(polyphone).to_string() == pattern)
+  }
+
+}
+
+/// Make sure a pronunciation has exactly one primary stress, eg. for words
+/// produced by grapheme-to-phoneme conversion.
+///
+/// If the pronunciation already has primary stress, the first primary stress is
+/// kept and any others are demoted to secondary stress. Otherwise the stress is
+/// placed by syllable weight, in the manner of the Latin stress rule English
+/// largely follows: the penultimate syllable if it is heavy (a long vowel or a
+/// closing consonant), else the antepenultimate. Two-syllable words are stressed
+/// on the first syllable unless it is reduced and the second is long. Reduced
+/// vowels (schwa) are never stressed when another vowel is available.
+///
+/// Remaining vowels of unknown stress are marked unstressed.
+pub fn assign_stress(polyphone: &mut Polyphone) {
+  let vowel_positions = polyphone.iter()
+    .enumerate()
+    .filter(|(_, phoneme)| matches!(phoneme, Phoneme::Vowel(_)))
+    .map(|(i, _)| i)
+    .collect::<Vec<usize>>();
+
+  let has_primary = vowel_positions.iter()
+    .any(|i| vowel_at(polyphone, *i).get_stress() == &VowelStress::PrimaryStress);
+
+  let stressed = if has_primary {
+    None
+  } else {
+    choose_stressed_syllable(polyphone)
+  };
+
+  let mut seen_primary = false;
+
+  for i in vowel_positions {
+    let vowel = vowel_at(polyphone, i);
+    let stress = match vowel.get_stress() {
+      _ if Some(i) == stressed => VowelStress::PrimaryStress,
+      VowelStress::PrimaryStress if seen_primary => VowelStress::SecondaryStress,
+      VowelStress::UnknownStress => VowelStress::NoStress,
+      stress => *stress,
+    };
+    seen_primary |= stress == VowelStress::PrimaryStress;
+    polyphone[i] = Phoneme::Vowel(vowel.with_stress(stress));
+  }
+}
+
+// Choose the position of the vowel to receive primary stress, if there are vowels.
+fn choose_stressed_syllable(polyphone: &[Phoneme]) -> Option<usize> {
+  // Position of every vowel nucleus, and whether its syllable is closed by a
+  // consonant. Syllabic consonants can't take stress.
+  let mut nuclei = Vec::new();
+  let mut offset = 0;
+  for syllable in syllabify(polyphone) {
+    if let Some(i) = syllable.iter().position(|p| matches!(p, Phoneme::Vowel(_))) {
+      nuclei.push((offset + i, i + 1 < syllable.len()));
+    }
+    offset += syllable.len();
+  }
+
+  let count = nuclei.len();
+  let vowel = |n: usize| vowel_at(polyphone, nuclei[n].0);
+  let is_heavy = |n: usize| is_long(&vowel(n)) || nuclei[n].1;
+
+  let chosen = match count {
+    0 => return None,
+    1 => 0,
+    2 if is_reduced(&vowel(0)) && is_long(&vowel(1)) => 1,
+    2 => 0,
+    _ if is_heavy(count - 2) => count - 2,
+    _ => count - 3,
+  };
+
+  // Step away from reduced vowels, leftward first.
+  let chosen = (0 ..= chosen).rev()
+    .chain(chosen + 1 .. count)
+    .find(|n| !is_reduced(&vowel(*n)))
+    .unwrap_or(chosen);
+
+  Some(nuclei[chosen].0)
+}
+
+fn vowel_at(polyphone: &[Phoneme], i: usize) -> Vowel {
+  match polyphone[i] {
+    Phoneme::Vowel(vowel) => vowel,
+    Phoneme::Consonant(_) => unreachable!("position holds a vowel"),
+  }
+}
+
+// Tense vowels and diphthongs.
+fn is_long(vowel: &Vowel) -> bool {
+  matches!(vowel, Vowel::AA(_) | Vowel::AO(_) | Vowel::AW(_) | Vowel::AY(_) | Vowel::ER(_)
+    | Vowel::EY(_) | Vowel::IY(_) | Vowel::OW(_) | Vowel::OY(_) | Vowel::UW(_) | Vowel::UX(_))
+}
+
+// Schwa-like vowels, which resist stress.
+fn is_reduced(vowel: &Vowel) -> bool {
+  matches!(vowel, Vowel::AX(_) | Vowel::AXR(_) | Vowel::IX(_)
+    | Vowel::AH(VowelStress::NoStress) | Vowel::AH(VowelStress::UnknownStress))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::Consonant;
+  use crate::testing::{arpabet, polyphone};
+
+  #[test]
+  fn strip_stress() {
+    let arpabet = polyphone("AA1 R P AH0 B EH2 T");
+    assert_eq!(arpabet.strip_stress(), polyphone("AA R P AH B EH T"));
+    assert_eq!(arpabet.to_stressless_strs(), vec!["AA", "R", "P", "AH", "B", "EH", "T"]);
+    assert!(arpabet.eq_ignoring_stress(&polyphone("AA2 R P AH1 B EH0 T")));
+    assert!(arpabet.eq_ignoring_stress(&polyphone("AA R P AH B EH T")));
+    assert!(!arpabet.eq_ignoring_stress(&polyphone("AA1 R P AH0 B EH2")));
+    assert!(!arpabet.eq_ignoring_stress(&polyphone("AA1 R P IH0 B EH2 T")));
+
+    let mut arpa = Arpabet::new();
+    arpa.insert("arpabet".to_string(), arpabet);
+    assert_eq!(arpa.get_polyphone_stressless("arpabet"), Some(polyphone("AA R P AH B EH T")));
+    assert_eq!(arpa.get_polyphone_stressless("alphabet"), None);
+  }
+
+  fn stressed(text: &str) -> String {
+    let mut polyphone = polyphone(text);
+    assign_stress(&mut polyphone);
+    polyphone.iter().map(|p| p.to_str()).collect::<Vec<&str>>().join(" ")
+  }
+
+  #[test]
+  fn stress_patterns() {
+    let arpabet = polyphone("AA1 R P AH0 B EH2 T");
+    assert_eq!(stress_pattern(&arpabet).to_string(), "102");
+    assert_eq!(stress_pattern(&arpabet), StressPattern::parse("102").unwrap());
+    assert_eq!(stress_pattern(&[Phoneme::Vowel(Vowel::AH(VowelStress::UnknownStress))])
+      .to_string(), "?");
+    assert!(stress_pattern(&[Phoneme::Consonant(Consonant::M)]).is_empty());
+    assert!(StressPattern::parse("1x").is_err());
+  }
+
+  #[test]
+  fn words_with_stress_pattern() {
+    let arpa = arpabet(&[
+      ("table", "T EY1 B AH0 L"),
+      ("about", "AH0 B AW1 T"),
+      ("record", "R EH1 K ER0 D"),
+      ("record(1)", "R IH0 K AO1 R D"),
+      ("cat", "K AE1 T"),
+    ]);
+
+    assert_eq!(arpa.words_with_stress_pattern("10"), vec!["record", "table"]);
+    assert_eq!(arpa.words_with_stress_pattern("01"), vec!["about", "record"]);
+    assert_eq!(arpa.words_with_stress_pattern("1"), vec!["cat"]);
+    assert!(arpa.words_with_stress_pattern("x").is_empty());
+  }
+
+  #[test]
+  fn assign_stress_by_weight() {
+    // One syllable.
+    assert_eq!(stressed("K AE T"), "K AE1 T");
+    // Two syllables: initial, or final when the first is reduced and the second long.
+    assert_eq!(stressed("T EY B AX L"), "T EY1 B AX0 L");
+    assert_eq!(stressed("AH B AW T"), "AH0 B AW1 T");
+    // Heavy penultimate.
+    assert_eq!(stressed("AX JH EH N D AX"), "AX0 JH EH1 N D AX0");
+    assert_eq!(stressed("B AX N AA N AX"), "B AX0 N AA1 N AX0");
+    // Light penultimate: antepenultimate.
+    assert_eq!(stressed("K AE N AX D AX"), "K AE1 N AX0 D AX0");
+  }
+
+  #[test]
+  fn assign_stress_keeps_existing() {
+    assert_eq!(stressed("IH0 N S AY1 T"), "IH0 N S AY1 T");
+    assert_eq!(stressed("AA1 R P AH0 B EH1 T"), "AA1 R P AH0 B EH2 T");
+    assert_eq!(stressed("AE2 N T IY B AA D IY"), "AE2 N T IY0 B AA1 D IY0");
+  }
+
+  #[test]
+  fn assign_stress_without_vowels() {
+    let mut hmm = polyphone("HH M");
+    assign_stress(&mut hmm);
+    assert_eq!(hmm, polyphone("HH M"));
+  }
+
+  #[test]
+  fn stress_minimal_pairs() {
+    let arpa = arpabet(&[
+      ("insight", "IH1 N S AY2 T"),
+      ("incite", "IH0 N S AY1 T"),
+      // Alternates of one word don't pair up.
+      ("incite(1)", "IH2 N S AY1 T"),
+      // Homophones aren't stress pairs.
+      ("red", "R EH1 D"),
+      ("read", "R EH1 D"),
+    ]);
+
+    let pairs = arpa.stress_minimal_pairs();
+    let words = pairs.iter()
+      .map(|pair| (pair.first, pair.second))
+      .collect::<Vec<(&str, &str)>>();
+
+    assert_eq!(words, vec![("incite", "insight"), ("incite", "insight")]);
+    assert_eq!(pairs[0].second_polyphone, arpa.get_polyphone_ref("insight").unwrap());
+  }
+}
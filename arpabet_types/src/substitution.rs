@@ -0,0 +1,336 @@
+//! This module applies systematic phoneme substitutions, eg. R -> W and
+//! TH -> F as in child speech, to single pronunciations, transcriptions or
+//! whole dictionaries.
+//!
+//! A table is a list of rules, each written on its own line as the phoneme to
+//! replace, its replacement or `-` to delete it, and optionally the context
+//! the rule applies in:
+//!
+//! ```text
+//! # Gliding and fronting.
+//! R W
+//! L W before-vowel
+//! TH F
+//! K T initial
+//! # Cluster reduction.
+//! S - before-consonant
+//! ```
+//!
+//! The contexts are `anywhere` (the default), `initial`, `medial`, `final`,
+//! `before-vowel`, `before-consonant`, `before-nasal`, `after-vowel` and
+//! `after-consonant`.
+//! Vowels written without stress, eg. `AH`, match any stress, and as
+//! replacements keep the stress of the vowel replaced.
+//!
+//! Rules are applied simultaneously: contexts are judged on the original
+//! pronunciation, and the first rule matching each phoneme wins.
+
+use crate::articulation::Manner;
+use crate::constants::PHONEME_MAP;
+use crate::error::ArpabetError;
+use crate::extensions::SentenceToken;
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::{Arpabet, Polyphone};
+
+/// Where in a word a substitution applies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubstitutionContext {
+  /// Everywhere.
+  #[default]
+  Anywhere,
+  /// At the start of a word.
+  Initial,
+  /// Neither at the start nor at the end of a word.
+  Medial,
+  /// At the end of a word.
+  Final,
+  /// Followed by a vowel.
+  BeforeVowel,
+  /// Followed by a consonant.
+  BeforeConsonant,
+  /// Followed by a nasal consonant: M, N, NG, EM, EN or NX.
+  BeforeNasal,
+  /// Preceded by a vowel.
+  AfterVowel,
+  /// Preceded by a consonant.
+  AfterConsonant,
+}
+
+impl SubstitutionContext {
+  /// The name used in tables, eg. "before-vowel".
+  pub fn to_str(&self) -> &'static str {
+    match self {
+      SubstitutionContext::Anywhere => "anywhere",
+      SubstitutionContext::Initial => "initial",
+      SubstitutionContext::Medial => "medial",
+      SubstitutionContext::Final => "final",
+      SubstitutionContext::BeforeVowel => "before-vowel",
+      SubstitutionContext::BeforeConsonant => "before-consonant",
+      SubstitutionContext::BeforeNasal => "before-nasal",
+      SubstitutionContext::AfterVowel => "after-vowel",
+      SubstitutionContext::AfterConsonant => "after-consonant",
+    }
+  }
+
+  /// Parse a name used in tables, eg. "before-vowel".
+  pub fn from_name(name: &str) -> Option<Self> {
+    [
+      SubstitutionContext::Anywhere,
+      SubstitutionContext::Initial,
+      SubstitutionContext::Medial,
+      SubstitutionContext::Final,
+      SubstitutionContext::BeforeVowel,
+      SubstitutionContext::BeforeConsonant,
+      SubstitutionContext::BeforeNasal,
+      SubstitutionContext::AfterVowel,
+      SubstitutionContext::AfterConsonant,
+    ].iter().copied().find(|context| context.to_str() == name)
+  }
+
+  // Whether the phoneme at the index of the pronunciation is in the context.
+  fn holds(&self, polyphone: &[Phoneme], index: usize) -> bool {
+    let previous = index.checked_sub(1).and_then(|i| polyphone.get(i));
+    let next = polyphone.get(index + 1);
+    match self {
+      SubstitutionContext::Anywhere => true,
+      SubstitutionContext::Initial => previous.is_none(),
+      SubstitutionContext::Medial => previous.is_some() && next.is_some(),
+      SubstitutionContext::Final => next.is_none(),
+      SubstitutionContext::BeforeVowel => matches!(next, Some(Phoneme::Vowel(_))),
+      SubstitutionContext::BeforeConsonant => matches!(next, Some(Phoneme::Consonant(_))),
+      SubstitutionContext::BeforeNasal => match next {
+        Some(Phoneme::Consonant(consonant)) => consonant.manner() == Manner::Nasal,
+        _ => false,
+      },
+      SubstitutionContext::AfterVowel => matches!(previous, Some(Phoneme::Vowel(_))),
+      SubstitutionContext::AfterConsonant => matches!(previous, Some(Phoneme::Consonant(_))),
+    }
+  }
+}
+
+/// A rule replacing or deleting a phoneme in a context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Substitution {
+  /// The phoneme replaced. A vowel of unknown stress matches any stress.
+  pub from: Phoneme,
+  /// The replacement, or None to delete the phoneme. A vowel of unknown
+  /// stress takes the stress of the vowel replaced.
+  pub to: Option<Phoneme>,
+  /// Where the rule applies.
+  pub context: SubstitutionContext,
+}
+
+impl Substitution {
+  // Whether the rule applies to the phoneme at the index.
+  fn applies(&self, polyphone: &[Phoneme], index: usize) -> bool {
+    let phoneme = &polyphone[index];
+    let matches = match (self.from, phoneme) {
+      (Phoneme::Vowel(from), Phoneme::Vowel(vowel)) if is_stressless(&from) =>
+        from.to_str_stressless() == vowel.to_str_stressless(),
+      (from, _) => from == *phoneme,
+    };
+    matches && self.context.holds(polyphone, index)
+  }
+
+  // The replacement of the phoneme, if any.
+  fn replace(&self, phoneme: &Phoneme) -> Option<Phoneme> {
+    match (self.to?, phoneme) {
+      (Phoneme::Vowel(to), Phoneme::Vowel(vowel)) if is_stressless(&to) =>
+        Some(Phoneme::Vowel(to.with_stress(*vowel.get_stress()))),
+      (to, _) => Some(to),
+    }
+  }
+}
+
+/// An ordered list of substitutions. See the module documentation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubstitutionTable {
+  rules: Vec<Substitution>,
+}
+
+impl SubstitutionTable {
+  /// An empty table, which changes nothing.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a rule, applied where no earlier rule is.
+  pub fn substitute(mut self, from: Phoneme, to: Option<Phoneme>, context: SubstitutionContext)
+    -> Self
+  {
+    self.rules.push(Substitution { from, to, context });
+    self
+  }
+
+  /// Parse a table, one rule per line. Blank lines and text after '#' are
+  /// ignored. See the module documentation for the syntax.
+  pub fn parse(table: &str) -> Result<Self, ArpabetError> {
+    let mut rules = Vec::new();
+    for (i, line) in table.lines().enumerate() {
+      let line = line.split('#').next().unwrap_or("").trim();
+      if line.is_empty() {
+        continue;
+      }
+      let invalid = || ArpabetError::InvalidFormat { line_number: i + 1, text: line.to_string() };
+      let fields : Vec<&str> = line.split_whitespace().collect();
+      let (from, to, context) = match fields[..] {
+        [from, to] => (from, to, SubstitutionContext::Anywhere),
+        [from, to, context] => {
+          (from, to, SubstitutionContext::from_name(context).ok_or_else(invalid)?)
+        },
+        _ => return Err(invalid()),
+      };
+      let from = *PHONEME_MAP.get(from).ok_or_else(invalid)?;
+      let to = match to {
+        "-" => None,
+        to => Some(*PHONEME_MAP.get(to).ok_or_else(invalid)?),
+      };
+      rules.push(Substitution { from, to, context });
+    }
+    Ok(Self { rules })
+  }
+
+  /// The rules, in order.
+  pub fn rules(&self) -> &[Substitution] {
+    &self.rules
+  }
+
+  /// Apply the rules to a word's pronunciation.
+  pub fn apply(&self, polyphone: &[Phoneme]) -> Polyphone {
+    (0 .. polyphone.len())
+      .filter_map(|i| {
+        match self.rules.iter().find(|rule| rule.applies(polyphone, i)) {
+          Some(rule) => rule.replace(&polyphone[i]),
+          None => Some(polyphone[i]),
+        }
+      })
+      .collect()
+  }
+
+  /// Apply the rules to a transcription, eg. from `transcribe_sentence`, word
+  /// by word. Punctuation is kept as it is.
+  pub fn apply_to_tokens(&self, tokens: &[SentenceToken]) -> Vec<SentenceToken> {
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut word = Vec::new();
+    for token in tokens {
+      match token {
+        SentenceToken::Phoneme(phoneme) => word.push(*phoneme),
+        SentenceToken::Punctuation(_) => {
+          output.extend(self.apply(&word).into_iter().map(SentenceToken::Phoneme));
+          word.clear();
+          output.push(*token);
+        },
+      }
+    }
+    output.extend(self.apply(&word).into_iter().map(SentenceToken::Phoneme));
+    output
+  }
+}
+
+// Whether the vowel was written without a stress digit.
+fn is_stressless(vowel: &Vowel) -> bool {
+  *vowel.get_stress() == VowelStress::UnknownStress
+}
+
+impl Arpabet {
+  /// A copy of the dictionary with the substitutions applied to every
+  /// pronunciation. Metadata and frequencies are kept.
+  pub fn with_substitutions(&self, table: &SubstitutionTable) -> Arpabet {
+    let entries = self.iter()
+      .map(|(word, polyphone)| (word.to_string(), table.apply(polyphone)))
+      .collect();
+    let mut substituted = Arpabet::from_map(entries);
+    substituted.metadata = self.metadata.clone();
+    substituted.frequencies = self.frequencies.clone();
+    substituted
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::extensions::Punctuation;
+  use crate::testing::polyphone;
+
+  fn table() -> SubstitutionTable {
+    SubstitutionTable::parse("
+      # Child speech.
+      R W
+      TH F
+      K T initial
+      S - before-consonant  # Cluster reduction.
+      AE EH
+    ").unwrap()
+  }
+
+  #[test]
+  fn apply() {
+    let table = table();
+    assert_eq!(table.rules().len(), 5);
+    assert_eq!(table.apply(&polyphone("R EH1 D")), polyphone("W EH1 D"));
+    assert_eq!(table.apply(&polyphone("TH R IY1")), polyphone("F W IY1"));
+    assert_eq!(table.apply(&polyphone("K AA1 K")), polyphone("T AA1 K"));
+    assert_eq!(table.apply(&polyphone("S T AA1 P S")), polyphone("T AA1 P S"));
+    assert_eq!(table.apply(&polyphone("K AE1 T AE2")), polyphone("T EH1 T EH2"));
+    assert!(table.apply(&[]).is_empty());
+    assert_eq!(SubstitutionTable::new().apply(&polyphone("R EH1 D")), polyphone("R EH1 D"));
+  }
+
+  #[test]
+  fn first_rule_wins() {
+    let table = SubstitutionTable::new()
+      .substitute(*PHONEME_MAP.get("R").unwrap(), None, SubstitutionContext::Final)
+      .substitute(*PHONEME_MAP.get("R").unwrap(), PHONEME_MAP.get("W").copied(),
+        SubstitutionContext::Anywhere);
+    assert_eq!(table.apply(&polyphone("R AO1 R")), polyphone("W AO1"));
+  }
+
+  #[test]
+  fn contexts() {
+    let p = polyphone("S T AA1 R Z");
+    assert!(SubstitutionContext::Medial.holds(&p, 1));
+    assert!(!SubstitutionContext::Medial.holds(&p, 4));
+    assert!(SubstitutionContext::AfterVowel.holds(&p, 3));
+    assert!(SubstitutionContext::AfterConsonant.holds(&p, 4));
+    assert!(SubstitutionContext::BeforeVowel.holds(&p, 1));
+    assert!(SubstitutionContext::BeforeNasal.holds(&polyphone("P EH1 N"), 1));
+    assert!(!SubstitutionContext::BeforeNasal.holds(&polyphone("P EH1 T"), 1));
+    assert_eq!(SubstitutionContext::from_name("after-vowel"),
+      Some(SubstitutionContext::AfterVowel));
+    assert_eq!(SubstitutionContext::from_name("nowhere"), None);
+  }
+
+  #[test]
+  fn invalid() {
+    for table in ["R", "R W nowhere", "R XX", "R W initial extra"].iter() {
+      assert!(matches!(SubstitutionTable::parse(table),
+        Err(ArpabetError::InvalidFormat { line_number: 1, .. })), "{}", table);
+    }
+  }
+
+  #[test]
+  fn tokens() {
+    let comma = SentenceToken::Punctuation(Punctuation::Comma);
+    let phonemes = |pronunciation| polyphone(pronunciation).into_iter().map(SentenceToken::Phoneme);
+    let mut tokens : Vec<SentenceToken> = phonemes("K AA1 R").collect();
+    tokens.push(comma);
+    tokens.extend(phonemes("S K AY1"));
+
+    let mut expected : Vec<SentenceToken> = phonemes("T AA1 W").collect();
+    expected.push(comma);
+    expected.extend(phonemes("K AY1"));
+    assert_eq!(table().apply_to_tokens(&tokens), expected);
+  }
+
+  #[test]
+  fn dictionary() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("red".to_string(), polyphone("R EH1 D"));
+    arpa.insert("cat".to_string(), polyphone("K AE1 T"));
+
+    let substituted = arpa.with_substitutions(&table());
+    assert_eq!(substituted.get_polyphone("red"), Some(polyphone("W EH1 D")));
+    assert_eq!(substituted.get_polyphone("cat"), Some(polyphone("T EH1 T")));
+    assert_eq!(arpa.get_polyphone("red"), Some(polyphone("R EH1 D")));
+  }
+}
@@ -0,0 +1,114 @@
+//! This module splits pronunciations into syllables.
+
+use crate::phoneme::{Consonant, Phoneme, VowelStress};
+
+/// English onset clusters of two or more consonants.
+/// Single consonants other than NG are all legal onsets.
+pub const COMPLEX_ONSETS : [&str; 43] = [
+  "B L", "B R", "B Y", "D R", "D W", "F L", "F R", "F Y", "G L", "G R", "G W", "G Y",
+  "HH Y", "K L", "K R", "K W", "K Y", "M Y", "P L", "P R", "P Y", "S F", "S K", "S L",
+  "S M", "S N", "S P", "S T", "S W", "SH R", "T R", "T W", "TH R", "TH W", "V Y",
+  "S K L", "S K R", "S K W", "S K Y", "S P L", "S P R", "S P Y", "S T R",
+];
+
+/// Whether the phoneme can be the nucleus of a syllable: a vowel, or one of the
+/// syllabic consonants EL, EM and EN.
+pub fn is_syllabic(phoneme: &Phoneme) -> bool {
+  match phoneme {
+    Phoneme::Vowel(_) => true,
+    Phoneme::Consonant(consonant) =>
+      matches!(consonant, Consonant::EL | Consonant::EM | Consonant::EN),
+  }
+}
+
+/// Get the stress of a syllable: the stress of its vowel, or None if its
+/// nucleus is a syllabic consonant or it has no nucleus.
+pub fn syllable_stress(syllable: &[Phoneme]) -> Option<VowelStress> {
+  syllable.iter().find_map(|phoneme| match phoneme {
+    Phoneme::Vowel(vowel) => Some(*vowel.get_stress()),
+    Phoneme::Consonant(_) => None,
+  })
+}
+
+/// Split a pronunciation into syllables by the maximal onset principle:
+/// consonants between two nuclei start the later syllable as far as English
+/// onsets allow, eg. 'extra' -> [EH1 K] [S T R AH0]. A pronunciation without
+/// any nucleus is returned as a single syllable.
+pub fn syllabify(polyphone: &[Phoneme]) -> Vec<&[Phoneme]> {
+  if polyphone.is_empty() {
+    return Vec::new();
+  }
+
+  let nuclei = polyphone.iter()
+    .enumerate()
+    .filter(|(_, phoneme)| is_syllabic(phoneme))
+    .map(|(i, _)| i)
+    .collect::<Vec<usize>>();
+
+  let mut syllables = Vec::with_capacity(nuclei.len().max(1));
+  let mut start = 0;
+
+  for pair in nuclei.windows(2) {
+    let (previous, next) = (pair[0], pair[1]);
+    let boundary = (previous + 1 ..= next)
+      .find(|k| is_legal_onset(&polyphone[*k .. next]))
+      .unwrap_or(next);
+    syllables.push(&polyphone[start .. boundary]);
+    start = boundary;
+  }
+
+  syllables.push(&polyphone[start ..]);
+  syllables
+}
+
+fn is_legal_onset(consonants: &[Phoneme]) -> bool {
+  match consonants {
+    [] => true,
+    [Phoneme::Consonant(Consonant::NG)] => false,
+    [_] => true,
+    _ => {
+      let cluster = consonants.iter()
+        .map(|phoneme| phoneme.to_str())
+        .collect::<Vec<&str>>()
+        .join(" ");
+      COMPLEX_ONSETS.contains(&cluster.as_str())
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  fn syllables(text: &str) -> Vec<String> {
+    let polyphone = polyphone(text);
+    syllabify(&polyphone).iter()
+      .map(|syllable| syllable.iter().map(|p| p.to_str()).collect::<Vec<&str>>().join(" "))
+      .collect()
+  }
+
+  #[test]
+  fn maximal_onset() {
+    assert_eq!(syllables("EH1 K S T R AH0"), vec!["EH1 K", "S T R AH0"]);
+    assert_eq!(syllables("AH0 B AE1 N D AH0 N"), vec!["AH0", "B AE1 N", "D AH0 N"]);
+    assert_eq!(syllables("S IH1 NG ER0"), vec!["S IH1 NG", "ER0"]);
+    assert_eq!(syllables("B AA1 T EL"), vec!["B AA1", "T EL"]);
+  }
+
+  #[test]
+  fn edge_cases() {
+    assert!(syllabify(&[]).is_empty());
+    assert_eq!(syllables("HH M"), vec!["HH M"]);
+    assert_eq!(syllables("S T R EH1 NG K TH S"), vec!["S T R EH1 NG K TH S"]);
+  }
+
+  #[test]
+  fn stress() {
+    let extra = polyphone("EH1 K S T R AH0");
+    let syllables = syllabify(&extra);
+    assert_eq!(syllable_stress(syllables[0]), Some(VowelStress::PrimaryStress));
+    assert_eq!(syllable_stress(syllables[1]), Some(VowelStress::NoStress));
+    assert_eq!(syllable_stress(&polyphone("T EL")), None);
+  }
+}
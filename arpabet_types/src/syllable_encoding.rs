@@ -0,0 +1,244 @@
+//! This module encodes whole syllables as compact unsigned ints, for prosody
+//! models that operate on syllables rather than phonemes.
+//!
+//! A syllable is reduced to a coarse class: the class of its nucleus, its
+//! stress, and whether its onset and coda are empty, a single consonant or a
+//! cluster. There are `SYLLABLE_VOCABULARY_SIZE` classes, numbered from 1;
+//! see `syllable_vocabulary`.
+
+use crate::error::ArpabetError;
+use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+use crate::syllable::{is_syllabic, syllabify};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Number of syllable classes. Codes run from 1 to this number inclusive.
+pub const SYLLABLE_VOCABULARY_SIZE : usize = 162;
+
+/// A coarse class of syllable nucleus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NucleusClass {
+  /// Front vowels: IY, IH, IX, EH, AE.
+  Front,
+  /// Central vowels: AH, AX, ER, AXR, UX.
+  Central,
+  /// Back vowels: AA, AO, UH, UW.
+  Back,
+  /// Diphthongs: AY, AW, OY, EY, OW.
+  Diphthong,
+  /// The syllabic consonants EL, EM and EN.
+  Syllabic,
+  /// No nucleus, eg. in 'hmm' (HH M).
+  NoNucleus,
+}
+
+/// The size of a syllable onset or coda.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Margin {
+  /// No consonants.
+  Empty,
+  /// A single consonant.
+  Single,
+  /// Two or more consonants.
+  Cluster,
+}
+
+/// A coarse description of a syllable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SyllableClass {
+  /// Consonants before the nucleus.
+  pub onset: Margin,
+  /// The class of the nucleus.
+  pub nucleus: NucleusClass,
+  /// Stress of the nucleus. Never `UnknownStress`: unknown stress and syllables
+  /// without a stressed vowel count as `NoStress`.
+  pub stress: VowelStress,
+  /// Consonants after the nucleus.
+  pub coda: Margin,
+}
+
+const NUCLEI : [NucleusClass; 6] = [
+  NucleusClass::Front,
+  NucleusClass::Central,
+  NucleusClass::Back,
+  NucleusClass::Diphthong,
+  NucleusClass::Syllabic,
+  NucleusClass::NoNucleus,
+];
+
+const STRESSES : [VowelStress; 3] = [
+  VowelStress::NoStress,
+  VowelStress::PrimaryStress,
+  VowelStress::SecondaryStress,
+];
+
+const MARGINS : [Margin; 3] = [Margin::Empty, Margin::Single, Margin::Cluster];
+
+impl SyllableClass {
+  /// Classify a syllable, eg. one returned by `syllabify`.
+  pub fn of(syllable: &[Phoneme]) -> Self {
+    let nucleus = syllable.iter().position(is_syllabic);
+    let (onset, coda) = match nucleus {
+      Some(i) => (i, syllable.len() - i - 1),
+      None => (syllable.len(), 0),
+    };
+    let nucleus = nucleus.map(|i| syllable[i]);
+
+    let stress = match nucleus {
+      Some(Phoneme::Vowel(vowel)) => match vowel.get_stress() {
+        VowelStress::UnknownStress => VowelStress::NoStress,
+        stress => *stress,
+      },
+      _ => VowelStress::NoStress,
+    };
+
+    Self {
+      onset: margin(onset),
+      nucleus: nucleus.map_or(NucleusClass::NoNucleus, nucleus_class),
+      stress,
+      coda: margin(coda),
+    }
+  }
+}
+
+/// Split a pronunciation into syllables and encode each as its class code.
+pub fn encode_syllables(polyphone: &[Phoneme]) -> Vec<u8> {
+  syllabify(polyphone).into_iter()
+    .map(|syllable| u8::from(SyllableClass::of(syllable)))
+    .collect()
+}
+
+/// Every syllable class, in code order: the class at index i has code i + 1.
+pub fn syllable_vocabulary() -> Vec<SyllableClass> {
+  (1 ..= SYLLABLE_VOCABULARY_SIZE)
+    .filter_map(|code| SyllableClass::try_from(code as u8).ok())
+    .collect()
+}
+
+fn margin(consonants: usize) -> Margin {
+  match consonants {
+    0 => Margin::Empty,
+    1 => Margin::Single,
+    _ => Margin::Cluster,
+  }
+}
+
+fn nucleus_class(phoneme: Phoneme) -> NucleusClass {
+  match phoneme {
+    Phoneme::Vowel(vowel) => match vowel {
+      Vowel::IY(_) | Vowel::IH(_) | Vowel::IX(_) | Vowel::EH(_) | Vowel::AE(_) => NucleusClass::Front,
+      Vowel::AH(_) | Vowel::AX(_) | Vowel::ER(_) | Vowel::AXR(_) | Vowel::UX(_) => NucleusClass::Central,
+      Vowel::AA(_) | Vowel::AO(_) | Vowel::UH(_) | Vowel::UW(_) => NucleusClass::Back,
+      Vowel::AY(_) | Vowel::AW(_) | Vowel::OY(_) | Vowel::EY(_) | Vowel::OW(_) => NucleusClass::Diphthong,
+    },
+    Phoneme::Consonant(Consonant::EL) | Phoneme::Consonant(Consonant::EM)
+      | Phoneme::Consonant(Consonant::EN) => NucleusClass::Syllabic,
+    Phoneme::Consonant(_) => NucleusClass::NoNucleus,
+  }
+}
+
+fn index_of<T: PartialEq>(values: &[T], value: &T) -> usize {
+  values.iter().position(|v| v == value).unwrap_or(0)
+}
+
+impl From<SyllableClass> for u8 {
+  /// Map syllable classes to unsigned ints, from 1 to `SYLLABLE_VOCABULARY_SIZE`.
+  /// These can serve as the numeric inputs into ML models.
+  fn from(class: SyllableClass) -> Self {
+    let index = ((index_of(&NUCLEI, &class.nucleus) * STRESSES.len()
+      + index_of(&STRESSES, &class.stress)) * MARGINS.len()
+      + index_of(&MARGINS, &class.onset)) * MARGINS.len()
+      + index_of(&MARGINS, &class.coda);
+    index as u8 + 1
+  }
+}
+
+impl TryFrom<u8> for SyllableClass {
+  type Error = ArpabetError;
+
+  /// Map unsigned ints back to syllable classes. The inverse of `u8::from`.
+  fn try_from(code: u8) -> Result<Self, Self::Error> {
+    let index = (code as usize).checked_sub(1)
+      .filter(|index| *index < SYLLABLE_VOCABULARY_SIZE)
+      .ok_or(ArpabetError::UnknownCode { code })?;
+
+    Ok(Self {
+      coda: MARGINS[index % MARGINS.len()],
+      onset: MARGINS[index / MARGINS.len() % MARGINS.len()],
+      stress: STRESSES[index / (MARGINS.len() * MARGINS.len()) % STRESSES.len()],
+      nucleus: NUCLEI[index / (MARGINS.len() * MARGINS.len() * STRESSES.len())],
+    })
+  }
+}
+
+impl fmt::Display for SyllableClass {
+  /// A readable label for the vocabulary, eg. "CC-FRONT1-C" for 'stick' or
+  /// "-CENTRAL0-" for the 'a' in 'about'.
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let nucleus = match self.nucleus {
+      NucleusClass::Front => "FRONT",
+      NucleusClass::Central => "CENTRAL",
+      NucleusClass::Back => "BACK",
+      NucleusClass::Diphthong => "DIPHTHONG",
+      NucleusClass::Syllabic => "SYLLABIC",
+      NucleusClass::NoNucleus => "NONE",
+    };
+    let stress = match self.stress {
+      VowelStress::PrimaryStress => 1,
+      VowelStress::SecondaryStress => 2,
+      VowelStress::NoStress | VowelStress::UnknownStress => 0,
+    };
+    write!(f, "{}-{}{}-{}", margin_label(self.onset), nucleus, stress, margin_label(self.coda))
+  }
+}
+
+fn margin_label(margin: Margin) -> &'static str {
+  match margin {
+    Margin::Empty => "",
+    Margin::Single => "C",
+    Margin::Cluster => "CC",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+  use std::collections::HashSet;
+
+  fn labels(text: &str) -> Vec<String> {
+    encode_syllables(&polyphone(text)).into_iter()
+      .map(|code| SyllableClass::try_from(code).unwrap().to_string())
+      .collect()
+  }
+
+  #[test]
+  fn classify() {
+    assert_eq!(labels("S T IH1 K"), vec!["CC-FRONT1-C"]);
+    assert_eq!(labels("AH0 B AW1 T"), vec!["-CENTRAL0-", "C-DIPHTHONG1-C"]);
+    assert_eq!(labels("B AA1 T EL"), vec!["C-BACK1-", "C-SYLLABIC0-"]);
+    assert_eq!(labels("HH M"), vec!["CC-NONE0-"]);
+    assert_eq!(labels("K AE T"), vec!["C-FRONT0-C"]);
+    assert!(encode_syllables(&[]).is_empty());
+  }
+
+  #[test]
+  fn vocabulary() {
+    let vocabulary = syllable_vocabulary();
+    assert_eq!(vocabulary.len(), SYLLABLE_VOCABULARY_SIZE);
+
+    for (i, class) in vocabulary.iter().enumerate() {
+      assert_eq!(u8::from(*class) as usize, i + 1);
+    }
+
+    let labels = vocabulary.iter().map(|class| class.to_string()).collect::<HashSet<String>>();
+    assert_eq!(labels.len(), SYLLABLE_VOCABULARY_SIZE);
+  }
+
+  #[test]
+  fn unknown_codes() {
+    assert!(SyllableClass::try_from(0).is_err());
+    assert!(SyllableClass::try_from(SYLLABLE_VOCABULARY_SIZE as u8).is_ok());
+    assert!(SyllableClass::try_from(SYLLABLE_VOCABULARY_SIZE as u8 + 1).is_err());
+  }
+}
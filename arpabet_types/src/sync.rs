@@ -0,0 +1,12 @@
+//! This module contains helpers for the crate's locks.
+
+use std::sync::LockResult;
+
+/// Take a lock even if a thread panicked while holding it.
+///
+/// NB: The crate's locks guard state that a panic can't leave half-changed,
+/// eg. changes are published only once complete, so a poisoned lock is still
+/// usable.
+pub(crate) fn ignore_poison<G>(result: LockResult<G>) -> G {
+  result.unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,19 @@
+//! This module contains helpers shared by the unit tests.
+
+use crate::constants::PHONEME_MAP;
+use crate::{Arpabet, Polyphone};
+
+/// Parse a pronunciation of space-separated ARPABET symbols, eg. "T EH1 S T".
+/// Panics on unknown symbols.
+pub(crate) fn polyphone(pronunciation: &str) -> Polyphone {
+  pronunciation.split(' ').map(|p| *PHONEME_MAP.get(p).unwrap()).collect()
+}
+
+/// Build a dictionary from (word, pronunciation) pairs, eg. ("test", "T EH1 S T").
+pub(crate) fn arpabet(entries: &[(&str, &str)]) -> Arpabet {
+  let mut arpa = Arpabet::new();
+  for (word, pronunciation) in entries.iter() {
+    arpa.insert(word.to_string(), polyphone(pronunciation));
+  }
+  arpa
+}
@@ -0,0 +1,151 @@
+//! This module normalizes texting-speak, where digits stand in for the sounds
+//! of their names, eg. "gr8" for 'great' or "4ever" for 'forever', so chat
+//! messages can be transcribed. See `TranscribeOptions::texting`.
+
+use crate::casing::fold_case;
+use std::collections::HashMap;
+
+/// Common texting abbreviations and the words they stand for.
+pub const TEXTING_WORDS : [(&str, &str); 16] = [
+  ("2day", "today"),
+  ("2moro", "tomorrow"),
+  ("2morrow", "tomorrow"),
+  ("2nite", "tonight"),
+  ("4ever", "forever"),
+  ("any1", "anyone"),
+  ("b4", "before"),
+  ("every1", "everyone"),
+  ("gr8", "great"),
+  ("h8", "hate"),
+  ("l8", "late"),
+  ("l8r", "later"),
+  ("m8", "mate"),
+  ("some1", "someone"),
+  ("str8", "straight"),
+  ("w8", "wait"),
+];
+
+/// Digits and the letters that spell what they sound like in texting-speak.
+pub const TEXTING_DIGITS : [(char, &str); 4] = [
+  ('1', "one"),
+  ('2', "to"),
+  ('4', "for"),
+  ('8', "ate"),
+];
+
+/// Rewrites words that mix letters and digits into the words they stand for.
+///
+/// Known abbreviations (`TEXTING_WORDS` by default) are expanded first; other
+/// words have each digit spelled out (`TEXTING_DIGITS` by default), eg. "sk8"
+/// becomes "skate". Words without both letters and digits are left alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextingNormalizer {
+  words: HashMap<String, String>,
+  digits: HashMap<char, String>,
+}
+
+impl TextingNormalizer {
+  /// A normalizer with the default tables.
+  pub fn new() -> Self {
+    Self {
+      words: TEXTING_WORDS.iter().map(|(from, to)| (from.to_string(), to.to_string())).collect(),
+      digits: TEXTING_DIGITS.iter().map(|(from, to)| (*from, to.to_string())).collect(),
+    }
+  }
+
+  /// A normalizer with no abbreviations or digits, to configure from scratch.
+  pub fn empty() -> Self {
+    Self {
+      words: HashMap::new(),
+      digits: HashMap::new(),
+    }
+  }
+
+  /// Expand an abbreviation, in any casing, to the word.
+  pub fn insert_word(&mut self, abbreviation: &str, word: &str) {
+    self.words.insert(fold_case(abbreviation), fold_case(word));
+  }
+
+  /// Stop expanding an abbreviation.
+  pub fn remove_word(&mut self, abbreviation: &str) {
+    self.words.remove(&fold_case(abbreviation));
+  }
+
+  /// Spell a digit with the letters, eg. '8' as "ate".
+  pub fn insert_digit(&mut self, digit: char, letters: &str) {
+    self.digits.insert(digit, fold_case(letters));
+  }
+
+  /// Stop spelling out a digit.
+  pub fn remove_digit(&mut self, digit: char) {
+    self.digits.remove(&digit);
+  }
+
+  /// The lowercase words a mixed letter and digit word may stand for, most
+  /// likely first. Empty for other words.
+  pub fn candidates(&self, word: &str) -> Vec<String> {
+    let is_hybrid = word.chars().any(|c| c.is_alphabetic())
+      && word.chars().any(|c| c.is_ascii_digit());
+    if !is_hybrid {
+      return Vec::new();
+    }
+
+    let word = fold_case(word);
+    let mut candidates = Vec::new();
+
+    if let Some(expansion) = self.words.get(&word) {
+      candidates.push(expansion.clone());
+    }
+
+    let mut spelled = String::with_capacity(word.len() + 8);
+    for c in word.chars() {
+      match self.digits.get(&c) {
+        Some(letters) => spelled.push_str(letters),
+        None => spelled.push(c),
+      }
+    }
+    if !spelled.contains(|c: char| c.is_ascii_digit()) && !candidates.contains(&spelled) {
+      candidates.push(spelled);
+    }
+
+    candidates
+  }
+}
+
+impl Default for TextingNormalizer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn candidates() {
+    let normalizer = TextingNormalizer::new();
+    assert_eq!(normalizer.candidates("GR8"), vec!["great", "grate"]);
+    assert_eq!(normalizer.candidates("b4"), vec!["before", "bfor"]);
+    assert_eq!(normalizer.candidates("sk8r"), vec!["skater"]);
+    assert_eq!(normalizer.candidates("2day"), vec!["today"]);
+    assert!(normalizer.candidates("great").is_empty());
+    assert!(normalizer.candidates("2024").is_empty());
+    assert!(normalizer.candidates("x9").is_empty());
+  }
+
+  #[test]
+  fn configured() {
+    let mut normalizer = TextingNormalizer::empty();
+    assert!(normalizer.candidates("gr8").is_empty());
+
+    normalizer.insert_digit('8', "ate");
+    normalizer.insert_word("CUL8R", "later");
+    assert_eq!(normalizer.candidates("gr8"), vec!["grate"]);
+    assert_eq!(normalizer.candidates("cul8r"), vec!["later", "culater"]);
+
+    normalizer.remove_word("cul8r");
+    normalizer.remove_digit('8');
+    assert!(normalizer.candidates("cul8r").is_empty());
+  }
+}
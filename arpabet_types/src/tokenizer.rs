@@ -0,0 +1,467 @@
+//! This module splits raw English text into word and punctuation tokens.
+//!
+//! Tokens locate themselves by byte offsets. Front ends that index text
+//! differently, eg. JavaScript by UTF-16 code units, can convert them with
+//! `token_spans`.
+
+use crate::extensions::Punctuation;
+use std::borrow::Cow;
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The content of a token.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+  /// A word, with apostrophe variants normalized to ASCII (').
+  /// Casing is left as it appeared in the text.
+  Word(String),
+  /// A punctuation mark.
+  Punctuation(Punctuation),
+  /// A non-speech annotation such as a stage direction, eg. "laughs" from
+  /// "[laughs]". Only produced under `AnnotationPolicy::Emit`.
+  Annotation(String),
+}
+
+/// How bracketed annotations in the text, eg. "[laughs]" or "[pause]", are
+/// tokenized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnnotationPolicy {
+  /// Treat the brackets as interjections and read the words inside.
+  #[default]
+  Read,
+  /// Produce a single `TokenKind::Annotation` token.
+  Emit,
+  /// Leave annotations out entirely.
+  Skip,
+}
+
+/// Options for tokenizing text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenizerOptions {
+  /// How bracketed annotations are tokenized.
+  pub annotations: AnnotationPolicy,
+}
+
+/// A token along with its location in the source text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+  /// What the token is.
+  pub kind: TokenKind,
+  /// Byte offset of the start of the token in the source text.
+  pub start: usize,
+  /// Byte offset just past the end of the token in the source text.
+  pub end: usize,
+}
+
+/// The unit in which offsets into text are counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OffsetUnit {
+  /// UTF-8 bytes, as Rust slices strings.
+  #[default]
+  Bytes,
+  /// Unicode scalar values, ie. Rust chars, as Python indexes strings.
+  Chars,
+  /// UTF-16 code units, as JavaScript indexes strings. Characters beyond the
+  /// Basic Multilingual Plane, eg. most emoji, count as two.
+  Utf16,
+  /// Extended grapheme clusters, ie. user-perceived characters. An emoji with
+  /// a skin tone modifier or a ZWJ sequence such as a family counts as one.
+  Graphemes,
+}
+
+/// A location in text: the offsets of its start and just past its end,
+/// counted in some `OffsetUnit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+  /// Offset of the start.
+  pub start: usize,
+  /// Offset just past the end.
+  pub end: usize,
+}
+
+impl Token {
+  /// The token's location in its source text, counted in the unit. Use
+  /// `token_spans` to convert many tokens of the same text.
+  pub fn span(&self, text: &str, unit: OffsetUnit) -> Span {
+    token_spans(text, std::slice::from_ref(self), unit)[0]
+  }
+}
+
+/// The locations of tokens of the text, counted in the unit. The text is
+/// scanned once, however many tokens there are.
+///
+/// With `OffsetUnit::Graphemes`, a token that starts or ends inside a grapheme
+/// cluster, eg. "e" of "e\u{301}" (é with a combining accent), is widened to
+/// the whole cluster.
+pub fn token_spans(text: &str, tokens: &[Token], unit: OffsetUnit) -> Vec<Span> {
+  // The byte offset at which each unit starts and its index, then the end.
+  let mut boundaries : Vec<(usize, usize)> = match unit {
+    OffsetUnit::Bytes => {
+      return tokens.iter().map(|token| Span { start: token.start, end: token.end }).collect();
+    },
+    OffsetUnit::Chars => text.char_indices()
+      .enumerate()
+      .map(|(index, (offset, _))| (offset, index))
+      .collect(),
+    OffsetUnit::Utf16 => text.char_indices()
+      .scan(0, |index, (offset, c)| {
+        let boundary = (offset, *index);
+        *index += c.len_utf16();
+        Some(boundary)
+      })
+      .collect(),
+    OffsetUnit::Graphemes => text.grapheme_indices(true)
+      .enumerate()
+      .map(|(index, (offset, _))| (offset, index))
+      .collect(),
+  };
+  let units = match unit {
+    OffsetUnit::Utf16 => text.encode_utf16().count(),
+    _ => boundaries.len(),
+  };
+  boundaries.push((text.len(), units));
+
+  tokens.iter()
+    .map(|token| {
+      let start = boundaries.partition_point(|(offset, _)| *offset <= token.start) - 1;
+      let end = boundaries.partition_point(|(offset, _)| *offset < token.end);
+      Span { start: boundaries[start].1, end: boundaries[end].1 }
+    })
+    .collect()
+}
+
+/// Whether the character is an apostrophe or one of its common stand-ins,
+/// eg. the right single quotation mark (’) that word processors substitute.
+pub fn is_apostrophe(c: char) -> bool {
+  matches!(c, '\'' | '\u{2019}' | '\u{2018}' | '`' | '\u{00B4}' | '\u{02BC}' | '\u{2032}')
+}
+
+/// Whether the character is a double quotation mark, straight or typographic.
+pub fn is_quote(c: char) -> bool {
+  matches!(c, '"' | '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{00AB}'
+      | '\u{00BB}' | '\u{2033}')
+}
+
+/// Replace apostrophe variants with the ASCII apostrophe, eg. "don’t" -> "don't".
+pub fn normalize_apostrophes(text: &str) -> Cow<'_, str> {
+  if text.chars().any(|c| c != '\'' && is_apostrophe(c)) {
+    Cow::Owned(text.chars()
+      .map(|c| if is_apostrophe(c) { '\'' } else { c })
+      .collect())
+  } else {
+    Cow::Borrowed(text)
+  }
+}
+
+/// Split text into words and punctuation with the default options. See
+/// `tokenize_with_options`.
+pub fn tokenize(text: &str) -> Vec<Token> {
+  tokenize_with_options(text, &TokenizerOptions::default())
+}
+
+/// Split text into words and punctuation. Whitespace separates tokens and
+/// is not reported; symbols without a spoken or punctuation role are skipped.
+///
+/// Combining marks are kept inside words, eg. the accent of a decomposed
+/// "café". Apostrophes and hyphens are kept inside words when surrounded by
+/// letters or digits ("don't", "well-known"). Elsewhere an apostrophe acts as a quote.
+/// Decimal points and thousands separators are kept inside numbers ("3.14",
+/// "1,000").
+///
+/// An annotation is non-blank text in square brackets on a single line, eg.
+/// "[laughs]"; it is handled according to `options.annotations`.
+pub fn tokenize_with_options(text: &str, options: &TokenizerOptions) -> Vec<Token> {
+  let chars : Vec<(usize, char)> = text.char_indices().collect();
+  let end_of = |i: usize| chars.get(i).map(|(offset, _)| *offset).unwrap_or(text.len());
+
+  let mut tokens = Vec::new();
+  let mut i = 0;
+
+  while i < chars.len() {
+    let (start, c) = chars[i];
+
+    if c.is_alphanumeric() {
+      let mut j = i + 1;
+      while j < chars.len() {
+        let current = chars[j].1;
+        let joins = (is_apostrophe(current) || current == '-')
+          && chars.get(j + 1).map(|(_, next)| next.is_alphanumeric()).unwrap_or(false);
+        if is_word_char(current)
+            || (joins && is_word_char(chars[j - 1].1))
+            || joins_number(&chars, j) {
+          j += 1;
+        } else {
+          break;
+        }
+      }
+      let end = end_of(j);
+      tokens.push(Token {
+        kind: TokenKind::Word(normalize_apostrophes(&text[start .. end]).into_owned()),
+        start,
+        end,
+      });
+      i = j;
+      continue;
+    }
+
+    if c == '[' && options.annotations != AnnotationPolicy::Read {
+      if let Some(close) = annotation_end(&chars, i) {
+        let end = end_of(close + 1);
+        if options.annotations == AnnotationPolicy::Emit {
+          let annotation = text[start + 1 .. end - 1].trim().to_string();
+          tokens.push(Token { kind: TokenKind::Annotation(annotation), start, end });
+        }
+        i = close + 1;
+        continue;
+      }
+    }
+
+    // Runs of periods become an ellipsis.
+    if c == '.' {
+      let mut j = i + 1;
+      while j < chars.len() && chars[j].1 == '.' {
+        j += 1;
+      }
+      let punctuation = if j - i > 1 { Punctuation::Ellipsis } else { Punctuation::Period };
+      tokens.push(Token { kind: TokenKind::Punctuation(punctuation), start, end: end_of(j) });
+      i = j;
+      continue;
+    }
+
+    let punctuation = match c {
+      ',' | ';' | ':' => Some(Punctuation::Comma),
+      '?' => Some(Punctuation::Question),
+      '!' => Some(Punctuation::Exclamation),
+      '\u{2026}' => Some(Punctuation::Ellipsis),
+      '-' | '\u{2013}' | '\u{2014}' | '(' | ')' | '[' | ']' => Some(Punctuation::Interjection),
+      _ if is_quote(c) || is_apostrophe(c) => Some(Punctuation::Quote),
+      _ => None,
+    };
+
+    if let Some(punctuation) = punctuation {
+      tokens.push(Token { kind: TokenKind::Punctuation(punctuation), start, end: end_of(i + 1) });
+    }
+
+    i += 1;
+  }
+
+  tokens
+}
+
+// Whether the character continues a word: a letter, a digit or a combining
+// mark, eg. U+0301 COMBINING ACUTE ACCENT.
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || is_combining_mark(c)
+}
+
+// Whether the character at the index is a decimal point or thousands
+// separator inside a number, eg. in "3.14" or "1,000".
+fn joins_number(chars: &[(usize, char)], i: usize) -> bool {
+  let digit_at = |i: usize| chars.get(i).map(|(_, c)| c.is_ascii_digit()).unwrap_or(false);
+  match chars[i].1 {
+    '.' => digit_at(i - 1) && digit_at(i + 1),
+    ',' => digit_at(i - 1) && (1 ..= 3).all(|k| digit_at(i + k)) && !digit_at(i + 4),
+    _ => false,
+  }
+}
+
+// The index of the ']' closing an annotation opened at the index, if the
+// brackets enclose non-blank text on a single line.
+fn annotation_end(chars: &[(usize, char)], open: usize) -> Option<usize> {
+  let close = chars[open + 1 ..].iter()
+    .position(|(_, c)| matches!(c, ']' | '[' | '\n'))
+    .map(|offset| open + 1 + offset)
+    .filter(|close| chars[*close].1 == ']')?;
+  if chars[open + 1 .. close].iter().all(|(_, c)| c.is_whitespace()) {
+    None
+  } else {
+    Some(close)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn kinds(text: &str) -> Vec<TokenKind> {
+    tokenize(text).into_iter().map(|token| token.kind).collect()
+  }
+
+  fn word(text: &str) -> TokenKind {
+    TokenKind::Word(text.to_string())
+  }
+
+  fn punctuation(punctuation: Punctuation) -> TokenKind {
+    TokenKind::Punctuation(punctuation)
+  }
+
+  #[test]
+  fn spans_in_units() {
+    let text = "\u{1F44D}\u{1F3FD} hi, \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467} you";
+    let tokens = tokenize(text);
+    assert_eq!(tokens.len(), 3);
+
+    let spans = |unit| token_spans(text, &tokens, unit)
+      .into_iter()
+      .map(|span| (span.start, span.end))
+      .collect::<Vec<_>>();
+    assert_eq!(spans(OffsetUnit::Bytes), vec![(9, 11), (11, 12), (32, 35)]);
+    assert_eq!(spans(OffsetUnit::Chars), vec![(3, 5), (5, 6), (13, 16)]);
+    assert_eq!(spans(OffsetUnit::Utf16), vec![(5, 7), (7, 8), (18, 21)]);
+    assert_eq!(spans(OffsetUnit::Graphemes), vec![(2, 4), (4, 5), (8, 11)]);
+
+    let utf16 : Vec<u16> = text.encode_utf16().collect();
+    assert_eq!(String::from_utf16(&utf16[18 .. 21]).unwrap(), "you");
+    assert_eq!(tokens[2].span(text, OffsetUnit::Graphemes), Span { start: 8, end: 11 });
+  }
+
+  #[test]
+  fn spans_widen_to_graphemes() {
+    // The combining accent is part of the word.
+    let text = "cafe\u{301} ok";
+    let tokens = tokenize(text);
+    assert_eq!(tokens[0].kind, word("cafe\u{301}"));
+    assert_eq!(tokens[0].span(text, OffsetUnit::Chars), Span { start: 0, end: 5 });
+    assert_eq!(tokens[0].span(text, OffsetUnit::Graphemes), Span { start: 0, end: 4 });
+    assert_eq!(tokenize("cafe\u{301}'s")[0].kind, word("cafe\u{301}'s"));
+    assert_eq!(tokens[1].span(text, OffsetUnit::Graphemes), Span { start: 5, end: 7 });
+    assert!(token_spans("", &[], OffsetUnit::Utf16).is_empty());
+  }
+
+  #[test]
+  fn normalize() {
+    assert_eq!(normalize_apostrophes("don’t"), "don't");
+    assert_eq!(normalize_apostrophes("don`t"), "don't");
+    assert_eq!(normalize_apostrophes("don't"), "don't");
+    assert!(matches!(normalize_apostrophes("dont"), Cow::Borrowed(_)));
+  }
+
+  #[test]
+  fn words_and_punctuation() {
+    assert_eq!(kinds("Hello, world!"), vec![
+      word("Hello"),
+      punctuation(Punctuation::Comma),
+      word("world"),
+      punctuation(Punctuation::Exclamation),
+    ]);
+
+    assert_eq!(kinds("Wait... what? Yes."), vec![
+      word("Wait"),
+      punctuation(Punctuation::Ellipsis),
+      word("what"),
+      punctuation(Punctuation::Question),
+      word("Yes"),
+      punctuation(Punctuation::Period),
+    ]);
+  }
+
+  #[test]
+  fn apostrophe_variants() {
+    assert_eq!(kinds("don’t"), vec![word("don't")]);
+    assert_eq!(kinds("don‘t"), vec![word("don't")]);
+    assert_eq!(kinds("don`t"), vec![word("don't")]);
+    assert_eq!(kinds("don't"), vec![word("don't")]);
+  }
+
+  #[test]
+  fn quotes() {
+    assert_eq!(kinds("“Hi,” she said"), vec![
+      punctuation(Punctuation::Quote),
+      word("Hi"),
+      punctuation(Punctuation::Comma),
+      punctuation(Punctuation::Quote),
+      word("she"),
+      word("said"),
+    ]);
+
+    assert_eq!(kinds("‘quoted’"), vec![
+      punctuation(Punctuation::Quote),
+      word("quoted"),
+      punctuation(Punctuation::Quote),
+    ]);
+  }
+
+  #[test]
+  fn hyphens() {
+    assert_eq!(kinds("well-known"), vec![word("well-known")]);
+    assert_eq!(kinds("wait - no"), vec![
+      word("wait"),
+      punctuation(Punctuation::Interjection),
+      word("no"),
+    ]);
+  }
+
+  #[test]
+  fn numbers() {
+    assert_eq!(kinds("3.14 and 1,000,000."), vec![
+      word("3.14"),
+      word("and"),
+      word("1,000,000"),
+      punctuation(Punctuation::Period),
+    ]);
+    assert_eq!(kinds("1,2 or 1,0000"), vec![
+      word("1"),
+      punctuation(Punctuation::Comma),
+      word("2"),
+      word("or"),
+      word("1"),
+      punctuation(Punctuation::Comma),
+      word("0000"),
+    ]);
+    assert_eq!(kinds("No. 5"), vec![word("No"), punctuation(Punctuation::Period), word("5")]);
+  }
+
+  #[test]
+  fn spans() {
+    let tokens = tokenize("I’m here.");
+    assert_eq!(tokens[0], Token { kind: word("I'm"), start: 0, end: 5 });
+    assert_eq!(tokens[1], Token { kind: word("here"), start: 6, end: 10 });
+    assert_eq!(tokens[2], Token { kind: punctuation(Punctuation::Period), start: 10, end: 11 });
+  }
+
+  #[test]
+  fn skips_symbols() {
+    assert_eq!(kinds("a # b"), vec![word("a"), word("b")]);
+    assert!(kinds("   ").is_empty());
+  }
+
+  #[test]
+  fn annotations() {
+    let text = "Well [laughs] no. [ long pause ] Fine [].";
+
+    let with = |annotations| {
+      tokenize_with_options(text, &TokenizerOptions { annotations }).into_iter()
+        .map(|token| token.kind)
+        .collect::<Vec<TokenKind>>()
+    };
+
+    assert_eq!(with(AnnotationPolicy::Read), kinds(text));
+    assert_eq!(kinds(text)[1], punctuation(Punctuation::Interjection));
+
+    assert_eq!(with(AnnotationPolicy::Emit), vec![
+      word("Well"),
+      TokenKind::Annotation("laughs".to_string()),
+      word("no"),
+      punctuation(Punctuation::Period),
+      TokenKind::Annotation("long pause".to_string()),
+      word("Fine"),
+      punctuation(Punctuation::Interjection),
+      punctuation(Punctuation::Interjection),
+      punctuation(Punctuation::Period),
+    ]);
+
+    assert_eq!(with(AnnotationPolicy::Skip), vec![
+      word("Well"),
+      word("no"),
+      punctuation(Punctuation::Period),
+      word("Fine"),
+      punctuation(Punctuation::Interjection),
+      punctuation(Punctuation::Interjection),
+      punctuation(Punctuation::Period),
+    ]);
+
+    let tokens = tokenize_with_options("a [sighs]",
+      &TokenizerOptions { annotations: AnnotationPolicy::Emit });
+    assert_eq!((tokens[1].start, tokens[1].end), (2, 9));
+  }
+}
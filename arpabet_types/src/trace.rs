@@ -0,0 +1,237 @@
+//! This module explains how a word's pronunciation is found, for answering
+//! "why is this word pronounced like that?".
+//!
+//! A trace follows the same steps as transcription (see
+//! `Arpabet::transcribe_sentence_with_options`), recording each source
+//! consulted in order and which one supplied the pronunciation.
+
+use crate::phoneme::Phoneme;
+use crate::transcribe::{TranscribeOptions, WordContext};
+use crate::{Arpabet, Polyphone};
+use std::fmt;
+
+/// Where a pronunciation came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PronunciationSource {
+  /// The spoken name of a letter, eg. 'b' -> B IY1. See `letters::letter_name`.
+  LetterName,
+  /// The reduced indefinite article 'a' (AH0), chosen from context.
+  Article,
+  /// The weak form of a function word, chosen from context. See the
+  /// `weak_forms` module.
+  WeakForm,
+  /// The dictionary.
+  Dictionary,
+  /// The pronunciations of a hyphenated word's parts, joined.
+  Compound,
+  /// The word read letter by letter. See `initialism::initialism_pronunciation`.
+  Initialism,
+  /// The application's pronunciations of words containing digits. See
+  /// `TranscribeOptions::alphanumeric_overlay`.
+  Overlay,
+  /// A word containing digits read in parts. See `AlphanumericPolicy`.
+  Alphanumeric,
+  /// A number read as words. See `numbers::number_words`.
+  Number,
+  /// The pronunciation of the word's lemma plus its suffix, eg. 'jumped' from
+  /// 'jump'. See `TranscribeOptions::lemmatizer`.
+  Lemma,
+}
+
+impl PronunciationSource {
+  /// Describe the source.
+  pub fn to_str(&self) -> &'static str {
+    match self {
+      PronunciationSource::LetterName => "letter name",
+      PronunciationSource::Article => "article",
+      PronunciationSource::WeakForm => "weak form",
+      PronunciationSource::Dictionary => "dictionary",
+      PronunciationSource::Compound => "compound",
+      PronunciationSource::Initialism => "initialism",
+      PronunciationSource::Overlay => "overlay",
+      PronunciationSource::Alphanumeric => "alphanumeric",
+      PronunciationSource::Number => "number",
+      PronunciationSource::Lemma => "lemma",
+    }
+  }
+}
+
+/// One step taken while looking a word up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LookupStep {
+  /// The word was normalized before lookup, eg. lowercased.
+  Normalized {
+    /// The word before normalization.
+    from: String,
+    /// The word after normalization.
+    to: String,
+  },
+  /// A source was consulted. When nothing is found, the next source is tried.
+  Consulted {
+    /// The source consulted.
+    source: PronunciationSource,
+    /// The key looked up.
+    key: String,
+    /// Whether the source had a pronunciation.
+    found: bool,
+  },
+  /// The word was split at hyphens and each part looked up in turn, stopping
+  /// at the first part without a pronunciation.
+  Split {
+    /// The traces of the parts looked up.
+    parts: Vec<LookupTrace>,
+  },
+}
+
+impl LookupStep {
+  pub(crate) fn consulted(source: PronunciationSource, key: &str, found: bool) -> Self {
+    LookupStep::Consulted { source, key: key.to_string(), found }
+  }
+}
+
+/// The steps taken to pronounce a word, and the outcome.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LookupTrace {
+  /// The word looked up.
+  pub word: String,
+  /// The steps taken, in order.
+  pub steps: Vec<LookupStep>,
+  /// The pronunciation found, if any.
+  pub pronunciation: Option<Polyphone>,
+  /// The source of the pronunciation, if one was found.
+  pub source: Option<PronunciationSource>,
+}
+
+impl LookupTrace {
+  pub(crate) fn new(word: &str, steps: Vec<LookupStep>, found: Option<(Polyphone, PronunciationSource)>) -> Self {
+    let (pronunciation, source) = match found {
+      Some((polyphone, source)) => (Some(polyphone), Some(source)),
+      None => (None, None),
+    };
+    Self {
+      word: word.to_string(),
+      steps,
+      pronunciation,
+      source,
+    }
+  }
+
+  fn write_indented(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    writeln!(f, "{}'{}'", indent, self.word)?;
+
+    for step in self.steps.iter() {
+      match step {
+        LookupStep::Normalized { from, to } =>
+          writeln!(f, "{}  normalized '{}' to '{}'", indent, from, to)?,
+        LookupStep::Consulted { source, key, found } =>
+          writeln!(f, "{}  consulted {} for '{}': {}", indent, source.to_str(), key,
+            if *found { "found" } else { "not found" })?,
+        LookupStep::Split { parts } => {
+          writeln!(f, "{}  split at hyphens", indent)?;
+          for part in parts.iter() {
+            part.write_indented(f, depth + 2)?;
+          }
+        },
+      }
+    }
+
+    match (&self.pronunciation, self.source) {
+      (Some(polyphone), Some(source)) => {
+        let symbols = polyphone.iter().map(Phoneme::to_str).collect::<Vec<&str>>();
+        writeln!(f, "{}  => {} (from {})", indent, symbols.join(" "), source.to_str())
+      },
+      _ => writeln!(f, "{}  => no pronunciation", indent),
+    }
+  }
+}
+
+impl fmt::Display for LookupTrace {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.write_indented(f, 0)
+  }
+}
+
+impl Arpabet {
+  /// Explain how a word on its own is pronounced by transcription with the
+  /// default options. See `explain_lookup_with_options`.
+  pub fn explain_lookup(&self, word: &str) -> LookupTrace {
+    self.explain_lookup_with_options(word, &TranscribeOptions::default())
+  }
+
+  /// Explain how a word on its own is pronounced by transcription with the
+  /// options: each normalization applied, each source consulted in order, and
+  /// the source of the final pronunciation. Without neighboring words, context
+  /// dependent readings such as weak forms do not apply.
+  pub fn explain_lookup_with_options(&self, word: &str, options: &TranscribeOptions) -> LookupTrace {
+    let mut steps = Some(Vec::new());
+    let found = self.trace_word(word, &WordContext::default(), options, &mut steps);
+    LookupTrace::new(word, steps.unwrap_or_default(), found)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+  use crate::transcribe::SingleLetterPolicy;
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("hi", "HH AY1"),
+    ("x", "EH1 K S"),
+  ];
+
+  #[test]
+  fn dictionary_word() {
+    let trace = testing::arpabet(DICTIONARY).explain_lookup("Hi");
+    assert_eq!(trace.steps, vec![
+      LookupStep::Normalized { from: "Hi".to_string(), to: "hi".to_string() },
+      LookupStep::consulted(PronunciationSource::Dictionary, "hi", true),
+    ]);
+    assert_eq!(trace.source, Some(PronunciationSource::Dictionary));
+    assert_eq!(trace.to_string(), "'Hi'\n  normalized 'Hi' to 'hi'\n  \
+      consulted dictionary for 'hi': found\n  => HH AY1 (from dictionary)\n");
+  }
+
+  #[test]
+  fn single_letter_fallback() {
+    let arpa = testing::arpabet(DICTIONARY);
+    let options = TranscribeOptions {
+      single_letters: SingleLetterPolicy::Word,
+      ..TranscribeOptions::default()
+    };
+
+    let trace = arpa.explain_lookup_with_options("b", &options);
+    assert_eq!(trace.steps, vec![
+      LookupStep::consulted(PronunciationSource::Dictionary, "b", false),
+      LookupStep::consulted(PronunciationSource::LetterName, "b", true),
+    ]);
+    assert_eq!(trace.source, Some(PronunciationSource::LetterName));
+
+    let trace = arpa.explain_lookup_with_options("x", &options);
+    assert_eq!(trace.source, Some(PronunciationSource::Dictionary));
+    assert_eq!(trace.steps.len(), 1);
+  }
+
+  #[test]
+  fn compound_and_missing() {
+    let arpa = testing::arpabet(DICTIONARY);
+
+    let trace = arpa.explain_lookup("hi-hi");
+    assert_eq!(trace.source, Some(PronunciationSource::Compound));
+    assert_eq!(trace.pronunciation.as_ref().map(|p| p.len()), Some(4));
+    match trace.steps.last() {
+      Some(LookupStep::Split { parts }) => assert_eq!(parts.len(), 2),
+      other => panic!("Unexpected step: {:?}", other),
+    }
+
+    let trace = arpa.explain_lookup("hi-there");
+    assert_eq!(trace.pronunciation, None);
+    assert!(trace.to_string().ends_with("    'there'\n      \
+      consulted dictionary for 'there': not found\n      => no pronunciation\n  => no pronunciation\n"));
+
+    // Explaining agrees with transcription.
+    assert!(arpa.transcribe_sentence("hi-there").is_err());
+    assert!(arpa.transcribe_sentence("hi-hi").is_ok());
+  }
+}
@@ -0,0 +1,1058 @@
+//! This module turns English text into pronunciations.
+
+use crate::casing::{fold_char, CaseFolding};
+use crate::error::ArpabetError;
+use crate::extensions::{Punctuation, SentenceToken};
+use crate::heteronym::{heteronym_pronunciation, PartOfSpeech};
+use crate::initialism::{initialism_pronunciation, is_capitalized_initialism, is_initialism};
+use crate::lemmatize::{english_stemmer, inflect, Lemmatizer};
+use crate::letters::{digit_name, letter_name};
+use crate::phoneme::{Phoneme, Vowel, VowelStress};
+use crate::texting::TextingNormalizer;
+use crate::tokenizer::{tokenize_with_options, token_spans, OffsetUnit, Span, Token, TokenKind,
+  TokenizerOptions};
+use crate::trace::{LookupStep, LookupTrace, PronunciationSource};
+use crate::weak_forms::weak_form;
+use crate::{Arpabet, Polyphone, Word};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// How one-character word tokens, eg. "a", "I" or "x", are pronounced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SingleLetterPolicy {
+  /// Decide from the neighboring words. "a" is read as the article (AH0) unless
+  /// it stands on its own ("plan A.") or follows "the" or "letter", where it is
+  /// read as the letter (EY1). Other letters are read as letter names.
+  #[default]
+  Contextual,
+  /// Read as words, using the dictionary pronunciation.
+  Word,
+  /// Always read as letter names.
+  Letter,
+}
+
+/// How word tokens containing digits, eg. "3D" or "MP3", are pronounced when
+/// missing from the dictionary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlphanumericPolicy {
+  /// Only look the whole token up, so missing tokens can't be pronounced.
+  #[default]
+  Dictionary,
+  /// Split into runs of letters and of digits. Runs of several letters are
+  /// looked up as words or else read as initialisms; single letters and
+  /// digits are named, eg. "MP3" -> 'em pee three', "R2D2" -> 'are two dee two'.
+  Split,
+  /// Name every letter and digit, eg. "3D" -> 'three dee'.
+  Spell,
+}
+
+/// What becomes of words that can't be pronounced, by the dictionary or any
+/// enabled fallback.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownWordPolicy {
+  /// Fail with `ArpabetError::UnknownWord`.
+  #[default]
+  Fail,
+  /// Leave the word out.
+  Skip,
+  /// Name its letters and digits, eg. "xkcd" -> 'ex kay see dee'. Words with
+  /// neither still fail.
+  Spell,
+}
+
+/// The words surrounding a token. A neighbor is None at the edges of the text
+/// and when punctuation intervenes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordContext<'a> {
+  /// The word before the token.
+  pub previous: Option<&'a str>,
+  /// The word after the token.
+  pub next: Option<&'a str>,
+}
+
+/// Options for transcribing text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TranscribeOptions {
+  /// How one-character words are pronounced.
+  pub single_letters: SingleLetterPolicy,
+  /// Use the weak forms of function words ('to' -> T AH0) when another word
+  /// follows. Phrase-final function words keep their strong forms, as in
+  /// "where are you from?". See the `weak_forms` module.
+  pub weak_forms: bool,
+  /// How the text is split into tokens, eg. whether bracketed annotations
+  /// such as "[laughs]" become `Punctuation::Annotation` tokens.
+  pub tokenizer: TokenizerOptions,
+  /// Read texting-speak missing from the dictionary, eg. "gr8" as 'great'.
+  /// Off by default. See the `texting` module.
+  pub texting: Option<TextingNormalizer>,
+  /// Read likely initialisms letter by letter, eg. "BBC" as B IY2 B IY2 S IY1.
+  /// Capitalized words with vowel letters, eg. "FBI", are only spelled out when
+  /// missing from the dictionary. See the `initialism` module.
+  pub initialisms: bool,
+  /// Read numbers missing from the dictionary as words, eg. "42" as 'forty
+  /// two' and "21st" as 'twenty first'. Off by default. See the `numbers`
+  /// module.
+  pub numbers: bool,
+  /// How words containing digits are pronounced when missing from the
+  /// dictionary.
+  pub alphanumerics: AlphanumericPolicy,
+  /// Pronunciations of lowercase words containing digits, eg. "mp3", that take
+  /// precedence over the dictionary and the `alphanumerics` policy.
+  pub alphanumeric_overlay: HashMap<Word, Polyphone>,
+  /// How words are folded to lowercase for lookup. See the `casing` module.
+  pub case_folding: CaseFolding,
+  /// The most words a multi-word entry may span, eg. 3, the default, to read
+  /// "new york times" from the entry 'new_york_times'. Values below 2 read
+  /// word by word. See `PHRASE_SEPARATOR`.
+  pub max_phrase_length: usize,
+  /// The unit in which `transcribe_words` reports where words are, eg.
+  /// `OffsetUnit::Utf16` to highlight them in a JavaScript front end.
+  pub offsets: OffsetUnit,
+  /// What becomes of words that can't be pronounced. They fail the
+  /// transcription by default.
+  pub unknown_words: UnknownWordPolicy,
+  /// Reduces words missing from the dictionary to their lemmas, so they are
+  /// pronounced as a lemma plus the suffix, eg. "jumped" as 'jump' + T. The
+  /// `EnglishStemmer` by default; None turns the fallback off. See the
+  /// `lemmatize` module.
+  pub lemmatizer: Option<Arc<dyn Lemmatizer>>,
+}
+
+/// A word of transcribed text, with its location and pronunciation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TranscribedWord {
+  /// The word as it appears in the text, with apostrophes normalized.
+  pub word: String,
+  /// Where the word is in the text, counted in `TranscribeOptions::offsets`.
+  pub span: Span,
+  /// Pronunciation of the word.
+  pub polyphone: Polyphone,
+}
+
+impl Default for TranscribeOptions {
+  fn default() -> Self {
+    Self {
+      single_letters: SingleLetterPolicy::default(),
+      weak_forms: false,
+      tokenizer: TokenizerOptions::default(),
+      texting: None,
+      initialisms: false,
+      numbers: false,
+      alphanumerics: AlphanumericPolicy::default(),
+      alphanumeric_overlay: HashMap::new(),
+      case_folding: CaseFolding::default(),
+      max_phrase_length: DEFAULT_MAX_PHRASE_LENGTH,
+      offsets: OffsetUnit::default(),
+      unknown_words: UnknownWordPolicy::default(),
+      lemmatizer: Some(english_stemmer()),
+    }
+  }
+}
+
+/// The separator of the words of multi-word entries, eg. 'new_york'.
+pub const PHRASE_SEPARATOR : &str = "_";
+
+// The most words a multi-word entry may span by default.
+const DEFAULT_MAX_PHRASE_LENGTH : usize = 3;
+
+// Words after which a lone 'a' names the letter, eg. "the letter a".
+const LETTER_MARKERS : [&str; 2] = ["the", "letter"];
+
+impl Arpabet {
+  /// Transcribe English text into a stream of sentence tokens, using the
+  /// default options. See `transcribe_sentence_with_options`.
+  pub fn transcribe_sentence(&self, text: &str) -> Result<Vec<SentenceToken>, ArpabetError> {
+    self.transcribe_sentence_with_options(text, &TranscribeOptions::default())
+  }
+
+  /// Transcribe English text into a stream of sentence tokens: a start token,
+  /// the phonemes of each word with spaces between words, punctuation where it
+  /// occurs, and an end token.
+  /// eg. "Hi, you." -> [start] HH AY1 [comma] [space] Y UW1 [period] [end]
+  ///
+  /// Hyphenated words missing from the dictionary are pronounced part by part.
+  /// Runs of words with a multi-word entry are read as one word when
+  /// `options.max_phrase_length` allows, preferring the longest entries.
+  /// Fails with `ArpabetError::UnknownWord` on the first unpronounceable word,
+  /// unless `options.unknown_words` says otherwise.
+  pub fn transcribe_sentence_with_options(&self, text: &str, options: &TranscribeOptions)
+    -> Result<Vec<SentenceToken>, ArpabetError>
+  {
+    self.transcribe_tagged(text, &[], options)
+  }
+
+  // Transcribe text, pronouncing the word tokens with the parts of speech of
+  // the tags, in order. See `Arpabet::transcribe_sentence_with_pos`.
+  pub(crate) fn transcribe_tagged(&self,
+                                   text: &str,
+                                   tags: &[PartOfSpeech],
+                                   options: &TranscribeOptions)
+                                   -> Result<Vec<SentenceToken>, ArpabetError> {
+    let tokens = tokenize_with_options(text, &options.tokenizer);
+    let phrases = self.phrase_matches(&tokens, options);
+    let mut tags = tags.iter();
+
+    let mut output = vec![SentenceToken::Punctuation(Punctuation::StartToken)];
+    let mut seen_word = false;
+
+    let mut i = 0;
+    while i < tokens.len() {
+      match tokens[i].kind {
+        TokenKind::Punctuation(punctuation) => {
+          output.push(SentenceToken::Punctuation(punctuation));
+        },
+        TokenKind::Annotation(_) => {
+          output.push(SentenceToken::Punctuation(Punctuation::Annotation));
+        },
+        TokenKind::Word(ref word) => {
+          let polyphone = match phrases.get(&i) {
+            Some((length, polyphone)) => {
+              tags.nth(length - 1);
+              i += length - 1;
+              Some(polyphone.clone())
+            },
+            None => {
+              let context = word_context(&tokens, i);
+              let found = tags.next()
+                .and_then(|tag| heteronym_pronunciation(word, *tag))
+                .or_else(|| self.pronounce_word(word, &context, options));
+              match found {
+                Some(polyphone) => Some(polyphone),
+                None => unknown_word(word, options.unknown_words)?,
+              }
+            },
+          };
+
+          if let Some(polyphone) = polyphone {
+            if seen_word {
+              output.push(SentenceToken::Punctuation(Punctuation::Space));
+            }
+            seen_word = true;
+
+            output.extend(polyphone.into_iter().map(SentenceToken::Phoneme));
+          }
+        },
+      }
+      i += 1;
+    }
+
+    output.push(SentenceToken::Punctuation(Punctuation::EndToken));
+    Ok(output)
+  }
+
+  // Choose the multi-word entries to read, by the index of the token each
+  // starts at, with the number of words each covers. Within each run of words
+  // the longest entry is preferred, backtracking to shorter entries where a
+  // longer one would leave the words after it unpronounceable. Unless unknown
+  // words fail, they may be left to the unknown word policy, but readings with
+  // the fewest of them are preferred.
+  fn phrase_matches(&self, tokens: &[Token], options: &TranscribeOptions)
+    -> HashMap<usize, (usize, Polyphone)>
+  {
+    let mut matches = HashMap::new();
+    if options.max_phrase_length < 2 {
+      return matches;
+    }
+    let words : Vec<Option<&str>> = tokens.iter()
+      .map(|token| match token.kind {
+        TokenKind::Word(ref word) => Some(word.as_str()),
+        _ => None,
+      })
+      .collect();
+    let allow_unknown = options.unknown_words != UnknownWordPolicy::Fail;
+
+    let mut start = 0;
+    while start < words.len() {
+      if words[start].is_none() {
+        start += 1;
+        continue;
+      }
+      let end = (start .. words.len()).find(|i| words[*i].is_none()).unwrap_or(words.len());
+
+      // The multi-word entries in the run, by where they start and how many
+      // words they cover. Runs without any are read word by word.
+      let mut entries : HashMap<(usize, usize), Polyphone> = HashMap::new();
+      for i in start .. end {
+        for length in 2 ..= options.max_phrase_length.min(end - i) {
+          let key = words[i .. i + length].iter()
+            .map(|word| options.case_folding.fold(word.unwrap_or_default()))
+            .collect::<Vec<String>>()
+            .join(PHRASE_SEPARATOR);
+          if let Some(polyphone) = self.get_polyphone(&key) {
+            entries.insert((i, length), polyphone);
+          }
+        }
+      }
+      if entries.is_empty() {
+        start = end;
+        continue;
+      }
+
+      // choices[i - start] is how to read the words from i, if they can all be
+      // read: the number of unknown words left, the number of words read next,
+      // and the entry when several.
+      let mut choices : Vec<Option<(usize, usize, Option<Polyphone>)>> =
+        vec![None; end - start + 1];
+      choices[end - start] = Some((0, 0, None));
+      for i in (start .. end).rev() {
+        let longest = options.max_phrase_length.min(end - i);
+        let mut best : Option<(usize, usize, Option<Polyphone>)> = None;
+        for length in (1 ..= longest).rev() {
+          let unknown_after = match choices[i + length - start] {
+            Some((unknown, _, _)) => unknown,
+            None => continue,
+          };
+          let step = if length == 1 {
+            let word = words[i].unwrap_or_default();
+            match self.pronounce_word(word, &word_context(tokens, i), options) {
+              Some(_) => Some((unknown_after, 1, None)),
+              None if allow_unknown => Some((unknown_after + 1, 1, None)),
+              None => None,
+            }
+          } else {
+            entries.get(&(i, length)).map(|polyphone| (unknown_after, length, Some(polyphone.clone())))
+          };
+          // Lengths are tried longest first, so ties keep the longer entry.
+          if let Some(step) = step {
+            if !matches!(&best, Some(best) if best.0 <= step.0) {
+              best = Some(step);
+            }
+          }
+        }
+        choices[i - start] = best;
+      }
+
+      let mut i = start;
+      while i < end {
+        match choices[i - start].take() {
+          Some((_, length, Some(polyphone))) => {
+            matches.insert(i, (length, polyphone));
+            i += length;
+          },
+          _ => i += 1,
+        }
+      }
+      start = end;
+    }
+    matches
+  }
+
+  /// Transcribe English text word by word, locating each word in the text, eg.
+  /// to highlight words as they are spoken. Words are pronounced as by
+  /// `transcribe_sentence_with_options`, and unknown words handled the same
+  /// way. Runs of words read from a multi-word entry are reported as one word
+  /// spanning them all, eg. "New York".
+  pub fn transcribe_words(&self, text: &str, options: &TranscribeOptions)
+    -> Result<Vec<TranscribedWord>, ArpabetError>
+  {
+    let tokens = tokenize_with_options(text, &options.tokenizer);
+    let spans = token_spans(text, &tokens, options.offsets);
+
+    let mut words = Vec::new();
+    for (range, polyphone) in self.read_words(&tokens, options) {
+      let word = phrase_text(text, &tokens[range.clone()]);
+      let polyphone = match polyphone {
+        Some(polyphone) => Some(polyphone),
+        None => unknown_word(&word, options.unknown_words)?,
+      };
+      if let Some(polyphone) = polyphone {
+        let span = Span { start: spans[range.start].start, end: spans[range.end - 1].end };
+        words.push(TranscribedWord { word, span, polyphone });
+      }
+    }
+    Ok(words)
+  }
+
+  /// Pronounce every word of the text as `transcribe_sentence_with_options`
+  /// would, without failing: unpronounceable words are paired with None.
+  pub(crate) fn pronounce_words(&self, text: &str, options: &TranscribeOptions)
+    -> Vec<(String, Option<Polyphone>)>
+  {
+    let tokens = tokenize_with_options(text, &options.tokenizer);
+    self.read_words(&tokens, options)
+      .into_iter()
+      .map(|(range, polyphone)| (phrase_text(text, &tokens[range]), polyphone))
+      .collect()
+  }
+
+  // Pronounce the word tokens in order, reading multi-word entries as by
+  // `phrase_matches`. Each reading is the range of tokens it covers, with its
+  // pronunciation or None if the word can't be pronounced.
+  fn read_words(&self, tokens: &[Token], options: &TranscribeOptions)
+    -> Vec<(Range<usize>, Option<Polyphone>)>
+  {
+    let mut phrases = self.phrase_matches(tokens, options);
+    let mut readings = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+      if let TokenKind::Word(ref word) = tokens[i].kind {
+        match phrases.remove(&i) {
+          Some((length, polyphone)) => {
+            readings.push((i .. i + length, Some(polyphone)));
+            i += length;
+            continue;
+          },
+          None => {
+            let polyphone = self.pronounce_word(word, &word_context(tokens, i), options);
+            readings.push((i .. i + 1, polyphone));
+          },
+        }
+      }
+      i += 1;
+    }
+    readings
+  }
+
+  // Pronounce a single word token from the text.
+  pub(crate) fn pronounce_word(&self, word: &str, context: &WordContext, options: &TranscribeOptions)
+    -> Option<Polyphone>
+  {
+    self.trace_word(word, context, options, &mut None).map(|(polyphone, _)| polyphone)
+  }
+
+  // Pronounce a single word token, recording each step taken if tracing.
+  // See `Arpabet::explain_lookup`.
+  pub(crate) fn trace_word(&self,
+                           word: &str,
+                           context: &WordContext,
+                           options: &TranscribeOptions,
+                           trace: &mut Option<Vec<LookupStep>>)
+                           -> Option<(Polyphone, PronunciationSource)> {
+    if let Some(found) = self.single_character(word, context, options.single_letters, trace) {
+      return Some(found);
+    }
+
+    if options.weak_forms && context.next.is_some() {
+      let polyphone = weak_form(word);
+      record(trace, || LookupStep::consulted(PronunciationSource::WeakForm, word, polyphone.is_some()));
+      if let Some(polyphone) = polyphone {
+        return Some((polyphone, PronunciationSource::WeakForm));
+      }
+    }
+
+    if options.initialisms && is_initialism(word) {
+      if let Some(found) = initialism(word, trace) {
+        return Some(found);
+      }
+    }
+
+    let lowercase = options.case_folding.fold(word);
+    if lowercase != word {
+      record(trace, || LookupStep::Normalized { from: word.to_string(), to: lowercase.clone() });
+    }
+
+    let has_digits = lowercase.chars().any(|c| c.is_ascii_digit());
+    if has_digits && !options.alphanumeric_overlay.is_empty() {
+      let polyphone = options.alphanumeric_overlay.get(&lowercase).cloned();
+      record(trace, || LookupStep::consulted(PronunciationSource::Overlay, &lowercase, polyphone.is_some()));
+      if let Some(polyphone) = polyphone {
+        return Some((polyphone, PronunciationSource::Overlay));
+      }
+    }
+
+    let polyphone = self.get_polyphone(&lowercase);
+    record(trace, || LookupStep::consulted(PronunciationSource::Dictionary, &lowercase, polyphone.is_some()));
+    if let Some(polyphone) = polyphone {
+      return Some((polyphone, PronunciationSource::Dictionary));
+    }
+
+    if let Some(ref texting) = options.texting {
+      for candidate in texting.candidates(&lowercase) {
+        record(trace, || LookupStep::Normalized { from: lowercase.clone(), to: candidate.clone() });
+        let polyphone = self.get_polyphone(&candidate);
+        record(trace, || LookupStep::consulted(PronunciationSource::Dictionary, &candidate, polyphone.is_some()));
+        if let Some(polyphone) = polyphone {
+          return Some((polyphone, PronunciationSource::Dictionary));
+        }
+      }
+    }
+
+    if has_digits && options.numbers {
+      let polyphone = self.pronounce_number(&lowercase);
+      record(trace, || LookupStep::consulted(PronunciationSource::Number, &lowercase, polyphone.is_some()));
+      if let Some(polyphone) = polyphone {
+        return Some((polyphone, PronunciationSource::Number));
+      }
+    }
+
+    if has_digits && options.alphanumerics != AlphanumericPolicy::Dictionary {
+      let polyphone = self.alphanumeric(&lowercase, options.alphanumerics);
+      record(trace, || LookupStep::consulted(PronunciationSource::Alphanumeric, &lowercase, polyphone.is_some()));
+      if let Some(polyphone) = polyphone {
+        return Some((polyphone, PronunciationSource::Alphanumeric));
+      }
+    }
+
+    if options.initialisms && is_capitalized_initialism(word) {
+      if let Some(found) = initialism(word, trace) {
+        return Some(found);
+      }
+    }
+
+    if let Some(ref lemmatizer) = options.lemmatizer {
+      if !has_digits {
+        for lemma in lemmatizer.lemmas(&lowercase) {
+          let polyphone = self.get_polyphone(&lemma)
+            .and_then(|polyphone| inflect(&lowercase, &lemma, &polyphone));
+          record(trace, || LookupStep::consulted(PronunciationSource::Lemma, &lemma, polyphone.is_some()));
+          if let Some(polyphone) = polyphone {
+            return Some((polyphone, PronunciationSource::Lemma));
+          }
+        }
+      }
+    }
+
+    if lowercase.contains('-') {
+      let mut polyphone = Vec::new();
+      let mut parts = Vec::new();
+      let mut complete = true;
+      for part in lowercase.split('-') {
+        let mut part_trace = trace.as_ref().map(|_| Vec::new());
+        let found = self.trace_word(part, &WordContext::default(), options, &mut part_trace);
+        if let Some(steps) = part_trace {
+          parts.push(LookupTrace::new(part, steps, found.clone()));
+        }
+        match found {
+          Some((part_polyphone, _)) => polyphone.extend(part_polyphone),
+          None => {
+            complete = false;
+            break;
+          },
+        }
+      }
+      record(trace, || LookupStep::Split { parts });
+      if complete {
+        return Some((polyphone, PronunciationSource::Compound));
+      }
+    }
+
+    None
+  }
+
+  // Pronounce a lowercase word containing digits under the policy. None if it
+  // has characters other than letters and digits.
+  fn alphanumeric(&self, word: &str, policy: AlphanumericPolicy) -> Option<Polyphone> {
+    let mut polyphone = Vec::new();
+    for run in alphanumeric_runs(word)? {
+      let letters = run.chars().count() > 1 && !run.starts_with(|c: char| c.is_ascii_digit());
+      if letters && policy == AlphanumericPolicy::Split {
+        polyphone.extend(self.get_polyphone(run).or_else(|| initialism_pronunciation(run))?);
+      } else {
+        for c in run.chars() {
+          polyphone.extend(letter_name(c).or_else(|| digit_name(c))?);
+        }
+      }
+    }
+    Some(polyphone)
+  }
+
+  /// Pronounce a one-character word token under the policy. Returns None for
+  /// longer tokens, and for characters that are neither letters nor in the
+  /// dictionary. The pronoun "I" is always AY1.
+  pub fn pronounce_single_character(&self,
+                                    token: &str,
+                                    context: &WordContext,
+                                    policy: SingleLetterPolicy)
+                                    -> Option<Polyphone> {
+    self.single_character(token, context, policy, &mut None).map(|(polyphone, _)| polyphone)
+  }
+
+  // Pronounce a one-character word token, recording each step taken if tracing.
+  fn single_character(&self,
+                      token: &str,
+                      context: &WordContext,
+                      policy: SingleLetterPolicy,
+                      trace: &mut Option<Vec<LookupStep>>)
+                      -> Option<(Polyphone, PronunciationSource)> {
+    let mut chars = token.chars();
+    let c = match (chars.next(), chars.next()) {
+      (Some(c), None) => fold_char(c),
+      _ => return None,
+    };
+    let key = c.to_string();
+
+    let consult = |source: PronunciationSource, trace: &mut Option<Vec<LookupStep>>| {
+      let polyphone = match source {
+        PronunciationSource::LetterName => letter_name(c),
+        _ => self.get_polyphone(&key),
+      };
+      record(trace, || LookupStep::consulted(source, &key, polyphone.is_some()));
+      polyphone.map(|polyphone| (polyphone, source))
+    };
+
+    let letter_first = match policy {
+      SingleLetterPolicy::Word => false,
+      SingleLetterPolicy::Letter => true,
+      SingleLetterPolicy::Contextual => {
+        if c == 'a' && is_article(context) {
+          record(trace, || LookupStep::consulted(PronunciationSource::Article, &key, true));
+          return Some((vec![Phoneme::Vowel(Vowel::AH(VowelStress::NoStress))], PronunciationSource::Article));
+        }
+        true
+      },
+    };
+
+    if letter_first {
+      consult(PronunciationSource::LetterName, trace)
+        .or_else(|| consult(PronunciationSource::Dictionary, trace))
+    } else {
+      consult(PronunciationSource::Dictionary, trace)
+        .or_else(|| consult(PronunciationSource::LetterName, trace))
+    }
+  }
+}
+
+// Split a word into runs of letters and runs of digits, eg. "mp3" -> "mp", "3".
+// None if the word has other characters.
+fn alphanumeric_runs(word: &str) -> Option<Vec<&str>> {
+  let mut runs = Vec::new();
+  let mut start = 0;
+  let mut previous_digit = None;
+  for (i, c) in word.char_indices() {
+    let is_digit = c.is_ascii_digit();
+    if !is_digit && !c.is_ascii_alphabetic() {
+      return None;
+    }
+    if previous_digit.map(|previous| previous != is_digit).unwrap_or(false) {
+      runs.push(&word[start .. i]);
+      start = i;
+    }
+    previous_digit = Some(is_digit);
+  }
+  if start < word.len() {
+    runs.push(&word[start ..]);
+  }
+  Some(runs)
+}
+
+// The text of a run of word tokens as it appears, eg. "New York", with the
+// apostrophes of the words normalized.
+fn phrase_text(text: &str, tokens: &[Token]) -> String {
+  let mut phrase = String::new();
+  for (i, token) in tokens.iter().enumerate() {
+    if i > 0 {
+      phrase.push_str(&text[tokens[i - 1].end .. token.start]);
+    }
+    if let TokenKind::Word(ref word) = token.kind {
+      phrase.push_str(word);
+    }
+  }
+  phrase
+}
+
+// Handle a word that can't be pronounced under the policy: its spelling, None
+// to leave it out, or an error.
+fn unknown_word(word: &str, policy: UnknownWordPolicy) -> Result<Option<Polyphone>, ArpabetError> {
+  match policy {
+    UnknownWordPolicy::Fail => {},
+    UnknownWordPolicy::Skip => return Ok(None),
+    UnknownWordPolicy::Spell => {
+      let spelling : Polyphone = word.chars()
+        .filter_map(|c| letter_name(c).or_else(|| digit_name(c)))
+        .flatten()
+        .collect();
+      if !spelling.is_empty() {
+        return Ok(Some(spelling));
+      }
+    },
+  }
+  Err(ArpabetError::UnknownWord { word: word.to_string() })
+}
+
+// Record a step, if tracing.
+fn record<F: FnOnce() -> LookupStep>(trace: &mut Option<Vec<LookupStep>>, step: F) {
+  if let Some(steps) = trace {
+    steps.push(step());
+  }
+}
+
+// Pronounce a word letter by letter, recording the step if tracing.
+fn initialism(word: &str, trace: &mut Option<Vec<LookupStep>>) -> Option<(Polyphone, PronunciationSource)> {
+  let polyphone = initialism_pronunciation(word);
+  record(trace, || LookupStep::consulted(PronunciationSource::Initialism, word, polyphone.is_some()));
+  polyphone.map(|polyphone| (polyphone, PronunciationSource::Initialism))
+}
+
+// The words either side of the token at the index.
+pub(crate) fn word_context(tokens: &[Token], index: usize) -> WordContext<'_> {
+  let word_at = |i: usize| match tokens.get(i).map(|token| &token.kind) {
+    Some(TokenKind::Word(word)) => Some(word.as_str()),
+    _ => None,
+  };
+  WordContext {
+    previous: index.checked_sub(1).and_then(word_at),
+    next: word_at(index + 1),
+  }
+}
+
+// Whether a lone 'a' in this context is the indefinite article.
+fn is_article(context: &WordContext) -> bool {
+  let after_marker = context.previous
+    .map(|previous| LETTER_MARKERS.iter().any(|m| previous.eq_ignore_ascii_case(m)))
+    .unwrap_or(false);
+  context.next.is_some() && !after_marker
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{arpabet, polyphone};
+  use crate::tokenizer::AnnotationPolicy;
+
+  fn context<'a>(previous: Option<&'a str>, next: Option<&'a str>) -> WordContext<'a> {
+    WordContext { previous, next }
+  }
+
+  const DICTIONARY : &[(&str, &str)] = &[
+    ("hi", "HH AY1"),
+    ("you", "Y UW1"),
+    ("two", "T UW1"),
+  ];
+
+  fn strings(tokens: &[SentenceToken]) -> Vec<&'static str> {
+    tokens.iter().map(|token| token.to_str()).collect()
+  }
+
+  #[test]
+  fn transcribe_sentence() {
+    let arpa = arpabet(DICTIONARY);
+
+    assert_eq!(strings(&arpa.transcribe_sentence("Hi, you.").unwrap()),
+      vec!["[start]", "HH", "AY1", "[comma]", "[space]", "Y", "UW1", "[period]", "[end]"]);
+
+    assert_eq!(strings(&arpa.transcribe_sentence("“You two?”").unwrap()),
+      vec!["[start]", "[quote]", "Y", "UW1", "[space]", "T", "UW1", "[question]", "[quote]",
+        "[end]"]);
+
+    assert_eq!(strings(&arpa.transcribe_sentence("").unwrap()), vec!["[start]", "[end]"]);
+  }
+
+  #[test]
+  fn transcribe_sentence_words() {
+    let arpa = arpabet(DICTIONARY);
+
+    // Hyphenated compounds fall back to their parts.
+    assert_eq!(strings(&arpa.transcribe_sentence("hi-you").unwrap()),
+      vec!["[start]", "HH", "AY1", "Y", "UW1", "[end]"]);
+
+    // Single letters follow the policy.
+    assert_eq!(strings(&arpa.transcribe_sentence("a hi").unwrap()),
+      vec!["[start]", "AH0", "[space]", "HH", "AY1", "[end]"]);
+
+    let options = TranscribeOptions {
+      single_letters: SingleLetterPolicy::Letter,
+      ..Default::default()
+    };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("a hi", &options).unwrap()),
+      vec!["[start]", "EY1", "[space]", "HH", "AY1", "[end]"]);
+  }
+
+  #[test]
+  fn transcribe_sentence_weak_forms() {
+    let mut arpa = arpabet(DICTIONARY);
+    arpa.insert("to".to_string(), polyphone("T UW1"));
+    let options = TranscribeOptions { weak_forms: true, ..Default::default() };
+
+    assert_eq!(strings(&arpa.transcribe_sentence("to you").unwrap()),
+      vec!["[start]", "T", "UW1", "[space]", "Y", "UW1", "[end]"]);
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("to you", &options).unwrap()),
+      vec!["[start]", "T", "AH0", "[space]", "Y", "UW1", "[end]"]);
+    // Phrase-final function words are stressed.
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("you to.", &options).unwrap()),
+      vec!["[start]", "Y", "UW1", "[space]", "T", "UW1", "[period]", "[end]"]);
+  }
+
+  #[test]
+  fn transcribe_sentence_unknown_word() {
+    match arpabet(DICTIONARY).transcribe_sentence("hi there") {
+      Err(ArpabetError::UnknownWord { word }) => assert_eq!(word, "there"),
+      _ => panic!("Should have failed on 'there'"),
+    }
+    assert!(arpabet(DICTIONARY).transcribe_sentence("hi-there").is_err());
+  }
+
+  #[test]
+  fn transcribe_sentence_texting() {
+    let mut arpa = arpabet(DICTIONARY);
+    arpa.insert("great".to_string(), polyphone("G R EY1 T"));
+    assert!(arpa.transcribe_sentence("Gr8, you.").is_err());
+
+    let options = TranscribeOptions { texting: Some(TextingNormalizer::new()), ..Default::default() };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("Gr8, you.", &options).unwrap()),
+      vec!["[start]", "G", "R", "EY1", "T", "[comma]", "[space]", "Y", "UW1", "[period]", "[end]"]);
+
+    let trace = arpa.explain_lookup_with_options("gr8", &options);
+    assert_eq!(trace.steps.len(), 3);
+  }
+
+  #[test]
+  fn transcribe_sentence_case_folding() {
+    let arpa = arpabet(DICTIONARY);
+    assert_eq!(strings(&arpa.transcribe_sentence("hı").unwrap()), vec!["[start]", "HH", "AY1", "[end]"]);
+
+    let unicode = TranscribeOptions { case_folding: CaseFolding::Unicode, ..Default::default() };
+    assert!(arpa.transcribe_sentence_with_options("hı", &unicode).is_err());
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("HI", &unicode).unwrap()),
+      vec!["[start]", "HH", "AY1", "[end]"]);
+  }
+
+  #[test]
+  fn transcribe_sentence_initialisms() {
+    let mut arpa = arpabet(DICTIONARY);
+    arpa.insert("bbc".to_string(), polyphone("B IY1"));
+    assert!(arpa.transcribe_sentence("Hi FBI").is_err());
+    assert_eq!(strings(&arpa.transcribe_sentence("BBC").unwrap()), vec!["[start]", "B", "IY1", "[end]"]);
+
+    let options = TranscribeOptions { initialisms: true, ..Default::default() };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("BBC", &options).unwrap()),
+      vec!["[start]", "B", "IY2", "B", "IY2", "S", "IY1", "[end]"]);
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("Hi FBI", &options).unwrap()),
+      vec!["[start]", "HH", "AY1", "[space]", "EH2", "F", "B", "IY2", "AY1", "[end]"]);
+    // Capitalized words with vowels prefer the dictionary.
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("HI YOU", &options).unwrap()),
+      vec!["[start]", "HH", "AY1", "[space]", "Y", "UW1", "[end]"]);
+
+    let trace = arpa.explain_lookup_with_options("FBI", &options);
+    assert_eq!(trace.source, Some(PronunciationSource::Initialism));
+  }
+
+  #[test]
+  fn transcribe_sentence_alphanumerics() {
+    let mut arpa = arpabet(DICTIONARY);
+    arpa.insert("player".to_string(), polyphone("P L EY1 ER0"));
+    assert!(arpa.transcribe_sentence("MP3").is_err());
+
+    let transcribe = |text: &str, options: &TranscribeOptions| {
+      strings(&arpa.transcribe_sentence_with_options(text, options).unwrap())
+    };
+
+    let split = TranscribeOptions { alphanumerics: AlphanumericPolicy::Split, ..Default::default() };
+    assert_eq!(transcribe("MP3", &split),
+      vec!["[start]", "EH2", "M", "P", "IY1", "TH", "R", "IY1", "[end]"]);
+    assert_eq!(transcribe("3D", &split), vec!["[start]", "TH", "R", "IY1", "D", "IY1", "[end]"]);
+    assert_eq!(transcribe("player2", &split),
+      vec!["[start]", "P", "L", "EY1", "ER0", "T", "UW1", "[end]"]);
+
+    let spell = TranscribeOptions { alphanumerics: AlphanumericPolicy::Spell, ..Default::default() };
+    assert_eq!(transcribe("Hi5", &spell),
+      vec!["[start]", "EY1", "CH", "AY1", "F", "AY1", "V", "[end]"]);
+
+    let mut overlay = split.clone();
+    overlay.alphanumeric_overlay.insert("mp3".to_string(), polyphone("M IY1"));
+    assert_eq!(transcribe("MP3 3D", &overlay),
+      vec!["[start]", "M", "IY1", "[space]", "TH", "R", "IY1", "D", "IY1", "[end]"]);
+
+    assert_eq!(alphanumeric_runs("r2d2"), Some(vec!["r", "2", "d", "2"]));
+    assert_eq!(alphanumeric_runs("mp3's"), None);
+  }
+
+  #[test]
+  fn transcribe_sentence_unknown_words() {
+    let arpa = arpabet(DICTIONARY);
+    let transcribe = |text: &str, unknown_words: UnknownWordPolicy| {
+      let options = TranscribeOptions { unknown_words, ..Default::default() };
+      arpa.transcribe_sentence_with_options(text, &options).map(|tokens| strings(&tokens))
+    };
+
+    assert!(transcribe("hi zx you", UnknownWordPolicy::Fail).is_err());
+    assert_eq!(transcribe("hi zx, you", UnknownWordPolicy::Skip).unwrap(),
+      vec!["[start]", "HH", "AY1", "[comma]", "[space]", "Y", "UW1", "[end]"]);
+    assert_eq!(transcribe("zx hi", UnknownWordPolicy::Skip).unwrap(),
+      vec!["[start]", "HH", "AY1", "[end]"]);
+    assert_eq!(transcribe("hi zx", UnknownWordPolicy::Spell).unwrap(),
+      vec!["[start]", "HH", "AY1", "[space]", "Z", "IY1", "EH1", "K", "S", "[end]"]);
+    assert!(transcribe("hi ñ", UnknownWordPolicy::Spell).is_err());
+
+    let options = TranscribeOptions {
+      unknown_words: UnknownWordPolicy::Skip,
+      ..Default::default()
+    };
+    let words = arpa.transcribe_words("zx you", &options).unwrap();
+    assert_eq!(words.len(), 1);
+    assert_eq!(words[0].word, "you");
+  }
+
+  #[test]
+  fn transcribe_sentence_numbers() {
+    let mut arpa = arpabet(DICTIONARY);
+    arpa.insert("point".to_string(), polyphone("P OY1 N T"));
+    assert!(arpa.transcribe_sentence("2.2").is_err());
+
+    let options = TranscribeOptions { numbers: true, ..Default::default() };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("2.2, you", &options).unwrap()),
+      vec!["[start]", "T", "UW1", "P", "OY1", "N", "T", "T", "UW1", "[comma]", "[space]", "Y",
+        "UW1", "[end]"]);
+    assert!(arpa.transcribe_sentence_with_options("22", &options).is_err());
+
+    let trace = arpa.explain_lookup_with_options("2.2", &options);
+    assert_eq!(trace.source, Some(PronunciationSource::Number));
+  }
+
+  #[test]
+  fn transcribe_sentence_phrases() {
+    let arpa = arpabet(&[
+      ("new", "N UW1"),
+      ("york", "Y AO1 R K"),
+      ("times", "T AY1 M Z"),
+      ("new_york", "N UW1 Y AO1 R K"),
+      ("new_york_times", "N UW1 Y AO2 R K T AY1 M Z"),
+      ("la", "L AA1"),
+      ("la_la", "L AH0 L AA1"),
+      ("la_land", "L AA1 L AE2 N D"),
+    ]);
+    let transcribe = |text: &str, max_phrase_length: usize| {
+      let options = TranscribeOptions { max_phrase_length, ..Default::default() };
+      arpa.transcribe_sentence_with_options(text, &options).map(|tokens| strings(&tokens).join(" "))
+    };
+
+    assert_eq!(transcribe("New York Times", 3).unwrap(),
+      "[start] N UW1 Y AO2 R K T AY1 M Z [end]");
+    assert_eq!(transcribe("New York Times", 2).unwrap(),
+      "[start] N UW1 Y AO1 R K [space] T AY1 M Z [end]");
+    assert_eq!(transcribe("New York Times", 0).unwrap(),
+      "[start] N UW1 [space] Y AO1 R K [space] T AY1 M Z [end]");
+    assert_eq!(strings(&arpa.transcribe_sentence("New York Times").unwrap()).join(" "),
+      transcribe("New York Times", 3).unwrap());
+    assert_eq!(transcribe("new, york", 3).unwrap(), "[start] N UW1 [comma] [space] Y AO1 R K [end]");
+
+    // 'la la' would leave 'land' unread, so backtrack to 'la', 'la land'.
+    assert_eq!(transcribe("la la land", 3).unwrap(), "[start] L AA1 [space] L AA1 L AE2 N D [end]");
+    assert_eq!(transcribe("la la", 3).unwrap(), "[start] L AH0 L AA1 [end]");
+    assert!(transcribe("la la land", 1).is_err());
+
+    // Unknown words left to the policy don't stop entries before them.
+    let skip = |text: &str| {
+      let options = TranscribeOptions {
+        max_phrase_length: 3,
+        unknown_words: UnknownWordPolicy::Skip,
+        ..Default::default()
+      };
+      arpa.transcribe_sentence_with_options(text, &options).map(|tokens| strings(&tokens).join(" "))
+    };
+    assert_eq!(skip("New York Times qqq").unwrap(), "[start] N UW1 Y AO2 R K T AY1 M Z [end]");
+    assert_eq!(skip("qqq New York").unwrap(), "[start] N UW1 Y AO1 R K [end]");
+    assert_eq!(skip("la la land").unwrap(), "[start] L AA1 [space] L AA1 L AE2 N D [end]");
+    assert!(transcribe("New York Times qqq", 3).is_err());
+
+    // Word by word transcription reads the same entries.
+    let options = TranscribeOptions { max_phrase_length: 3, ..Default::default() };
+    for text in ["New York Times", "new  york times", "la la land", "la la"].iter() {
+      let words = arpa.transcribe_words(text, &options).unwrap();
+      let mut joined = vec!["[start]"];
+      for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+          joined.push("[space]");
+        }
+        joined.extend(word.polyphone.iter().map(|phoneme| phoneme.to_str()));
+      }
+      joined.push("[end]");
+      assert_eq!(joined.join(" "), transcribe(text, 3).unwrap());
+    }
+    let words = arpa.transcribe_words("the New  York Times", &TranscribeOptions {
+      unknown_words: UnknownWordPolicy::Skip,
+      ..options
+    }).unwrap();
+    assert_eq!(words.len(), 1);
+    assert_eq!(words[0].word, "New  York Times");
+    assert_eq!(words[0].span, Span { start: 4, end: 19 });
+    let options = TranscribeOptions { max_phrase_length: 2, ..Default::default() };
+    assert_eq!(arpa.pronounce_words("new york", &options),
+      vec![("new york".to_string(), Some(polyphone("N UW1 Y AO1 R K")))]);
+  }
+
+  #[test]
+  fn transcribe_sentence_annotations() {
+    let arpa = arpabet(DICTIONARY);
+    assert!(arpa.transcribe_sentence("Hi [laughs] you.").is_err());
+
+    let options = TranscribeOptions {
+      tokenizer: TokenizerOptions { annotations: AnnotationPolicy::Emit },
+      ..Default::default()
+    };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("Hi [laughs] you.", &options)
+      .unwrap()),
+      vec!["[start]", "HH", "AY1", "[annotation]", "[space]", "Y", "UW1", "[period]", "[end]"]);
+
+    let options = TranscribeOptions {
+      tokenizer: TokenizerOptions { annotations: AnnotationPolicy::Skip },
+      ..Default::default()
+    };
+    assert_eq!(strings(&arpa.transcribe_sentence_with_options("Hi [laughs] you.", &options)
+      .unwrap()),
+      vec!["[start]", "HH", "AY1", "[space]", "Y", "UW1", "[period]", "[end]"]);
+  }
+
+  #[test]
+  fn transcribe_words() {
+    let arpa = arpabet(DICTIONARY);
+    let text = "\u{1F44B}\u{1F3FD} Hi, you!";
+    let words = arpa.transcribe_words(text, &TranscribeOptions::default()).unwrap();
+    assert_eq!(words.len(), 2);
+    assert_eq!(words[0].word, "Hi");
+    assert_eq!(words[0].span, Span { start: 9, end: 11 });
+    assert_eq!(words[1].polyphone, arpa.get_polyphone("you").unwrap());
+    assert_eq!(&text[words[1].span.start .. words[1].span.end], "you");
+
+    let options = TranscribeOptions { offsets: OffsetUnit::Utf16, ..Default::default() };
+    let words = arpa.transcribe_words(text, &options).unwrap();
+    assert_eq!(words[0].span, Span { start: 5, end: 7 });
+    assert_eq!(words[1].span, Span { start: 9, end: 12 });
+
+    assert!(arpa.transcribe_words("Hi there", &options).is_err());
+  }
+
+  #[test]
+  fn contextual_a() {
+    let arpa = Arpabet::new();
+    let article = Some(polyphone("AH0"));
+    let letter = Some(polyphone("EY1"));
+    let policy = SingleLetterPolicy::Contextual;
+
+    assert_eq!(arpa.pronounce_single_character("A", &context(None, Some("dog")), policy), article);
+    assert_eq!(arpa.pronounce_single_character("a", &context(Some("saw"), Some("dog")), policy),
+      article);
+    assert_eq!(arpa.pronounce_single_character("A", &context(Some("plan"), None), policy), letter);
+    assert_eq!(arpa.pronounce_single_character("a", &context(Some("letter"), Some("is")), policy),
+      letter);
+    assert_eq!(arpa.pronounce_single_character("A", &context(Some("The"), Some("team")), policy),
+      letter);
+  }
+
+  #[test]
+  fn pronoun_i() {
+    let arpa = Arpabet::new();
+    let ay = Some(polyphone("AY1"));
+
+    for policy in [SingleLetterPolicy::Contextual, SingleLetterPolicy::Letter].iter() {
+      assert_eq!(arpa.pronounce_single_character("I", &context(None, Some("am")), *policy),
+        ay.clone());
+    }
+  }
+
+  #[test]
+  fn word_and_letter_policies() {
+    let mut arpa = Arpabet::new();
+    arpa.insert("a".to_string(), polyphone("AH0"));
+    let none = context(None, None);
+
+    assert_eq!(arpa.pronounce_single_character("a", &none, SingleLetterPolicy::Word),
+      Some(polyphone("AH0")));
+    assert_eq!(arpa.pronounce_single_character("a", &context(None, Some("dog")),
+      SingleLetterPolicy::Letter),
+      Some(polyphone("EY1")));
+    // Letters missing from the dictionary fall back to their names.
+    assert_eq!(arpa.pronounce_single_character("b", &none, SingleLetterPolicy::Word),
+      Some(polyphone("B IY1")));
+  }
+
+  #[test]
+  fn not_single_characters() {
+    let arpa = Arpabet::new();
+    let none = context(None, None);
+
+    assert_eq!(arpa.pronounce_single_character("ab", &none, SingleLetterPolicy::Contextual), None);
+    assert_eq!(arpa.pronounce_single_character("", &none, SingleLetterPolicy::Contextual), None);
+    assert_eq!(arpa.pronounce_single_character("7", &none, SingleLetterPolicy::Contextual), None);
+  }
+}
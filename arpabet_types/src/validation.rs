@@ -0,0 +1,168 @@
+//! This module checks sentence token streams for the invariants synthesis
+//! backends rely on, so malformed input can be rejected with a clear reason.
+
+use crate::extensions::{Punctuation, SentenceToken};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A problem found in a token stream. Positions are token indices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamIssue {
+  /// The stream has no tokens at all.
+  Empty,
+  /// The first token isn't a start token.
+  MissingStartToken,
+  /// A start token appears after the beginning of the stream.
+  MisplacedStartToken {
+    /// Index of the start token.
+    position: usize,
+  },
+  /// The last token isn't an end token.
+  MissingEndToken,
+  /// Tokens follow an end token.
+  TokensAfterEndToken {
+    /// Index of the end token.
+    position: usize,
+  },
+  /// A space directly follows another space.
+  RepeatedSpace {
+    /// Index of the second space.
+    position: usize,
+  },
+  /// A numeric code that maps to no token.
+  UnknownCode {
+    /// Index of the code.
+    position: usize,
+    /// The unmapped code.
+    code: u8,
+  },
+}
+
+impl fmt::Display for StreamIssue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      StreamIssue::Empty => write!(f, "The token stream is empty."),
+      StreamIssue::MissingStartToken =>
+          write!(f, "The token stream must begin with {}.", Punctuation::StartToken.to_str()),
+      StreamIssue::MisplacedStartToken { ref position } =>
+          write!(f, "Unexpected {} at token {}; it may only begin the stream.",
+            Punctuation::StartToken.to_str(), position),
+      StreamIssue::MissingEndToken =>
+          write!(f, "The token stream must finish with {}.", Punctuation::EndToken.to_str()),
+      StreamIssue::TokensAfterEndToken { ref position } =>
+          write!(f, "Tokens follow the {} at token {}.", Punctuation::EndToken.to_str(), position),
+      StreamIssue::RepeatedSpace { ref position } =>
+          write!(f, "Repeated {} at token {}.", Punctuation::Space.to_str(), position),
+      StreamIssue::UnknownCode { ref position, ref code } =>
+          write!(f, "Unknown token code {} at token {}.", code, position),
+    }
+  }
+}
+
+/// Check a token stream: it must begin with a start token, finish with an end
+/// token, and contain no other start or end tokens and no repeated spaces.
+/// Returns every issue found, in stream order; empty if the stream is valid.
+pub fn validate_token_stream(tokens: &[SentenceToken]) -> Vec<StreamIssue> {
+  let mut issues = Vec::new();
+
+  if tokens.is_empty() {
+    issues.push(StreamIssue::Empty);
+    return issues;
+  }
+
+  let start = SentenceToken::Punctuation(Punctuation::StartToken);
+  let end = SentenceToken::Punctuation(Punctuation::EndToken);
+  let space = SentenceToken::Punctuation(Punctuation::Space);
+
+  if tokens[0] != start {
+    issues.push(StreamIssue::MissingStartToken);
+  }
+
+  for (position, token) in tokens.iter().enumerate() {
+    if *token == start && position > 0 {
+      issues.push(StreamIssue::MisplacedStartToken { position });
+    }
+    if *token == end && position + 1 < tokens.len() {
+      issues.push(StreamIssue::TokensAfterEndToken { position });
+    }
+    if *token == space && position > 0 && tokens[position - 1] == space {
+      issues.push(StreamIssue::RepeatedSpace { position });
+    }
+  }
+
+  if tokens[tokens.len() - 1] != end {
+    issues.push(StreamIssue::MissingEndToken);
+  }
+
+  issues
+}
+
+/// Check a stream of numeric token codes (see `From<SentenceToken> for u8`):
+/// every code must map to a token, and the tokens must pass
+/// `validate_token_stream`.
+pub fn validate_code_stream(codes: &[u8]) -> Vec<StreamIssue> {
+  let mut issues = Vec::new();
+  let mut tokens = Vec::with_capacity(codes.len());
+
+  for (position, code) in codes.iter().enumerate() {
+    match SentenceToken::try_from(*code) {
+      Ok(token) => tokens.push(token),
+      Err(_) => issues.push(StreamIssue::UnknownCode { position, code: *code }),
+    }
+  }
+
+  // Positions of structural issues are only meaningful when every code decoded.
+  if issues.is_empty() {
+    issues = validate_token_stream(&tokens);
+  }
+
+  issues
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+
+  const START : SentenceToken = SentenceToken::Punctuation(Punctuation::StartToken);
+  const END : SentenceToken = SentenceToken::Punctuation(Punctuation::EndToken);
+  const SPACE : SentenceToken = SentenceToken::Punctuation(Punctuation::Space);
+  const HH : SentenceToken = SentenceToken::Phoneme(Phoneme::Consonant(Consonant::HH));
+  const AY : SentenceToken = SentenceToken::Phoneme(Phoneme::Vowel(Vowel::AY(VowelStress::PrimaryStress)));
+
+  #[test]
+  fn valid_stream() {
+    assert!(validate_token_stream(&[START, HH, AY, SPACE, HH, AY, END]).is_empty());
+    assert!(validate_token_stream(&[START, END]).is_empty());
+  }
+
+  #[test]
+  fn invalid_streams() {
+    assert_eq!(validate_token_stream(&[]), vec![StreamIssue::Empty]);
+    assert_eq!(validate_token_stream(&[HH, AY]),
+      vec![StreamIssue::MissingStartToken, StreamIssue::MissingEndToken]);
+    assert_eq!(validate_token_stream(&[START, HH, SPACE, SPACE, AY, END]),
+      vec![StreamIssue::RepeatedSpace { position: 3 }]);
+    assert_eq!(validate_token_stream(&[START, HH, END, START, AY, END]), vec![
+      StreamIssue::TokensAfterEndToken { position: 2 },
+      StreamIssue::MisplacedStartToken { position: 3 },
+    ]);
+  }
+
+  #[test]
+  fn code_streams() {
+    let codes = [START, HH, AY, END].iter().map(|token| u8::from(*token)).collect::<Vec<u8>>();
+    assert!(validate_code_stream(&codes).is_empty());
+    assert_eq!(validate_code_stream(&[201, 11, 0, 254]),
+      vec![StreamIssue::UnknownCode { position: 2, code: 0 }]);
+    assert_eq!(validate_code_stream(&[11, 254]), vec![StreamIssue::MissingStartToken]);
+  }
+
+  #[test]
+  fn messages() {
+    assert_eq!(StreamIssue::RepeatedSpace { position: 3 }.to_string(),
+      "Repeated [space] at token 3.");
+    assert_eq!(StreamIssue::MissingEndToken.to_string(),
+      "The token stream must finish with [end].");
+  }
+}
@@ -0,0 +1,119 @@
+//! This module identifies a word's pronunciation variants durably, so external
+//! systems can refer to "word X, variant v" across lexicon updates.
+//!
+//! Variant markers such as 'read(1)' are positional: they shift when variants
+//! are added, removed or merged in a different order. A `VariantId` is instead
+//! a hash of the pronunciation itself, so it survives re-parsing, reordering
+//! and merging, and only changes when the pronunciation does. Ids are unique
+//! among a word's variants, not across words.
+
+use crate::error::ArpabetError;
+use crate::phoneme::Phoneme;
+use crate::Arpabet;
+use std::fmt;
+use std::str::FromStr;
+
+/// A stable identifier of one of a word's pronunciations, written as 'v' and
+/// eight hex digits, eg. "v233774ff". See the module documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VariantId(u32);
+
+impl VariantId {
+  /// The id of a pronunciation: the FNV-1a hash of its phoneme symbols, eg.
+  /// "R EH1 D", folded to 32 bits. Stress is significant.
+  pub fn of(polyphone: &[Phoneme]) -> Self {
+    let symbols = polyphone.iter()
+      .map(|phoneme| phoneme.to_str())
+      .collect::<Vec<&str>>()
+      .join(" ");
+    let hash = symbols.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+      (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    VariantId((hash ^ (hash >> 32)) as u32)
+  }
+
+  /// The numeric value.
+  pub fn value(&self) -> u32 {
+    self.0
+  }
+}
+
+impl fmt::Display for VariantId {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "v{:08x}", self.0)
+  }
+}
+
+impl FromStr for VariantId {
+  type Err = ArpabetError;
+
+  /// Parse an id as written by `Display`, eg. "v233774ff".
+  fn from_str(id: &str) -> Result<Self, Self::Err> {
+    id.strip_prefix('v')
+      .filter(|hex| hex.len() == 8)
+      .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+      .map(VariantId)
+      .ok_or_else(|| ArpabetError::StringParseError {
+        description: format!("Not a variant id: '{}'", id),
+      })
+  }
+}
+
+impl Arpabet {
+  /// Get every pronunciation of a word with its variant id, in variant order.
+  /// See `get_polyphone_variants`.
+  pub fn get_identified_variants(&self, word: &str) -> Vec<(VariantId, &[Phoneme])> {
+    self.get_polyphone_variants(word)
+      .into_iter()
+      .map(|polyphone| (VariantId::of(polyphone), polyphone))
+      .collect()
+  }
+
+  /// Get the pronunciation of a word with the variant id, if it still has it.
+  pub fn get_variant(&self, word: &str, id: VariantId) -> Option<&[Phoneme]> {
+    self.get_polyphone_variants(word)
+      .into_iter()
+      .find(|polyphone| VariantId::of(polyphone) == id)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::polyphone;
+
+  #[test]
+  fn ids() {
+    let red = VariantId::of(&polyphone("R EH1 D"));
+    assert_eq!(red, VariantId::of(&polyphone("R EH1 D")));
+    assert_ne!(red, VariantId::of(&polyphone("R IY1 D")));
+    assert_ne!(red, VariantId::of(&polyphone("R EH2 D")));
+
+    let text = red.to_string();
+    assert_eq!(text.len(), 9);
+    assert_eq!(text.parse::<VariantId>().unwrap(), red);
+    assert!("1234abcd".parse::<VariantId>().is_err());
+    assert!("v1234".parse::<VariantId>().is_err());
+    assert!("vxxxxxxxx".parse::<VariantId>().is_err());
+
+    // Pinned, since ids must not change between releases.
+    assert_eq!(red.to_string(), "v233774ff");
+  }
+
+  #[test]
+  fn survive_reordering() {
+    let mut a = Arpabet::new();
+    a.insert("read".to_string(), polyphone("R IY1 D"));
+    a.insert("read(1)".to_string(), polyphone("R EH1 D"));
+
+    let mut b = Arpabet::new();
+    b.insert("read".to_string(), polyphone("R EH1 D"));
+    b.insert("read(1)".to_string(), polyphone("R IY1 D"));
+
+    let (id, _) = a.get_identified_variants("read")[1];
+    assert_eq!(a.get_variant("read", id), Some(&polyphone("R EH1 D")[..]));
+    assert_eq!(b.get_variant("read", id), Some(&polyphone("R EH1 D")[..]));
+    assert_eq!(b.get_identified_variants("read")[0].0, id);
+    assert_eq!(a.get_variant("red", id), None);
+  }
+}
@@ -0,0 +1,207 @@
+//! This module watermarks transcriptions for provenance, by choosing among a
+//! word's equivalent dictionary pronunciations, eg. 'either' as IY1 DH ER0 or
+//! AY1 DH ER0, according to a secret key.
+//!
+//! Each choice depends on the key, the word and the word before it, so the
+//! pattern survives cutting and splicing the text. Listeners hear ordinary
+//! pronunciation variants, while `Arpabet::detect_watermark` finds far more
+//! keyed choices than chance would. Marking is reversible: see
+//! `Arpabet::remove_watermark`.
+
+use crate::binary::fnv1a;
+use crate::casing::fold_case;
+use crate::phoneme::Phoneme;
+use crate::transcribe::TranscribedWord;
+use crate::Arpabet;
+
+/// A secret key choosing pronunciation variants.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Watermark {
+  key: Vec<u8>,
+}
+
+impl Watermark {
+  /// A watermark with the key.
+  pub fn new<K: AsRef<[u8]>>(key: K) -> Self {
+    Self { key: key.as_ref().to_vec() }
+  }
+
+  /// The keyed choice among `variants` pronunciations of the word, after the
+  /// previous word if any.
+  pub fn choose(&self, previous: Option<&str>, word: &str, variants: usize) -> usize {
+    if variants < 2 {
+      return 0;
+    }
+    let previous = previous.map(fold_case).unwrap_or_default();
+    let word = fold_case(word);
+    let bytes = self.key.iter().copied()
+      .chain(std::iter::once(0xff))
+      .chain(previous.bytes())
+      .chain(std::iter::once(0xff))
+      .chain(word.bytes());
+    (mix(fnv1a(bytes)) % variants as u64) as usize
+  }
+}
+
+/// The evidence of a watermark in a transcription. See
+/// `Arpabet::detect_watermark`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WatermarkDetection {
+  /// Words pronounced as one of several dictionary variants.
+  pub candidates: usize,
+  /// Candidates pronounced as the key chooses.
+  pub matches: usize,
+  /// Matches expected by chance in unmarked text.
+  pub expected: f64,
+  // The variance of the matches by chance.
+  variance: f64,
+}
+
+impl WatermarkDetection {
+  /// Standard deviations of the matches above chance. Zero without
+  /// candidates.
+  pub fn z_score(&self) -> f64 {
+    if self.variance == 0.0 {
+      0.0
+    } else {
+      (self.matches as f64 - self.expected) / self.variance.sqrt()
+    }
+  }
+
+  /// Whether the matches are at least `threshold` standard deviations above
+  /// chance. A threshold of 4 falsely flags about one unmarked text in 30,000.
+  pub fn is_watermarked(&self, threshold: f64) -> bool {
+    self.z_score() >= threshold
+  }
+}
+
+impl Arpabet {
+  /// Mark transcribed words, eg. from `transcribe_words`, by pronouncing each
+  /// word with dictionary variants as the watermark chooses. Words pronounced
+  /// otherwise, eg. as weak forms, are kept.
+  pub fn apply_watermark(&self, words: &[TranscribedWord], watermark: &Watermark)
+    -> Vec<TranscribedWord>
+  {
+    self.rewrite_variants(words, |previous, word, variants| {
+      watermark.choose(previous, word, variants.len())
+    })
+  }
+
+  /// Undo `apply_watermark`: pronounce each word with dictionary variants as
+  /// its primary pronunciation.
+  pub fn remove_watermark(&self, words: &[TranscribedWord]) -> Vec<TranscribedWord> {
+    self.rewrite_variants(words, |_, _, _| 0)
+  }
+
+  /// Count how many words are pronounced as the watermark would choose,
+  /// against how many would be by chance.
+  pub fn detect_watermark(&self, words: &[TranscribedWord], watermark: &Watermark)
+    -> WatermarkDetection
+  {
+    let mut detection = WatermarkDetection::default();
+    let mut previous = None;
+    for word in words.iter() {
+      let variants = self.get_polyphone_variants(&fold_case(&word.word));
+      if variants.len() > 1 && variants.contains(&word.polyphone.as_slice()) {
+        let chance = 1.0 / variants.len() as f64;
+        detection.candidates += 1;
+        detection.expected += chance;
+        detection.variance += chance * (1.0 - chance);
+        let chosen = watermark.choose(previous, &word.word, variants.len());
+        if variants[chosen] == word.polyphone.as_slice() {
+          detection.matches += 1;
+        }
+      }
+      previous = Some(word.word.as_str());
+    }
+    detection
+  }
+
+  // Replace the pronunciation of each word pronounced as one of several
+  // dictionary variants with the variant chosen from the previous word, the
+  // word and the variants.
+  fn rewrite_variants<F>(&self, words: &[TranscribedWord], choose: F) -> Vec<TranscribedWord>
+    where F: Fn(Option<&str>, &str, &[&[Phoneme]]) -> usize
+  {
+    words.iter()
+      .enumerate()
+      .map(|(i, word)| {
+        let mut word = word.clone();
+        let variants = self.get_polyphone_variants(&fold_case(&word.word));
+        if variants.len() > 1 && variants.contains(&word.polyphone.as_slice()) {
+          let previous = i.checked_sub(1).map(|j| words[j].word.as_str());
+          word.polyphone = variants[choose(previous, &word.word, &variants)].to_vec();
+        }
+        word
+      })
+      .collect()
+  }
+}
+
+// Spread every bit of a hash over the low bits, which FNV-1a leaves poorly
+// mixed (the MurmurHash3 finalizer).
+fn mix(mut hash: u64) -> u64 {
+  hash ^= hash >> 33;
+  hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+  hash ^= hash >> 33;
+  hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+  hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing;
+  use crate::transcribe::TranscribeOptions;
+
+  #[test]
+  fn choose() {
+    let watermark = Watermark::new("secret");
+    assert_eq!(watermark.choose(None, "cat", 1), 0);
+    assert_eq!(watermark.choose(Some("the"), "Either", 2),
+      watermark.choose(Some("THE"), "either", 2));
+    let choices : Vec<usize> = (0 .. 64)
+      .map(|i| watermark.choose(Some(&i.to_string()), "either", 2))
+      .collect();
+    assert!(choices.contains(&0) && choices.contains(&1));
+  }
+
+  #[test]
+  fn apply_detect_and_remove() {
+    let arpa = testing::arpabet(&[
+      ("either", "IY1 DH ER0"),
+      ("either(1)", "AY1 DH ER0"),
+      ("tomato", "T AH0 M EY1 T OW2"),
+      ("tomato(1)", "T AH0 M AA1 T OW2"),
+      ("route", "R UW1 T"),
+      ("route(1)", "R AW1 T"),
+      ("and", "AE1 N D"),
+      ("or", "AO1 R"),
+      ("but", "B AH1 T"),
+      ("so", "S OW1"),
+      ("then", "DH EH1 N"),
+    ]);
+    let fillers = ["and", "or", "but", "so", "then"];
+    let variable = ["either", "tomato", "route"];
+    let text = (0 .. 60)
+      .map(|i| format!("{} {}", fillers[i % 5], variable[i % 3]))
+      .collect::<Vec<_>>()
+      .join(" ");
+    let words = arpa.transcribe_words(&text, &TranscribeOptions::default()).unwrap();
+    let watermark = Watermark::new("secret");
+
+    let marked = arpa.apply_watermark(&words, &watermark);
+    assert_eq!(marked.len(), words.len());
+    let detection = arpa.detect_watermark(&marked, &watermark);
+    assert_eq!(detection.candidates, 60);
+    assert_eq!(detection.matches, 60);
+    assert_eq!(detection.expected, 30.0);
+    assert!(detection.is_watermarked(4.0));
+
+    assert!(!arpa.detect_watermark(&words, &watermark).is_watermarked(4.0));
+    assert!(!arpa.detect_watermark(&marked, &Watermark::new("other")).is_watermarked(4.0));
+
+    assert_eq!(arpa.remove_watermark(&marked), words);
+    assert_eq!(WatermarkDetection::default().z_score(), 0.0);
+  }
+}
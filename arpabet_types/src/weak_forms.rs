@@ -0,0 +1,86 @@
+//! This module contains the weak (reduced) forms of English function words,
+//! as used in unstressed positions of connected speech, eg. 'to' -> T AH0.
+
+use crate::casing::fold_case;
+use crate::constants::PHONEME_MAP;
+use crate::Polyphone;
+
+/// Function words and their usual weak forms.
+pub const WEAK_FORMS : [(&str, &str); 36] = [
+  ("a", "AH0"),
+  ("am", "AH0 M"),
+  ("an", "AH0 N"),
+  ("and", "AH0 N D"),
+  ("are", "ER0"),
+  ("as", "AH0 Z"),
+  ("at", "AH0 T"),
+  ("but", "B AH0 T"),
+  ("can", "K AH0 N"),
+  ("could", "K UH0 D"),
+  ("does", "D AH0 Z"),
+  ("for", "F ER0"),
+  ("from", "F R AH0 M"),
+  ("had", "HH AH0 D"),
+  ("has", "HH AH0 Z"),
+  ("have", "HH AH0 V"),
+  ("he", "HH IY0"),
+  ("her", "HH ER0"),
+  ("him", "HH IH0 M"),
+  ("his", "HH IH0 Z"),
+  ("must", "M AH0 S T"),
+  ("of", "AH0 V"),
+  ("or", "ER0"),
+  ("shall", "SH AH0 L"),
+  ("should", "SH UH0 D"),
+  ("some", "S AH0 M"),
+  ("than", "DH AH0 N"),
+  ("that", "DH AH0 T"),
+  ("the", "DH AH0"),
+  ("them", "DH AH0 M"),
+  ("to", "T AH0"),
+  ("us", "AH0 S"),
+  ("was", "W AH0 Z"),
+  ("were", "W ER0"),
+  ("would", "W UH0 D"),
+  ("your", "Y ER0"),
+];
+
+/// Get the weak form of a function word, in any casing.
+/// Returns None for words without a weak form.
+pub fn weak_form(word: &str) -> Option<Polyphone> {
+  let word = fold_case(word);
+  WEAK_FORMS.binary_search_by(|(w, _)| w.cmp(&word.as_str()))
+    .ok()
+    .map(|i| {
+      WEAK_FORMS[i].1.split(' ')
+        .filter_map(|phoneme| PHONEME_MAP.get(phoneme).copied())
+        .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::phoneme::{Consonant, Phoneme, Vowel, VowelStress};
+
+  #[test]
+  fn weak_forms() {
+    assert_eq!(weak_form("To"), Some(vec![
+      Phoneme::Consonant(Consonant::T),
+      Phoneme::Vowel(Vowel::AH(VowelStress::NoStress)),
+    ]));
+    assert_eq!(weak_form("and").map(|p| p.len()), Some(3));
+    assert_eq!(weak_form("cat"), None);
+  }
+
+  #[test]
+  fn table_is_sorted_and_valid() {
+    for pair in WEAK_FORMS.windows(2) {
+      assert!(pair[0].0 < pair[1].0, "{} should sort before {}", pair[0].0, pair[1].0);
+    }
+    for (word, pronunciation) in WEAK_FORMS.iter() {
+      let polyphone = weak_form(word).unwrap();
+      assert_eq!(polyphone.len(), pronunciation.split(' ').count());
+    }
+  }
+}